@@ -0,0 +1,251 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Small partitioning tool built on `gpt_disk_io`.
+//!
+//! This doubles as a reference for the order of operations needed to
+//! inspect and modify a GPT disk with this library.
+//!
+//! ```text
+//! gpt-tool print <disk>
+//! gpt-tool verify <disk>
+//! gpt-tool create <disk> <num-blocks> <type-guid> <name>
+//! gpt-tool delete <disk> <index>
+//! gpt-tool backup <disk> <backup-file>
+//! gpt-tool restore <disk> <backup-file>
+//! ```
+//!
+//! The disk is accessed with a 512-byte block size. To create a disk
+//! to try this on:
+//!
+//! ```text
+//! truncate --size 10MiB disk.bin
+//! sgdisk disk.bin --new=1:2048:4096 --change-name=1:'hello world!'
+//! cargo run --features=std --example gpt-tool print disk.bin
+//! ```
+
+use gpt_disk_io::gpt_disk_types::{
+    BlockSize, GptHeader, GptPartitionEntryArray, GptPartitionType, Guid,
+    MasterBootRecord,
+};
+use gpt_disk_io::validate::Severity;
+use gpt_disk_io::{
+    BlockIoAdapter, BufDisk, CreatePartitionOptions, FreeGapFilter,
+    PartitionExtent,
+};
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::num::NonZeroU64;
+use std::process::ExitCode;
+use std::{env, mem};
+
+const BLOCK_SIZE: BlockSize = BlockSize::BS_512;
+
+const USAGE: &str = "usage:
+  gpt-tool print <disk>
+  gpt-tool verify <disk>
+  gpt-tool create <disk> <num-blocks> <type-guid> <name>
+  gpt-tool delete <disk> <index>
+  gpt-tool backup <disk> <backup-file>
+  gpt-tool restore <disk> <backup-file>";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+type ToolDisk = BufDisk<BlockIoAdapter<File>>;
+
+fn open_disk(path: &str, write: bool) -> Result<ToolDisk> {
+    let file = OpenOptions::new().read(true).write(write).open(path)?;
+    Ok(BufDisk::new(BlockIoAdapter::new(file, BLOCK_SIZE))?)
+}
+
+/// Generate a random GUID for a new partition.
+///
+/// This avoids a dependency on a random number crate by using the
+/// randomly-seeded hasher from the standard library.
+fn random_guid() -> Guid {
+    let mut bytes = [0; 16];
+    for chunk in bytes.chunks_mut(8) {
+        let hash = RandomState::new().build_hasher().finish();
+        chunk.copy_from_slice(&hash.to_le_bytes());
+    }
+    Guid::from_random_bytes(bytes)
+}
+
+fn print(path: &str) -> Result<()> {
+    let mut disk = open_disk(path, false)?;
+    let header = disk.read_primary_gpt_header_verified()?;
+    println!("{header}");
+
+    let layout = header.get_partition_entry_array_layout()?;
+    for (index, entry) in
+        disk.read_gpt_partition_entries(layout)?.iter().enumerate()
+    {
+        if entry.is_used() {
+            println!("{index}: {entry}");
+        }
+    }
+
+    for gap in disk.free_gaps(&header, FreeGapFilter::default())? {
+        println!("free: {gap} ({} blocks)", gap.num_blocks());
+    }
+    Ok(())
+}
+
+fn verify(path: &str) -> Result<bool> {
+    let mut disk = open_disk(path, false)?;
+    let findings = disk.verify()?;
+    for finding in &findings {
+        println!("{}: {finding}", finding.severity());
+    }
+    if findings.is_empty() {
+        println!("no problems found");
+    }
+    Ok(findings.iter().all(|f| f.severity() != Severity::Error))
+}
+
+fn create(path: &str, num_blocks: &str, ptype: &str, name: &str) -> Result<()> {
+    let num_blocks = NonZeroU64::new(num_blocks.parse()?)
+        .ok_or("number of blocks must be nonzero")?;
+    let ptype: GptPartitionType = ptype.parse()?;
+    let name = name.parse()?;
+
+    let mut disk = open_disk(path, true)?;
+    let index = disk.create_partition(
+        ptype,
+        PartitionExtent::Blocks(num_blocks),
+        name,
+        CreatePartitionOptions::new(random_guid()),
+    )?;
+    disk.flush()?;
+    println!("created partition {index}");
+    Ok(())
+}
+
+fn delete(path: &str, index: &str) -> Result<()> {
+    let index = index.parse()?;
+    let mut disk = open_disk(path, true)?;
+    disk.delete_partition(index)?;
+    disk.flush()?;
+    println!("deleted partition {index}");
+    Ok(())
+}
+
+/// Write a backup of the partition table to `backup_path`.
+///
+/// The backup contains the MBR, the primary header, the secondary
+/// header, and the primary partition entry array, each padded to a
+/// whole number of blocks.
+fn backup(path: &str, backup_path: &str) -> Result<()> {
+    let mut disk = open_disk(path, false)?;
+    let mbr = disk.read_mbr()?;
+    let primary = disk.read_primary_gpt_header_verified()?;
+    let secondary = disk.read_secondary_gpt_header_verified()?;
+    let layout = primary.get_partition_entry_array_layout()?;
+
+    let block_size = BLOCK_SIZE.to_usize().unwrap();
+    let mut out = Vec::new();
+    for bytes in [
+        bytemuck::bytes_of(&mbr),
+        bytemuck::bytes_of(&primary),
+        bytemuck::bytes_of(&secondary),
+    ] {
+        out.extend_from_slice(bytes);
+        out.resize(out.len().next_multiple_of(block_size), 0);
+    }
+    for entry in disk.read_gpt_partition_entries(layout)? {
+        out.extend_from_slice(bytemuck::bytes_of(&entry));
+    }
+    out.resize(out.len().next_multiple_of(block_size), 0);
+
+    fs::write(backup_path, out)?;
+    println!("wrote backup to {backup_path}");
+    Ok(())
+}
+
+/// Restore a backup created by [`backup`].
+fn restore(path: &str, backup_path: &str) -> Result<()> {
+    let backup = fs::read(backup_path)?;
+    let block_size = BLOCK_SIZE.to_usize().unwrap();
+    if backup.len() < block_size * 3 || backup.len() % block_size != 0 {
+        return Err("backup file has an invalid size".into());
+    }
+    let block = |i: usize| &backup[i * block_size..(i + 1) * block_size];
+
+    let mbr: MasterBootRecord = bytemuck::pod_read_unaligned(
+        &block(0)[..mem::size_of::<MasterBootRecord>()],
+    );
+    let read_header = |i| -> Result<GptHeader> {
+        let header: GptHeader = bytemuck::pod_read_unaligned(
+            &block(i)[..mem::size_of::<GptHeader>()],
+        );
+        if !header.is_signature_valid()
+            || header.header_crc32 != header.calculate_header_crc32()
+        {
+            return Err("backup contains an invalid GPT header".into());
+        }
+        Ok(header)
+    };
+    let primary = read_header(1)?;
+    let secondary = read_header(2)?;
+
+    let mut disk = open_disk(path, true)?;
+    if secondary.my_lba.to_u64() >= disk.num_blocks()? {
+        return Err("backup does not fit on the disk".into());
+    }
+
+    let layout = primary.get_partition_entry_array_layout()?;
+    let mut array_bytes = backup[block_size * 3..].to_vec();
+    let mut array =
+        GptPartitionEntryArray::new(layout, BLOCK_SIZE, &mut array_bytes)?;
+    if array.calculate_crc32() != primary.partition_entry_array_crc32 {
+        return Err("backup partition entry array CRC32 mismatch".into());
+    }
+
+    // Write the arrays before the headers that describe them.
+    disk.disk_mut().write_gpt_partition_entry_array(&array)?;
+    array.set_start_lba(secondary.partition_entry_lba.into());
+    disk.disk_mut().write_gpt_partition_entry_array(&array)?;
+    disk.write_secondary_gpt_header(&secondary)?;
+    disk.write_primary_gpt_header(&primary)?;
+    disk.write_mbr(&mbr)?;
+    disk.flush()?;
+    println!("restored backup from {backup_path}");
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<bool> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["print", disk] => print(disk)?,
+        ["verify", disk] => return verify(disk),
+        ["create", disk, num_blocks, ptype, name] => {
+            create(disk, num_blocks, ptype, name)?;
+        }
+        ["delete", disk, index] => delete(disk, index)?,
+        ["backup", disk, file] => backup(disk, file)?,
+        ["restore", disk, file] => restore(disk, file)?,
+        _ => {
+            eprintln!("{USAGE}");
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -6,12 +6,31 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub(crate) mod byte_block_io;
+pub(crate) mod offset_block_io;
+pub(crate) mod range_block_io;
+pub(crate) mod retry_block_io;
 pub(crate) mod slice_block_io;
 
+#[cfg(feature = "std")]
+pub(crate) mod http_range_reader;
+#[cfg(feature = "std")]
+pub(crate) mod nbd_block_io;
 #[cfg(feature = "std")]
 pub(crate) mod std_block_io;
 
-use core::fmt::{Debug, Display};
+#[cfg(feature = "alloc")]
+pub(crate) mod dry_run_block_io;
+#[cfg(feature = "alloc")]
+pub(crate) mod fault_injecting_block_io;
+#[cfg(feature = "alloc")]
+pub(crate) mod journaled_block_io;
+#[cfg(feature = "alloc")]
+pub(crate) mod seekable_zstd_block_io;
+#[cfg(feature = "alloc")]
+pub(crate) mod vec_block_io;
+
+use core::error::Error;
 use gpt_disk_types::{BlockSize, Lba};
 
 /// Trait for reading from and writing to a block device.
@@ -19,12 +38,57 @@ use gpt_disk_types::{BlockSize, Lba};
 /// See also [`BlockIoAdapter`].
 pub trait BlockIo {
     /// IO error type.
-    type Error: Debug + Display + Send + Sync + 'static;
+    type Error: Error + Send + Sync + 'static;
 
     /// Get the [`BlockSize`]. The return value is not allowed to
     /// change.
     fn block_size(&self) -> BlockSize;
 
+    /// Get the physical block size of the device, which is the smallest
+    /// unit the device can write without a read-modify-write cycle. For
+    /// example, many disks have a 512-byte logical [`block_size`] but a
+    /// 4096-byte physical block size.
+    ///
+    /// This is used to align new partitions. It must be a multiple of
+    /// [`block_size`], and is not allowed to change. The default
+    /// implementation returns [`block_size`].
+    ///
+    /// [`block_size`]: Self::block_size
+    fn physical_block_size(&self) -> BlockSize {
+        self.block_size()
+    }
+
+    /// Whether the device is known to be read-only, for example because
+    /// it was opened without write access or the media is
+    /// write-protected.
+    ///
+    /// This is a hint: returning `false` does not guarantee that writes
+    /// will succeed. [`Disk`] checks it before every write, so that a
+    /// multi-step update fails before anything is written rather than
+    /// partway through. The return value is not allowed to change. The
+    /// default implementation returns `false`.
+    ///
+    /// [`Disk`]: crate::Disk
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Required alignment, in bytes, of the buffers passed to
+    /// [`read_blocks`] and [`write_blocks`]. For example, a device
+    /// accessed with direct IO may require buffers aligned to the
+    /// physical block size.
+    ///
+    /// This must be a power of two, and is not allowed to change.
+    /// [`Disk`] checks it before every read and write. The default
+    /// implementation returns 1.
+    ///
+    /// [`Disk`]: crate::Disk
+    /// [`read_blocks`]: Self::read_blocks
+    /// [`write_blocks`]: Self::write_blocks
+    fn buffer_alignment(&self) -> usize {
+        1
+    }
+
     /// Get the number of logical blocks in the disk.
     ///
     /// If the underlying storage has a number of bytes that are not
@@ -67,6 +131,54 @@ pub trait BlockIo {
     fn flush(&mut self) -> Result<(), Self::Error>;
 }
 
+/// Forwarding implementation, so that a [`Disk`] can borrow a
+/// [`BlockIo`] rather than taking ownership of it.
+///
+/// [`Disk`]: crate::Disk
+impl<T: BlockIo + ?Sized> BlockIo for &mut T {
+    type Error = T::Error;
+
+    fn block_size(&self) -> BlockSize {
+        (**self).block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        (**self).physical_block_size()
+    }
+
+    fn is_read_only(&self) -> bool {
+        (**self).is_read_only()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        (**self).buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        (**self).num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        (**self).read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        (**self).write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+}
+
 /// Adapter for types that can act as storage, but don't have a block
 /// size. This is used to provide `BlockIo` impls for byte slices,
 /// files, and various other types.
@@ -101,11 +213,15 @@ pub trait BlockIo {
 ///
 /// # With the `alloc` feature
 ///
-/// Construct a `BlockIoAdapter` that owns a `Vec<u8>`:
+/// Construct a `BlockIoAdapter` that owns its storage. `Vec<u8>` and
+/// `Box<[u8]>` are read-write. `Arc<[u8]>` is read-only. `Cow<[u8]>`
+/// is read-write, and borrowed data is copied on the first write.
 ///
 /// ```
 /// use gpt_disk_io::gpt_disk_types::BlockSize;
 /// use gpt_disk_io::{BlockIo, BlockIoAdapter, SliceBlockIoError};
+/// use std::borrow::Cow;
+/// use std::sync::Arc;
 ///
 /// #[cfg(feature = "alloc")]
 /// fn example_alloc() -> Result<(), SliceBlockIoError> {
@@ -113,6 +229,19 @@ pub trait BlockIo {
 ///     let mut bio = BlockIoAdapter::new(data, BlockSize::BS_512);
 ///     assert_eq!(bio.num_blocks()?, 1);
 ///
+///     let data: Box<[u8]> = vec![0; 512].into_boxed_slice();
+///     let mut bio = BlockIoAdapter::new(data, BlockSize::BS_512);
+///     assert_eq!(bio.num_blocks()?, 1);
+///
+///     let data: Arc<[u8]> = Arc::from(vec![0; 512]);
+///     let mut bio = BlockIoAdapter::new(data, BlockSize::BS_512);
+///     assert_eq!(bio.num_blocks()?, 1);
+///
+///     let data = [0; 512];
+///     let mut bio =
+///         BlockIoAdapter::new(Cow::Borrowed(&data[..]), BlockSize::BS_512);
+///     assert_eq!(bio.num_blocks()?, 1);
+///
 ///     Ok(())
 /// }
 /// ```
@@ -141,10 +270,8 @@ pub trait BlockIo {
 ///     let mut bio = BlockIoAdapter::new(&file, BlockSize::BS_512);
 ///     assert_eq!(bio.num_blocks()?, 1);
 ///
-///     // Construct a `BlockIoAdapter` from another type that
-///     // implements `Read + Write + Seek`, but does not directly implement
-///     // `ReadWriteSeek`. Due to trait constraints, this requires an `&mut`
-///     // borrow; transferring ownership of `cursor` would fail to compile.
+///     // Construct a `BlockIoAdapter` that borrows any type that
+///     // implements `ReadWriteSeek`, such as a `Cursor`.
 ///     let mut cursor = Cursor::new(vec![0; 512]);
 ///     let mut bio = BlockIoAdapter::new(&mut cursor, BlockSize::BS_512);
 ///     assert_eq!(bio.num_blocks()?, 1);
@@ -157,6 +284,8 @@ pub trait BlockIo {
 pub struct BlockIoAdapter<T> {
     storage: T,
     block_size: BlockSize,
+    physical_block_size: Option<BlockSize>,
+    read_only: bool,
 }
 
 impl<T> BlockIoAdapter<T> {
@@ -166,6 +295,8 @@ impl<T> BlockIoAdapter<T> {
         Self {
             storage,
             block_size,
+            physical_block_size: None,
+            read_only: false,
         }
     }
 
@@ -175,6 +306,52 @@ impl<T> BlockIoAdapter<T> {
         self.block_size
     }
 
+    /// Get the physical block size. This is the same as the
+    /// [`block_size`] unless set with [`set_physical_block_size`].
+    ///
+    /// [`block_size`]: Self::block_size
+    /// [`set_physical_block_size`]: Self::set_physical_block_size
+    #[must_use]
+    pub fn physical_block_size(&self) -> BlockSize {
+        self.physical_block_size.unwrap_or(self.block_size)
+    }
+
+    /// Set the physical block size reported by
+    /// [`BlockIo::physical_block_size`]. Pass `None` to use the
+    /// [`block_size`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `physical_block_size` is not a multiple of the
+    /// [`block_size`].
+    ///
+    /// [`block_size`]: Self::block_size
+    pub fn set_physical_block_size(
+        &mut self,
+        physical_block_size: Option<BlockSize>,
+    ) {
+        if let Some(pbs) = physical_block_size {
+            assert!(
+                pbs.to_u64() % self.block_size.to_u64() == 0,
+                "physical block size must be a multiple of the block size"
+            );
+        }
+        self.physical_block_size = physical_block_size;
+    }
+
+    /// Mark the storage as read-only. [`BlockIo::is_read_only`] returns
+    /// `true` if this is set, or if the storage is known to be
+    /// read-only (such as `&[u8]`, or a `File` opened without write
+    /// access on Linux).
+    ///
+    /// Writes made directly through the adapter are still attempted,
+    /// but a [`Disk`] using the adapter will refuse to write.
+    ///
+    /// [`Disk`]: crate::Disk
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
     /// Get a reference to the underlying storage.
     #[must_use]
     pub fn storage(&self) -> &T {
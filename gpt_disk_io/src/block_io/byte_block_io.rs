@@ -0,0 +1,290 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Storage that is accessed by byte offset rather than by block, such
+/// as a flash memory driver.
+///
+/// This is the backend used by [`ByteBlockIo`].
+pub trait ByteIo {
+    /// IO error type.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Get the total size of the storage in bytes.
+    fn size(&mut self) -> Result<u64, Self::Error>;
+
+    /// Get the size in bytes of the smallest unit that can be written,
+    /// such as the erase sector size of NOR flash. Every call to
+    /// [`write_bytes`] is aligned to this size and a multiple of it in
+    /// length. This is not allowed to change, and must be nonzero.
+    ///
+    /// The default implementation returns 1.
+    ///
+    /// [`write_bytes`]: Self::write_bytes
+    fn write_unit(&self) -> u64 {
+        1
+    }
+
+    /// Read exactly `dst.len()` bytes starting at byte `offset`.
+    fn read_bytes(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Write all of `src` starting at byte `offset`. For flash memory,
+    /// the implementation is responsible for erasing before writing.
+    fn write_bytes(
+        &mut self,
+        offset: u64,
+        src: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Flush any pending writes to the storage.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Error type used by [`ByteBlockIo`].
+///
+/// This type implements the [`Error`] trait, with
+/// [`ByteBlockIoError::Io`] reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ByteBlockIoError<IoError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// A read-modify-write is needed, but the scratch buffer is smaller
+    /// than the [`ByteIo::write_unit`].
+    ScratchTooSmall,
+
+    /// The [`ByteIo::write_unit`] is zero, or is larger than the block
+    /// size but not a multiple of it.
+    InvalidWriteUnit,
+
+    /// Error from the [`ByteIo`].
+    Io(IoError),
+}
+
+impl<E: Display> Display for ByteBlockIoError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::ScratchTooSmall => {
+                f.write_str("scratch buffer is smaller than the write unit")
+            }
+            Self::InvalidWriteUnit => f.write_str("invalid write unit"),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ByteBlockIoError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// [`BlockIo`] adapter for byte-addressed storage implementing
+/// [`ByteIo`].
+///
+/// Reads are passed straight through. If the storage's
+/// [`write_unit`] is larger than the block size (for example, NOR
+/// flash with 4KiB erase sectors and a 512-byte block size), a write
+/// that only covers part of a unit is done as a read-modify-write of
+/// the whole unit, using a scratch buffer provided with
+/// [`with_scratch`]. If the write unit divides the block size, no
+/// scratch buffer is needed and [`new`] can be used.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{ByteBlockIo, ByteIo, Disk};
+///
+/// fn open<F: ByteIo>(flash: F) -> Result<(), Box<dyn std::error::Error>> {
+///     // Scratch buffer the size of one erase sector.
+///     let mut scratch = [0; 4096];
+///     let block_io =
+///         ByteBlockIo::with_scratch(flash, BlockSize::BS_512, &mut scratch);
+///     let mut disk = Disk::new(block_io)?;
+///     let mut block_buf = [0; 512];
+///     disk.write_protective_mbr(&mut block_buf)?;
+///     disk.flush()?;
+///     Ok(())
+/// }
+/// ```
+///
+/// [`new`]: Self::new
+/// [`with_scratch`]: Self::with_scratch
+/// [`write_unit`]: ByteIo::write_unit
+#[allow(clippy::module_name_repetitions)]
+pub struct ByteBlockIo<B, S = [u8; 0]> {
+    io: B,
+    block_size: BlockSize,
+    scratch: S,
+}
+
+impl<B: ByteIo> ByteBlockIo<B> {
+    /// Create a `ByteBlockIo` without a scratch buffer. Writes will
+    /// fail with [`ByteBlockIoError::ScratchTooSmall`] if they require a
+    /// read-modify-write.
+    pub fn new(io: B, block_size: BlockSize) -> Self {
+        Self::with_scratch(io, block_size, [])
+    }
+}
+
+impl<B: ByteIo, S: AsMut<[u8]>> ByteBlockIo<B, S> {
+    /// Create a `ByteBlockIo` with a scratch buffer for
+    /// read-modify-write. The buffer must be at least as large as the
+    /// [`write_unit`].
+    ///
+    /// [`write_unit`]: ByteIo::write_unit
+    pub fn with_scratch(io: B, block_size: BlockSize, scratch: S) -> Self {
+        Self {
+            io,
+            block_size,
+            scratch,
+        }
+    }
+
+    /// Get a reference to the underlying [`ByteIo`].
+    pub fn inner(&self) -> &B {
+        &self.io
+    }
+
+    /// Consume the `ByteBlockIo` and return the underlying [`ByteIo`].
+    pub fn into_inner(self) -> B {
+        self.io
+    }
+
+    /// Write `src` at byte `offset`, which is block aligned, using
+    /// read-modify-write for any write unit that is only partly
+    /// covered.
+    fn write_with_rmw(
+        &mut self,
+        offset: u64,
+        src: &[u8],
+        unit: u64,
+    ) -> Result<(), ByteBlockIoError<B::Error>> {
+        let unit_usize =
+            usize::try_from(unit).map_err(|_| ByteBlockIoError::Overflow)?;
+        let len =
+            u64::try_from(src.len()).map_err(|_| ByteBlockIoError::Overflow)?;
+        let end = offset.checked_add(len).ok_or(ByteBlockIoError::Overflow)?;
+
+        let mut unit_start = offset - offset % unit;
+        while unit_start < end {
+            let unit_end = unit_start
+                .checked_add(unit)
+                .ok_or(ByteBlockIoError::Overflow)?;
+            let copy_start = offset.max(unit_start);
+            let copy_end = end.min(unit_end);
+            // OK to unwrap: these are offsets within `src`.
+            let src_range = usize::try_from(copy_start - offset).unwrap()
+                ..usize::try_from(copy_end - offset).unwrap();
+
+            if copy_start == unit_start && copy_end == unit_end {
+                self.io
+                    .write_bytes(unit_start, &src[src_range])
+                    .map_err(ByteBlockIoError::Io)?;
+            } else {
+                let scratch = self
+                    .scratch
+                    .as_mut()
+                    .get_mut(..unit_usize)
+                    .ok_or(ByteBlockIoError::ScratchTooSmall)?;
+                self.io
+                    .read_bytes(unit_start, scratch)
+                    .map_err(ByteBlockIoError::Io)?;
+                // OK to unwrap: these are offsets within the unit.
+                let dst_start =
+                    usize::try_from(copy_start - unit_start).unwrap();
+                let dst_end = usize::try_from(copy_end - unit_start).unwrap();
+                scratch[dst_start..dst_end].copy_from_slice(&src[src_range]);
+                self.io
+                    .write_bytes(unit_start, scratch)
+                    .map_err(ByteBlockIoError::Io)?;
+            }
+            unit_start = unit_end;
+        }
+        Ok(())
+    }
+}
+
+impl<B: ByteIo, S: AsMut<[u8]>> BlockIo for ByteBlockIo<B, S> {
+    type Error = ByteBlockIoError<B::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        let size = self.io.size().map_err(ByteBlockIoError::Io)?;
+        Ok(size / self.block_size.to_u64())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(dst);
+
+        let offset = start_lba
+            .to_u64()
+            .checked_mul(self.block_size.to_u64())
+            .ok_or(ByteBlockIoError::Overflow)?;
+        self.io
+            .read_bytes(offset, dst)
+            .map_err(ByteBlockIoError::Io)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(src);
+
+        let block_size = self.block_size.to_u64();
+        let offset = start_lba
+            .to_u64()
+            .checked_mul(block_size)
+            .ok_or(ByteBlockIoError::Overflow)?;
+
+        let unit = self.io.write_unit();
+        if unit == 0 {
+            return Err(ByteBlockIoError::InvalidWriteUnit);
+        }
+        if block_size % unit == 0 {
+            // Every block is made up of whole write units.
+            return self
+                .io
+                .write_bytes(offset, src)
+                .map_err(ByteBlockIoError::Io);
+        }
+        if unit % block_size != 0 {
+            return Err(ByteBlockIoError::InvalidWriteUnit);
+        }
+        self.write_with_rmw(offset, src, unit)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush().map_err(ByteBlockIoError::Io)
+    }
+}
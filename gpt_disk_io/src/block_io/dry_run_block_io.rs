@@ -0,0 +1,193 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use gpt_disk_types::{crc, BlockSize, Crc32, Lba, U32Le};
+
+/// A write recorded by [`DryRunBlockIo`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RecordedWrite {
+    /// First block written.
+    pub start_lba: Lba,
+
+    /// Number of bytes written. This is always a multiple of the block
+    /// size.
+    pub length_in_bytes: usize,
+
+    /// CRC32 checksum of the data written.
+    pub crc32: Crc32,
+}
+
+/// [`BlockIo`] wrapper that records writes instead of applying them.
+///
+/// Writes are kept in memory and are visible to later reads through
+/// the wrapper, so multi-step operations (such as those on
+/// [`Disk`]) behave exactly as they would on the real device. The
+/// underlying [`BlockIo`] is only ever read from.
+///
+/// After running an operation, [`writes`] describes what would change.
+/// The writes can then be discarded by dropping the wrapper (or calling
+/// [`into_inner`]), or applied in their original order with
+/// [`commit`].
+///
+/// Writes are not bounds-checked until they are committed.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{BlockIoAdapter, Disk, DryRunBlockIo};
+///
+/// let mut storage = vec![0; 512 * 64];
+/// let block_io =
+///     BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+///
+/// let mut disk = Disk::new(DryRunBlockIo::new(block_io)).unwrap();
+/// let mut block_buf = [0; 512];
+/// disk.write_protective_mbr(&mut block_buf).unwrap();
+/// // The write is visible through the `Disk`...
+/// assert_eq!(disk.read_mbr(&mut block_buf).unwrap().signature, [0x55, 0xaa]);
+/// drop(disk);
+///
+/// // ...but the underlying storage was not modified.
+/// assert_eq!(storage, vec![0; 512 * 64]);
+/// ```
+///
+/// [`Disk`]: crate::Disk
+/// [`commit`]: Self::commit
+/// [`into_inner`]: Self::into_inner
+/// [`writes`]: Self::writes
+#[allow(clippy::module_name_repetitions)]
+pub struct DryRunBlockIo<Io: BlockIo> {
+    io: Io,
+    /// Pending data for each written block, keyed by LBA.
+    overlay: BTreeMap<u64, Vec<u8>>,
+    writes: Vec<RecordedWrite>,
+    /// Data for each entry in `writes`.
+    write_data: Vec<Vec<u8>>,
+}
+
+impl<Io: BlockIo> DryRunBlockIo<Io> {
+    /// Create a `DryRunBlockIo` with no recorded writes.
+    pub fn new(io: Io) -> Self {
+        Self {
+            io,
+            overlay: BTreeMap::new(),
+            writes: Vec::new(),
+            write_data: Vec::new(),
+        }
+    }
+
+    /// Get the writes recorded so far, in the order they were made.
+    #[must_use]
+    pub fn writes(&self) -> &[RecordedWrite] {
+        &self.writes
+    }
+
+    /// Discard all recorded writes.
+    pub fn clear(&mut self) {
+        self.overlay.clear();
+        self.writes.clear();
+        self.write_data.clear();
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `DryRunBlockIo` and return the underlying
+    /// [`BlockIo`]. Recorded writes are discarded.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Apply the recorded writes to the underlying [`BlockIo`] in the
+    /// order they were made, flush it, and return it.
+    ///
+    /// If a write fails, the remaining writes are not attempted.
+    pub fn commit(mut self) -> Result<Io, Io::Error> {
+        for (write, data) in self.writes.iter().zip(&self.write_data) {
+            self.io.write_blocks(write.start_lba, data)?;
+        }
+        self.io.flush()?;
+        Ok(self.io)
+    }
+
+    fn block_size_as_usize(&self) -> usize {
+        // OK to unwrap: a block buffer was passed in, so the block
+        // size fits in a `usize`.
+        self.io.block_size().to_usize().unwrap()
+    }
+}
+
+impl<Io: BlockIo> BlockIo for DryRunBlockIo<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        self.io.buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.io.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.io.read_blocks(start_lba, dst)?;
+
+        let block_size = self.block_size_as_usize();
+        let start = start_lba.to_u64();
+        for (lba, chunk) in (start..).zip(dst.chunks_exact_mut(block_size)) {
+            if let Some(data) = self.overlay.get(&lba) {
+                chunk.copy_from_slice(data);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.io.block_size().assert_valid_block_buffer(src);
+
+        let block_size = self.block_size_as_usize();
+        let start = start_lba.to_u64();
+        for (lba, chunk) in (start..).zip(src.chunks_exact(block_size)) {
+            self.overlay.insert(lba, chunk.to_vec());
+        }
+
+        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        self.writes.push(RecordedWrite {
+            start_lba,
+            length_in_bytes: src.len(),
+            crc32: Crc32(U32Le(crc.checksum(src).to_le_bytes())),
+        });
+        self.write_data.push(src.to_vec());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
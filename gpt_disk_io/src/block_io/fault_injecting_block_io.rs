@@ -0,0 +1,315 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Schedule of failures injected by [`FaultInjectingBlockIo`].
+///
+/// Operations are numbered from zero in the order they are made,
+/// counting only the operations enabled with
+/// [`FaultInjectingBlockIo::set_faulty_operations`]. Every schedule is
+/// deterministic, so a failing test can be reproduced exactly.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum FaultSchedule {
+    /// Never inject a failure.
+    #[default]
+    Never,
+
+    /// Fail the operations whose numbers are in the list.
+    Script(Vec<u64>),
+
+    /// Fail every operation from the given number onward. This
+    /// simulates a crash, or the device being removed.
+    CrashAfter(u64),
+
+    /// Fail each operation with a probability of `1 / one_in`, using a
+    /// pseudorandom number generator seeded with `seed`. A `one_in` of
+    /// zero never fails.
+    Seeded {
+        /// Seed for the pseudorandom number generator.
+        seed: u64,
+
+        /// Inverse of the failure probability.
+        one_in: u32,
+    },
+}
+
+/// Which [`BlockIo`] operations [`FaultInjectingBlockIo`] may fail.
+///
+/// [`BlockIo::num_blocks`] never fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FaultyOperations {
+    /// Fail calls to [`BlockIo::read_blocks`].
+    pub reads: bool,
+
+    /// Fail calls to [`BlockIo::write_blocks`].
+    pub writes: bool,
+
+    /// Fail calls to [`BlockIo::flush`].
+    pub flushes: bool,
+}
+
+impl FaultyOperations {
+    /// Reads, writes, and flushes may all fail.
+    pub const ALL: Self = Self {
+        reads: true,
+        writes: true,
+        flushes: true,
+    };
+
+    /// Only writes and flushes may fail.
+    pub const WRITES: Self = Self {
+        reads: false,
+        writes: true,
+        flushes: true,
+    };
+}
+
+impl Default for FaultyOperations {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Error type used by [`FaultInjectingBlockIo`].
+///
+/// This type implements the [`Error`] trait, with
+/// [`FaultInjectionError::Io`] reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FaultInjectionError<IoError> {
+    /// A failure was injected by the [`FaultSchedule`].
+    Injected {
+        /// Number of the operation that failed.
+        operation: u64,
+    },
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<E: Display> Display for FaultInjectionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Injected { operation } => {
+                write!(f, "injected failure in operation {operation}")
+            }
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for FaultInjectionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Injected { .. } => None,
+        }
+    }
+}
+
+/// Advance a `SplitMix64` generator and return the next output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// [`BlockIo`] wrapper that injects failures on a deterministic
+/// schedule.
+///
+/// This is intended for testing error handling, such as the commit and
+/// recovery paths of [`JournaledBlockIo`]. Wrapping an in-memory
+/// [`BlockIo`] such as [`VecBlockIo`] gives a fully deterministic
+/// device that does no real IO, so it works under Miri.
+///
+/// A failed operation has no effect on the underlying [`BlockIo`].
+///
+/// # Example
+///
+/// Exhaustively test an operation against a crash at every point:
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{
+///     Disk, FaultInjectingBlockIo, FaultSchedule, FaultyOperations,
+///     VecBlockIo,
+/// };
+///
+/// for crash_after in 0.. {
+///     let mut block_io = FaultInjectingBlockIo::new(
+///         VecBlockIo::new(BlockSize::BS_512, 64),
+///         FaultSchedule::CrashAfter(crash_after),
+///     );
+///     block_io.set_faulty_operations(FaultyOperations::WRITES);
+///
+///     let mut disk = Disk::new(block_io).unwrap();
+///     let mut block_buf = [0; 512];
+///     let result = disk
+///         .write_protective_mbr(&mut block_buf)
+///         .and_then(|()| disk.flush());
+///     if result.is_ok() {
+///         break;
+///     }
+///     // ... check that the device is in a consistent state ...
+/// }
+/// ```
+///
+/// [`JournaledBlockIo`]: crate::JournaledBlockIo
+/// [`VecBlockIo`]: crate::VecBlockIo
+#[allow(clippy::module_name_repetitions)]
+pub struct FaultInjectingBlockIo<Io: BlockIo> {
+    io: Io,
+    schedule: FaultSchedule,
+    faulty_operations: FaultyOperations,
+    rng_state: u64,
+    operations: u64,
+    injected_faults: u64,
+}
+
+impl<Io: BlockIo> FaultInjectingBlockIo<Io> {
+    /// Create a `FaultInjectingBlockIo` that fails operations according
+    /// to `schedule`.
+    pub fn new(io: Io, schedule: FaultSchedule) -> Self {
+        let mut this = Self {
+            io,
+            schedule: FaultSchedule::Never,
+            faulty_operations: FaultyOperations::ALL,
+            rng_state: 0,
+            operations: 0,
+            injected_faults: 0,
+        };
+        this.set_schedule(schedule);
+        this
+    }
+
+    /// Replace the schedule. Operation numbering restarts at zero.
+    pub fn set_schedule(&mut self, schedule: FaultSchedule) {
+        if let FaultSchedule::Seeded { seed, .. } = schedule {
+            self.rng_state = seed;
+        }
+        self.schedule = schedule;
+        self.operations = 0;
+    }
+
+    /// Set which operations may fail, and are counted by the schedule.
+    /// By default, this is [`FaultyOperations::ALL`].
+    pub fn set_faulty_operations(&mut self, operations: FaultyOperations) {
+        self.faulty_operations = operations;
+    }
+
+    /// Get the number of operations counted by the schedule so far.
+    #[must_use]
+    pub fn operations(&self) -> u64 {
+        self.operations
+    }
+
+    /// Get the number of failures injected so far.
+    #[must_use]
+    pub fn injected_faults(&self) -> u64 {
+        self.injected_faults
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `FaultInjectingBlockIo` and return the underlying
+    /// [`BlockIo`].
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Count an operation, and return an error if the schedule says it
+    /// should fail.
+    fn check(
+        &mut self,
+        faulty: bool,
+    ) -> Result<(), FaultInjectionError<Io::Error>> {
+        if !faulty {
+            return Ok(());
+        }
+        let operation = self.operations;
+        self.operations += 1;
+
+        let fail = match &self.schedule {
+            FaultSchedule::Never => false,
+            FaultSchedule::Script(ops) => ops.contains(&operation),
+            FaultSchedule::CrashAfter(n) => operation >= *n,
+            FaultSchedule::Seeded { one_in, .. } => {
+                let value = splitmix64(&mut self.rng_state);
+                *one_in != 0 && value % u64::from(*one_in) == 0
+            }
+        };
+        if fail {
+            self.injected_faults += 1;
+            Err(FaultInjectionError::Injected { operation })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<Io: BlockIo> BlockIo for FaultInjectingBlockIo<Io> {
+    type Error = FaultInjectionError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.io.is_read_only()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        self.io.buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.io.num_blocks().map_err(FaultInjectionError::Io)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.check(self.faulty_operations.reads)?;
+        self.io
+            .read_blocks(start_lba, dst)
+            .map_err(FaultInjectionError::Io)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.check(self.faulty_operations.writes)?;
+        self.io
+            .write_blocks(start_lba, src)
+            .map_err(FaultInjectionError::Io)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.check(self.faulty_operations.flushes)?;
+        self.io.flush().map_err(FaultInjectionError::Io)
+    }
+}
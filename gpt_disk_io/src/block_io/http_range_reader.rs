@@ -0,0 +1,233 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::block_io::range_block_io::RangeReader;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Parse the value of a `Content-Range` header, which has the format
+/// `bytes first-last/total`. Returns `first`, and `total` unless it is
+/// `*` (unknown).
+fn parse_content_range(value: &str) -> Result<(u64, Option<u64>), io::Error> {
+    let invalid = || invalid_data("invalid Content-Range");
+    let (range, total) = value
+        .strip_prefix("bytes ")
+        .and_then(|value| value.split_once('/'))
+        .ok_or_else(invalid)?;
+    let (first, _) = range.split_once('-').ok_or_else(invalid)?;
+    let first = first.trim().parse().map_err(|_| invalid())?;
+    let total = if total == "*" {
+        None
+    } else {
+        Some(total.parse().map_err(|_| invalid())?)
+    };
+    Ok((first, total))
+}
+
+/// Minimal [`RangeReader`] that fetches byte ranges of a plain
+/// `http://` URL with HTTP/1.1 range requests.
+///
+/// Each read opens a new connection and sends a `GET` request with a
+/// `Range` header. The server must respond with `206 Partial Content`
+/// and a `Content-Length` header. TLS, redirects, proxies, and chunked
+/// responses are not supported; implement [`RangeReader`] on top of a
+/// full-featured HTTP client if those are needed.
+///
+/// The `Content-Range` header of each response must start at the
+/// requested offset. The total size is read from the `Content-Range`
+/// header of the first response and cached.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{Disk, HttpRangeReader, RangeBlockIo};
+///
+/// let reader = HttpRangeReader::new("http://example.com/disk.img")?;
+/// let mut disk = Disk::new(RangeBlockIo::new(reader, BlockSize::BS_512))?;
+/// let mut block_buf = vec![0; 512];
+/// let header = disk.read_primary_gpt_header_verified(&mut block_buf)?;
+/// println!("{header}");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HttpRangeReader {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Option<Duration>,
+    size: Option<u64>,
+}
+
+impl HttpRangeReader {
+    /// Create a reader for `url`, which must be of the form
+    /// `http://host[:port][/path]`. No connection is made until the
+    /// first read.
+    pub fn new(url: &str) -> Result<Self, io::Error> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "URL must start with http://",
+            )
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|_| {
+                    io::Error::new(ErrorKind::InvalidInput, "invalid port")
+                })?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "empty host"));
+        }
+        Ok(Self {
+            host: host.into(),
+            port,
+            path: path.into(),
+            timeout: None,
+            size: None,
+        })
+    }
+
+    /// Set the read and write timeout of each connection. By default
+    /// there is no timeout.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Value of the `Host` header. The port is only omitted if it is
+    /// the default, as required by RFC 7230 section 5.4.
+    fn host_header(&self) -> String {
+        if self.port == 80 {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// Send a request for `len` bytes at `offset`. Returns a reader
+    /// positioned at the start of the response body, along with the
+    /// body length and the total size from the `Content-Range` header.
+    fn request(
+        &mut self,
+        offset: u64,
+        len: u64,
+    ) -> Result<(BufReader<TcpStream>, u64, Option<u64>), io::Error> {
+        let last = offset
+            .checked_add(len - 1)
+            .ok_or_else(|| invalid_data("range overflow"))?;
+
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nRange: bytes={offset}-{last}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.host_header(),
+        )?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let status = line.split_whitespace().nth(1);
+        if status != Some("206") {
+            return Err(io::Error::other(format!(
+                "expected HTTP status 206, got: {}",
+                line.trim_end()
+            )));
+        }
+
+        let mut content_length = None;
+        let mut content_range = None;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(invalid_data("unexpected end of headers"));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid_data("invalid Content-Length"))?,
+                );
+            } else if name.eq_ignore_ascii_case("content-range") {
+                content_range = Some(parse_content_range(value)?);
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                return Err(invalid_data("unsupported Transfer-Encoding"));
+            }
+        }
+
+        let content_length = content_length
+            .ok_or_else(|| invalid_data("missing Content-Length"))?;
+        let (first, total_size) = content_range
+            .ok_or_else(|| invalid_data("missing Content-Range"))?;
+        if first != offset {
+            return Err(invalid_data(
+                "Content-Range does not match the requested range",
+            ));
+        }
+        Ok((reader, content_length, total_size))
+    }
+}
+
+impl RangeReader for HttpRangeReader {
+    type Error = io::Error;
+
+    fn size(&mut self) -> Result<u64, Self::Error> {
+        if let Some(size) = self.size {
+            return Ok(size);
+        }
+        let (_, _, total_size) = self.request(0, 1)?;
+        let size = total_size
+            .ok_or_else(|| invalid_data("missing size in Content-Range"))?;
+        self.size = Some(size);
+        Ok(size)
+    }
+
+    fn read_range(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        if dst.is_empty() {
+            return Ok(());
+        }
+        let len = u64::try_from(dst.len())
+            .map_err(|_| invalid_data("range overflow"))?;
+        let (mut reader, content_length, total_size) =
+            self.request(offset, len)?;
+        if content_length != len {
+            return Err(invalid_data("response has the wrong length"));
+        }
+        if self.size.is_none() {
+            self.size = total_size;
+        }
+        reader.read_exact(dst)
+    }
+}
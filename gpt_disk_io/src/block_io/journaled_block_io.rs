@@ -0,0 +1,589 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{crc, BlockSize, Crc32, Lba};
+
+/// Magic bytes at the start of a journal header block.
+const JOURNAL_MAGIC: [u8; 8] = *b"GPTJOURN";
+
+/// Journal format version.
+const JOURNAL_VERSION: u32 = 1;
+
+/// Size in bytes of the used part of the journal header block.
+const JOURNAL_HEADER_SIZE: usize = 32;
+
+/// Size in bytes of a record descriptor: start LBA and number of
+/// blocks.
+const JOURNAL_DESCRIPTOR_SIZE: usize = 16;
+
+fn calc_crc32(bytes: &[u8]) -> u32 {
+    crc::Crc::<u32>::new(&Crc32::ALGORITHM).checksum(bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    // OK to unwrap: the slice has the right length.
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    // OK to unwrap: the slice has the right length.
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// Error type used by [`JournaledBlockIo::commit`] and
+/// [`JournaledBlockIo::recover`].
+///
+/// This type implements the [`Error`] trait, with [`JournalError::Io`]
+/// reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum JournalError<IoError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// The pending writes do not fit in the journal region.
+    JournalTooSmall {
+        /// Number of blocks needed for the journal.
+        required_blocks: u64,
+    },
+
+    /// A pending write overlaps the journal region.
+    JournalOverlap,
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<E> From<E> for JournalError<E> {
+    fn from(err: E) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<E: Display> Display for JournalError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::JournalTooSmall { required_blocks } => write!(
+                f,
+                "journal region is too small: {required_blocks} blocks required"
+            ),
+            Self::JournalOverlap => {
+                f.write_str("write overlaps the journal region")
+            }
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for JournalError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// How [`JournaledBlockIo::recover`] should handle a complete journal
+/// left behind by an interrupted commit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum JournalRecovery {
+    /// Write the new data, completing the interrupted commit.
+    Replay,
+
+    /// Write the old data, undoing any part of the commit that was
+    /// already applied.
+    RollBack,
+}
+
+/// A contiguous run of pending blocks.
+struct JournalRecord {
+    start_lba: u64,
+    num_blocks: u64,
+}
+
+/// [`BlockIo`] wrapper that makes a group of writes crash-safe with a
+/// write-ahead journal.
+///
+/// Writes are kept in memory (and are visible to later reads through
+/// the wrapper) until [`commit`] is called. The commit then:
+///
+/// 1. Writes a journal containing both the old and new contents of
+///    every modified block to the journal region, and flushes.
+/// 2. Writes the new data to its final location, and flushes.
+/// 3. Invalidates the journal, and flushes.
+///
+/// If the commit is interrupted, for example by a power failure, call
+/// [`recover`] the next time the device is opened. A journal that was
+/// not completely written is ignored, since no other blocks have been
+/// modified yet. A complete journal is either replayed or rolled back,
+/// so the GPT headers and partition entry arrays are never left in a
+/// mix of old and new states.
+///
+/// The journal region is a range of blocks on the same device, chosen
+/// by the caller, that is not used by anything else (for example, space
+/// reserved outside of any partition). [`required_journal_blocks`] gives
+/// the size needed for the pending writes. Writes overlapping the
+/// journal region fail when committed.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::{BlockSize, Lba};
+/// use gpt_disk_io::{BlockIoAdapter, Disk, JournalRecovery, JournaledBlockIo};
+///
+/// let mut storage = vec![0; 512 * 256];
+/// let block_io =
+///     BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+///
+/// // Use the last 32 blocks for the journal.
+/// let mut block_io = JournaledBlockIo::new(block_io, Lba(224), 32);
+///
+/// // Replay any commit that was interrupted.
+/// block_io.recover(JournalRecovery::Replay).unwrap();
+///
+/// let mut disk = Disk::new(&mut block_io).unwrap();
+/// let mut block_buf = [0; 512];
+/// disk.write_protective_mbr(&mut block_buf).unwrap();
+/// // ... write GPT headers and partition entries ...
+/// drop(disk);
+///
+/// block_io.commit().unwrap();
+/// ```
+///
+/// [`commit`]: Self::commit
+/// [`recover`]: Self::recover
+/// [`required_journal_blocks`]: Self::required_journal_blocks
+#[allow(clippy::module_name_repetitions)]
+pub struct JournaledBlockIo<Io: BlockIo> {
+    io: Io,
+    journal_lba: Lba,
+    journal_blocks: u64,
+    /// Pending data for each written block, keyed by LBA.
+    pending: BTreeMap<u64, Vec<u8>>,
+}
+
+impl<Io: BlockIo> JournaledBlockIo<Io> {
+    /// Create a `JournaledBlockIo` with no pending writes. The journal
+    /// is stored in the `journal_blocks` blocks starting at
+    /// `journal_lba`.
+    pub fn new(io: Io, journal_lba: Lba, journal_blocks: u64) -> Self {
+        Self {
+            io,
+            journal_lba,
+            journal_blocks,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Get the first block of the journal region.
+    #[must_use]
+    pub fn journal_lba(&self) -> Lba {
+        self.journal_lba
+    }
+
+    /// Get the number of blocks in the journal region.
+    #[must_use]
+    pub fn journal_blocks(&self) -> u64 {
+        self.journal_blocks
+    }
+
+    /// Whether there are writes that have not been committed.
+    #[must_use]
+    pub fn has_pending_writes(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Discard all pending writes.
+    pub fn discard(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `JournaledBlockIo` and return the underlying
+    /// [`BlockIo`]. Pending writes are discarded.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    fn block_size_as_usize(&self) -> Result<usize, JournalError<Io::Error>> {
+        self.io
+            .block_size()
+            .to_usize()
+            .ok_or(JournalError::Overflow)
+    }
+
+    /// Group the pending blocks into contiguous runs.
+    fn records(&self) -> Vec<JournalRecord> {
+        let mut records: Vec<JournalRecord> = Vec::new();
+        for &lba in self.pending.keys() {
+            if let Some(last) = records.last_mut() {
+                if last.start_lba + last.num_blocks == lba {
+                    last.num_blocks += 1;
+                    continue;
+                }
+            }
+            records.push(JournalRecord {
+                start_lba: lba,
+                num_blocks: 1,
+            });
+        }
+        records
+    }
+
+    /// Number of descriptor blocks needed for `num_records` records.
+    fn descriptor_blocks(&self, num_records: usize) -> Option<u64> {
+        let bytes = num_records.checked_mul(JOURNAL_DESCRIPTOR_SIZE)?;
+        let blocks = bytes.div_ceil(self.block_size_as_usize().ok()?);
+        u64::try_from(blocks).ok()
+    }
+
+    /// Get the number of blocks the journal needs for the current
+    /// pending writes. This includes one header block, the record
+    /// descriptors, and two copies (old and new) of each modified
+    /// block.
+    pub fn required_journal_blocks(&self) -> Option<u64> {
+        let num_pending = u64::try_from(self.pending.len()).ok()?;
+        self.descriptor_blocks(self.records().len())?
+            .checked_add(num_pending.checked_mul(2)?)?
+            .checked_add(1)
+    }
+
+    /// Write the pending writes to the underlying [`BlockIo`] using
+    /// the journal. See the [type documentation] for details. If there
+    /// are no pending writes, nothing is written.
+    ///
+    /// On success, the pending writes are cleared. On failure, they are
+    /// kept, and [`recover`] should be called before retrying. Note
+    /// that if the failure occurs while invalidating the journal, the
+    /// new data has already been written, and there may be no journal
+    /// left to roll back.
+    ///
+    /// [`recover`]: Self::recover
+    /// [type documentation]: Self
+    pub fn commit(&mut self) -> Result<(), JournalError<Io::Error>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let journal_start = self.journal_lba.to_u64();
+        let journal_end = journal_start
+            .checked_add(self.journal_blocks)
+            .ok_or(JournalError::Overflow)?;
+        if self
+            .pending
+            .range(journal_start..journal_end)
+            .next()
+            .is_some()
+        {
+            return Err(JournalError::JournalOverlap);
+        }
+
+        let required_blocks = self
+            .required_journal_blocks()
+            .ok_or(JournalError::Overflow)?;
+        if required_blocks > self.journal_blocks {
+            return Err(JournalError::JournalTooSmall { required_blocks });
+        }
+
+        let journal = self.build_journal()?;
+
+        // Write the payload before the header, so that a journal with
+        // a valid header is always complete.
+        let block_size = self.block_size_as_usize()?;
+        let (header, payload) = journal.split_at(block_size);
+        self.io.write_blocks(Lba(journal_start + 1), payload)?;
+        self.io.flush()?;
+        self.io.write_blocks(self.journal_lba, header)?;
+        self.io.flush()?;
+
+        for (&lba, data) in &self.pending {
+            self.io.write_blocks(Lba(lba), data)?;
+        }
+        self.io.flush()?;
+
+        self.clear_journal()?;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Serialize the journal, reading the old contents of each
+    /// modified block from the underlying [`BlockIo`].
+    fn build_journal(&mut self) -> Result<Vec<u8>, JournalError<Io::Error>> {
+        let block_size = self.block_size_as_usize()?;
+        let records = self.records();
+        let descriptor_blocks = self
+            .descriptor_blocks(records.len())
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or(JournalError::Overflow)?;
+
+        let mut journal = vec![0; block_size * (1 + descriptor_blocks)];
+        for (i, record) in records.iter().enumerate() {
+            let offset = block_size + i * JOURNAL_DESCRIPTOR_SIZE;
+            journal[offset..offset + 8]
+                .copy_from_slice(&record.start_lba.to_le_bytes());
+            journal[offset + 8..offset + 16]
+                .copy_from_slice(&record.num_blocks.to_le_bytes());
+        }
+
+        for record in &records {
+            let len = usize::try_from(record.num_blocks)
+                .ok()
+                .and_then(|n| n.checked_mul(block_size))
+                .ok_or(JournalError::Overflow)?;
+            let start = journal.len();
+            journal.resize(start + len, 0);
+            self.io
+                .read_blocks(Lba(record.start_lba), &mut journal[start..])?;
+            for lba in record.start_lba..record.start_lba + record.num_blocks {
+                journal.extend_from_slice(&self.pending[&lba]);
+            }
+        }
+
+        let payload_blocks = u64::try_from(journal.len() / block_size - 1)
+            .map_err(|_| JournalError::Overflow)?;
+        let num_records =
+            u32::try_from(records.len()).map_err(|_| JournalError::Overflow)?;
+        let payload_crc32 = calc_crc32(&journal[block_size..]);
+
+        let header = &mut journal[..JOURNAL_HEADER_SIZE];
+        header[0..8].copy_from_slice(&JOURNAL_MAGIC);
+        header[8..12].copy_from_slice(&JOURNAL_VERSION.to_le_bytes());
+        header[12..16].copy_from_slice(&num_records.to_le_bytes());
+        header[16..24].copy_from_slice(&payload_blocks.to_le_bytes());
+        header[24..28].copy_from_slice(&payload_crc32.to_le_bytes());
+        let header_crc32 = calc_crc32(&header[..28]);
+        header[28..32].copy_from_slice(&header_crc32.to_le_bytes());
+
+        Ok(journal)
+    }
+
+    /// Invalidate the journal by zeroing its header block.
+    fn clear_journal(&mut self) -> Result<(), JournalError<Io::Error>> {
+        let zeros = vec![0; self.block_size_as_usize()?];
+        self.io.write_blocks(self.journal_lba, &zeros)?;
+        self.io.flush()?;
+        Ok(())
+    }
+
+    /// Read the journal, if a complete one is present.
+    fn read_journal(
+        &mut self,
+    ) -> Result<Option<Vec<u8>>, JournalError<Io::Error>> {
+        let block_size = self.block_size_as_usize()?;
+        let mut header = vec![0; block_size];
+        self.io.read_blocks(self.journal_lba, &mut header)?;
+
+        if header[0..8] != JOURNAL_MAGIC
+            || read_u32(&header, 8) != JOURNAL_VERSION
+            || read_u32(&header, 28) != calc_crc32(&header[..28])
+        {
+            return Ok(None);
+        }
+        let payload_blocks = read_u64(&header, 16);
+        if payload_blocks >= self.journal_blocks {
+            return Ok(None);
+        }
+        let Some(payload_len) = usize::try_from(payload_blocks)
+            .ok()
+            .and_then(|n| n.checked_mul(block_size))
+        else {
+            return Ok(None);
+        };
+
+        let mut journal = header;
+        journal.resize(block_size + payload_len, 0);
+        self.io.read_blocks(
+            Lba(self.journal_lba.to_u64() + 1),
+            &mut journal[block_size..],
+        )?;
+        if read_u32(&journal, 24) != calc_crc32(&journal[block_size..]) {
+            return Ok(None);
+        }
+        Ok(Some(journal))
+    }
+
+    /// Read the record descriptors of a journal returned by
+    /// [`read_journal`]. Returns the offset of the first record's data
+    /// along with the records, or `None` if the descriptors and data do
+    /// not exactly fill the journal.
+    ///
+    /// [`read_journal`]: Self::read_journal
+    fn journal_records(
+        &self,
+        journal: &[u8],
+    ) -> Option<(usize, Vec<JournalRecord>)> {
+        let block_size = self.block_size_as_usize().ok()?;
+        let num_records = usize::try_from(read_u32(journal, 12)).ok()?;
+        let descriptor_blocks =
+            usize::try_from(self.descriptor_blocks(num_records)?).ok()?;
+
+        let data_offset =
+            descriptor_blocks.checked_add(1)?.checked_mul(block_size)?;
+        if data_offset > journal.len() {
+            return None;
+        }
+
+        let mut records = Vec::with_capacity(num_records);
+        let mut end = data_offset;
+        for i in 0..num_records {
+            let desc = block_size + i * JOURNAL_DESCRIPTOR_SIZE;
+            let record = JournalRecord {
+                start_lba: read_u64(journal, desc),
+                num_blocks: read_u64(journal, desc + 8),
+            };
+            let len = usize::try_from(record.num_blocks)
+                .ok()?
+                .checked_mul(block_size)?;
+            end = end.checked_add(len.checked_mul(2)?)?;
+            records.push(record);
+        }
+        if end != journal.len() {
+            return None;
+        }
+        Some((data_offset, records))
+    }
+
+    /// Check for a journal left behind by an interrupted [`commit`],
+    /// and apply it according to `action`. The journal is then
+    /// invalidated. Pending writes are discarded.
+    ///
+    /// Returns `true` if a complete journal was found and applied.
+    /// A partially written journal, or one whose record descriptors do
+    /// not match its length, is invalidated without modifying any other
+    /// blocks, and `false` is returned.
+    ///
+    /// This should be called before any other operation when the device
+    /// is opened.
+    ///
+    /// [`commit`]: Self::commit
+    pub fn recover(
+        &mut self,
+        action: JournalRecovery,
+    ) -> Result<bool, JournalError<Io::Error>> {
+        self.pending.clear();
+
+        let block_size = self.block_size_as_usize()?;
+        let Some(journal) = self.read_journal()? else {
+            // Clear out any partial journal so that it cannot be
+            // mistaken for a complete one later.
+            let mut header = vec![0; block_size];
+            self.io.read_blocks(self.journal_lba, &mut header)?;
+            if header[0..8] == JOURNAL_MAGIC {
+                self.clear_journal()?;
+            }
+            return Ok(false);
+        };
+
+        // Validate every record before writing anything, so that an
+        // inconsistent journal is treated like an incomplete one.
+        let Some((mut offset, records)) = self.journal_records(&journal) else {
+            self.clear_journal()?;
+            return Ok(false);
+        };
+
+        for record in records {
+            // OK to unwrap and do unchecked arithmetic:
+            // `journal_records` checked that the data fits in the
+            // journal.
+            let len = usize::try_from(record.num_blocks).unwrap() * block_size;
+            let old = &journal[offset..offset + len];
+            let new = &journal[offset + len..offset + 2 * len];
+            let data = match action {
+                JournalRecovery::Replay => new,
+                JournalRecovery::RollBack => old,
+            };
+            self.io.write_blocks(Lba(record.start_lba), data)?;
+            offset += 2 * len;
+        }
+        self.io.flush()?;
+
+        self.clear_journal()?;
+        Ok(true)
+    }
+}
+
+impl<Io: BlockIo> BlockIo for JournaledBlockIo<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.io.is_read_only()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        self.io.buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.io.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.io.read_blocks(start_lba, dst)?;
+
+        // OK to unwrap: a block buffer was passed in, so the block
+        // size fits in a `usize`.
+        let block_size = self.io.block_size().to_usize().unwrap();
+        let start = start_lba.to_u64();
+        for (lba, chunk) in (start..).zip(dst.chunks_exact_mut(block_size)) {
+            if let Some(data) = self.pending.get(&lba) {
+                chunk.copy_from_slice(data);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.io.block_size().assert_valid_block_buffer(src);
+
+        // OK to unwrap: a block buffer was passed in, so the block
+        // size fits in a `usize`.
+        let block_size = self.io.block_size().to_usize().unwrap();
+        let start = start_lba.to_u64();
+        for (lba, chunk) in (start..).zip(src.chunks_exact(block_size)) {
+            self.pending.insert(lba, chunk.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Pending writes are only written by [`commit`], so this does
+    /// nothing.
+    ///
+    /// [`commit`]: Self::commit
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use gpt_disk_types::{BlockSize, Lba};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+const NBD_MAGIC: u64 = 0x4e42_444d_4147_4943; // "NBDMAGIC"
+const IHAVEOPT: u64 = 0x4948_4156_454f_5054; // "IHAVEOPT"
+
+const NBD_FLAG_FIXED_NEWSTYLE: u16 = 1 << 0;
+const NBD_FLAG_NO_ZEROES: u16 = 1 << 1;
+const NBD_FLAG_C_FIXED_NEWSTYLE: u32 = 1 << 0;
+const NBD_FLAG_C_NO_ZEROES: u32 = 1 << 1;
+
+const NBD_OPT_EXPORT_NAME: u32 = 1;
+
+const NBD_FLAG_READ_ONLY: u16 = 1 << 1;
+const NBD_FLAG_SEND_FLUSH: u16 = 1 << 2;
+
+const NBD_REQUEST_MAGIC: u32 = 0x2560_9513;
+const NBD_SIMPLE_REPLY_MAGIC: u32 = 0x6744_6698;
+
+const NBD_CMD_READ: u16 = 0;
+const NBD_CMD_WRITE: u16 = 1;
+const NBD_CMD_DISC: u16 = 2;
+const NBD_CMD_FLUSH: u16 = 3;
+
+/// Largest request sent to the server. Many servers reject requests
+/// larger than 32MiB, so bigger reads and writes are split up.
+const MAX_REQUEST_LEN: usize = 32 * 1024 * 1024;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn read_u16(stream: &mut impl Read) -> Result<u16, io::Error> {
+    let mut buf = [0; 2];
+    stream.read_exact(&mut buf)?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+fn read_u32(stream: &mut impl Read) -> Result<u32, io::Error> {
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_u64(stream: &mut impl Read) -> Result<u64, io::Error> {
+    let mut buf = [0; 8];
+    stream.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+/// [`BlockIo`] client for a disk exported over the [network block
+/// device] (NBD) protocol, for example by `qemu-nbd` or `nbdkit`.
+///
+/// The client uses the fixed newstyle handshake to select an export
+/// by name, then sends simple read, write, and flush requests one at a
+/// time. TLS and structured replies are not supported.
+///
+/// [`connect`] opens a TCP connection. Any other stream implementing
+/// [`Read`] and [`Write`], such as a `UnixStream` connected to the
+/// socket of `qemu-nbd --socket`, can be used with [`from_stream`].
+///
+/// If the server exports the disk read-only, [`BlockIo::is_read_only`]
+/// returns `true` and writes fail with [`ErrorKind::PermissionDenied`]
+/// without being sent. A disconnect request is sent when the
+/// `NbdBlockIo` is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{Disk, NbdBlockIo};
+///
+/// // Connect to `qemu-nbd --export-name=disk disk.img`.
+/// let block_io = NbdBlockIo::connect("localhost:10809", "disk", BlockSize::BS_512)?;
+/// let mut disk = Disk::new(block_io)?;
+/// let mut block_buf = vec![0; 512];
+/// let header = disk.read_primary_gpt_header_verified(&mut block_buf)?;
+/// println!("{header}");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`connect`]: Self::connect
+/// [`from_stream`]: Self::from_stream
+/// [network block device]: https://github.com/NetworkBlockDevice/nbd/blob/master/doc/proto.md
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct NbdBlockIo<S: Read + Write = TcpStream> {
+    stream: S,
+    block_size: BlockSize,
+    export_size: u64,
+    transmission_flags: u16,
+    next_handle: u64,
+}
+
+impl NbdBlockIo<TcpStream> {
+    /// Connect to an NBD server over TCP and open the export named
+    /// `export_name`. Many servers accept an empty name for their
+    /// default export.
+    pub fn connect<A: ToSocketAddrs>(
+        addr: A,
+        export_name: &str,
+        block_size: BlockSize,
+    ) -> Result<Self, io::Error> {
+        let stream = TcpStream::connect(addr)?;
+        // Requests are small and sent one at a time, so don't wait to
+        // batch them.
+        stream.set_nodelay(true)?;
+        Self::from_stream(stream, export_name, block_size)
+    }
+}
+
+impl<S: Read + Write> NbdBlockIo<S> {
+    /// Perform the NBD handshake over `stream` and open the export
+    /// named `export_name`.
+    pub fn from_stream(
+        mut stream: S,
+        export_name: &str,
+        block_size: BlockSize,
+    ) -> Result<Self, io::Error> {
+        if read_u64(&mut stream)? != NBD_MAGIC
+            || read_u64(&mut stream)? != IHAVEOPT
+        {
+            return Err(invalid_data("not a newstyle NBD server"));
+        }
+        let handshake_flags = read_u16(&mut stream)?;
+        if handshake_flags & NBD_FLAG_FIXED_NEWSTYLE == 0 {
+            return Err(invalid_data("NBD server is not fixed newstyle"));
+        }
+        let no_zeroes = handshake_flags & NBD_FLAG_NO_ZEROES != 0;
+        let mut client_flags = NBD_FLAG_C_FIXED_NEWSTYLE;
+        if no_zeroes {
+            client_flags |= NBD_FLAG_C_NO_ZEROES;
+        }
+
+        let name_len = u32::try_from(export_name.len()).map_err(|_| {
+            io::Error::new(ErrorKind::InvalidInput, "export name too long")
+        })?;
+        let mut msg = Vec::with_capacity(20 + export_name.len());
+        msg.extend(client_flags.to_be_bytes());
+        msg.extend(IHAVEOPT.to_be_bytes());
+        msg.extend(NBD_OPT_EXPORT_NAME.to_be_bytes());
+        msg.extend(name_len.to_be_bytes());
+        msg.extend(export_name.as_bytes());
+        stream.write_all(&msg)?;
+        stream.flush()?;
+
+        // The server closes the connection if the export does not
+        // exist, so an unexpected EOF here is reported as such.
+        let export_size = read_u64(&mut stream).map_err(|err| {
+            if err.kind() == ErrorKind::UnexpectedEof {
+                io::Error::new(ErrorKind::NotFound, "NBD export not found")
+            } else {
+                err
+            }
+        })?;
+        let transmission_flags = read_u16(&mut stream)?;
+        if !no_zeroes {
+            let mut zeroes = [0; 124];
+            stream.read_exact(&mut zeroes)?;
+        }
+
+        Ok(Self {
+            stream,
+            block_size,
+            export_size,
+            transmission_flags,
+            next_handle: 0,
+        })
+    }
+
+    /// Get the size of the export in bytes.
+    #[must_use]
+    pub fn export_size(&self) -> u64 {
+        self.export_size
+    }
+
+    /// Get a reference to the underlying stream.
+    pub fn stream(&self) -> &S {
+        &self.stream
+    }
+
+    /// Send a request and wait for its reply. For reads, the reply data
+    /// is read into `dst`. For writes, `src` is sent after the request.
+    fn request(
+        &mut self,
+        command: u16,
+        offset: u64,
+        len: usize,
+        src: &[u8],
+        dst: &mut [u8],
+    ) -> Result<(), io::Error> {
+        let handle = self.next_handle;
+        self.next_handle = self.next_handle.wrapping_add(1);
+        // OK to unwrap: `len` is at most `MAX_REQUEST_LEN`.
+        let len = u32::try_from(len).unwrap();
+
+        let mut msg = [0; 28];
+        msg[0..4].copy_from_slice(&NBD_REQUEST_MAGIC.to_be_bytes());
+        // Bytes 4..6 are the command flags, which are not used.
+        msg[6..8].copy_from_slice(&command.to_be_bytes());
+        msg[8..16].copy_from_slice(&handle.to_be_bytes());
+        msg[16..24].copy_from_slice(&offset.to_be_bytes());
+        msg[24..28].copy_from_slice(&len.to_be_bytes());
+        self.stream.write_all(&msg)?;
+        self.stream.write_all(src)?;
+        self.stream.flush()?;
+
+        if read_u32(&mut self.stream)? != NBD_SIMPLE_REPLY_MAGIC {
+            return Err(invalid_data("invalid NBD reply magic"));
+        }
+        let error = read_u32(&mut self.stream)?;
+        if read_u64(&mut self.stream)? != handle {
+            return Err(invalid_data("NBD reply has the wrong handle"));
+        }
+        if error != 0 {
+            return Err(io::Error::other(format!(
+                "NBD server returned error {error}"
+            )));
+        }
+        self.stream.read_exact(dst)
+    }
+
+    /// Get the byte offset of `lba`, returning an error on overflow.
+    fn byte_offset(&self, lba: Lba, len: usize) -> Result<u64, io::Error> {
+        let overflow =
+            || io::Error::new(ErrorKind::InvalidInput, "LBA overflow");
+        let offset = lba
+            .to_u64()
+            .checked_mul(self.block_size.to_u64())
+            .ok_or_else(overflow)?;
+        let end = u64::try_from(len)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or_else(overflow)?;
+        if end > self.export_size {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "access past the end of the NBD export",
+            ));
+        }
+        Ok(offset)
+    }
+}
+
+impl<S: Read + Write> BlockIo for NbdBlockIo<S> {
+    type Error = io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    /// Returns `true` if the server exports the disk read-only.
+    fn is_read_only(&self) -> bool {
+        self.transmission_flags & NBD_FLAG_READ_ONLY != 0
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.export_size / self.block_size.to_u64())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(dst);
+
+        let mut offset = self.byte_offset(start_lba, dst.len())?;
+        for chunk in dst.chunks_mut(MAX_REQUEST_LEN) {
+            self.request(NBD_CMD_READ, offset, chunk.len(), &[], chunk)?;
+            // OK to unwrap: the chunk is at most `MAX_REQUEST_LEN`.
+            offset += u64::try_from(chunk.len()).unwrap();
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(src);
+
+        if self.is_read_only() {
+            return Err(io::Error::new(
+                ErrorKind::PermissionDenied,
+                "NBD export is read-only",
+            ));
+        }
+        let mut offset = self.byte_offset(start_lba, src.len())?;
+        for chunk in src.chunks(MAX_REQUEST_LEN) {
+            self.request(NBD_CMD_WRITE, offset, chunk.len(), chunk, &mut [])?;
+            // OK to unwrap: the chunk is at most `MAX_REQUEST_LEN`.
+            offset += u64::try_from(chunk.len()).unwrap();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Servers that don't advertise flush support write through.
+        if self.transmission_flags & NBD_FLAG_SEND_FLUSH == 0 {
+            return Ok(());
+        }
+        self.request(NBD_CMD_FLUSH, 0, 0, &[], &mut [])
+    }
+}
+
+impl<S: Read + Write> Drop for NbdBlockIo<S> {
+    fn drop(&mut self) {
+        // The server does not reply to a disconnect. Errors are
+        // ignored, since the connection is being closed anyway.
+        let mut msg = [0; 28];
+        msg[0..4].copy_from_slice(&NBD_REQUEST_MAGIC.to_be_bytes());
+        msg[6..8].copy_from_slice(&NBD_CMD_DISC.to_be_bytes());
+        msg[8..16].copy_from_slice(&self.next_handle.to_be_bytes());
+        let _ = self.stream.write_all(&msg);
+        let _ = self.stream.flush();
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::error::Error;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Error type used by [`OffsetBlockIo`].
+///
+/// This type implements the [`Error`] trait, with
+/// [`OffsetBlockIoError::Io`] reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum OffsetBlockIoError<IoError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// A byte offset or length is not a multiple of the block size.
+    Unaligned,
+
+    /// The range is not within the underlying [`BlockIo`], or an
+    /// access is not within the range.
+    OutOfBounds,
+
+    /// Error from the underlying [`BlockIo`].
+    Io(IoError),
+}
+
+impl<E: Display> Display for OffsetBlockIoError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::Unaligned => f.write_str(
+                "byte offset or length is not a multiple of the block size",
+            ),
+            Self::OutOfBounds => f.write_str("block range is out of bounds"),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for OffsetBlockIoError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// [`BlockIo`] wrapper that presents a range of blocks of another
+/// [`BlockIo`] as a standalone disk.
+///
+/// Block `n` of the `OffsetBlockIo` is block `start_lba + n` of the
+/// underlying [`BlockIo`]. This allows a disk image nested inside a
+/// larger container, such as a VM image or a firmware dump, to be
+/// opened with [`Disk`]. [`Disk::find_nested_gpts`] can be used to
+/// find the range.
+///
+/// The block size is the same as that of the underlying [`BlockIo`].
+///
+/// [`Disk`]: crate::Disk
+/// [`Disk::find_nested_gpts`]: crate::Disk::find_nested_gpts
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct OffsetBlockIo<Io> {
+    io: Io,
+    start_lba: u64,
+    num_blocks: u64,
+}
+
+impl<Io: BlockIo> OffsetBlockIo<Io> {
+    /// Create an `OffsetBlockIo` for `num_blocks` blocks of `io`,
+    /// starting at `start_lba`.
+    ///
+    /// Returns [`OffsetBlockIoError::OutOfBounds`] if the range extends
+    /// past the end of `io`.
+    pub fn new(
+        mut io: Io,
+        start_lba: Lba,
+        num_blocks: u64,
+    ) -> Result<Self, OffsetBlockIoError<Io::Error>> {
+        let end = start_lba
+            .to_u64()
+            .checked_add(num_blocks)
+            .ok_or(OffsetBlockIoError::Overflow)?;
+        if end > io.num_blocks().map_err(OffsetBlockIoError::Io)? {
+            return Err(OffsetBlockIoError::OutOfBounds);
+        }
+        Ok(Self {
+            io,
+            start_lba: start_lba.to_u64(),
+            num_blocks,
+        })
+    }
+
+    /// Create an `OffsetBlockIo` for all the blocks of `io` from
+    /// `start_lba` to the end.
+    ///
+    /// Returns [`OffsetBlockIoError::OutOfBounds`] if `start_lba` is
+    /// past the end of `io`.
+    pub fn to_end(
+        mut io: Io,
+        start_lba: Lba,
+    ) -> Result<Self, OffsetBlockIoError<Io::Error>> {
+        let num_blocks = io
+            .num_blocks()
+            .map_err(OffsetBlockIoError::Io)?
+            .checked_sub(start_lba.to_u64())
+            .ok_or(OffsetBlockIoError::OutOfBounds)?;
+        Self::new(io, start_lba, num_blocks)
+    }
+
+    /// Create an `OffsetBlockIo` for `len` bytes of `io`, starting at
+    /// byte `offset`.
+    ///
+    /// Returns [`OffsetBlockIoError::Unaligned`] if `offset` or `len`
+    /// is not a multiple of the block size, and
+    /// [`OffsetBlockIoError::OutOfBounds`] if the range extends past
+    /// the end of `io`.
+    pub fn from_byte_range(
+        io: Io,
+        offset: u64,
+        len: u64,
+    ) -> Result<Self, OffsetBlockIoError<Io::Error>> {
+        let block_size = io.block_size().to_u64();
+        if offset % block_size != 0 || len % block_size != 0 {
+            return Err(OffsetBlockIoError::Unaligned);
+        }
+        Self::new(io, Lba(offset / block_size), len / block_size)
+    }
+
+    /// Get the LBA in the underlying [`BlockIo`] of the first block.
+    #[must_use]
+    pub fn start_lba(&self) -> Lba {
+        Lba(self.start_lba)
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `OffsetBlockIo` and return the underlying
+    /// [`BlockIo`].
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Convert an access of `len` bytes at `start_lba` to an LBA in the
+    /// underlying [`BlockIo`], checking that it is within the range.
+    fn inner_lba(
+        &self,
+        start_lba: Lba,
+        len: usize,
+    ) -> Result<Lba, OffsetBlockIoError<Io::Error>> {
+        assert!(self.io.block_size().is_multiple_of_block_size(len));
+
+        let num_blocks = u64::try_from(len)
+            .map_err(|_| OffsetBlockIoError::Overflow)?
+            / self.io.block_size().to_u64();
+        let end = start_lba
+            .to_u64()
+            .checked_add(num_blocks)
+            .ok_or(OffsetBlockIoError::Overflow)?;
+        if end > self.num_blocks {
+            return Err(OffsetBlockIoError::OutOfBounds);
+        }
+        // OK to unwrap: `new` checked that the range fits in the
+        // underlying `BlockIo`.
+        Ok(Lba(self.start_lba.checked_add(start_lba.to_u64()).unwrap()))
+    }
+}
+
+impl<Io: BlockIo> BlockIo for OffsetBlockIo<Io> {
+    type Error = OffsetBlockIoError<Io::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.io.is_read_only()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        self.io.buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.num_blocks)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let lba = self.inner_lba(start_lba, dst.len())?;
+        self.io
+            .read_blocks(lba, dst)
+            .map_err(OffsetBlockIoError::Io)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        let lba = self.inner_lba(start_lba, src.len())?;
+        self.io
+            .write_blocks(lba, src)
+            .map_err(OffsetBlockIoError::Io)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush().map_err(OffsetBlockIoError::Io)
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use core::error::Error;
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Source of bytes that can be read at arbitrary offsets, such as a
+/// file on a remote server accessed with HTTP range requests.
+///
+/// This is the transport used by [`RangeBlockIo`]. With the `std`
+/// feature, [`HttpRangeReader`] implements it for plain `http://` URLs.
+/// Other transports (such as an HTTPS client) can be plugged in by
+/// implementing this trait.
+///
+/// [`HttpRangeReader`]: crate::HttpRangeReader
+pub trait RangeReader {
+    /// Transport error type.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Get the total size of the data in bytes.
+    fn size(&mut self) -> Result<u64, Self::Error>;
+
+    /// Read exactly `dst.len()` bytes starting at byte `offset`.
+    fn read_range(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Error type used by [`RangeBlockIo`].
+///
+/// This type implements the [`Error`] trait, with
+/// [`RangeBlockIoError::Transport`] reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RangeBlockIoError<TransportError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// Attempted to write. [`RangeBlockIo`] is read-only.
+    ReadOnly,
+
+    /// Error from the [`RangeReader`].
+    Transport(TransportError),
+}
+
+impl<E: Display> Display for RangeBlockIoError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::ReadOnly => {
+                f.write_str("attempted to write to a read-only range reader")
+            }
+            Self::Transport(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for RangeBlockIoError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Read-only [`BlockIo`] that reads blocks through a [`RangeReader`].
+///
+/// This allows the partition table of a disk image to be inspected
+/// without reading the whole image, for example from a remote server
+/// that supports HTTP range requests. Each call to [`read_blocks`]
+/// results in one call to [`RangeReader::read_range`], so reading a
+/// partition entry array with [`Disk::read_gpt_partition_entry_array`]
+/// is much cheaper than iterating over it one block at a time.
+///
+/// Writes fail with [`RangeBlockIoError::ReadOnly`].
+///
+/// [`Disk::read_gpt_partition_entry_array`]: crate::Disk::read_gpt_partition_entry_array
+/// [`read_blocks`]: BlockIo::read_blocks
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct RangeBlockIo<R> {
+    reader: R,
+    block_size: BlockSize,
+}
+
+impl<R: RangeReader> RangeBlockIo<R> {
+    /// Create a `RangeBlockIo`.
+    pub fn new(reader: R, block_size: BlockSize) -> Self {
+        Self { reader, block_size }
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume the `RangeBlockIo` and return the underlying reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: RangeReader> BlockIo for RangeBlockIo<R> {
+    type Error = RangeBlockIoError<R::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        let len = self.reader.size().map_err(RangeBlockIoError::Transport)?;
+        Ok(len / self.block_size.to_u64())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(dst);
+
+        let offset = start_lba
+            .to_u64()
+            .checked_mul(self.block_size.to_u64())
+            .ok_or(RangeBlockIoError::Overflow)?;
+        self.reader
+            .read_range(offset, dst)
+            .map_err(RangeBlockIoError::Transport)
+    }
+
+    fn write_blocks(
+        &mut self,
+        _start_lba: Lba,
+        _src: &[u8],
+    ) -> Result<(), Self::Error> {
+        Err(RangeBlockIoError::ReadOnly)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
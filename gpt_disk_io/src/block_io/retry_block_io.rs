@@ -0,0 +1,208 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::BlockIo;
+use gpt_disk_types::{BlockSize, Lba};
+
+/// [`BlockIo`] wrapper that retries failed operations.
+///
+/// This is intended for unreliable media, such as failing USB sticks or
+/// dying hard drives, where a read that fails once may succeed on a
+/// later attempt.
+///
+/// Each operation is attempted up to `1 + max_retries` times. Errors
+/// can be classified with [`set_retry_filter`], for example to only
+/// retry timeouts. A backoff function set with [`set_backoff`] is
+/// called before each retry; by default there is no delay.
+///
+/// The number of retries is available from [`last_retries`] (for the
+/// most recent operation) and [`total_retries`].
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::{BlockIo, Disk, RetryBlockIo};
+/// use std::io::{self, ErrorKind};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// fn open<Io: BlockIo<Error = io::Error>>(
+///     io: Io,
+/// ) -> Result<(), Box<dyn std::error::Error>> {
+///     let mut block_io = RetryBlockIo::new(io, 5);
+///     // Only retry errors that may be transient.
+///     block_io.set_retry_filter(|err: &io::Error| {
+///         matches!(err.kind(), ErrorKind::TimedOut | ErrorKind::Interrupted)
+///     });
+///     // Exponential backoff: 20ms, 40ms, 80ms, ...
+///     block_io.set_backoff(|attempt| {
+///         thread::sleep(Duration::from_millis(10 << attempt));
+///     });
+///
+///     let mut disk = Disk::new(block_io)?;
+///     let mut block_buf = vec![0; 512];
+///     let (header, _) = disk.read_gpt_header_with_fallback(&mut block_buf)?;
+///     println!("{header}");
+///     Ok(())
+/// }
+/// ```
+///
+/// [`last_retries`]: Self::last_retries
+/// [`set_backoff`]: Self::set_backoff
+/// [`set_retry_filter`]: Self::set_retry_filter
+/// [`total_retries`]: Self::total_retries
+#[allow(clippy::module_name_repetitions)]
+pub struct RetryBlockIo<Io: BlockIo> {
+    io: Io,
+    max_retries: u32,
+    retry_filter: fn(&Io::Error) -> bool,
+    backoff: fn(u32),
+    last_retries: u32,
+    total_retries: u64,
+}
+
+impl<Io: BlockIo> RetryBlockIo<Io> {
+    /// Create a `RetryBlockIo` that retries each failed operation up to
+    /// `max_retries` times. All errors are retried, with no delay
+    /// between attempts.
+    pub fn new(io: Io, max_retries: u32) -> Self {
+        Self {
+            io,
+            max_retries,
+            retry_filter: |_| true,
+            backoff: |_| {},
+            last_retries: 0,
+            total_retries: 0,
+        }
+    }
+
+    /// Get the maximum number of retries per operation.
+    #[must_use]
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Set the maximum number of retries per operation.
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    /// Set the function used to decide whether an error should be
+    /// retried. Errors for which it returns `false` are returned
+    /// immediately. By default all errors are retried.
+    pub fn set_retry_filter(&mut self, retry_filter: fn(&Io::Error) -> bool) {
+        self.retry_filter = retry_filter;
+    }
+
+    /// Set the function called before each retry. It is passed the
+    /// retry number, starting at 1, and typically sleeps. By default
+    /// it does nothing.
+    pub fn set_backoff(&mut self, backoff: fn(u32)) {
+        self.backoff = backoff;
+    }
+
+    /// Get the number of retries made by the most recent operation,
+    /// whether it succeeded or not.
+    #[must_use]
+    pub fn last_retries(&self) -> u32 {
+        self.last_retries
+    }
+
+    /// Get the total number of retries made since the `RetryBlockIo`
+    /// was created or [`reset_retries`] was called.
+    ///
+    /// [`reset_retries`]: Self::reset_retries
+    #[must_use]
+    pub fn total_retries(&self) -> u64 {
+        self.total_retries
+    }
+
+    /// Reset the retry counters to zero.
+    pub fn reset_retries(&mut self) {
+        self.last_retries = 0;
+        self.total_retries = 0;
+    }
+
+    /// Get a reference to the underlying [`BlockIo`].
+    pub fn inner(&self) -> &Io {
+        &self.io
+    }
+
+    /// Consume the `RetryBlockIo` and return the underlying
+    /// [`BlockIo`].
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    fn retry<T>(
+        &mut self,
+        mut op: impl FnMut(&mut Io) -> Result<T, Io::Error>,
+    ) -> Result<T, Io::Error> {
+        let mut retries = 0;
+        loop {
+            let result = op(&mut self.io);
+            let retry = match &result {
+                Ok(_) => false,
+                Err(err) => {
+                    retries < self.max_retries && (self.retry_filter)(err)
+                }
+            };
+            if !retry {
+                self.last_retries = retries;
+                return result;
+            }
+            retries += 1;
+            self.total_retries += 1;
+            (self.backoff)(retries);
+        }
+    }
+}
+
+impl<Io: BlockIo> BlockIo for RetryBlockIo<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.io.is_read_only()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        self.io.buffer_alignment()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.retry(BlockIo::num_blocks)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.retry(|io| io.read_blocks(start_lba, dst))
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.retry(|io| io.write_blocks(start_lba, src))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.retry(BlockIo::flush)
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::block_io::range_block_io::RangeReader;
+use crate::BlockIo;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Magic number of the skippable frame that holds the seek table.
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184d_2a5e;
+
+/// Magic number at the very end of a seekable zstd file.
+const SEEKABLE_MAGIC: u32 = 0x8f92_eab1;
+
+/// Size of the skippable frame header: magic and frame size.
+const SKIPPABLE_HEADER_SIZE: u64 = 8;
+
+/// Size of the seek table footer: number of frames, descriptor, and
+/// magic.
+const FOOTER_SIZE: u64 = 9;
+
+/// Decompressor for a single zstd frame.
+///
+/// This is the decompression backend used by [`SeekableZstdBlockIo`].
+/// It is typically a thin wrapper around a zstd library, for example
+/// `zstd::bulk::Decompressor::decompress_to_buffer` from the `zstd`
+/// crate.
+pub trait ZstdFrameDecoder {
+    /// Decompression error type.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Decompress the complete zstd frame in `src` into `dst`. The
+    /// length of `dst` is the decompressed size of the frame recorded in
+    /// the seek table; the implementation should fail if the frame does
+    /// not decompress to exactly that many bytes.
+    fn decode_frame(
+        &mut self,
+        src: &[u8],
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Error type used by [`SeekableZstdBlockIo`].
+///
+/// This type implements the [`Error`] trait, with
+/// [`SeekableZstdError::Transport`] or [`SeekableZstdError::Decoder`]
+/// reported as the [`source`].
+///
+/// [`source`]: Error::source
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SeekableZstdError<TransportError, DecoderError> {
+    /// Numeric overflow occurred.
+    Overflow,
+
+    /// Attempted to write. [`SeekableZstdBlockIo`] is read-only.
+    ReadOnly,
+
+    /// The seek table is missing or malformed.
+    InvalidSeekTable,
+
+    /// Attempted to read past the end of the decompressed data.
+    OutOfBounds,
+
+    /// Error from the [`RangeReader`].
+    Transport(TransportError),
+
+    /// Error from the [`ZstdFrameDecoder`].
+    Decoder(DecoderError),
+}
+
+impl<T: Display, D: Display> Display for SeekableZstdError<T, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::ReadOnly => {
+                f.write_str("attempted to write to a read-only zstd image")
+            }
+            Self::InvalidSeekTable => f.write_str("invalid zstd seek table"),
+            Self::OutOfBounds => {
+                f.write_str("read past the end of the decompressed data")
+            }
+            Self::Transport(err) => Display::fmt(err, f),
+            Self::Decoder(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<T: Error + 'static, D: Error + 'static> Error for SeekableZstdError<T, D> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Decoder(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Start offsets of a frame in the compressed and decompressed data.
+#[derive(Clone, Copy, Debug)]
+struct FrameOffsets {
+    compressed: u64,
+    decompressed: u64,
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    // OK to unwrap: callers always pass at least four bytes.
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// Read-only [`BlockIo`] over a disk image compressed in the [seekable
+/// zstd format].
+///
+/// A seekable zstd file is a series of independently compressed zstd
+/// frames followed by a seek table recording the compressed and
+/// decompressed size of each frame. The seek table is read when the
+/// `SeekableZstdBlockIo` is created; after that, a read only fetches
+/// and decompresses the frames that overlap the requested blocks. The
+/// most recently decompressed frame is cached, so reading a GPT header
+/// and its partition entry array typically decompresses just one or
+/// two frames, regardless of the size of the image.
+///
+/// The compressed data is read through a [`RangeReader`], so the image
+/// can be local or remote. Decompression is done by a
+/// [`ZstdFrameDecoder`], which lets the caller choose a zstd
+/// implementation. The optional per-frame checksums in the seek table
+/// are not verified.
+///
+/// Writes fail with [`SeekableZstdError::ReadOnly`].
+///
+/// [seekable zstd format]: https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md
+#[allow(clippy::module_name_repetitions)]
+pub struct SeekableZstdBlockIo<R, D> {
+    reader: R,
+    decoder: D,
+    block_size: BlockSize,
+    /// Offsets of each frame, plus one extra entry for the end of the
+    /// data.
+    frames: Vec<FrameOffsets>,
+    compressed_buf: Vec<u8>,
+    cache: Vec<u8>,
+    cached_frame: Option<usize>,
+    frames_decoded: u64,
+}
+
+impl<R: RangeReader, D: ZstdFrameDecoder> SeekableZstdBlockIo<R, D> {
+    /// Create a `SeekableZstdBlockIo`. This reads and validates the seek
+    /// table at the end of the compressed data.
+    pub fn new(
+        mut reader: R,
+        decoder: D,
+        block_size: BlockSize,
+    ) -> Result<Self, SeekableZstdError<R::Error, D::Error>> {
+        let frames = Self::read_seek_table(&mut reader)?;
+        Ok(Self {
+            reader,
+            decoder,
+            block_size,
+            frames,
+            compressed_buf: Vec::new(),
+            cache: Vec::new(),
+            cached_frame: None,
+            frames_decoded: 0,
+        })
+    }
+
+    fn read_seek_table(
+        reader: &mut R,
+    ) -> Result<Vec<FrameOffsets>, SeekableZstdError<R::Error, D::Error>> {
+        let size = reader.size().map_err(SeekableZstdError::Transport)?;
+
+        let footer_offset = size
+            .checked_sub(FOOTER_SIZE)
+            .ok_or(SeekableZstdError::InvalidSeekTable)?;
+        let mut footer = [0; 9];
+        reader
+            .read_range(footer_offset, &mut footer)
+            .map_err(SeekableZstdError::Transport)?;
+        let num_frames = read_u32_le(&footer[0..4]);
+        let descriptor = footer[4];
+        if read_u32_le(&footer[5..9]) != SEEKABLE_MAGIC
+            // Bits 2 through 6 are reserved and must be zero.
+            || descriptor & 0x7c != 0
+        {
+            return Err(SeekableZstdError::InvalidSeekTable);
+        }
+        let entry_size: u64 = if descriptor & 0x80 == 0 { 8 } else { 12 };
+
+        let table_size = u64::from(num_frames)
+            .checked_mul(entry_size)
+            .and_then(|v| v.checked_add(FOOTER_SIZE))
+            .ok_or(SeekableZstdError::Overflow)?;
+        let table_offset = size
+            .checked_sub(table_size + SKIPPABLE_HEADER_SIZE)
+            .ok_or(SeekableZstdError::InvalidSeekTable)?;
+        let mut table = vec![
+            0;
+            usize::try_from(table_size + SKIPPABLE_HEADER_SIZE)
+                .map_err(|_| SeekableZstdError::Overflow)?
+        ];
+        reader
+            .read_range(table_offset, &mut table)
+            .map_err(SeekableZstdError::Transport)?;
+        if read_u32_le(&table[0..4]) != SKIPPABLE_FRAME_MAGIC
+            || u64::from(read_u32_le(&table[4..8])) != table_size
+        {
+            return Err(SeekableZstdError::InvalidSeekTable);
+        }
+
+        let mut frames = Vec::new();
+        let mut offsets = FrameOffsets {
+            compressed: 0,
+            decompressed: 0,
+        };
+        // OK to unwrap: `entry_size` is 8 or 12.
+        let entry_size = usize::try_from(entry_size).unwrap();
+        for entry in table[8..table.len() - 9].chunks_exact(entry_size) {
+            frames.push(offsets);
+            offsets.compressed = offsets
+                .compressed
+                .checked_add(u64::from(read_u32_le(&entry[0..4])))
+                .ok_or(SeekableZstdError::Overflow)?;
+            offsets.decompressed = offsets
+                .decompressed
+                .checked_add(u64::from(read_u32_le(&entry[4..8])))
+                .ok_or(SeekableZstdError::Overflow)?;
+        }
+        // The frames must exactly fill the space before the seek table.
+        if offsets.compressed != table_offset {
+            return Err(SeekableZstdError::InvalidSeekTable);
+        }
+        frames.push(offsets);
+        Ok(frames)
+    }
+
+    /// Get the number of frames in the image.
+    #[must_use]
+    pub fn num_frames(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    /// Get the total size of the decompressed data in bytes.
+    #[must_use]
+    pub fn decompressed_size(&self) -> u64 {
+        // OK to unwrap: there is always at least one entry.
+        self.frames.last().unwrap().decompressed
+    }
+
+    /// Get the number of frames decompressed so far. A read served
+    /// entirely from the cached frame does not increase this.
+    #[must_use]
+    pub fn frames_decoded(&self) -> u64 {
+        self.frames_decoded
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume the `SeekableZstdBlockIo` and return the underlying
+    /// reader and decoder.
+    pub fn into_parts(self) -> (R, D) {
+        (self.reader, self.decoder)
+    }
+
+    /// Decompress frame `index` into the cache, unless it is already
+    /// there.
+    fn load_frame(
+        &mut self,
+        index: usize,
+    ) -> Result<(), SeekableZstdError<R::Error, D::Error>> {
+        if self.cached_frame == Some(index) {
+            return Ok(());
+        }
+        let start = self.frames[index];
+        let end = self.frames[index + 1];
+        let to_usize = |n: u64| {
+            usize::try_from(n).map_err(|_| SeekableZstdError::Overflow)
+        };
+
+        self.compressed_buf
+            .resize(to_usize(end.compressed - start.compressed)?, 0);
+        self.reader
+            .read_range(start.compressed, &mut self.compressed_buf)
+            .map_err(SeekableZstdError::Transport)?;
+
+        // Invalidate the cache first in case decompression fails
+        // partway through.
+        self.cached_frame = None;
+        self.cache
+            .resize(to_usize(end.decompressed - start.decompressed)?, 0);
+        self.decoder
+            .decode_frame(&self.compressed_buf, &mut self.cache)
+            .map_err(SeekableZstdError::Decoder)?;
+        self.cached_frame = Some(index);
+        self.frames_decoded += 1;
+        Ok(())
+    }
+}
+
+impl<R: RangeReader, D: ZstdFrameDecoder> BlockIo
+    for SeekableZstdBlockIo<R, D>
+{
+    type Error = SeekableZstdError<R::Error, D::Error>;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.decompressed_size() / self.block_size.to_u64())
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(dst);
+
+        let mut offset = start_lba
+            .to_u64()
+            .checked_mul(self.block_size.to_u64())
+            .ok_or(SeekableZstdError::Overflow)?;
+        let len = u64::try_from(dst.len())
+            .map_err(|_| SeekableZstdError::Overflow)?;
+        let end = offset.checked_add(len).ok_or(SeekableZstdError::Overflow)?;
+        if end > self.decompressed_size() {
+            return Err(SeekableZstdError::OutOfBounds);
+        }
+
+        let mut dst = dst;
+        while !dst.is_empty() {
+            // Find the last frame starting at or before `offset`. Empty
+            // frames are skipped because the next frame has the same
+            // start offset.
+            let index =
+                self.frames.partition_point(|f| f.decompressed <= offset) - 1;
+            self.load_frame(index)?;
+
+            // OK to unwrap: the offset is within the cached frame.
+            let start =
+                usize::try_from(offset - self.frames[index].decompressed)
+                    .unwrap();
+            let n = dst.len().min(self.cache.len() - start);
+            let (head, tail) = dst.split_at_mut(n);
+            head.copy_from_slice(&self.cache[start..start + n]);
+            dst = tail;
+            // OK to unwrap: `n` is at most `dst.len()`.
+            offset += u64::try_from(n).unwrap();
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        _start_lba: Lba,
+        _src: &[u8],
+    ) -> Result<(), Self::Error> {
+        Err(SeekableZstdError::ReadOnly)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
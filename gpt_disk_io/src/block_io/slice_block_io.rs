@@ -7,19 +7,22 @@
 // except according to those terms.
 
 use crate::{BlockIo, BlockIoAdapter};
+use core::error::Error;
 use core::fmt::{self, Debug, Display, Formatter};
 use core::ops::Range;
 use gpt_disk_types::{BlockSize, Lba};
 
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+use {
+    alloc::borrow::Cow, alloc::boxed::Box, alloc::sync::Arc, alloc::vec::Vec,
+};
 
-/// Error type used for `&[u8]` and `&mut [u8]` versions of [`BlockIoAdapter`].
+/// Error type used for byte-container versions of [`BlockIoAdapter`],
+/// such as `&[u8]` and `&mut [u8]`.
 ///
-/// If the `std` feature is enabled, this type implements the [`Error`]
-/// trait.
+/// This type implements the [`Error`] trait.
 ///
-/// [`Error`]: std::error::Error
+/// [`Error`]: core::error::Error
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum SliceBlockIoError {
@@ -60,26 +63,37 @@ impl Display for SliceBlockIoError {
     }
 }
 
-#[track_caller]
-fn buffer_byte_range_opt(
-    block_size: BlockSize,
-    start_lba: Lba,
-    buf: &[u8],
-) -> Option<Range<usize>> {
-    let start_lba = usize::try_from(start_lba).ok()?;
-    let start_byte = start_lba.checked_mul(block_size.to_usize()?)?;
-    let end_byte = start_byte.checked_add(buf.len())?;
-    Some(start_byte..end_byte)
-}
+impl Error for SliceBlockIoError {}
 
+/// Get the byte range covered by `buf` starting at `start_lba`.
+///
+/// The offset math is done in `u64` so that it cannot overflow on
+/// 32-bit targets; the result is only converted to `usize` at the end.
 #[track_caller]
-fn buffer_byte_range(
+pub(crate) fn buffer_byte_range(
     block_size: BlockSize,
     start_lba: Lba,
     buf: &[u8],
 ) -> Result<Range<usize>, SliceBlockIoError> {
-    buffer_byte_range_opt(block_size, start_lba, buf)
-        .ok_or(SliceBlockIoError::Overflow)
+    let len =
+        u64::try_from(buf.len()).map_err(|_| SliceBlockIoError::Overflow)?;
+    let start_byte = start_lba
+        .to_u64()
+        .checked_mul(block_size.to_u64())
+        .ok_or(SliceBlockIoError::Overflow)?;
+    let end_byte = start_byte
+        .checked_add(len)
+        .ok_or(SliceBlockIoError::Overflow)?;
+
+    // A range that doesn't fit in a `usize` can't be inside a slice.
+    let out_of_bounds = || SliceBlockIoError::OutOfBounds {
+        start_lba,
+        length_in_bytes: buf.len(),
+    };
+    let start_byte =
+        usize::try_from(start_byte).map_err(|_| out_of_bounds())?;
+    let end_byte = usize::try_from(end_byte).map_err(|_| out_of_bounds())?;
+    Ok(start_byte..end_byte)
 }
 
 #[track_caller]
@@ -137,6 +151,14 @@ impl BlockIo for BlockIoAdapter<&[u8]> {
         self.block_size
     }
 
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn num_blocks(&mut self) -> Result<u64, Self::Error> {
         num_blocks(self.storage, self.block_size)
     }
@@ -169,6 +191,14 @@ impl BlockIo for BlockIoAdapter<&mut [u8]> {
         self.block_size
     }
 
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     fn num_blocks(&mut self) -> Result<u64, Self::Error> {
         num_blocks(self.storage, self.block_size)
     }
@@ -202,6 +232,14 @@ impl BlockIo for BlockIoAdapter<Vec<u8>> {
         self.block_size
     }
 
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     fn num_blocks(&mut self) -> Result<u64, Self::Error> {
         num_blocks(&self.storage, self.block_size)
     }
@@ -226,3 +264,130 @@ impl BlockIo for BlockIoAdapter<Vec<u8>> {
         Ok(())
     }
 }
+
+#[cfg(feature = "alloc")]
+impl BlockIo for BlockIoAdapter<Box<[u8]>> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        num_blocks(&self.storage, self.block_size)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        read_blocks(&self.storage, self.block_size, start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        write_blocks(&mut self.storage, self.block_size, start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Shared storage is read-only; writes fail with
+/// [`SliceBlockIoError::ReadOnly`].
+#[cfg(feature = "alloc")]
+impl BlockIo for BlockIoAdapter<Arc<[u8]>> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        num_blocks(&self.storage, self.block_size)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        read_blocks(&self.storage, self.block_size, start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        _start_lba: Lba,
+        _src: &[u8],
+    ) -> Result<(), Self::Error> {
+        Err(Self::Error::ReadOnly)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The first write to borrowed storage copies it into an owned `Vec`;
+/// the borrowed data is never modified.
+#[cfg(feature = "alloc")]
+impl BlockIo for BlockIoAdapter<Cow<'_, [u8]>> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        num_blocks(&self.storage, self.block_size)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        read_blocks(&self.storage, self.block_size, start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        write_blocks(self.storage.to_mut(), self.block_size, start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -9,9 +9,31 @@
 use crate::{BlockIo, BlockIoAdapter};
 use gpt_disk_types::{BlockSize, Lba};
 use std::fs::File;
-use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+
+/// Get the byte offset of `lba`, returning an error on overflow.
+fn lba_byte_offset(block_size: BlockSize, lba: Lba) -> Result<u64, io::Error> {
+    lba.to_u64()
+        .checked_mul(block_size.to_u64())
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "LBA overflow"))
+}
+
+#[cfg(target_os = "linux")]
+use crate::linux::file_is_read_only;
+
+#[cfg(not(target_os = "linux"))]
+fn file_is_read_only(_file: &File) -> bool {
+    false
+}
 
 /// Combination trait for types that impl [`Read`], [`Write`], and [`Seek`].
+///
+/// This is implemented for [`File`], [`Cursor`], and `&mut T` where `T`
+/// implements `ReadWriteSeek`. Other types can implement it with an
+/// empty `impl` block, or override [`is_read_only`] if they can detect
+/// read-only storage.
+///
+/// [`is_read_only`]: Self::is_read_only
 pub trait ReadWriteSeek: Read + Write + Seek {
     /// Get the number of blocks for the given `block_size`.
     ///
@@ -32,11 +54,19 @@ pub trait ReadWriteSeek: Read + Write + Seek {
     ) -> Result<(), io::Error> {
         block_size.assert_valid_block_buffer(dst);
 
-        self.seek(SeekFrom::Start(start_lba.to_u64() * block_size.to_u64()))?;
+        self.seek(SeekFrom::Start(lba_byte_offset(block_size, start_lba)?))?;
         self.read_exact(dst)?;
         Ok(())
     }
 
+    /// Whether the storage is known to be read-only. See
+    /// [`BlockIo::is_read_only`].
+    ///
+    /// The default implementation returns `false`.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     /// Write contiguous blocks.
     fn write_blocks(
         &mut self,
@@ -46,15 +76,59 @@ pub trait ReadWriteSeek: Read + Write + Seek {
     ) -> Result<(), io::Error> {
         block_size.assert_valid_block_buffer(src);
 
-        self.seek(SeekFrom::Start(start_lba.to_u64() * block_size.to_u64()))?;
+        self.seek(SeekFrom::Start(lba_byte_offset(block_size, start_lba)?))?;
         self.write_all(src)?;
         Ok(())
     }
 }
 
-impl ReadWriteSeek for File {}
-impl ReadWriteSeek for &File {}
-impl<T> ReadWriteSeek for &mut T where T: Read + Write + Seek {}
+/// On Linux, a `File` is read-only if it was opened without write
+/// access, or if it is a block device with the read-only flag set
+/// (see `blockdev --setro`).
+impl ReadWriteSeek for File {
+    fn is_read_only(&self) -> bool {
+        file_is_read_only(self)
+    }
+}
+
+/// See the [`File`] implementation.
+impl ReadWriteSeek for &File {
+    fn is_read_only(&self) -> bool {
+        file_is_read_only(self)
+    }
+}
+
+impl<T> ReadWriteSeek for Cursor<T> where Cursor<T>: Read + Write + Seek {}
+
+/// Forwards to `T`, so that read-only detection works through a
+/// mutable borrow, for example of a [`File`].
+impl<T: ReadWriteSeek> ReadWriteSeek for &mut T {
+    fn num_blocks(&mut self, block_size: BlockSize) -> Result<u64, io::Error> {
+        (**self).num_blocks(block_size)
+    }
+
+    fn read_blocks(
+        &mut self,
+        block_size: BlockSize,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), io::Error> {
+        (**self).read_blocks(block_size, start_lba, dst)
+    }
+
+    fn is_read_only(&self) -> bool {
+        (**self).is_read_only()
+    }
+
+    fn write_blocks(
+        &mut self,
+        block_size: BlockSize,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), io::Error> {
+        (**self).write_blocks(block_size, start_lba, src)
+    }
+}
 
 impl<T> BlockIo for BlockIoAdapter<T>
 where
@@ -66,6 +140,14 @@ where
         self.block_size
     }
 
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only || self.storage.is_read_only()
+    }
+
     fn num_blocks(&mut self) -> Result<u64, Self::Error> {
         self.storage.num_blocks(self.block_size)
     }
@@ -98,6 +180,14 @@ impl BlockIo for BlockIoAdapter<&mut dyn ReadWriteSeek> {
         self.block_size
     }
 
+    fn physical_block_size(&self) -> BlockSize {
+        BlockIoAdapter::physical_block_size(self)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only || self.storage.is_read_only()
+    }
+
     fn num_blocks(&mut self) -> Result<u64, Self::Error> {
         self.storage.num_blocks(self.block_size)
     }
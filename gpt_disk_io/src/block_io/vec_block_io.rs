@@ -0,0 +1,167 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::block_io::slice_block_io::buffer_byte_range;
+use crate::{BlockIo, SliceBlockIoError};
+use alloc::vec::Vec;
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Growable in-memory [`BlockIo`] backed by a [`Vec<u8>`].
+///
+/// The disk has a fixed logical size of `num_blocks` blocks, but the
+/// backing `Vec` starts out empty (or with whatever data it was
+/// created with) and is only grown when a write goes past its current
+/// end. Reads of blocks that have never been written return zeroes.
+///
+/// This makes it cheap to build a large disk image in memory when only
+/// a small part of it (such as the GPT structures at the start and end)
+/// is ever written.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::{BlockSize, Lba};
+/// use gpt_disk_io::{BlockIo, VecBlockIo};
+///
+/// // 1GiB disk that doesn't allocate anything up front.
+/// let mut bio = VecBlockIo::new(BlockSize::BS_512, 2 * 1024 * 1024);
+/// assert_eq!(bio.num_blocks().unwrap(), 2 * 1024 * 1024);
+/// assert!(bio.storage().is_empty());
+///
+/// // Writing to the second block grows the storage to two blocks.
+/// bio.write_blocks(Lba(1), &[1; 512]).unwrap();
+/// assert_eq!(bio.storage().len(), 1024);
+///
+/// // Blocks past the end of the storage read as zero.
+/// let mut block = [0xff; 512];
+/// bio.read_blocks(Lba(100), &mut block).unwrap();
+/// assert_eq!(block, [0; 512]);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VecBlockIo {
+    storage: Vec<u8>,
+    block_size: BlockSize,
+    num_blocks: u64,
+}
+
+impl VecBlockIo {
+    /// Create an empty `VecBlockIo` with a logical size of `num_blocks`.
+    #[must_use]
+    pub fn new(block_size: BlockSize, num_blocks: u64) -> Self {
+        Self::from_vec(Vec::new(), block_size, num_blocks)
+    }
+
+    /// Create a `VecBlockIo` with a logical size of `num_blocks`,
+    /// using `storage` as the initial contents of the disk.
+    ///
+    /// If `storage` is larger than `num_blocks` blocks, the extra bytes
+    /// are not accessible through the [`BlockIo`] interface.
+    #[must_use]
+    pub fn from_vec(
+        storage: Vec<u8>,
+        block_size: BlockSize,
+        num_blocks: u64,
+    ) -> Self {
+        Self {
+            storage,
+            block_size,
+            num_blocks,
+        }
+    }
+
+    /// Get a reference to the underlying storage. The length of the
+    /// storage may be less than the logical size of the disk.
+    #[must_use]
+    pub fn storage(&self) -> &[u8] {
+        &self.storage
+    }
+
+    /// Consume the `VecBlockIo` and return the underlying storage. The
+    /// length of the storage may be less than the logical size of the
+    /// disk.
+    #[must_use]
+    pub fn take_storage(self) -> Vec<u8> {
+        self.storage
+    }
+
+    /// Check that the blocks covered by `buf` starting at `start_lba`
+    /// are within the logical size of the disk.
+    fn check_bounds(
+        &self,
+        start_lba: Lba,
+        buf: &[u8],
+    ) -> Result<(), SliceBlockIoError> {
+        let num_blocks = u64::try_from(buf.len())
+            .map_err(|_| SliceBlockIoError::Overflow)?
+            / self.block_size.to_u64();
+        let end_lba = start_lba
+            .to_u64()
+            .checked_add(num_blocks)
+            .ok_or(SliceBlockIoError::Overflow)?;
+        if end_lba > self.num_blocks {
+            return Err(SliceBlockIoError::OutOfBounds {
+                start_lba,
+                length_in_bytes: buf.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl BlockIo for VecBlockIo {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.num_blocks)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(dst);
+        self.check_bounds(start_lba, dst)?;
+
+        let range = buffer_byte_range(self.block_size, start_lba, dst)?;
+
+        // Copy whatever part of the range is backed by storage, and
+        // fill the rest with zeroes.
+        let stored = &self.storage[range.start.min(self.storage.len())..];
+        let num_stored = stored.len().min(dst.len());
+        let (left, right) = dst.split_at_mut(num_stored);
+        left.copy_from_slice(&stored[..num_stored]);
+        right.fill(0);
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.block_size.assert_valid_block_buffer(src);
+        self.check_bounds(start_lba, src)?;
+
+        let range = buffer_byte_range(self.block_size, start_lba, src)?;
+        if range.end > self.storage.len() {
+            self.storage.resize(range.end, 0);
+        }
+        self.storage[range].copy_from_slice(src);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
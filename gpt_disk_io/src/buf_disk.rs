@@ -0,0 +1,535 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::validate::{Finding, GptVerifyReport};
+use crate::{
+    BlockIo, CreatePartitionOptions, Disk, DiskError, FreeGapFilter,
+    GptHeaderCopy, GptHeaders, InitGptOptions, NestedGpt, NewPartitionEnd,
+    PartitionExtent, ResizePartitionOptions,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use gpt_disk_types::{
+    BlockSize, Crc32, GptHeader, GptPartitionEntry,
+    GptPartitionEntryArrayLayout, GptPartitionName, GptPartitionType, Guid,
+    Lba, LbaRangeInclusive, MasterBootRecord,
+};
+
+/// Wrapper around [`Disk`] that owns its scratch buffers.
+///
+/// Most [`Disk`] methods take a `block_buf` argument so that they can
+/// be used without an allocator. `BufDisk` allocates a block-sized
+/// buffer once when it is created, and allocates larger buffers (such
+/// as for a whole partition entry array) as needed, so none of its
+/// methods need buffer arguments. Method names and behavior are
+/// otherwise the same as on [`Disk`].
+///
+/// The underlying [`Disk`] is available via [`disk_mut`] for anything
+/// not covered here.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{BufDisk, VecBlockIo};
+///
+/// let block_io = VecBlockIo::new(BlockSize::BS_512, 8192);
+/// let mut disk = BufDisk::new(block_io)?;
+/// disk.write_protective_mbr()?;
+/// assert_eq!(disk.read_mbr()?.partitions[0].os_indicator, 0xee);
+/// # Ok::<(), gpt_disk_io::DiskError<gpt_disk_io::SliceBlockIoError>>(())
+/// ```
+///
+/// [`disk_mut`]: Self::disk_mut
+pub struct BufDisk<Io: BlockIo> {
+    disk: Disk<Io>,
+    block_buf: Vec<u8>,
+}
+
+impl<Io: BlockIo> BufDisk<Io> {
+    /// Create a `BufDisk`. This allocates a buffer the size of one
+    /// block.
+    pub fn new(io: Io) -> Result<Self, DiskError<Io::Error>> {
+        Self::from_disk(Disk::new(io)?)
+    }
+
+    /// Create a `BufDisk` from an existing [`Disk`]. This allocates a
+    /// buffer the size of one block.
+    pub fn from_disk(disk: Disk<Io>) -> Result<Self, DiskError<Io::Error>> {
+        let block_size =
+            disk.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        Ok(Self {
+            disk,
+            block_buf: vec![0; block_size],
+        })
+    }
+
+    /// Get a reference to the underlying [`Disk`].
+    pub fn disk(&self) -> &Disk<Io> {
+        &self.disk
+    }
+
+    /// Get a mutable reference to the underlying [`Disk`].
+    pub fn disk_mut(&mut self) -> &mut Disk<Io> {
+        &mut self.disk
+    }
+
+    /// Consume the `BufDisk` and return the underlying [`Disk`].
+    pub fn into_disk(self) -> Disk<Io> {
+        self.disk
+    }
+
+    /// Allocate a buffer large enough for the partition entry array
+    /// described by `layout`.
+    fn alloc_entry_array_buf(
+        &self,
+        layout: &GptPartitionEntryArrayLayout,
+    ) -> Result<Vec<u8>, DiskError<Io::Error>> {
+        let len = layout
+            .num_bytes_rounded_to_block_as_usize(self.block_size())
+            .ok_or(DiskError::Overflow)?;
+        Ok(vec![0; len])
+    }
+
+    /// See [`Disk::block_size`].
+    pub fn block_size(&self) -> BlockSize {
+        self.disk.block_size()
+    }
+
+    /// See [`Disk::physical_block_size`].
+    pub fn physical_block_size(&self) -> BlockSize {
+        self.disk.physical_block_size()
+    }
+
+    /// See [`Disk::is_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.disk.is_read_only()
+    }
+
+    /// See [`Disk::num_blocks`].
+    pub fn num_blocks(&mut self) -> Result<u64, DiskError<Io::Error>> {
+        self.disk.num_blocks()
+    }
+
+    /// See [`Disk::read_mbr`].
+    pub fn read_mbr(
+        &mut self,
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        self.disk.read_mbr(&mut self.block_buf)
+    }
+
+    /// See [`Disk::write_mbr`].
+    pub fn write_mbr(
+        &mut self,
+        mbr: &MasterBootRecord,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.write_mbr(mbr, &mut self.block_buf)
+    }
+
+    /// See [`Disk::write_protective_mbr`].
+    pub fn write_protective_mbr(&mut self) -> Result<(), DiskError<Io::Error>> {
+        self.disk.write_protective_mbr(&mut self.block_buf)
+    }
+
+    /// See [`Disk::write_protective_mbr_guarded`].
+    pub fn write_protective_mbr_guarded(
+        &mut self,
+        force: bool,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk
+            .write_protective_mbr_guarded(force, &mut self.block_buf)
+    }
+
+    /// See [`Disk::read_primary_gpt_header`].
+    pub fn read_primary_gpt_header(
+        &mut self,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk.read_primary_gpt_header(&mut self.block_buf)
+    }
+
+    /// See [`Disk::read_secondary_gpt_header`].
+    pub fn read_secondary_gpt_header(
+        &mut self,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk.read_secondary_gpt_header(&mut self.block_buf)
+    }
+
+    /// See [`Disk::read_gpt_header`].
+    pub fn read_gpt_header(
+        &mut self,
+        lba: Lba,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk.read_gpt_header(lba, &mut self.block_buf)
+    }
+
+    /// See [`Disk::read_primary_gpt_header_verified`].
+    pub fn read_primary_gpt_header_verified(
+        &mut self,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk
+            .read_primary_gpt_header_verified(&mut self.block_buf)
+    }
+
+    /// See [`Disk::read_secondary_gpt_header_verified`].
+    pub fn read_secondary_gpt_header_verified(
+        &mut self,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk
+            .read_secondary_gpt_header_verified(&mut self.block_buf)
+    }
+
+    /// See [`Disk::read_gpt_header_verified`].
+    pub fn read_gpt_header_verified(
+        &mut self,
+        lba: Lba,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.disk.read_gpt_header_verified(lba, &mut self.block_buf)
+    }
+
+    /// See [`Disk::read_gpt_header_with_fallback`].
+    pub fn read_gpt_header_with_fallback(
+        &mut self,
+    ) -> Result<(GptHeader, GptHeaderCopy), DiskError<Io::Error>> {
+        self.disk.read_gpt_header_with_fallback(&mut self.block_buf)
+    }
+
+    /// See [`Disk::read_gpt_headers`].
+    pub fn read_gpt_headers(
+        &mut self,
+    ) -> Result<GptHeaders, DiskError<Io::Error>> {
+        self.disk.read_gpt_headers(&mut self.block_buf)
+    }
+
+    /// See [`Disk::find_nested_gpts`].
+    pub fn find_nested_gpts<F>(
+        &mut self,
+        found: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnMut(NestedGpt),
+    {
+        self.disk.find_nested_gpts(&mut self.block_buf, found)
+    }
+
+    /// See [`Disk::write_primary_gpt_header`].
+    pub fn write_primary_gpt_header(
+        &mut self,
+        header: &GptHeader,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk
+            .write_primary_gpt_header(header, &mut self.block_buf)
+    }
+
+    /// See [`Disk::write_secondary_gpt_header`].
+    pub fn write_secondary_gpt_header(
+        &mut self,
+        header: &GptHeader,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk
+            .write_secondary_gpt_header(header, &mut self.block_buf)
+    }
+
+    /// See [`Disk::write_gpt_header`].
+    pub fn write_gpt_header(
+        &mut self,
+        lba: Lba,
+        header: &GptHeader,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.write_gpt_header(lba, header, &mut self.block_buf)
+    }
+
+    /// Read all entries of the partition entry array described by
+    /// `layout`, including unused entries.
+    ///
+    /// See [`Disk::gpt_partition_entry_array_iter`].
+    pub fn read_gpt_partition_entries(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+    ) -> Result<Vec<GptPartitionEntry>, DiskError<Io::Error>> {
+        self.disk
+            .gpt_partition_entry_array_iter(layout, &mut self.block_buf)?
+            .collect()
+    }
+
+    /// See [`Disk::read_gpt_partition_entry`].
+    pub fn read_gpt_partition_entry(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        index: u32,
+    ) -> Result<GptPartitionEntry, DiskError<Io::Error>> {
+        self.disk
+            .read_gpt_partition_entry(layout, index, &mut self.block_buf)
+    }
+
+    /// See [`Disk::write_gpt_partition_entry`].
+    pub fn write_gpt_partition_entry(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        index: u32,
+        entry: &GptPartitionEntry,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.write_gpt_partition_entry(
+            layout,
+            index,
+            entry,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::write_gpt_partition_entry_array_from_iter`].
+    pub fn write_gpt_partition_entry_array_from_iter<I>(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        entries: I,
+    ) -> Result<Crc32, DiskError<Io::Error>>
+    where
+        I: IntoIterator<Item = GptPartitionEntry>,
+    {
+        self.disk.write_gpt_partition_entry_array_from_iter(
+            layout,
+            entries,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::calculate_gpt_partition_entry_array_crc32`].
+    pub fn calculate_gpt_partition_entry_array_crc32(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+    ) -> Result<Crc32, DiskError<Io::Error>> {
+        self.disk.calculate_gpt_partition_entry_array_crc32(
+            layout,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::hash_partition`].
+    pub fn hash_partition<F>(
+        &mut self,
+        index: u32,
+        update: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.disk.hash_partition(index, update, &mut self.block_buf)
+    }
+
+    /// See [`Disk::calculate_partition_crc32`].
+    pub fn calculate_partition_crc32(
+        &mut self,
+        index: u32,
+    ) -> Result<Crc32, DiskError<Io::Error>> {
+        self.disk
+            .calculate_partition_crc32(index, &mut self.block_buf)
+    }
+
+    /// See [`Disk::calculate_partition_sha256`].
+    #[cfg(feature = "sha2")]
+    pub fn calculate_partition_sha256(
+        &mut self,
+        index: u32,
+    ) -> Result<[u8; 32], DiskError<Io::Error>> {
+        self.disk
+            .calculate_partition_sha256(index, &mut self.block_buf)
+    }
+
+    /// Get all free gaps in the usable range of `header`.
+    ///
+    /// See [`Disk::free_gaps`].
+    pub fn free_gaps(
+        &mut self,
+        header: &GptHeader,
+        filter: FreeGapFilter,
+    ) -> Result<Vec<LbaRangeInclusive>, DiskError<Io::Error>> {
+        self.disk
+            .free_gaps(header, filter, &mut self.block_buf)?
+            .collect()
+    }
+
+    /// See [`Disk::create_partition`].
+    pub fn create_partition(
+        &mut self,
+        partition_type: GptPartitionType,
+        extent: PartitionExtent,
+        name: GptPartitionName,
+        options: CreatePartitionOptions,
+    ) -> Result<u32, DiskError<Io::Error>> {
+        self.disk.create_partition(
+            partition_type,
+            extent,
+            name,
+            options,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::resize_partition`].
+    pub fn resize_partition(
+        &mut self,
+        index: u32,
+        end: NewPartitionEnd,
+        options: ResizePartitionOptions,
+    ) -> Result<LbaRangeInclusive, DiskError<Io::Error>> {
+        self.disk
+            .resize_partition(index, end, options, &mut self.block_buf)
+    }
+
+    /// See [`Disk::delete_partition`].
+    pub fn delete_partition(
+        &mut self,
+        index: u32,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.delete_partition(index, &mut self.block_buf)
+    }
+
+    /// See [`Disk::move_partition_entry_arrays`].
+    pub fn move_partition_entry_arrays(
+        &mut self,
+        primary_start: Lba,
+        secondary_start: Lba,
+    ) -> Result<(), DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk.move_partition_entry_arrays(
+            primary_start,
+            secondary_start,
+            &mut storage,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::sort_partitions`].
+    pub fn sort_partitions(&mut self) -> Result<(), DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk.sort_partitions(&mut storage, &mut self.block_buf)
+    }
+
+    /// See [`Disk::compact_partitions`].
+    pub fn compact_partitions(&mut self) -> Result<(), DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk
+            .compact_partitions(&mut storage, &mut self.block_buf)
+    }
+
+    /// See [`Disk::swap_partition_indices`].
+    pub fn swap_partition_indices(
+        &mut self,
+        a: u32,
+        b: u32,
+    ) -> Result<(), DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk.swap_partition_indices(
+            a,
+            b,
+            &mut storage,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::move_partition_to_index`].
+    pub fn move_partition_to_index(
+        &mut self,
+        from: u32,
+        to: u32,
+    ) -> Result<(), DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk.move_partition_to_index(
+            from,
+            to,
+            &mut storage,
+            &mut self.block_buf,
+        )
+    }
+
+    /// See [`Disk::rename_partition`].
+    pub fn rename_partition(
+        &mut self,
+        index: u32,
+        name: &str,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.rename_partition(index, name, &mut self.block_buf)
+    }
+
+    /// See [`Disk::init_gpt`].
+    pub fn init_gpt(
+        &mut self,
+        disk_guid: Guid,
+        options: InitGptOptions,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.init_gpt(disk_guid, options, &mut self.block_buf)
+    }
+
+    /// See [`Disk::repair_from_primary`].
+    pub fn repair_from_primary(&mut self) -> Result<(), DiskError<Io::Error>> {
+        self.disk.repair_from_primary(&mut self.block_buf)
+    }
+
+    /// See [`Disk::repair_from_secondary`].
+    pub fn repair_from_secondary(
+        &mut self,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.repair_from_secondary(&mut self.block_buf)
+    }
+
+    /// See [`Disk::set_disk_guid`].
+    pub fn set_disk_guid(
+        &mut self,
+        disk_guid: Guid,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.set_disk_guid(disk_guid, &mut self.block_buf)
+    }
+
+    /// See [`Disk::randomize_guids`].
+    pub fn randomize_guids<R>(
+        &mut self,
+        rng: R,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        R: FnMut() -> [u8; 16],
+    {
+        let header = self.read_primary_gpt_header_verified()?;
+        let mut storage =
+            self.alloc_entry_array_buf(&crate::edit::header_layout(&header)?)?;
+        self.disk
+            .randomize_guids(rng, &mut storage, &mut self.block_buf)
+    }
+
+    /// See [`Disk::move_secondary_gpt_to_end`].
+    pub fn move_secondary_gpt_to_end(
+        &mut self,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.disk.move_secondary_gpt_to_end(&mut self.block_buf)
+    }
+
+    /// See [`Disk::verify`].
+    pub fn verify(&mut self) -> Result<Vec<Finding>, DiskError<Io::Error>> {
+        self.disk.verify(&mut self.block_buf)
+    }
+
+    /// See [`Disk::verify_gpt`].
+    pub fn verify_gpt(
+        &mut self,
+    ) -> Result<GptVerifyReport, DiskError<Io::Error>> {
+        self.disk.verify_gpt(&mut self.block_buf)
+    }
+
+    /// See [`Disk::flush`].
+    pub fn flush(&mut self) -> Result<(), DiskError<Io::Error>> {
+        self.disk.flush()
+    }
+}
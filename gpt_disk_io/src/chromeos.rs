@@ -0,0 +1,198 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// This lint incorrectly says that "ChromeOS" should be in backticks.
+#![allow(clippy::doc_markdown)]
+
+use crate::{BlockIo, Disk, DiskError};
+use gpt_disk_types::{GptPartitionAttributes, GptPartitionType};
+
+/// Boot attributes of a ChromeOS kernel partition.
+///
+/// ChromeOS stores these in the type-specific bits (`48..=63`) of the
+/// [`GptPartitionAttributes`] of each [`CHROME_OS_KERNEL`]
+/// partition. The firmware boots the kernel with the highest
+/// `priority` that either is `successful` or has nonzero `tries`,
+/// decrementing `tries` on each attempt.
+///
+/// [`CHROME_OS_KERNEL`]: GptPartitionType::CHROME_OS_KERNEL
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ChromeOsKernelAttributes {
+    /// Boot priority, from `0` (not bootable) to `15` (highest).
+    pub priority: u8,
+
+    /// Number of boot attempts remaining, from `0` to `15`.
+    pub tries: u8,
+
+    /// Whether the kernel has booted successfully.
+    pub successful: bool,
+}
+
+impl ChromeOsKernelAttributes {
+    /// Maximum value of [`priority`] and [`tries`].
+    ///
+    /// [`priority`]: Self::priority
+    /// [`tries`]: Self::tries
+    pub const MAX: u8 = GptPartitionAttributes::CROS_MAX;
+
+    /// Extract the ChromeOS kernel attributes from `attributes`.
+    #[must_use]
+    pub fn from_attributes(attributes: GptPartitionAttributes) -> Self {
+        Self {
+            priority: attributes.cros_priority(),
+            tries: attributes.cros_tries(),
+            successful: attributes.cros_successful(),
+        }
+    }
+
+    /// Store these attributes in `attributes`. Other type-specific bits
+    /// are left unchanged.
+    ///
+    /// Returns `false` without modifying `attributes` if `priority` or
+    /// `tries` is greater than [`MAX`].
+    ///
+    /// [`MAX`]: Self::MAX
+    pub fn update_attributes(
+        self,
+        attributes: &mut GptPartitionAttributes,
+    ) -> bool {
+        if self.priority > Self::MAX || self.tries > Self::MAX {
+            return false;
+        }
+
+        // These can't fail since the values were checked above.
+        attributes.update_cros_priority(self.priority);
+        attributes.update_cros_tries(self.tries);
+        attributes.update_cros_successful(self.successful);
+        true
+    }
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Read the [`ChromeOsKernelAttributes`] of the partition at
+    /// `index` in the primary partition entry array.
+    ///
+    /// Returns [`DiskError::InvalidPartitionType`] if the partition is
+    /// not a ChromeOS kernel partition.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn chrome_os_kernel_attributes(
+        &mut self,
+        index: u32,
+        block_buf: &mut [u8],
+    ) -> Result<ChromeOsKernelAttributes, DiskError<Io::Error>> {
+        let header = self.read_primary_gpt_header_verified(block_buf)?;
+        let layout = crate::edit::header_layout(&header)?;
+        let entry = self.read_gpt_partition_entry(layout, index, block_buf)?;
+        if { entry.partition_type_guid } != GptPartitionType::CHROME_OS_KERNEL {
+            return Err(DiskError::InvalidPartitionType);
+        }
+        Ok(ChromeOsKernelAttributes::from_attributes(entry.attributes))
+    }
+
+    /// Set the [`ChromeOsKernelAttributes`] of the partition at `index`,
+    /// similar to `cgpt add -P <priority> -T <tries> -S <successful>`.
+    ///
+    /// The entry is updated in both partition entry arrays, and both
+    /// headers are updated with the new CRC32s.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionType`] if the partition is not a
+    ///   ChromeOS kernel partition.
+    /// * [`DiskError::InvalidAttributeValue`] if `priority` or `tries`
+    ///   is greater than [`ChromeOsKernelAttributes::MAX`].
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn set_chrome_os_kernel_attributes(
+        &mut self,
+        index: u32,
+        attributes: ChromeOsKernelAttributes,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_chrome_os_kernel_attributes(index, block_buf, |attrs| {
+            *attrs = attributes;
+        })
+    }
+
+    /// Set the boot priority of the ChromeOS kernel partition at
+    /// `index`, similar to `cgpt add -P`. Other attributes are
+    /// unchanged. See [`set_chrome_os_kernel_attributes`].
+    ///
+    /// [`set_chrome_os_kernel_attributes`]: Self::set_chrome_os_kernel_attributes
+    pub fn set_chrome_os_kernel_priority(
+        &mut self,
+        index: u32,
+        priority: u8,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_chrome_os_kernel_attributes(index, block_buf, |attrs| {
+            attrs.priority = priority;
+        })
+    }
+
+    /// Set the remaining boot tries of the ChromeOS kernel partition at
+    /// `index`, similar to `cgpt add -T`. Other attributes are
+    /// unchanged. See [`set_chrome_os_kernel_attributes`].
+    ///
+    /// [`set_chrome_os_kernel_attributes`]: Self::set_chrome_os_kernel_attributes
+    pub fn set_chrome_os_kernel_tries(
+        &mut self,
+        index: u32,
+        tries: u8,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_chrome_os_kernel_attributes(index, block_buf, |attrs| {
+            attrs.tries = tries;
+        })
+    }
+
+    /// Set the successful flag of the ChromeOS kernel partition at
+    /// `index`, similar to `cgpt add -S`. Other attributes are
+    /// unchanged. See [`set_chrome_os_kernel_attributes`].
+    ///
+    /// [`set_chrome_os_kernel_attributes`]: Self::set_chrome_os_kernel_attributes
+    pub fn set_chrome_os_kernel_successful(
+        &mut self,
+        index: u32,
+        successful: bool,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_chrome_os_kernel_attributes(index, block_buf, |attrs| {
+            attrs.successful = successful;
+        })
+    }
+
+    fn update_chrome_os_kernel_attributes<F>(
+        &mut self,
+        index: u32,
+        block_buf: &mut [u8],
+        f: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnOnce(&mut ChromeOsKernelAttributes),
+    {
+        self.update_gpt_partition_entry(index, block_buf, |entry| {
+            if { entry.partition_type_guid }
+                != GptPartitionType::CHROME_OS_KERNEL
+            {
+                return Err(DiskError::InvalidPartitionType);
+            }
+            let mut attrs =
+                ChromeOsKernelAttributes::from_attributes(entry.attributes);
+            f(&mut attrs);
+            let mut attributes = entry.attributes;
+            if !attrs.update_attributes(&mut attributes) {
+                return Err(DiskError::InvalidAttributeValue);
+            }
+            entry.attributes = attributes;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparison of the GPTs of two disks.
+//!
+//! The [`compare_gpt`] function reads the GPT header and partition
+//! entries of two [`Disk`]s and reports each way in which they differ
+//! as a [`Difference`]. This is useful for checking that a disk matches
+//! a template image.
+//!
+//! Partition entries are compared slot by slot: entry `i` of one disk
+//! is compared with entry `i` of the other.
+//!
+//! # Example
+//!
+//! ```
+//! use gpt_disk_io::compare::{compare_gpt, Difference};
+//! use gpt_disk_io::gpt_disk_types::{BlockSize, Guid};
+//! use gpt_disk_io::{BlockIoAdapter, Disk};
+//!
+//! fn check_guid_only_difference(
+//!     template: &mut [u8],
+//!     deployed: &mut [u8],
+//! ) -> bool {
+//!     let mut template =
+//!         Disk::new(BlockIoAdapter::new(template, BlockSize::BS_512))
+//!             .unwrap();
+//!     let mut deployed =
+//!         Disk::new(BlockIoAdapter::new(deployed, BlockSize::BS_512))
+//!             .unwrap();
+//!
+//!     let mut block_buf = [0; 512];
+//!     let mut ok = true;
+//!     compare_gpt(&mut template, &mut deployed, &mut block_buf, |diff| {
+//!         if !matches!(
+//!             diff,
+//!             Difference::DiskGuid { .. }
+//!                 | Difference::PartitionGuid { .. }
+//!         ) {
+//!             ok = false;
+//!         }
+//!     })
+//!     .unwrap();
+//!     ok
+//! }
+//! ```
+
+use crate::{BlockIo, Disk, DiskError};
+use core::fmt::{self, Debug, Display, Formatter};
+use gpt_disk_types::{
+    GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntryArrayLayout, GptPartitionName, GptPartitionType, Guid,
+    Lba,
+};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A difference between two disks reported by [`compare_gpt`].
+///
+/// In each variant, `a` is the value from the first disk and `b` is
+/// the value from the second disk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Difference {
+    /// The disk GUIDs differ.
+    DiskGuid {
+        /// Disk GUID of the first disk.
+        a: Guid,
+        /// Disk GUID of the second disk.
+        b: Guid,
+    },
+
+    /// The first usable LBAs differ.
+    FirstUsableLba {
+        /// First usable LBA of the first disk.
+        a: Lba,
+        /// First usable LBA of the second disk.
+        b: Lba,
+    },
+
+    /// The last usable LBAs differ.
+    LastUsableLba {
+        /// Last usable LBA of the first disk.
+        a: Lba,
+        /// Last usable LBA of the second disk.
+        b: Lba,
+    },
+
+    /// The number of entries in the partition entry arrays differ.
+    NumberOfPartitionEntries {
+        /// Number of entries on the first disk.
+        a: u32,
+        /// Number of entries on the second disk.
+        b: u32,
+    },
+
+    /// The entry at `index` is used on the second disk but not on the
+    /// first.
+    PartitionAdded {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+    },
+
+    /// The entry at `index` is used on the first disk but not on the
+    /// second.
+    PartitionRemoved {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+    },
+
+    /// The partition types differ.
+    PartitionType {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+        /// Partition type on the first disk.
+        a: GptPartitionType,
+        /// Partition type on the second disk.
+        b: GptPartitionType,
+    },
+
+    /// The unique partition GUIDs differ.
+    PartitionGuid {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+        /// Partition GUID on the first disk.
+        a: Guid,
+        /// Partition GUID on the second disk.
+        b: Guid,
+    },
+
+    /// The starting or ending LBAs differ.
+    PartitionRange {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+        /// Starting and ending LBA on the first disk.
+        a: (Lba, Lba),
+        /// Starting and ending LBA on the second disk.
+        b: (Lba, Lba),
+    },
+
+    /// The partition attributes differ.
+    PartitionAttributes {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+        /// Attributes on the first disk.
+        a: GptPartitionAttributes,
+        /// Attributes on the second disk.
+        b: GptPartitionAttributes,
+    },
+
+    /// The partition names differ.
+    PartitionName {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+        /// Name on the first disk.
+        a: GptPartitionName,
+        /// Name on the second disk.
+        b: GptPartitionName,
+    },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DiskGuid { a, b } => {
+                write!(f, "disk GUID differs: {a} != {b}")
+            }
+            Self::FirstUsableLba { a, b } => {
+                write!(f, "first usable LBA differs: {a} != {b}")
+            }
+            Self::LastUsableLba { a, b } => {
+                write!(f, "last usable LBA differs: {a} != {b}")
+            }
+            Self::NumberOfPartitionEntries { a, b } => {
+                write!(f, "number of partition entries differs: {a} != {b}")
+            }
+            Self::PartitionAdded { index } => {
+                write!(f, "partition {index} added")
+            }
+            Self::PartitionRemoved { index } => {
+                write!(f, "partition {index} removed")
+            }
+            Self::PartitionType { index, a, b } => {
+                write!(f, "partition {index} type differs: {a} != {b}")
+            }
+            Self::PartitionGuid { index, a, b } => {
+                write!(f, "partition {index} GUID differs: {a} != {b}")
+            }
+            Self::PartitionRange { index, a, b } => write!(
+                f,
+                "partition {index} range differs: {}..={} != {}..={}",
+                a.0, a.1, b.0, b.1
+            ),
+            Self::PartitionAttributes { index, a, b } => {
+                write!(f, "partition {index} attributes differ: {a} != {b}")
+            }
+            Self::PartitionName { index, a, b } => {
+                write!(f, "partition {index} name differs: \"{a}\" != \"{b}\"")
+            }
+        }
+    }
+}
+
+/// Error type returned by [`compare_gpt`], indicating which disk the
+/// error came from.
+///
+/// If both IO error types implement [`Error`], this type implements
+/// [`Error`] as well, with the inner [`DiskError`] reported as the
+/// [`source`].
+///
+/// [`Error`]: core::error::Error
+/// [`source`]: core::error::Error::source
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum CompareError<ErrorA: Debug + Display, ErrorB: Debug + Display> {
+    /// Error from the first disk.
+    DiskA(DiskError<ErrorA>),
+
+    /// Error from the second disk.
+    DiskB(DiskError<ErrorB>),
+}
+
+impl<ErrorA, ErrorB> Display for CompareError<ErrorA, ErrorB>
+where
+    ErrorA: Debug + Display,
+    ErrorB: Debug + Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DiskA(err) => write!(f, "first disk: {err}"),
+            Self::DiskB(err) => write!(f, "second disk: {err}"),
+        }
+    }
+}
+
+impl<ErrorA, ErrorB> core::error::Error for CompareError<ErrorA, ErrorB>
+where
+    ErrorA: core::error::Error + 'static,
+    ErrorB: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::DiskA(err) => Some(err),
+            Self::DiskB(err) => Some(err),
+        }
+    }
+}
+
+/// Read a partition entry, treating an index past the end of the array
+/// as an unused entry.
+fn read_entry<Io: BlockIo>(
+    disk: &mut Disk<Io>,
+    layout: GptPartitionEntryArrayLayout,
+    index: u32,
+    block_buf: &mut [u8],
+) -> Result<GptPartitionEntry, DiskError<Io::Error>> {
+    if index < layout.num_entries {
+        disk.read_gpt_partition_entry(layout, index, block_buf)
+    } else {
+        Ok(GptPartitionEntry::default())
+    }
+}
+
+fn compare_headers<F>(a: &GptHeader, b: &GptHeader, report: &mut F)
+where
+    F: FnMut(Difference),
+{
+    if { a.disk_guid } != { b.disk_guid } {
+        report(Difference::DiskGuid {
+            a: a.disk_guid,
+            b: b.disk_guid,
+        });
+    }
+    if { a.first_usable_lba } != { b.first_usable_lba } {
+        report(Difference::FirstUsableLba {
+            a: a.first_usable_lba.into(),
+            b: b.first_usable_lba.into(),
+        });
+    }
+    if { a.last_usable_lba } != { b.last_usable_lba } {
+        report(Difference::LastUsableLba {
+            a: a.last_usable_lba.into(),
+            b: b.last_usable_lba.into(),
+        });
+    }
+    if { a.number_of_partition_entries } != { b.number_of_partition_entries } {
+        report(Difference::NumberOfPartitionEntries {
+            a: a.number_of_partition_entries.to_u32(),
+            b: b.number_of_partition_entries.to_u32(),
+        });
+    }
+}
+
+fn compare_entries<F>(
+    index: u32,
+    a: &GptPartitionEntry,
+    b: &GptPartitionEntry,
+    report: &mut F,
+) where
+    F: FnMut(Difference),
+{
+    match (a.is_used(), b.is_used()) {
+        (false, false) => return,
+        (false, true) => return report(Difference::PartitionAdded { index }),
+        (true, false) => return report(Difference::PartitionRemoved { index }),
+        (true, true) => {}
+    }
+
+    if { a.partition_type_guid } != { b.partition_type_guid } {
+        report(Difference::PartitionType {
+            index,
+            a: a.partition_type_guid,
+            b: b.partition_type_guid,
+        });
+    }
+    if { a.unique_partition_guid } != { b.unique_partition_guid } {
+        report(Difference::PartitionGuid {
+            index,
+            a: a.unique_partition_guid,
+            b: b.unique_partition_guid,
+        });
+    }
+    if { a.starting_lba } != { b.starting_lba } || { a.ending_lba } != {
+        b.ending_lba
+    } {
+        report(Difference::PartitionRange {
+            index,
+            a: (a.starting_lba.into(), a.ending_lba.into()),
+            b: (b.starting_lba.into(), b.ending_lba.into()),
+        });
+    }
+    if { a.attributes } != { b.attributes } {
+        report(Difference::PartitionAttributes {
+            index,
+            a: a.attributes,
+            b: b.attributes,
+        });
+    }
+    if { a.name } != { b.name } {
+        report(Difference::PartitionName {
+            index,
+            a: a.name,
+            b: b.name,
+        });
+    }
+}
+
+/// Compare the GPTs of two disks, calling `report` for each
+/// difference.
+///
+/// The header of each disk is read with
+/// [`Disk::read_gpt_header_with_fallback`], so a disk with a corrupt
+/// primary header is compared using its secondary header. Only fields
+/// that describe the partition table are compared; the location of
+/// the headers and partition entry arrays, and the CRC32s, are not.
+/// Use [`check_disk`] to check that each disk is internally
+/// consistent.
+///
+/// Partition entries are compared slot by slot, up to the larger of
+/// the two partition entry counts. Entries past the end of the shorter
+/// array are treated as unused. For a pair of entries that are both
+/// used, each field that differs is reported separately. Differences
+/// are reported in order of entry index.
+///
+/// `block_buf` is a mutable byte buffer with a length of at least one
+/// block of each disk.
+///
+/// [`check_disk`]: crate::validate::check_disk
+pub fn compare_gpt<IoA, IoB, F>(
+    disk_a: &mut Disk<IoA>,
+    disk_b: &mut Disk<IoB>,
+    block_buf: &mut [u8],
+    mut report: F,
+) -> Result<(), CompareError<IoA::Error, IoB::Error>>
+where
+    IoA: BlockIo,
+    IoB: BlockIo,
+    F: FnMut(Difference),
+{
+    let (header_a, _) = disk_a
+        .read_gpt_header_with_fallback(block_buf)
+        .map_err(CompareError::DiskA)?;
+    let (header_b, _) = disk_b
+        .read_gpt_header_with_fallback(block_buf)
+        .map_err(CompareError::DiskB)?;
+    compare_headers(&header_a, &header_b, &mut report);
+
+    let layout_a = header_a
+        .get_partition_entry_array_layout()
+        .map_err(|_| CompareError::DiskA(DiskError::CorruptGptHeader))?;
+    let layout_b = header_b
+        .get_partition_entry_array_layout()
+        .map_err(|_| CompareError::DiskB(DiskError::CorruptGptHeader))?;
+
+    for index in 0..layout_a.num_entries.max(layout_b.num_entries) {
+        let entry_a = read_entry(disk_a, layout_a, index, block_buf)
+            .map_err(CompareError::DiskA)?;
+        let entry_b = read_entry(disk_b, layout_b, index, block_buf)
+            .map_err(CompareError::DiskB)?;
+        compare_entries(index, &entry_a, &entry_b, &mut report);
+    }
+
+    Ok(())
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Compare the GPT of this disk with `other` and return all
+    /// differences found. See [`compare_gpt`] for details.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block of each disk.
+    #[cfg(feature = "alloc")]
+    pub fn compare_gpt<Other: BlockIo>(
+        &mut self,
+        other: &mut Disk<Other>,
+        block_buf: &mut [u8],
+    ) -> Result<Vec<Difference>, CompareError<Io::Error, Other::Error>> {
+        let mut differences = Vec::new();
+        compare_gpt(self, other, block_buf, |diff| differences.push(diff))?;
+        Ok(differences)
+    }
+}
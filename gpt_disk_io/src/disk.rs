@@ -6,14 +6,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::edit::header_layout;
 use crate::BlockIo;
-use bytemuck::{bytes_of, from_bytes};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use bytemuck::{bytes_of, bytes_of_mut, from_bytes};
+use core::error::Error;
 use core::fmt::{self, Debug, Display, Formatter};
 use core::mem;
 use gpt_disk_types::{
-    GptHeader, GptPartitionEntry, GptPartitionEntryArray,
-    GptPartitionEntryArrayError, GptPartitionEntryArrayLayout, Lba,
-    MasterBootRecord,
+    crc, BlockSize, Crc32, GptHeader, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayError,
+    GptPartitionEntryArrayLayout, HybridMbr, Lba, LbaLe, MasterBootRecord,
+    U32Le,
 };
 
 /// Iterator over entries in a partition entry array.
@@ -22,61 +27,93 @@ struct GptPartitionEntryIter<'disk, 'buf, Io: BlockIo> {
     block_buf: &'buf mut [u8],
     layout: GptPartitionEntryArrayLayout,
     next_index: u32,
-    current_lba: Lba,
-    byte_offset_within_lba: usize,
-    entry_size: usize,
+    /// The block currently held in `block_buf`, if any.
+    current_lba: Option<Lba>,
+    /// Number of blocks in the disk, if strict mode is enabled.
+    strict_num_blocks: Option<u64>,
 }
 
 impl<'disk, 'buf, Io: BlockIo> GptPartitionEntryIter<'disk, 'buf, Io> {
     fn new(
         disk: &'disk mut Disk<Io>,
         layout: GptPartitionEntryArrayLayout,
+        start_index: u32,
         block_buf: &'buf mut [u8],
     ) -> Result<Self, DiskError<Io::Error>> {
-        let mut iter = Self {
+        if start_index > layout.num_entries {
+            return Err(DiskError::InvalidPartitionIndex);
+        }
+        let strict_num_blocks = if disk.strict {
+            Some(disk.num_blocks()?)
+        } else {
+            None
+        };
+        Ok(Self {
             disk,
             block_buf,
-            next_index: 0,
-            current_lba: layout.start_lba,
-            byte_offset_within_lba: 0,
             layout,
-            entry_size: layout
-                .entry_size
-                .to_usize()
-                .ok_or(DiskError::Overflow)?,
-        };
-        iter.set_current_lba(iter.current_lba)?;
-        Ok(iter)
+            next_index: start_index,
+            current_lba: None,
+            strict_num_blocks,
+        })
     }
 
-    fn set_current_lba(
-        &mut self,
-        lba: Lba,
-    ) -> Result<(), DiskError<Io::Error>> {
-        self.current_lba = lba;
-        self.byte_offset_within_lba = 0;
-        Ok(self.disk.io.read_blocks(self.current_lba, self.block_buf)?)
+    /// Read `lba` into `block_buf`, unless it's already there.
+    fn load_block(&mut self, lba: Lba) -> Result<(), DiskError<Io::Error>> {
+        if self.current_lba != Some(lba) {
+            // Clear first so that a failed read isn't treated as cached.
+            self.current_lba = None;
+            self.disk.read_blocks(lba, self.block_buf)?;
+            self.current_lba = Some(lba);
+        }
+        Ok(())
     }
 
-    fn read_current_entry(&mut self) -> Option<<Self as Iterator>::Item> {
-        let entry_bytes = self.block_buf.get(
-            self.byte_offset_within_lba
-                ..self.byte_offset_within_lba + self.entry_size,
-        )?;
-
-        self.byte_offset_within_lba += self.entry_size;
-
-        self.next_index += 1;
+    /// Read the entry at `index`. The entry may span multiple blocks if
+    /// the block size is not a multiple of the entry size.
+    fn read_entry(
+        &mut self,
+        index: u32,
+    ) -> Result<GptPartitionEntry, DiskError<Io::Error>> {
+        let block_size = u64::try_from(self.block_buf.len())
+            .map_err(|_| DiskError::Overflow)?;
+        let offset = self
+            .layout
+            .entry_size
+            .to_u64()
+            .checked_mul(u64::from(index))
+            .ok_or(DiskError::Overflow)?;
+        let mut lba = self
+            .layout
+            .start_lba
+            .to_u64()
+            .checked_add(offset / block_size)
+            .ok_or(DiskError::Overflow)?;
+        // OK to unwrap: the remainder is less than `block_buf.len()`.
+        let mut offset_in_block = usize::try_from(offset % block_size).unwrap();
 
-        Some(Ok(*from_bytes::<GptPartitionEntry>(
-            &entry_bytes[..mem::size_of::<GptPartitionEntry>()],
-        )))
+        let mut entry = GptPartitionEntry::default();
+        let dst = bytes_of_mut(&mut entry);
+        let mut done = 0;
+        while done < dst.len() {
+            self.load_block(Lba(lba))?;
+            let len =
+                (self.block_buf.len() - offset_in_block).min(dst.len() - done);
+            dst[done..done + len].copy_from_slice(
+                &self.block_buf[offset_in_block..offset_in_block + len],
+            );
+            done += len;
+            offset_in_block = 0;
+            lba = lba.checked_add(1).ok_or(DiskError::Overflow)?;
+        }
+        if let Some(num_blocks) = self.strict_num_blocks {
+            check_gpt_partition_entry_strict(&entry, num_blocks)?;
+        }
+        Ok(entry)
     }
 }
 
-impl<'disk, 'buf, Io: BlockIo> Iterator
-    for GptPartitionEntryIter<'disk, 'buf, Io>
-{
+impl<Io: BlockIo> Iterator for GptPartitionEntryIter<'_, '_, Io> {
     type Item = Result<GptPartitionEntry, DiskError<Io::Error>>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -84,16 +121,32 @@ impl<'disk, 'buf, Io: BlockIo> Iterator
             return None;
         }
 
-        if let Some(entry) = self.read_current_entry() {
-            Some(entry)
+        let entry = self.read_entry(self.next_index);
+        if entry.is_ok() {
+            self.next_index += 1;
         } else {
-            let next_lba = Lba(self.current_lba.to_u64() + 1);
-            if let Err(err) = self.set_current_lba(next_lba) {
-                Some(Err(err))
-            } else {
-                self.read_current_entry()
-            }
+            // Stop iterating after an error.
+            self.next_index = self.layout.num_entries;
         }
+        Some(entry)
+    }
+
+    /// Skip directly to entry `n` from the current position. Only the
+    /// blocks containing that entry are read.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let n = u32::try_from(n).unwrap_or(u32::MAX);
+        self.next_index = self
+            .next_index
+            .saturating_add(n)
+            .min(self.layout.num_entries);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Iteration stops early after an error, so the lower bound is
+        // zero.
+        let remaining = self.layout.num_entries - self.next_index;
+        (0, usize::try_from(remaining).ok())
     }
 }
 
@@ -101,20 +154,115 @@ impl<'disk, 'buf, Io: BlockIo> Iterator
 /// <https://stackoverflow.com/a/50548538>.
 pub trait Captures<'a, 'b> {}
 
-impl<'a, 'b, T: ?Sized> Captures<'a, 'b> for T {}
+impl<T: ?Sized> Captures<'_, '_> for T {}
 
 /// Error type used by [`Disk`] methods.
+///
+/// If `IoError` implements [`Error`], this type implements [`Error`]
+/// as well, with [`DiskError::Io`] reported as the [`source`].
+/// Likewise, `Clone`, `PartialEq`, and `Eq` are implemented if
+/// `IoError` implements them.
+///
+/// Each variant has a stable numeric [`code`], for passing errors
+/// across an FFI boundary.
+///
+/// [`code`]: Self::code
+/// [`source`]: Error::source
 #[allow(clippy::module_name_repetitions)]
-#[derive(Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DiskError<IoError: Debug + Display> {
     /// The storage buffer is not large enough.
     BufferTooSmall,
 
+    /// A buffer's length is not a multiple of the block size.
+    InvalidBufferSize,
+
+    /// A buffer is not aligned to the [`BlockIo::buffer_alignment`]
+    /// required by the device. See [`Disk::set_bounce_buffer_enabled`]
+    /// for a way to handle this automatically.
+    ///
+    /// [`BlockIo::buffer_alignment`]: crate::BlockIo::buffer_alignment
+    UnalignedBuffer,
+
     /// Numeric overflow occurred.
     Overflow,
 
-    /// The partition entry size is larger than a single block.
-    BlockSizeSmallerThanPartitionEntry,
+    /// A GPT header has an invalid signature or CRC32 checksum.
+    CorruptGptHeader,
+
+    /// A partition entry array does not match the
+    /// [`partition_entry_array_crc32`] in its GPT header.
+    ///
+    /// [`partition_entry_array_crc32`]: GptHeader::partition_entry_array_crc32
+    CorruptPartitionEntryArray,
+
+    /// A partition entry index is not less than the number of entries
+    /// in the partition entry array, or refers to an unused entry when
+    /// a used one is required.
+    InvalidPartitionIndex,
+
+    /// All entries in the partition entry array are in use.
+    NoFreePartitionEntry,
+
+    /// There is no free space on the disk suitable for the requested
+    /// partition.
+    NoFreeSpace,
+
+    /// A requested partition size is invalid, such as an end LBA
+    /// before the start LBA, or a shrink that was not allowed.
+    InvalidPartitionSize,
+
+    /// A partition GUID is zero or is already in use by another
+    /// partition.
+    InvalidPartitionGuid,
+
+    /// A disk GUID is zero.
+    InvalidDiskGuid,
+
+    /// A partition does not have the type required by the operation.
+    InvalidPartitionType,
+
+    /// An attribute value is out of range.
+    InvalidAttributeValue,
+
+    /// A partition name is too long or contains characters that can't
+    /// be represented in UCS-2.
+    InvalidPartitionName,
+
+    /// The [`BlockIo`] reports that it is read-only (see
+    /// [`BlockIo::is_read_only`]). This is returned before anything is
+    /// written.
+    ///
+    /// [`BlockIo`]: crate::BlockIo
+    /// [`BlockIo::is_read_only`]: crate::BlockIo::is_read_only
+    ReadOnly,
+
+    /// The first block contains an MBR with legacy partitions, or other
+    /// data that is not a protective MBR.
+    MbrInUse,
+
+    /// A [`HybridMbr`] cannot be built for the disk, for example
+    /// because a mirrored partition extends past the end of the disk.
+    /// See [`HybridMbr::build`] for details.
+    InvalidHybridMbr,
+
+    /// The primary and secondary GPT headers describe partition entry
+    /// arrays with a different number of entries or entry size.
+    GptHeaderMismatch,
+
+    /// In [strict mode], a GPT header contains values that are out of
+    /// spec or out of range for the disk.
+    ///
+    /// [strict mode]: Disk::set_strict
+    OutOfSpecGptHeader,
+
+    /// In [strict mode], a used partition entry has an invalid or
+    /// out-of-range LBA range. Also returned by operations on a
+    /// partition's contents, such as [`Disk::hash_partition`], when the
+    /// range is not readable.
+    ///
+    /// [strict mode]: Disk::set_strict
+    OutOfSpecPartitionEntry,
 
     /// Error from a [`BlockIo`] implementation (see [`BlockIo::Error`]).
     ///
@@ -123,6 +271,70 @@ pub enum DiskError<IoError: Debug + Display> {
     Io(IoError),
 }
 
+impl<IoError> DiskError<IoError>
+where
+    IoError: Debug + Display,
+{
+    /// Get a numeric code for the error variant. Codes are never zero,
+    /// so zero can be used to indicate success. The code of an existing
+    /// variant will not change, and new variants get new codes.
+    ///
+    /// * 1: [`BufferTooSmall`](Self::BufferTooSmall)
+    /// * 2: [`Overflow`](Self::Overflow)
+    /// * 3: [`CorruptGptHeader`](Self::CorruptGptHeader)
+    /// * 4: [`InvalidPartitionIndex`](Self::InvalidPartitionIndex)
+    /// * 5: [`NoFreePartitionEntry`](Self::NoFreePartitionEntry)
+    /// * 6: [`NoFreeSpace`](Self::NoFreeSpace)
+    /// * 7: [`InvalidPartitionSize`](Self::InvalidPartitionSize)
+    /// * 8: [`InvalidPartitionGuid`](Self::InvalidPartitionGuid)
+    /// * 9: [`InvalidDiskGuid`](Self::InvalidDiskGuid)
+    /// * 10: [`InvalidPartitionType`](Self::InvalidPartitionType)
+    /// * 11: [`InvalidAttributeValue`](Self::InvalidAttributeValue)
+    /// * 12: [`ReadOnly`](Self::ReadOnly)
+    /// * 13: [`MbrInUse`](Self::MbrInUse)
+    /// * 14: [`GptHeaderMismatch`](Self::GptHeaderMismatch)
+    /// * 15: [`OutOfSpecGptHeader`](Self::OutOfSpecGptHeader)
+    /// * 16: [`OutOfSpecPartitionEntry`](Self::OutOfSpecPartitionEntry)
+    /// * 17: [`InvalidBufferSize`](Self::InvalidBufferSize)
+    /// * 18: [`UnalignedBuffer`](Self::UnalignedBuffer)
+    /// * 19: [`InvalidPartitionName`](Self::InvalidPartitionName)
+    /// * 20: [`InvalidHybridMbr`](Self::InvalidHybridMbr)
+    /// * 21: [`CorruptPartitionEntryArray`](Self::CorruptPartitionEntryArray)
+    /// * 100: [`Io`](Self::Io)
+    ///
+    /// The [`Io`](Self::Io) code does not identify the underlying
+    /// error; use the [`BlockIo::Error`] value for that.
+    ///
+    /// [`BlockIo::Error`]: crate::BlockIo::Error
+    #[must_use]
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::BufferTooSmall => 1,
+            Self::Overflow => 2,
+            Self::CorruptGptHeader => 3,
+            Self::InvalidPartitionIndex => 4,
+            Self::NoFreePartitionEntry => 5,
+            Self::NoFreeSpace => 6,
+            Self::InvalidPartitionSize => 7,
+            Self::InvalidPartitionGuid => 8,
+            Self::InvalidDiskGuid => 9,
+            Self::InvalidPartitionType => 10,
+            Self::InvalidAttributeValue => 11,
+            Self::ReadOnly => 12,
+            Self::MbrInUse => 13,
+            Self::GptHeaderMismatch => 14,
+            Self::OutOfSpecGptHeader => 15,
+            Self::OutOfSpecPartitionEntry => 16,
+            Self::InvalidBufferSize => 17,
+            Self::UnalignedBuffer => 18,
+            Self::InvalidPartitionName => 19,
+            Self::InvalidHybridMbr => 20,
+            Self::CorruptPartitionEntryArray => 21,
+            Self::Io(_) => 100,
+        }
+    }
+}
+
 impl<IoError> From<IoError> for DiskError<IoError>
 where
     IoError: Debug + Display,
@@ -139,15 +351,386 @@ where
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::BufferTooSmall => f.write_str("storage buffer is too small"),
+            Self::InvalidBufferSize => {
+                f.write_str("buffer length is not a multiple of the block size")
+            }
+            Self::UnalignedBuffer => f.write_str(
+                "buffer does not have the alignment required by the device",
+            ),
             Self::Overflow => f.write_str("numeric overflow occurred"),
-            Self::BlockSizeSmallerThanPartitionEntry => {
-                f.write_str("partition entries are larger than a single block")
+            Self::CorruptGptHeader => {
+                f.write_str("GPT header has an invalid signature or CRC32")
+            }
+            Self::CorruptPartitionEntryArray => f.write_str(
+                "partition entry array does not match the CRC32 in the GPT header",
+            ),
+            Self::InvalidPartitionIndex => {
+                f.write_str("partition entry index is out of range")
+            }
+            Self::NoFreePartitionEntry => {
+                f.write_str("no free entries in the partition entry array")
+            }
+            Self::NoFreeSpace => {
+                f.write_str("not enough free space for the partition")
+            }
+            Self::InvalidPartitionSize => {
+                f.write_str("requested partition size is invalid")
+            }
+            Self::InvalidPartitionGuid => {
+                f.write_str("partition GUID is zero or already in use")
+            }
+            Self::InvalidDiskGuid => f.write_str("disk GUID is zero"),
+            Self::InvalidPartitionType => {
+                f.write_str("partition has the wrong type for this operation")
+            }
+            Self::InvalidAttributeValue => {
+                f.write_str("attribute value is out of range")
+            }
+            Self::InvalidPartitionName => f.write_str(
+                "partition name is too long or contains invalid characters",
+            ),
+            Self::ReadOnly => f.write_str("disk is read-only"),
+            Self::MbrInUse => {
+                f.write_str("first block contains a non-protective MBR")
+            }
+            Self::InvalidHybridMbr => {
+                f.write_str("hybrid MBR cannot be built for the disk")
+            }
+            Self::GptHeaderMismatch => f.write_str(
+                "primary and secondary GPT headers have different partition entry arrays",
+            ),
+            Self::OutOfSpecGptHeader => {
+                f.write_str("GPT header contains out-of-spec values")
+            }
+            Self::OutOfSpecPartitionEntry => {
+                f.write_str("partition entry contains out-of-spec values")
             }
             Self::Io(io) => Display::fmt(io, f),
         }
     }
 }
 
+// This requires `IoError: Error` rather than just `Debug + Display` so
+// that the IO error can be returned from `source`.
+impl<IoError> Error for DiskError<IoError>
+where
+    IoError: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(io) => Some(io),
+            _ => None,
+        }
+    }
+}
+
+/// Direction of a [`Disk::access_bytes`] call.
+enum ByteAccess<'a> {
+    Read(&'a mut [u8]),
+    Write(&'a [u8]),
+}
+
+impl ByteAccess<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Read(dst) => dst.len(),
+            Self::Write(src) => src.len(),
+        }
+    }
+}
+
+/// Which copy of the GPT header was used.
+///
+/// Returned by [`Disk::read_gpt_header_with_fallback`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptHeaderCopy {
+    /// The primary header in the second block.
+    Primary,
+
+    /// The secondary (backup) header in the last block.
+    Secondary,
+}
+
+impl Display for GptHeaderCopy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary => f.write_str("primary"),
+            Self::Secondary => f.write_str("secondary"),
+        }
+    }
+}
+
+/// Agreement between the primary and secondary GPT headers.
+///
+/// Returned by [`GptHeaders::consistency`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GptHeaderConsistency {
+    /// Both headers are valid and describe the same partition table.
+    Match,
+
+    /// Both headers are valid, but they disagree. For example, they
+    /// have different disk GUIDs or partition entry array CRCs, or
+    /// their `my_lba` and `alternate_lba` fields don't point at each
+    /// other.
+    Mismatch,
+
+    /// Only the primary header is valid.
+    PrimaryOnly,
+
+    /// Only the secondary header is valid.
+    SecondaryOnly,
+
+    /// Neither header is valid.
+    NeitherValid,
+}
+
+impl Display for GptHeaderConsistency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Match => f.write_str("headers match"),
+            Self::Mismatch => f.write_str("headers do not match"),
+            Self::PrimaryOnly => f.write_str("only primary header is valid"),
+            Self::SecondaryOnly => {
+                f.write_str("only secondary header is valid")
+            }
+            Self::NeitherValid => f.write_str("neither header is valid"),
+        }
+    }
+}
+
+/// A GPT header found by [`Disk::find_nested_gpts`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct NestedGpt {
+    /// The header that was found.
+    pub header: GptHeader,
+
+    /// LBA in the scanned disk at which the header was found.
+    pub header_lba: Lba,
+
+    /// LBA in the scanned disk at which the nested disk starts, so that
+    /// `header_lba` is `start_lba + header.my_lba`.
+    pub start_lba: Lba,
+
+    /// Number of blocks in the nested disk, based on the location of
+    /// the secondary header.
+    pub num_blocks: u64,
+}
+
+impl NestedGpt {
+    /// Check if `header` is valid, and if so, create a `NestedGpt` for
+    /// it.
+    fn new(header: GptHeader, header_lba: Lba) -> Option<Self> {
+        if !header.is_signature_valid()
+            || header.header_crc32 != header.calculate_header_crc32()
+        {
+            return None;
+        }
+        let my_lba = header.my_lba.to_u64();
+        let alternate_lba = header.alternate_lba.to_u64();
+        let start_lba = header_lba.to_u64().checked_sub(my_lba)?;
+        // The secondary header is in the last block.
+        let num_blocks = my_lba.max(alternate_lba).checked_add(1)?;
+        Some(Self {
+            header,
+            header_lba,
+            start_lba: Lba(start_lba),
+            num_blocks,
+        })
+    }
+
+    /// Get which copy of the GPT header this is, based on whether
+    /// `my_lba` is before or after `alternate_lba`.
+    #[must_use]
+    pub fn copy(&self) -> GptHeaderCopy {
+        if self.header.my_lba.to_u64() <= self.header.alternate_lba.to_u64() {
+            GptHeaderCopy::Primary
+        } else {
+            GptHeaderCopy::Secondary
+        }
+    }
+}
+
+/// Both GPT headers of a disk.
+///
+/// Returned by [`Disk::read_gpt_headers`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GptHeaders {
+    /// The primary header, or `None` if it is corrupt.
+    pub primary: Option<GptHeader>,
+
+    /// The secondary header, or `None` if it is corrupt.
+    pub secondary: Option<GptHeader>,
+}
+
+impl GptHeaders {
+    /// Get whether the headers are valid and agree with each other.
+    #[must_use]
+    pub fn consistency(&self) -> GptHeaderConsistency {
+        match (&self.primary, &self.secondary) {
+            (Some(primary), Some(secondary)) => {
+                if gpt_headers_agree(primary, secondary)
+                    && primary.alternate_lba == secondary.my_lba
+                    && secondary.alternate_lba == primary.my_lba
+                {
+                    GptHeaderConsistency::Match
+                } else {
+                    GptHeaderConsistency::Mismatch
+                }
+            }
+            (Some(_), None) => GptHeaderConsistency::PrimaryOnly,
+            (None, Some(_)) => GptHeaderConsistency::SecondaryOnly,
+            (None, None) => GptHeaderConsistency::NeitherValid,
+        }
+    }
+
+    /// Get the header to use, preferring the primary header. This is
+    /// the same header [`Disk::read_gpt_header_with_fallback`] would
+    /// return.
+    #[must_use]
+    pub fn preferred(&self) -> Option<(GptHeader, GptHeaderCopy)> {
+        match (self.primary, self.secondary) {
+            (Some(primary), _) => Some((primary, GptHeaderCopy::Primary)),
+            (None, Some(secondary)) => {
+                Some((secondary, GptHeaderCopy::Secondary))
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+/// Check that the fields the two headers should share are the same.
+pub(crate) fn gpt_headers_agree(
+    primary: &GptHeader,
+    secondary: &GptHeader,
+) -> bool {
+    primary.revision == secondary.revision
+        && primary.header_size == secondary.header_size
+        && primary.first_usable_lba == secondary.first_usable_lba
+        && primary.last_usable_lba == secondary.last_usable_lba
+        && { primary.disk_guid } == { secondary.disk_guid }
+        && primary.number_of_partition_entries
+            == secondary.number_of_partition_entries
+        && primary.size_of_partition_entry == secondary.size_of_partition_entry
+        && primary.partition_entry_array_crc32
+            == secondary.partition_entry_array_crc32
+}
+
+/// Check the signature and CRC32 of a header that was just read.
+fn verify_gpt_header<IoError: Debug + Display>(
+    header: GptHeader,
+) -> Result<GptHeader, DiskError<IoError>> {
+    if header.is_signature_valid()
+        && header.header_crc32 == header.calculate_header_crc32()
+    {
+        Ok(header)
+    } else {
+        Err(DiskError::CorruptGptHeader)
+    }
+}
+
+/// Headers cached by [`Disk`] when header caching is enabled. The
+/// headers are stored exactly as read, without any validation.
+#[derive(Default)]
+struct HeaderCache {
+    /// Header at LBA 1.
+    primary: Option<GptHeader>,
+    /// Header at any other LBA, normally the secondary header.
+    other: Option<(Lba, GptHeader)>,
+}
+
+impl HeaderCache {
+    fn get(&self, lba: Lba) -> Option<GptHeader> {
+        if lba == Lba(1) {
+            self.primary
+        } else {
+            self.other.and_then(|(other_lba, header)| {
+                (other_lba == lba).then_some(header)
+            })
+        }
+    }
+
+    fn insert(&mut self, lba: Lba, header: GptHeader) {
+        if lba == Lba(1) {
+            self.primary = Some(header);
+        } else {
+            self.other = Some((lba, header));
+        }
+    }
+
+    /// Remove any headers in the `num_blocks` blocks starting at
+    /// `start_lba`.
+    fn invalidate(&mut self, start_lba: Lba, num_blocks: u64) {
+        let start = start_lba.to_u64();
+        let overlaps = |lba: Lba| {
+            lba.to_u64()
+                .checked_sub(start)
+                .is_some_and(|offset| offset < num_blocks)
+        };
+        if overlaps(Lba(1)) {
+            self.primary = None;
+        }
+        if self.other.is_some_and(|(lba, _)| overlaps(lba)) {
+            self.other = None;
+        }
+    }
+}
+
+/// Strict mode check of a header read from a disk of `num_blocks`
+/// blocks.
+fn check_gpt_header_strict<IoError: Debug + Display>(
+    header: &GptHeader,
+    block_size: BlockSize,
+    num_blocks: u64,
+) -> Result<(), DiskError<IoError>> {
+    let in_disk = |lba: LbaLe| lba.to_u64() < num_blocks;
+    let header_size = header.header_size.to_u32();
+    let first_usable = header.first_usable_lba.to_u64();
+    let last_usable = header.last_usable_lba.to_u64();
+
+    // The entry size is checked when creating the layout.
+    let array_in_disk = header
+        .get_partition_entry_array_layout()
+        .ok()
+        .and_then(|layout| {
+            let array_blocks = layout.num_blocks(block_size)?;
+            let array_end =
+                layout.start_lba.to_u64().checked_add(array_blocks)?;
+            Some(array_end <= num_blocks)
+        })
+        .unwrap_or(false);
+
+    if usize::try_from(header_size).is_ok_and(|size| {
+        size >= mem::size_of::<GptHeader>()
+            && block_size.to_usize().is_some_and(|bs| size <= bs)
+    }) && in_disk(header.my_lba)
+        && in_disk(header.alternate_lba)
+        && first_usable <= last_usable
+        && last_usable < num_blocks
+        && array_in_disk
+    {
+        Ok(())
+    } else {
+        Err(DiskError::OutOfSpecGptHeader)
+    }
+}
+
+/// Strict mode check of a partition entry read from a disk of
+/// `num_blocks` blocks. Unused entries are not checked.
+fn check_gpt_partition_entry_strict<IoError: Debug + Display>(
+    entry: &GptPartitionEntry,
+    num_blocks: u64,
+) -> Result<(), DiskError<IoError>> {
+    if !entry.is_used()
+        || entry
+            .lba_range()
+            .is_some_and(|range| range.end().to_u64() < num_blocks)
+    {
+        Ok(())
+    } else {
+        Err(DiskError::OutOfSpecPartitionEntry)
+    }
+}
+
 /// Read and write GPT disk data.
 ///
 /// The disk is accessed via an object implementing the [`BlockIo`]
@@ -164,6 +747,29 @@ where
 /// argument that is multiple blocks in size.) These buffer arguments
 /// allow `Disk` to avoid doing any internal memory allocation.
 ///
+/// Buffers passed to the [`BlockIo`] must have a length that is a
+/// multiple of the block size, and be aligned to
+/// [`BlockIo::buffer_alignment`]. `Disk` checks this before each read
+/// and write, returning [`DiskError::InvalidBufferSize`] or
+/// [`DiskError::UnalignedBuffer`] rather than passing an invalid buffer
+/// along. With the `alloc` feature, [`set_bounce_buffer_enabled`] can
+/// be used to copy data through an aligned buffer instead.
+///
+/// # Strict mode
+///
+/// By default, headers and partition entries are returned as-is, even
+/// if their fields are nonsensical. Call [`set_strict`] to have reads
+/// reject them instead; see that method for the checks performed.
+///
+/// # Header cache
+///
+/// Every operation that needs a GPT header reads it from the disk. If
+/// reads are slow, for example over a network, call
+/// [`set_header_cache_enabled`] to keep headers in memory after they
+/// are first read. Writes through the `Disk` keep the cache up to date;
+/// if the storage may be modified by something else, call
+/// [`clear_header_cache`] before reading.
+///
 /// # Partition entry arrays
 ///
 /// Partition entry arrays can be read in two ways: one block at a time
@@ -176,23 +782,251 @@ where
 /// [`write_gpt_partition_entry_array`]; a block-at-a-time method may be
 /// added in the future.
 ///
+/// [`clear_header_cache`]: Self::clear_header_cache
 /// [`flush`]: Self::flush
+/// [`set_bounce_buffer_enabled`]: Self::set_bounce_buffer_enabled
+/// [`set_header_cache_enabled`]: Self::set_header_cache_enabled
+/// [`set_strict`]: Self::set_strict
 /// [`gpt_partition_entry_array_iter`]: Self::gpt_partition_entry_array_iter
 /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
 /// [`write_gpt_partition_entry_array`]: Self::write_gpt_partition_entry_array
 pub struct Disk<Io: BlockIo> {
     io: Io,
+    strict: bool,
+    header_cache: Option<HeaderCache>,
+    #[cfg(feature = "alloc")]
+    bounce_buffer_enabled: bool,
 }
 
 impl<Io: BlockIo> Disk<Io> {
-    /// Create a `Disk`.
+    /// Create a `Disk`. Strict mode and the header cache are initially
+    /// disabled.
     pub fn new(io: Io) -> Result<Self, DiskError<Io::Error>> {
-        Ok(Self { io })
+        Ok(Self {
+            io,
+            strict: false,
+            header_cache: None,
+            #[cfg(feature = "alloc")]
+            bounce_buffer_enabled: false,
+        })
+    }
+
+    /// Enable or disable strict mode.
+    ///
+    /// When strict mode is enabled, every GPT header read is checked,
+    /// and [`DiskError::OutOfSpecGptHeader`] is returned if:
+    /// * `header_size` is smaller than [`GptHeader`] or larger than a
+    ///   block.
+    /// * `size_of_partition_entry` is not a valid
+    ///   [`GptPartitionEntrySize`].
+    /// * `my_lba`, `alternate_lba`, or `last_usable_lba` are past the end
+    ///   of the disk.
+    /// * `first_usable_lba` is greater than `last_usable_lba`.
+    /// * The partition entry array extends past the end of the disk.
+    ///
+    /// Every partition entry read is also checked, and
+    /// [`DiskError::OutOfSpecPartitionEntry`] is returned if the entry
+    /// is in use and its `starting_lba` is greater than its
+    /// `ending_lba`, or its `ending_lba` is past the end of the disk.
+    ///
+    /// These checks are in addition to the signature and CRC32 checks
+    /// done by the `_verified` methods.
+    ///
+    /// [`GptPartitionEntrySize`]: gpt_disk_types::GptPartitionEntrySize
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Check if strict mode is enabled. See [`set_strict`].
+    ///
+    /// [`set_strict`]: Self::set_strict
+    #[must_use]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Enable or disable the header cache. See the [header cache]
+    /// section of the type documentation.
+    ///
+    /// Disabling the cache discards any cached headers.
+    ///
+    /// [header cache]: Self#header-cache
+    pub fn set_header_cache_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.header_cache.get_or_insert_with(HeaderCache::default);
+        } else {
+            self.header_cache = None;
+        }
+    }
+
+    /// Check if the header cache is enabled. See
+    /// [`set_header_cache_enabled`].
+    ///
+    /// [`set_header_cache_enabled`]: Self::set_header_cache_enabled
+    #[must_use]
+    pub fn is_header_cache_enabled(&self) -> bool {
+        self.header_cache.is_some()
+    }
+
+    /// Discard any cached headers, so that the next read of each header
+    /// goes to the disk. This does not disable the cache.
+    pub fn clear_header_cache(&mut self) {
+        if let Some(cache) = &mut self.header_cache {
+            *cache = HeaderCache::default();
+        }
+    }
+
+    /// Enable or disable bounce buffering. When enabled, a buffer that
+    /// is not aligned to [`BlockIo::buffer_alignment`] is handled by
+    /// allocating an aligned buffer for the read or write and copying
+    /// the data through it, rather than returning
+    /// [`DiskError::UnalignedBuffer`]. Disabled by default.
+    #[cfg(feature = "alloc")]
+    pub fn set_bounce_buffer_enabled(&mut self, enabled: bool) {
+        self.bounce_buffer_enabled = enabled;
+    }
+
+    /// Check if bounce buffering is enabled. See
+    /// [`set_bounce_buffer_enabled`].
+    ///
+    /// [`set_bounce_buffer_enabled`]: Self::set_bounce_buffer_enabled
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn is_bounce_buffer_enabled(&self) -> bool {
+        self.bounce_buffer_enabled
+    }
+
+    /// Check that `buf` can be passed to the underlying [`BlockIo`].
+    ///
+    /// Returns `Ok(true)` if `buf` is valid, `Ok(false)` if its length
+    /// is valid but it is not aligned, and an error if its length is
+    /// not a multiple of the block size.
+    fn check_buffer(&self, buf: &[u8]) -> Result<bool, DiskError<Io::Error>> {
+        let block_size =
+            self.io.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        if buf.len() % block_size != 0 {
+            return Err(DiskError::InvalidBufferSize);
+        }
+        let alignment = self.io.buffer_alignment();
+        if !alignment.is_power_of_two() {
+            // No buffer can satisfy an invalid alignment.
+            return Err(DiskError::UnalignedBuffer);
+        }
+        Ok(buf.is_empty() || buf.as_ptr().align_offset(alignment) == 0)
+    }
+
+    /// Read blocks from the underlying [`BlockIo`].
+    pub(crate) fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        if !self.check_buffer(dst)? {
+            #[cfg(feature = "alloc")]
+            if self.bounce_buffer_enabled {
+                let mut storage = Vec::new();
+                let bounce = self.bounce_buffer(&mut storage, dst.len())?;
+                self.io.read_blocks(start_lba, bounce)?;
+                dst.copy_from_slice(bounce);
+                return Ok(());
+            }
+            return Err(DiskError::UnalignedBuffer);
+        }
+        Ok(self.io.read_blocks(start_lba, dst)?)
+    }
+
+    /// Write blocks to the underlying [`BlockIo`], discarding any
+    /// cached headers that are overwritten.
+    ///
+    /// Since [`BlockIo::is_read_only`] can't change, checking it here
+    /// means that an operation on a read-only disk fails at its first
+    /// write, before anything has been modified.
+    pub(crate) fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        if self.io.is_read_only() {
+            return Err(DiskError::ReadOnly);
+        }
+        let aligned = self.check_buffer(src)?;
+        if let Some(cache) = &mut self.header_cache {
+            let num_blocks = u64::try_from(src.len())
+                .map_err(|_| DiskError::Overflow)?
+                .div_ceil(self.io.block_size().to_u64());
+            cache.invalidate(start_lba, num_blocks);
+        }
+        if !aligned {
+            #[cfg(feature = "alloc")]
+            if self.bounce_buffer_enabled {
+                let mut storage = Vec::new();
+                let bounce = self.bounce_buffer(&mut storage, src.len())?;
+                bounce.copy_from_slice(src);
+                return Ok(self.io.write_blocks(start_lba, bounce)?);
+            }
+            return Err(DiskError::UnalignedBuffer);
+        }
+        Ok(self.io.write_blocks(start_lba, src)?)
+    }
+
+    /// Allocate `storage` so that it contains an aligned buffer of
+    /// length `len`, and return that buffer.
+    #[cfg(feature = "alloc")]
+    fn bounce_buffer<'buf>(
+        &self,
+        storage: &'buf mut Vec<u8>,
+        len: usize,
+    ) -> Result<&'buf mut [u8], DiskError<Io::Error>> {
+        let alignment = self.io.buffer_alignment();
+        storage.resize(
+            len.checked_add(alignment - 1).ok_or(DiskError::Overflow)?,
+            0,
+        );
+        let offset = storage.as_ptr().align_offset(alignment);
+        storage
+            .get_mut(offset..)
+            .and_then(|buf| buf.get_mut(..len))
+            .ok_or(DiskError::UnalignedBuffer)
+    }
+
+    /// Apply the strict mode header check, if enabled.
+    fn check_gpt_header(
+        &mut self,
+        header: GptHeader,
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        if self.strict {
+            let num_blocks = self.io.num_blocks()?;
+            check_gpt_header_strict(&header, self.block_size(), num_blocks)?;
+        }
+        Ok(header)
+    }
+
+    /// Get the [`BlockSize`] of the underlying [`BlockIo`].
+    pub fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    /// Get the physical block size of the underlying [`BlockIo`]. See
+    /// [`BlockIo::physical_block_size`].
+    pub fn physical_block_size(&self) -> BlockSize {
+        self.io.physical_block_size()
+    }
+
+    /// Whether the underlying [`BlockIo`] is read-only. If so, all write
+    /// operations fail with [`DiskError::ReadOnly`]. See
+    /// [`BlockIo::is_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.io.is_read_only()
+    }
+
+    /// Get the number of logical blocks in the underlying [`BlockIo`].
+    pub fn num_blocks(&mut self) -> Result<u64, DiskError<Io::Error>> {
+        Ok(self.io.num_blocks()?)
     }
 
     /// Clip the size of `block_buf` to a single block. Return
     /// `BufferTooSmall` if the buffer isn't big enough.
-    fn clip_block_buf_size<'buf>(
+    pub(crate) fn clip_block_buf_size<'buf>(
         &self,
         block_buf: &'buf mut [u8],
     ) -> Result<&'buf mut [u8], DiskError<Io::Error>> {
@@ -205,8 +1039,29 @@ impl<Io: BlockIo> Disk<Io> {
         }
     }
 
+    /// Read the MBR from the first block. No validation of the MBR is
+    /// performed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_mbr(
+        &mut self,
+        mut block_buf: &mut [u8],
+    ) -> Result<MasterBootRecord, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        self.read_blocks(Lba(0), block_buf)?;
+        let bytes = block_buf
+            .get(..mem::size_of::<MasterBootRecord>())
+            // OK to unwrap since the block size type guarantees a
+            // minimum size of 512 bytes, which is the size of the MBR.
+            .unwrap();
+        Ok(*from_bytes(bytes))
+    }
+
     /// Read the primary GPT header from the second block. No validation
-    /// of the header is performed.
+    /// of the header is performed; see
+    /// [`read_primary_gpt_header_verified`] for a checked version.
+    ///
+    /// [`read_primary_gpt_header_verified`]: Self::read_primary_gpt_header_verified
     pub fn read_primary_gpt_header(
         &mut self,
         block_buf: &mut [u8],
@@ -215,7 +1070,10 @@ impl<Io: BlockIo> Disk<Io> {
     }
 
     /// Read the secondary GPT header from the last block. No validation
-    /// of the header is performed.
+    /// of the header is performed; see
+    /// [`read_secondary_gpt_header_verified`] for a checked version.
+    ///
+    /// [`read_secondary_gpt_header_verified`]: Self::read_secondary_gpt_header_verified
     ///
     /// `block_buf` is a mutable byte buffer with a length of at least one block.
     pub fn read_secondary_gpt_header(
@@ -229,27 +1087,242 @@ impl<Io: BlockIo> Disk<Io> {
     }
 
     /// Read a GPT header at the given [`Lba`]. No validation of the
-    /// header is performed.
+    /// header is performed, other than the [strict mode] checks if
+    /// enabled.
     ///
     /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [strict mode]: Self::set_strict
     pub fn read_gpt_header(
+        &mut self,
+        lba: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        let header = self.read_gpt_header_unchecked(lba, block_buf)?;
+        self.check_gpt_header(header)
+    }
+
+    /// Read a GPT header at the given [`Lba`] without any checks. The
+    /// header cache is used if enabled.
+    fn read_gpt_header_unchecked(
         &mut self,
         lba: Lba,
         mut block_buf: &mut [u8],
     ) -> Result<GptHeader, DiskError<Io::Error>> {
         block_buf = self.clip_block_buf_size(block_buf)?;
-        self.io.read_blocks(lba, block_buf)?;
+        if let Some(header) =
+            self.header_cache.as_ref().and_then(|cache| cache.get(lba))
+        {
+            return Ok(header);
+        }
+
+        self.read_blocks(lba, block_buf)?;
         let bytes = block_buf
             .get(..mem::size_of::<GptHeader>())
             // OK to unwrap since the block size type guarantees a
             // minimum size greater than GptHeader.
             .unwrap();
-        Ok(*from_bytes(bytes))
+        let header = *from_bytes(bytes);
+        if let Some(cache) = &mut self.header_cache {
+            cache.insert(lba, header);
+        }
+        Ok(header)
+    }
+
+    /// Read the primary GPT header from the second block and verify
+    /// its signature and CRC32 checksum.
+    ///
+    /// Returns [`DiskError::CorruptGptHeader`] if verification fails.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_primary_gpt_header_verified(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        self.read_gpt_header_verified(Lba(1), block_buf)
+    }
+
+    /// Read the secondary GPT header from the last block and verify its
+    /// signature and CRC32 checksum.
+    ///
+    /// Returns [`DiskError::CorruptGptHeader`] if verification fails.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_secondary_gpt_header_verified(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        let num_blocks = self.io.num_blocks()?;
+        let last_block =
+            Lba(num_blocks.checked_sub(1).ok_or(DiskError::Overflow)?);
+        self.read_gpt_header_verified(last_block, block_buf)
+    }
+
+    /// Read a GPT header at the given [`Lba`] and verify its signature
+    /// and CRC32 checksum.
+    ///
+    /// Returns [`DiskError::CorruptGptHeader`] if verification fails.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn read_gpt_header_verified(
+        &mut self,
+        lba: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeader, DiskError<Io::Error>> {
+        let header = self.read_gpt_header_unchecked(lba, block_buf)?;
+        let header = verify_gpt_header(header)?;
+        self.check_gpt_header(header)
+    }
+
+    /// Read the primary GPT header, falling back to the secondary header
+    /// if the primary is corrupt.
+    ///
+    /// Both headers are verified as in
+    /// [`read_primary_gpt_header_verified`]. The secondary header is
+    /// located via the last block of the disk, since the primary
+    /// header's `alternate_lba` field can't be trusted if the primary
+    /// header is corrupt. IO errors are returned immediately without
+    /// trying the secondary header.
+    ///
+    /// Returns the header along with which copy it was read from. If
+    /// both headers are corrupt, [`DiskError::CorruptGptHeader`] is
+    /// returned.
+    ///
+    /// In [strict mode], a primary header that fails the strict checks
+    /// is treated as corrupt as well.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_primary_gpt_header_verified`]: Self::read_primary_gpt_header_verified
+    /// [strict mode]: Self::set_strict
+    pub fn read_gpt_header_with_fallback(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, GptHeaderCopy), DiskError<Io::Error>> {
+        match self.read_primary_gpt_header_verified(block_buf) {
+            Ok(header) => Ok((header, GptHeaderCopy::Primary)),
+            Err(
+                DiskError::CorruptGptHeader | DiskError::OutOfSpecGptHeader,
+            ) => {
+                let header =
+                    self.read_secondary_gpt_header_verified(block_buf)?;
+                Ok((header, GptHeaderCopy::Secondary))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Read and verify both GPT headers.
+    ///
+    /// Each header is verified as in
+    /// [`read_primary_gpt_header_verified`] and
+    /// [`read_secondary_gpt_header_verified`]. A header that fails
+    /// verification (or, in [strict mode], the strict checks) is
+    /// returned as `None` rather than as an error. IO errors are
+    /// returned immediately.
+    ///
+    /// Use [`GptHeaders::consistency`] to check whether the headers
+    /// agree with each other.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_primary_gpt_header_verified`]: Self::read_primary_gpt_header_verified
+    /// [`read_secondary_gpt_header_verified`]: Self::read_secondary_gpt_header_verified
+    /// [strict mode]: Self::set_strict
+    pub fn read_gpt_headers(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptHeaders, DiskError<Io::Error>> {
+        fn ok_if_valid<E: Debug + Display>(
+            result: Result<GptHeader, DiskError<E>>,
+        ) -> Result<Option<GptHeader>, DiskError<E>> {
+            match result {
+                Ok(header) => Ok(Some(header)),
+                Err(
+                    DiskError::CorruptGptHeader | DiskError::OutOfSpecGptHeader,
+                ) => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+
+        let primary =
+            ok_if_valid(self.read_primary_gpt_header_verified(block_buf))?;
+        let secondary =
+            ok_if_valid(self.read_secondary_gpt_header_verified(block_buf))?;
+        Ok(GptHeaders { primary, secondary })
+    }
+
+    /// Scan every block of the disk for valid GPT headers, calling
+    /// `found` for each one. This finds disks nested inside a larger
+    /// container, such as a VM image or a firmware dump, which can then
+    /// be opened with [`OffsetBlockIo`].
+    ///
+    /// A header is valid if its signature and CRC32 are correct, and
+    /// its `my_lba` is not past the block it was found in. The header's
+    /// `my_lba` and `alternate_lba` are used to work out where the
+    /// nested disk starts and how large it is. Each nested disk
+    /// usually produces two results, one for each header, with the same
+    /// [`start_lba`]. A GPT at the start of this disk is also found,
+    /// with a `start_lba` of zero.
+    ///
+    /// Only block-aligned headers with the same block size as this disk
+    /// are found. To limit the scan to part of the disk, wrap the
+    /// [`BlockIo`] in an [`OffsetBlockIo`] first.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least
+    /// one block. Blocks are read in chunks as large as `block_buf`
+    /// allows, so a larger buffer results in fewer, larger reads.
+    ///
+    /// [`OffsetBlockIo`]: crate::OffsetBlockIo
+    /// [`start_lba`]: NestedGpt::start_lba
+    pub fn find_nested_gpts<F>(
+        &mut self,
+        block_buf: &mut [u8],
+        mut found: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnMut(NestedGpt),
+    {
+        // Check the buffer is large enough.
+        self.clip_block_buf_size(block_buf)?;
+
+        let block_size = self.io.block_size().to_u64();
+        let block_size_usize =
+            self.io.block_size().to_usize().ok_or(DiskError::Overflow)?;
+        let buf_blocks = u64::try_from(block_buf.len())
+            .map_err(|_| DiskError::Overflow)?
+            / block_size;
+        let num_blocks = self.io.num_blocks()?;
+
+        let mut lba = 0;
+        while lba < num_blocks {
+            let chunk_blocks = buf_blocks.min(num_blocks - lba);
+            let len = usize::try_from(chunk_blocks * block_size)
+                .map_err(|_| DiskError::Overflow)?;
+            let chunk = &mut block_buf[..len];
+            self.read_blocks(Lba(lba), chunk)?;
+
+            for (found_lba, block) in
+                (lba..).zip(chunk.chunks_exact(block_size_usize))
+            {
+                let header: GptHeader =
+                    *from_bytes(&block[..mem::size_of::<GptHeader>()]);
+                if let Some(nested) = NestedGpt::new(header, Lba(found_lba)) {
+                    found(nested);
+                }
+            }
+            lba += chunk_blocks;
+        }
+        Ok(())
     }
 
     /// Read the entire partition entry array. The `storage` buffer must
     /// be at least [`layout.num_bytes_rounded_to_block`] in size.
     ///
+    /// In [strict mode], every entry in the array is checked.
+    ///
+    /// [strict mode]: Self::set_strict
+    ///
     /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
     pub fn read_gpt_partition_entry_array<'buf>(
         &mut self,
@@ -265,28 +1338,401 @@ impl<Io: BlockIo> Disk<Io> {
                     GptPartitionEntryArrayError::Overflow => {
                         DiskError::Overflow
                     }
+                    GptPartitionEntryArrayError::InvalidIndex => {
+                        DiskError::InvalidPartitionIndex
+                    }
                 })?;
-        self.io
-            .read_blocks(layout.start_lba, entry_array.storage_mut())?;
+        self.read_blocks(layout.start_lba, entry_array.storage_mut())?;
+        if self.strict {
+            let num_blocks = self.io.num_blocks()?;
+            for index in 0..layout.num_entries {
+                if let Some(entry) = entry_array.get_partition_entry(index) {
+                    check_gpt_partition_entry_strict(entry, num_blocks)?;
+                }
+            }
+        }
         Ok(entry_array)
     }
 
+    /// Calculate the CRC32 checksum of the partition entry array
+    /// described by `layout`, reading it one block at a time. The
+    /// result can be compared with the header's
+    /// [`partition_entry_array_crc32`] field.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`partition_entry_array_crc32`]: GptHeader::partition_entry_array_crc32
+    pub fn calculate_gpt_partition_entry_array_crc32(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        mut block_buf: &mut [u8],
+    ) -> Result<Crc32, DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        let mut digest = crc.digest();
+
+        let mut remaining =
+            layout.num_bytes_exact().ok_or(DiskError::Overflow)?;
+        let mut lba = layout.start_lba;
+        while remaining > 0 {
+            self.read_blocks(lba, block_buf)?;
+            let len = usize::try_from(remaining)
+                .unwrap_or(usize::MAX)
+                .min(block_buf.len());
+            digest.update(&block_buf[..len]);
+
+            // OK to unwrap: `len` is no larger than `remaining`.
+            remaining -= u64::try_from(len).unwrap();
+            lba =
+                Lba(lba.to_u64().checked_add(1).ok_or(DiskError::Overflow)?);
+        }
+
+        Ok(Crc32(U32Le(digest.finalize().to_le_bytes())))
+    }
+
+    /// Read the contents of partition `index` from start to end,
+    /// passing the data to `update` in order. This can be used to
+    /// checksum a partition with any hash function, for example:
+    ///
+    /// ```
+    /// # use gpt_disk_io::{BlockIo, Disk, DiskError};
+    /// # use std::hash::{DefaultHasher, Hasher};
+    /// # fn example<Io: BlockIo>(disk: &mut Disk<Io>) -> Result<(), DiskError<Io::Error>> {
+    /// let mut hasher = DefaultHasher::new();
+    /// let mut buf = vec![0; 1024 * 1024];
+    /// disk.hash_partition(0, |data| hasher.write(data), &mut buf)?;
+    /// println!("{:x}", hasher.finish());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The partition is located through the primary header, falling
+    /// back to the secondary header as in
+    /// [`read_gpt_header_with_fallback`].
+    ///
+    /// Errors:
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range or the entry is unused.
+    /// * [`DiskError::OutOfSpecPartitionEntry`] if the partition's LBA
+    ///   range is invalid or extends past the end of the disk.
+    ///
+    /// `buf` is a mutable byte buffer with a length of at least one
+    /// block. Each read fills as many whole blocks of `buf` as
+    /// possible, so a larger buffer results in fewer, larger reads.
+    ///
+    /// [`read_gpt_header_with_fallback`]: Self::read_gpt_header_with_fallback
+    pub fn hash_partition<F>(
+        &mut self,
+        index: u32,
+        mut update: F,
+        buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnMut(&[u8]),
+    {
+        let (header, _) = self.read_gpt_header_with_fallback(buf)?;
+        let layout = header_layout(&header)?;
+        let entry = self.read_gpt_partition_entry(layout, index, buf)?;
+        if !entry.is_used() {
+            return Err(DiskError::InvalidPartitionIndex);
+        }
+        let range = entry
+            .lba_range()
+            .ok_or(DiskError::OutOfSpecPartitionEntry)?;
+        if range.end().to_u64() >= self.io.num_blocks()? {
+            return Err(DiskError::OutOfSpecPartitionEntry);
+        }
+
+        let block_size = self.io.block_size().to_u64();
+        let buf_blocks = u64::try_from(buf.len())
+            .map_err(|_| DiskError::Overflow)?
+            / block_size;
+
+        let mut lba = range.start().to_u64();
+        let end = range.end().to_u64();
+        while lba <= end {
+            let num_blocks = buf_blocks.min(end - lba + 1);
+            let len = usize::try_from(num_blocks * block_size)
+                .map_err(|_| DiskError::Overflow)?;
+            let chunk = &mut buf[..len];
+            self.read_blocks(Lba(lba), chunk)?;
+            update(chunk);
+            lba += num_blocks;
+        }
+        Ok(())
+    }
+
+    /// Calculate the CRC32 checksum of the contents of partition
+    /// `index`. See [`hash_partition`] for details.
+    ///
+    /// `buf` is a mutable byte buffer with a length of at least one
+    /// block.
+    ///
+    /// [`hash_partition`]: Self::hash_partition
+    pub fn calculate_partition_crc32(
+        &mut self,
+        index: u32,
+        buf: &mut [u8],
+    ) -> Result<Crc32, DiskError<Io::Error>> {
+        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        let mut digest = crc.digest();
+        self.hash_partition(index, |data| digest.update(data), buf)?;
+        Ok(Crc32(U32Le(digest.finalize().to_le_bytes())))
+    }
+
+    /// Calculate the SHA-256 digest of the contents of partition
+    /// `index`. See [`hash_partition`] for details.
+    ///
+    /// `buf` is a mutable byte buffer with a length of at least one
+    /// block.
+    ///
+    /// [`hash_partition`]: Self::hash_partition
+    #[cfg(feature = "sha2")]
+    pub fn calculate_partition_sha256(
+        &mut self,
+        index: u32,
+        buf: &mut [u8],
+    ) -> Result<[u8; 32], DiskError<Io::Error>> {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        self.hash_partition(index, |data| hasher.update(data), buf)?;
+        Ok(hasher.finalize().into())
+    }
+
+    /// Get the byte offset of entry `index` from the start of the
+    /// partition entry array.
+    fn partition_entry_byte_offset(
+        layout: &GptPartitionEntryArrayLayout,
+        index: u32,
+    ) -> Result<u64, DiskError<Io::Error>> {
+        if index >= layout.num_entries {
+            return Err(DiskError::InvalidPartitionIndex);
+        }
+        layout
+            .entry_size
+            .to_u64()
+            .checked_mul(u64::from(index))
+            .ok_or(DiskError::Overflow)
+    }
+
+    /// Read or write `bytes` at `byte_offset` bytes past the start of
+    /// `start_lba`, one block at a time. Writes are done with a
+    /// read-modify-write of each block so that surrounding bytes are
+    /// preserved.
+    fn access_bytes(
+        &mut self,
+        start_lba: Lba,
+        byte_offset: u64,
+        mut bytes: ByteAccess<'_>,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let block_size = self.io.block_size().to_u64();
+        let mut lba = start_lba
+            .to_u64()
+            .checked_add(byte_offset / block_size)
+            .ok_or(DiskError::Overflow)?;
+        // OK to unwrap: the remainder is less than the block size,
+        // which fits in a `usize` since `block_buf` was clipped to it.
+        let mut offset_in_block =
+            usize::try_from(byte_offset % block_size).unwrap();
+        let mut done = 0;
+        while done < bytes.len() {
+            let len =
+                (block_buf.len() - offset_in_block).min(bytes.len() - done);
+            let block_range = offset_in_block..offset_in_block + len;
+            self.read_blocks(Lba(lba), block_buf)?;
+            match &mut bytes {
+                ByteAccess::Read(dst) => {
+                    dst[done..done + len]
+                        .copy_from_slice(&block_buf[block_range]);
+                }
+                ByteAccess::Write(src) => {
+                    block_buf[block_range]
+                        .copy_from_slice(&src[done..done + len]);
+                    self.write_blocks(Lba(lba), block_buf)?;
+                }
+            }
+            done += len;
+            offset_in_block = 0;
+            lba = lba.checked_add(1).ok_or(DiskError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    /// Read a single [`GptPartitionEntry`] from the partition entry
+    /// array described by `layout`.
+    ///
+    /// Only the first 128 bytes of the entry are read; if the entry
+    /// size is larger than that, the remaining bytes are ignored.
+    ///
+    /// In [strict mode], the entry is checked before it is returned.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [strict mode]: Self::set_strict
+    pub fn read_gpt_partition_entry(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        index: u32,
+        block_buf: &mut [u8],
+    ) -> Result<GptPartitionEntry, DiskError<Io::Error>> {
+        let offset = Self::partition_entry_byte_offset(&layout, index)?;
+        let mut entry = GptPartitionEntry::default();
+        self.access_bytes(
+            layout.start_lba,
+            offset,
+            ByteAccess::Read(bytes_of_mut(&mut entry)),
+            block_buf,
+        )?;
+        if self.strict {
+            check_gpt_partition_entry_strict(&entry, self.io.num_blocks()?)?;
+        }
+        Ok(entry)
+    }
+
+    /// Write a single [`GptPartitionEntry`] to the partition entry
+    /// array described by `layout`. The rest of the array is left
+    /// unchanged.
+    ///
+    /// Only the first 128 bytes of the entry are written; if the entry
+    /// size is larger than that, the remaining bytes are left
+    /// unchanged.
+    ///
+    /// Note that this does not update the
+    /// [`partition_entry_array_crc32`] field of the headers.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`partition_entry_array_crc32`]: GptHeader::partition_entry_array_crc32
+    pub fn write_gpt_partition_entry(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        index: u32,
+        entry: &GptPartitionEntry,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let offset = Self::partition_entry_byte_offset(&layout, index)?;
+        self.access_bytes(
+            layout.start_lba,
+            offset,
+            ByteAccess::Write(bytes_of(entry)),
+            block_buf,
+        )
+    }
+
     /// Write an entire [`GptPartitionEntryArray`] to disk.
     pub fn write_gpt_partition_entry_array(
         &mut self,
         entry_array: &GptPartitionEntryArray,
     ) -> Result<(), DiskError<Io::Error>> {
-        Ok(self.io.write_blocks(
-            entry_array.layout().start_lba,
-            entry_array.storage(),
-        )?)
+        self.write_blocks(entry_array.layout().start_lba, entry_array.storage())
+    }
+
+    /// Write a partition entry array from an iterator of entries,
+    /// without needing a buffer for the whole array.
+    ///
+    /// The array described by `layout` is written one block at a time.
+    /// Entry `i` of the array is set to the `i`th entry from `entries`.
+    /// If `entries` runs out before the end of the array, the remaining
+    /// entries are zeroed. If the entry size is larger than 128 bytes,
+    /// the extra bytes of each entry are zeroed, as is any space in the
+    /// last block past the end of the array.
+    ///
+    /// Returns the CRC32 of the array, for use in
+    /// [`partition_entry_array_crc32`].
+    ///
+    /// If `entries` has more than `layout.num_entries` entries,
+    /// [`DiskError::InvalidPartitionIndex`] is returned after the array
+    /// has been written.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`partition_entry_array_crc32`]: GptHeader::partition_entry_array_crc32
+    pub fn write_gpt_partition_entry_array_from_iter<I>(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        entries: I,
+        mut block_buf: &mut [u8],
+    ) -> Result<Crc32, DiskError<Io::Error>>
+    where
+        I: IntoIterator<Item = GptPartitionEntry>,
+    {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        let mut digest = crc.digest();
+
+        let mut entries = entries.into_iter();
+        let entry_size =
+            layout.entry_size.to_usize().ok_or(DiskError::Overflow)?;
+        let mut entry = GptPartitionEntry::default();
+        // Byte offset within the current entry.
+        let mut offset_in_entry = 0;
+
+        let mut remaining =
+            layout.num_bytes_exact().ok_or(DiskError::Overflow)?;
+        let mut lba = layout.start_lba;
+        while remaining > 0 {
+            block_buf.fill(0);
+            let block_len = usize::try_from(remaining)
+                .unwrap_or(usize::MAX)
+                .min(block_buf.len());
+
+            let mut offset_in_block = 0;
+            while offset_in_block < block_len {
+                if offset_in_entry == 0 {
+                    entry = entries.next().unwrap_or_default();
+                }
+                let len = (block_len - offset_in_block)
+                    .min(entry_size - offset_in_entry);
+                let entry_bytes = bytes_of(&entry);
+                if offset_in_entry < entry_bytes.len() {
+                    let copy_len = len.min(entry_bytes.len() - offset_in_entry);
+                    block_buf[offset_in_block..offset_in_block + copy_len]
+                        .copy_from_slice(
+                            &entry_bytes
+                                [offset_in_entry..offset_in_entry + copy_len],
+                        );
+                }
+                offset_in_block += len;
+                offset_in_entry = (offset_in_entry + len) % entry_size;
+            }
+
+            digest.update(&block_buf[..block_len]);
+            self.write_blocks(lba, block_buf)?;
+
+            // OK to unwrap: `block_len` is no larger than `remaining`.
+            remaining -= u64::try_from(block_len).unwrap();
+            lba =
+                Lba(lba.to_u64().checked_add(1).ok_or(DiskError::Overflow)?);
+        }
+
+        if entries.next().is_some() {
+            return Err(DiskError::InvalidPartitionIndex);
+        }
+        Ok(Crc32(U32Le(digest.finalize().to_le_bytes())))
     }
 
     /// Get an iterator over partition entries. The `layout` parameter
     /// indicates where to read the entries from; see
     /// [`GptPartitionEntryArrayLayout`] for more.
     ///
+    /// The array is read one block at a time. Any entry size and block
+    /// size is supported, including entries that are larger than a
+    /// block or that span two blocks. Only the first 128 bytes of each
+    /// entry are returned.
+    ///
+    /// In [strict mode], each entry is checked as it is read, and
+    /// iteration stops after the first entry that fails.
+    ///
     /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [strict mode]: Self::set_strict
+    #[allow(clippy::type_complexity)]
     pub fn gpt_partition_entry_array_iter<'disk, 'buf>(
         &'disk mut self,
         layout: GptPartitionEntryArrayLayout,
@@ -298,13 +1744,45 @@ impl<Io: BlockIo> Disk<Io> {
     > {
         block_buf = self.clip_block_buf_size(block_buf)?;
 
-        let entry_size =
-            layout.entry_size.to_usize().ok_or(DiskError::Overflow)?;
-        if entry_size > block_buf.len() {
-            return Err(DiskError::BlockSizeSmallerThanPartitionEntry);
-        }
+        GptPartitionEntryIter::<'disk, 'buf>::new(self, layout, 0, block_buf)
+    }
+
+    /// Get an iterator over partition entries, starting at entry
+    /// `start_index`. This is the same as
+    /// [`gpt_partition_entry_array_iter`], except that reading starts
+    /// at `start_index` rather than the beginning of the array.
+    ///
+    /// Together with [`Iterator::nth`] and [`Iterator::skip`], which
+    /// skip directly to the requested entry without reading the blocks
+    /// in between, this allows the array to be processed in chunks
+    /// using only a single block-sized buffer.
+    ///
+    /// Returns [`DiskError::InvalidPartitionIndex`] if `start_index` is
+    /// greater than the number of entries. A `start_index` equal to the
+    /// number of entries gives an empty iterator.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`gpt_partition_entry_array_iter`]: Self::gpt_partition_entry_array_iter
+    #[allow(clippy::type_complexity)]
+    pub fn gpt_partition_entry_array_iter_from<'disk, 'buf>(
+        &'disk mut self,
+        layout: GptPartitionEntryArrayLayout,
+        start_index: u32,
+        mut block_buf: &'buf mut [u8],
+    ) -> Result<
+        impl Iterator<Item = Result<GptPartitionEntry, DiskError<Io::Error>>>
+            + Captures<'disk, 'buf>,
+        DiskError<Io::Error>,
+    > {
+        block_buf = self.clip_block_buf_size(block_buf)?;
 
-        GptPartitionEntryIter::<'disk, 'buf>::new(self, layout, block_buf)
+        GptPartitionEntryIter::<'disk, 'buf>::new(
+            self,
+            layout,
+            start_index,
+            block_buf,
+        )
     }
 
     /// Write a protective MBR to the first block. If the block size is
@@ -320,6 +1798,68 @@ impl<Io: BlockIo> Disk<Io> {
         self.write_mbr(&mbr, block_buf)
     }
 
+    /// Write a protective MBR like [`write_protective_mbr`], but refuse
+    /// to overwrite an existing legacy partition table.
+    ///
+    /// The first block is read first, and may only be overwritten if
+    /// it is entirely zero, or if it has a valid MBR signature and
+    /// every non-empty partition record is a GPT protective record (OS
+    /// type `0xee`). Otherwise [`DiskError::MbrInUse`] is returned and
+    /// nothing is written. Pass `force = true` to skip the check.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`write_protective_mbr`]: Self::write_protective_mbr
+    pub fn write_protective_mbr_guarded(
+        &mut self,
+        force: bool,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        if !force {
+            block_buf = self.clip_block_buf_size(block_buf)?;
+            self.read_blocks(Lba(0), block_buf)?;
+            let is_blank = block_buf.iter().all(|b| *b == 0);
+            let mbr: MasterBootRecord = *from_bytes(
+                // OK to unwrap: the block size is at least 512 bytes,
+                // which is the size of the MBR.
+                block_buf.get(..mem::size_of::<MasterBootRecord>()).unwrap(),
+            );
+            let is_protective = mbr.signature == [0x55, 0xaa]
+                && mbr
+                    .partitions
+                    .iter()
+                    .all(|p| p.os_indicator == 0 || p.os_indicator == 0xee);
+            if !is_blank && !is_protective {
+                return Err(DiskError::MbrInUse);
+            }
+        }
+        self.write_protective_mbr(block_buf)
+    }
+
+    /// Write a hybrid MBR to the first block. The MBR is created with
+    /// [`HybridMbr::build`] using the number of blocks in the disk. If
+    /// the block size is bigger than the MBR, the rest of the block
+    /// will be filled with zeroes.
+    ///
+    /// The GPT headers and partition entry arrays are not read or
+    /// modified, so the mirrored partitions in `hybrid_mbr` should match
+    /// partitions in the GPT.
+    ///
+    /// Returns [`DiskError::InvalidHybridMbr`] if the MBR cannot be
+    /// built, in which case nothing is written.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn write_hybrid_mbr(
+        &mut self,
+        hybrid_mbr: &HybridMbr,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let mbr = hybrid_mbr
+            .build(self.io.num_blocks()?)
+            .map_err(|_| DiskError::InvalidHybridMbr)?;
+        self.write_mbr(&mbr, block_buf)
+    }
+
     /// Write an MBR to the first block. If the block size is bigger
     /// than the MBR, the rest of the block will be filled with zeroes.
     ///
@@ -345,8 +1885,7 @@ impl<Io: BlockIo> Disk<Io> {
             right.fill(0);
         }
 
-        self.io.write_blocks(Lba(0), block_buf)?;
-        Ok(())
+        self.write_blocks(Lba(0), block_buf)
     }
 
     /// Write the primary GPT header to the second block.
@@ -414,7 +1953,10 @@ impl<Io: BlockIo> Disk<Io> {
             right.fill(0);
         }
 
-        self.io.write_blocks(lba, block_buf)?;
+        self.write_blocks(lba, block_buf)?;
+        if let Some(cache) = &mut self.header_cache {
+            cache.insert(lba, *header);
+        }
         Ok(())
     }
 
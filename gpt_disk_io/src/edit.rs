@@ -0,0 +1,1441 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::disk::Captures;
+use crate::{BlockIo, Disk, DiskError};
+use core::fmt::{Debug, Display};
+use core::iter;
+use core::num::NonZeroU64;
+use core::ops::Range;
+use gpt_disk_types::{
+    GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayLayout, GptPartitionName,
+    GptPartitionType, Guid, Lba, LbaLe, LbaRangeInclusive, PartitionAlignment,
+};
+
+/// Size or location of a new partition.
+///
+/// See [`Disk::create_partition`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum PartitionExtent {
+    /// Allocate this many blocks from the first free gap that is large
+    /// enough after aligning the start.
+    Blocks(NonZeroU64),
+
+    /// Use exactly this range of blocks. The range must be free and
+    /// within the usable range of the disk. No alignment is applied.
+    Range(LbaRangeInclusive),
+}
+
+/// Options for [`Disk::create_partition`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct CreatePartitionOptions {
+    /// GUID for the new partition. This must be nonzero and must not be
+    /// in use by any other partition on the disk. Typically this is
+    /// generated from random bytes with [`Guid::from_random_bytes`].
+    pub unique_partition_guid: Guid,
+
+    /// Attributes of the new partition.
+    pub attributes: GptPartitionAttributes,
+
+    /// Alignment of the partition's starting LBA, in blocks. If `None`,
+    /// the start is aligned to 1MiB, which is the default used by most
+    /// partitioning tools, or to the [physical block size] if that is
+    /// larger. Not used with [`PartitionExtent::Range`].
+    ///
+    /// [physical block size]: crate::BlockIo::physical_block_size
+    pub alignment: Option<NonZeroU64>,
+}
+
+impl CreatePartitionOptions {
+    /// Create options with the given partition GUID, no attributes,
+    /// and the default alignment.
+    #[must_use]
+    pub fn new(unique_partition_guid: Guid) -> Self {
+        Self {
+            unique_partition_guid,
+            attributes: GptPartitionAttributes::default(),
+            alignment: None,
+        }
+    }
+}
+
+/// New end of a partition.
+///
+/// See [`Disk::resize_partition`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NewPartitionEnd {
+    /// Set the partition's last block (inclusive).
+    Lba(Lba),
+
+    /// Set the size of the partition in blocks.
+    Blocks(NonZeroU64),
+
+    /// Grow the partition to fill all of the free space directly after
+    /// it.
+    Max,
+}
+
+/// Options for [`Disk::resize_partition`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ResizePartitionOptions {
+    /// Allow the partition to get smaller. Shrinking a partition cuts
+    /// off any data past the new end, such as the tail of a
+    /// filesystem, so this is `false` by default.
+    pub allow_shrink: bool,
+
+    /// With [`NewPartitionEnd::Max`], round the end of the partition
+    /// down so that the block after it is a multiple of this value.
+    /// The partition is never shrunk by rounding. If `None`, the end is
+    /// not aligned. Not used with the other variants.
+    pub end_alignment: Option<NonZeroU64>,
+}
+
+/// Options for [`Disk::init_gpt`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct InitGptOptions {
+    /// Number of entries in the partition entry array. Each entry is
+    /// 128 bytes. The default is 128 entries, the minimum required by
+    /// the UEFI Specification.
+    pub num_partition_entries: u32,
+
+    /// Overwrite the first block even if it contains a legacy MBR
+    /// partition table or other data. See
+    /// [`Disk::write_protective_mbr_guarded`]. `false` by default.
+    pub force: bool,
+}
+
+impl Default for InitGptOptions {
+    fn default() -> Self {
+        Self {
+            num_partition_entries: 128,
+            force: false,
+        }
+    }
+}
+
+/// Constraints on the ranges returned by [`Disk::free_gaps`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FreeGapFilter {
+    /// Minimum number of blocks in a gap. Smaller gaps are skipped.
+    pub min_blocks: u64,
+
+    /// Alignment of the start of each gap, in blocks. The start of each
+    /// gap is rounded up to a multiple of this value before checking
+    /// `min_blocks`, and the returned gap starts at the aligned LBA.
+    pub alignment: NonZeroU64,
+}
+
+impl Default for FreeGapFilter {
+    /// No minimum size and no alignment.
+    fn default() -> Self {
+        Self {
+            min_blocks: 0,
+            alignment: NonZeroU64::MIN,
+        }
+    }
+}
+
+impl FreeGapFilter {
+    /// Trim the start of `gap` to the alignment, and check its size.
+    fn apply(&self, gap: LbaRangeInclusive) -> Option<LbaRangeInclusive> {
+        let start = gap.start().align_up(self.alignment)?;
+        let gap = LbaRangeInclusive::new(start, gap.end())?;
+        if gap.num_blocks() >= self.min_blocks {
+            Some(gap)
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterator returned by [`Disk::free_gaps`].
+struct FreeGapIter<'disk, 'buf, Io: BlockIo> {
+    disk: &'disk mut Disk<Io>,
+    block_buf: &'buf mut [u8],
+    layout: GptPartitionEntryArrayLayout,
+    usable: LbaRangeInclusive,
+    filter: FreeGapFilter,
+    /// LBA to start searching from, or `None` if iteration is done.
+    next_lba: Option<Lba>,
+}
+
+impl<Io: BlockIo> Iterator for FreeGapIter<'_, '_, Io> {
+    type Item = Result<LbaRangeInclusive, DiskError<Io::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let from = self.next_lba?;
+            let gap = match self.disk.next_free_gap(
+                self.layout,
+                self.usable,
+                from,
+                self.block_buf,
+            ) {
+                Ok(Some(gap)) => gap,
+                Ok(None) => {
+                    self.next_lba = None;
+                    return None;
+                }
+                Err(err) => {
+                    self.next_lba = None;
+                    return Some(Err(err));
+                }
+            };
+            self.next_lba = gap.end().to_u64().checked_add(1).map(Lba);
+            if let Some(gap) = self.filter.apply(gap) {
+                return Some(Ok(gap));
+            }
+        }
+    }
+}
+
+/// Swap entries `a` and `b` of `array`, including any bytes past the
+/// end of the [`GptPartitionEntry`] struct.
+///
+/// Panics if either index is out of bounds.
+fn swap_entries(array: &mut GptPartitionEntryArray, a: u32, b: u32) {
+    if a == b {
+        return;
+    }
+    let (lo, hi) = (a.min(b), a.max(b));
+    // OK to unwrap: the array was created successfully, so the size and
+    // offset of every entry fits in a `usize`.
+    let entry_size = array.layout().entry_size.to_usize().unwrap();
+    let lo = usize::try_from(lo).unwrap() * entry_size;
+    let hi = usize::try_from(hi).unwrap() * entry_size;
+    let (left, right) = array.storage_mut().split_at_mut(hi);
+    left[lo..lo + entry_size].swap_with_slice(&mut right[..entry_size]);
+}
+
+/// Get the partition entry array layout from a verified header.
+pub(crate) fn header_layout<IoError>(
+    header: &GptHeader,
+) -> Result<GptPartitionEntryArrayLayout, DiskError<IoError>>
+where
+    IoError: Debug + Display,
+{
+    header
+        .get_partition_entry_array_layout()
+        .map_err(|_| DiskError::CorruptGptHeader)
+}
+
+/// Get the usable range from a verified header.
+pub(crate) fn header_usable_range<IoError>(
+    header: &GptHeader,
+) -> Result<LbaRangeInclusive, DiskError<IoError>>
+where
+    IoError: Debug + Display,
+{
+    LbaRangeInclusive::new(
+        header.first_usable_lba.into(),
+        header.last_usable_lba.into(),
+    )
+    .ok_or(DiskError::CorruptGptHeader)
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Read both headers, verifying them. Used by operations that
+    /// modify the partition table.
+    pub(crate) fn read_gpt_headers_for_update(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, GptHeader), DiskError<Io::Error>> {
+        let primary = self.read_primary_gpt_header_verified(block_buf)?;
+        let secondary = self.read_secondary_gpt_header_verified(block_buf)?;
+        Ok((primary, secondary))
+    }
+
+    /// Recalculate the partition entry array CRCs of both headers from
+    /// the arrays on disk, then update the header CRCs and write both
+    /// headers.
+    pub(crate) fn write_gpt_headers_after_update(
+        &mut self,
+        primary: &mut GptHeader,
+        secondary: &mut GptHeader,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        primary.partition_entry_array_crc32 = self
+            .calculate_gpt_partition_entry_array_crc32(
+                header_layout(primary)?,
+                block_buf,
+            )?;
+        primary.update_header_crc32();
+        secondary.partition_entry_array_crc32 = self
+            .calculate_gpt_partition_entry_array_crc32(
+                header_layout(secondary)?,
+                block_buf,
+            )?;
+        secondary.update_header_crc32();
+
+        self.write_primary_gpt_header(primary, block_buf)?;
+        self.write_secondary_gpt_header(secondary, block_buf)
+    }
+
+    /// Copy `num_blocks` blocks from `src` to `dst`, one block at a
+    /// time. The ranges must not overlap.
+    fn copy_blocks(
+        &mut self,
+        src: Lba,
+        dst: Lba,
+        num_blocks: u64,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        block_buf = self.clip_block_buf_size(block_buf)?;
+        for i in 0..num_blocks {
+            let src = src.checked_add(i).ok_or(DiskError::Overflow)?;
+            let dst = dst.checked_add(i).ok_or(DiskError::Overflow)?;
+            self.read_blocks(src, block_buf)?;
+            self.write_blocks(dst, block_buf)?;
+        }
+        Ok(())
+    }
+
+    /// Read the header at `lba` and verify it and its partition entry
+    /// array. Used by the repair operations.
+    fn read_gpt_for_repair(
+        &mut self,
+        lba: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<(GptHeader, u64), DiskError<Io::Error>> {
+        let header = self.read_gpt_header_verified(lba, block_buf)?;
+        let layout = header_layout(&header)?;
+        let crc32 =
+            self.calculate_gpt_partition_entry_array_crc32(layout, block_buf)?;
+        if crc32 != header.partition_entry_array_crc32 {
+            return Err(DiskError::CorruptPartitionEntryArray);
+        }
+        let array_blocks = layout
+            .num_blocks(self.block_size())
+            .ok_or(DiskError::Overflow)?;
+        Ok((header, array_blocks))
+    }
+
+    /// Find the first free range of blocks within `usable` that starts
+    /// at or after `from`. Returns `None` if there is no such range.
+    ///
+    /// Entries with an invalid range (end before start) are ignored.
+    pub(crate) fn next_free_gap(
+        &mut self,
+        layout: GptPartitionEntryArrayLayout,
+        usable: LbaRangeInclusive,
+        from: Lba,
+        block_buf: &mut [u8],
+    ) -> Result<Option<LbaRangeInclusive>, DiskError<Io::Error>> {
+        let mut start = from.to_u64().max(usable.start().to_u64());
+        let usable_end = usable.end().to_u64();
+
+        // Each pass over the array either finds a gap or skips past a
+        // used partition, so the number of passes is bounded by the
+        // number of entries.
+        while start <= usable_end {
+            let mut used_until = None;
+            let mut next_used = None;
+            for entry in
+                self.gpt_partition_entry_array_iter(layout, block_buf)?
+            {
+                let entry = entry?;
+                if !entry.is_used() {
+                    continue;
+                }
+                let Some(range) = entry.lba_range() else {
+                    continue;
+                };
+                let (entry_start, entry_end) =
+                    (range.start().to_u64(), range.end().to_u64());
+                if entry_start <= start && start <= entry_end {
+                    used_until = used_until.max(Some(entry_end));
+                } else if entry_start > start {
+                    next_used = Some(
+                        next_used
+                            .map_or(entry_start, |n: u64| n.min(entry_start)),
+                    );
+                }
+            }
+
+            if let Some(used_until) = used_until {
+                let Some(next) = used_until.checked_add(1) else {
+                    break;
+                };
+                start = next;
+                continue;
+            }
+
+            let end = next_used.map_or(usable_end, |n| (n - 1).min(usable_end));
+            return Ok(LbaRangeInclusive::new(Lba(start), Lba(end)));
+        }
+        Ok(None)
+    }
+
+    /// Get an iterator over the free ranges of blocks in the usable
+    /// range of `header`, in ascending order.
+    ///
+    /// A range is free if it is not covered by any used partition in
+    /// the partition entry array described by `header`. Entries with an
+    /// invalid range (end before start) are ignored. Gaps are trimmed
+    /// and filtered according to `filter`.
+    ///
+    /// Each step of the iterator reads the partition entry array,
+    /// so iterating is `O(n^2)` in the number of entries.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// # Example
+    ///
+    /// Find the largest free range on a disk:
+    ///
+    /// ```
+    /// use gpt_disk_io::{BlockIo, Disk, DiskError, FreeGapFilter};
+    /// use gpt_disk_types::LbaRangeInclusive;
+    ///
+    /// fn largest_gap<Io: BlockIo>(
+    ///     disk: &mut Disk<Io>,
+    ///     block_buf: &mut [u8],
+    /// ) -> Result<Option<LbaRangeInclusive>, DiskError<Io::Error>> {
+    ///     let header = disk.read_primary_gpt_header_verified(block_buf)?;
+    ///     let mut largest: Option<LbaRangeInclusive> = None;
+    ///     for gap in
+    ///         disk.free_gaps(&header, FreeGapFilter::default(), block_buf)?
+    ///     {
+    ///         let gap = gap?;
+    ///         if largest.map_or(true, |l| gap.num_blocks() > l.num_blocks()) {
+    ///             largest = Some(gap);
+    ///         }
+    ///     }
+    ///     Ok(largest)
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn free_gaps<'disk, 'buf>(
+        &'disk mut self,
+        header: &GptHeader,
+        filter: FreeGapFilter,
+        block_buf: &'buf mut [u8],
+    ) -> Result<
+        impl Iterator<Item = Result<LbaRangeInclusive, DiskError<Io::Error>>>
+            + Captures<'disk, 'buf>,
+        DiskError<Io::Error>,
+    > {
+        Ok(FreeGapIter {
+            layout: header_layout(header)?,
+            usable: header_usable_range(header)?,
+            next_lba: Some(header.first_usable_lba.into()),
+            disk: self,
+            block_buf,
+            filter,
+        })
+    }
+
+    /// Add a new partition to the partition table.
+    ///
+    /// This is similar to `sgdisk --new`. The steps are:
+    /// 1. Read and verify both GPT headers.
+    /// 2. Find the first unused entry in the partition entry array, and
+    ///    check that [`CreatePartitionOptions::unique_partition_guid`]
+    ///    is not already in use.
+    /// 3. Pick the partition's blocks according to `extent`. For
+    ///    [`PartitionExtent::Blocks`], the first free gap in the usable
+    ///    range that fits the partition after aligning its start is
+    ///    used.
+    /// 4. Write the new entry to both partition entry arrays.
+    /// 5. Update the partition entry array CRC32 and header CRC32 of
+    ///    both headers, and write both headers.
+    ///
+    /// Returns the index of the new entry in the partition entry array.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::NoFreePartitionEntry`] if every entry is in use.
+    /// * [`DiskError::InvalidPartitionGuid`] if the partition GUID is
+    ///   zero or already in use.
+    /// * [`DiskError::NoFreeSpace`] if no suitable free range exists.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn create_partition(
+        &mut self,
+        partition_type: GptPartitionType,
+        extent: PartitionExtent,
+        name: GptPartitionName,
+        options: CreatePartitionOptions,
+        block_buf: &mut [u8],
+    ) -> Result<u32, DiskError<Io::Error>> {
+        let (mut primary, mut secondary) =
+            self.read_gpt_headers_for_update(block_buf)?;
+        let layout = header_layout(&primary)?;
+        let usable = header_usable_range(&primary)?;
+
+        let guid = options.unique_partition_guid;
+        if guid == Guid::ZERO {
+            return Err(DiskError::InvalidPartitionGuid);
+        }
+
+        let mut free_index = None;
+        for (index, entry) in (0u32..)
+            .zip(self.gpt_partition_entry_array_iter(layout, block_buf)?)
+        {
+            let entry = entry?;
+            if entry.is_used() {
+                if { entry.unique_partition_guid } == guid {
+                    return Err(DiskError::InvalidPartitionGuid);
+                }
+            } else if free_index.is_none() {
+                free_index = Some(index);
+            }
+        }
+        let index = free_index.ok_or(DiskError::NoFreePartitionEntry)?;
+
+        let range = match extent {
+            PartitionExtent::Blocks(num_blocks) => {
+                let alignment = options.alignment.unwrap_or_else(|| {
+                    PartitionAlignment::DEFAULT
+                        .with_physical_block_size(self.physical_block_size())
+                        .num_blocks(self.block_size())
+                });
+                let filter = FreeGapFilter {
+                    min_blocks: num_blocks.get(),
+                    alignment,
+                };
+                let gap = self
+                    .free_gaps(&primary, filter, block_buf)?
+                    .next()
+                    .ok_or(DiskError::NoFreeSpace)??;
+                // OK to unwrap: the gap is at least `num_blocks` long.
+                LbaRangeInclusive::new(
+                    gap.start(),
+                    Lba(gap.start().to_u64() + (num_blocks.get() - 1)),
+                )
+                .unwrap()
+            }
+            PartitionExtent::Range(range) => {
+                let gap = self
+                    .next_free_gap(layout, usable, range.start(), block_buf)?
+                    .ok_or(DiskError::NoFreeSpace)?;
+                if gap.start() != range.start() || gap.end() < range.end() {
+                    return Err(DiskError::NoFreeSpace);
+                }
+                range
+            }
+        };
+
+        let entry = GptPartitionEntry {
+            partition_type_guid: partition_type,
+            unique_partition_guid: guid,
+            starting_lba: range.start().into(),
+            ending_lba: range.end().into(),
+            attributes: options.attributes,
+            name,
+        };
+        self.write_gpt_partition_entry(layout, index, &entry, block_buf)?;
+        self.write_gpt_partition_entry(
+            header_layout(&secondary)?,
+            index,
+            &entry,
+            block_buf,
+        )?;
+
+        self.write_gpt_headers_after_update(
+            &mut primary,
+            &mut secondary,
+            block_buf,
+        )?;
+        Ok(index)
+    }
+
+    /// Change the end of a partition, similar to `parted resizepart`.
+    ///
+    /// The steps are:
+    /// 1. Read and verify both GPT headers.
+    /// 2. Read entry `index` from the primary partition entry array, and
+    ///    check that its range is valid and within the usable range.
+    /// 3. Calculate the new end from `end`. Growing only succeeds if
+    ///    every block added is free and within the usable range;
+    ///    shrinking only succeeds if
+    ///    [`ResizePartitionOptions::allow_shrink`] is set.
+    /// 4. Write the updated entry to both partition entry arrays.
+    /// 5. Update the partition entry array CRC32 and header CRC32 of
+    ///    both headers, and write both headers.
+    ///
+    /// The start of the partition and its data are not changed. If the
+    /// end does not change, nothing is written.
+    ///
+    /// Returns the new range of the partition.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range or the entry is unused.
+    /// * [`DiskError::OutOfSpecPartitionEntry`] if the current range of
+    ///   the partition is invalid or outside the usable range.
+    /// * [`DiskError::InvalidPartitionSize`] if the new end is before
+    ///   the start, or the partition would shrink without
+    ///   [`ResizePartitionOptions::allow_shrink`].
+    /// * [`DiskError::NoFreeSpace`] if growing would overlap another
+    ///   partition or extend past the usable range.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn resize_partition(
+        &mut self,
+        index: u32,
+        end: NewPartitionEnd,
+        options: ResizePartitionOptions,
+        block_buf: &mut [u8],
+    ) -> Result<LbaRangeInclusive, DiskError<Io::Error>> {
+        let (primary, _) = self.read_gpt_headers_for_update(block_buf)?;
+        let layout = header_layout(&primary)?;
+        let usable = header_usable_range(&primary)?;
+
+        let entry = self.read_gpt_partition_entry(layout, index, block_buf)?;
+        if !entry.is_used() {
+            return Err(DiskError::InvalidPartitionIndex);
+        }
+        let range = entry
+            .lba_range()
+            .filter(|range| {
+                range.start() >= usable.start() && range.end() <= usable.end()
+            })
+            .ok_or(DiskError::OutOfSpecPartitionEntry)?;
+        let start = range.start().to_u64();
+        let old_end = range.end().to_u64();
+
+        // The free range directly after the partition, if any.
+        let gap_after = match old_end.checked_add(1) {
+            Some(next) => self
+                .next_free_gap(layout, usable, Lba(next), block_buf)?
+                .filter(|gap| gap.start().to_u64() == next),
+            None => None,
+        };
+
+        let new_end = match end {
+            NewPartitionEnd::Lba(lba) => lba.to_u64(),
+            NewPartitionEnd::Blocks(num_blocks) => start
+                .checked_add(num_blocks.get() - 1)
+                .ok_or(DiskError::Overflow)?,
+            NewPartitionEnd::Max => {
+                let Some(gap) = gap_after else {
+                    return Ok(range);
+                };
+                let mut new_end = gap.end().to_u64();
+                if let Some(alignment) = options.end_alignment {
+                    // Round the block after the end down to the
+                    // alignment.
+                    let after = new_end.saturating_add(1);
+                    new_end = (after - after % alignment.get())
+                        .saturating_sub(1)
+                        .max(old_end);
+                }
+                new_end
+            }
+        };
+
+        if new_end < start {
+            return Err(DiskError::InvalidPartitionSize);
+        }
+        if new_end < old_end && !options.allow_shrink {
+            return Err(DiskError::InvalidPartitionSize);
+        }
+        if new_end > old_end {
+            let gap = gap_after.ok_or(DiskError::NoFreeSpace)?;
+            if gap.end().to_u64() < new_end {
+                return Err(DiskError::NoFreeSpace);
+            }
+        }
+
+        // OK to unwrap: `new_end` is at least `start`.
+        let new_range =
+            LbaRangeInclusive::new(range.start(), Lba(new_end)).unwrap();
+        if new_end != old_end {
+            self.update_gpt_partition_entry(index, block_buf, |entry| {
+                entry.ending_lba = new_range.end().into();
+                Ok(())
+            })?;
+        }
+        Ok(new_range)
+    }
+
+    /// Remove a partition from the partition table, similar to
+    /// `sgdisk --delete`.
+    ///
+    /// Entry `index` is zeroed in both partition entry arrays, and both
+    /// headers are updated. Partition data is not modified. Deleting
+    /// an entry that is already unused is not an error.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn delete_partition(
+        &mut self,
+        index: u32,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_gpt_partition_entry(index, block_buf, |entry| {
+            *entry = GptPartitionEntry::default();
+            Ok(())
+        })
+    }
+
+    /// Change the name of a partition, similar to `sgdisk --change-name`.
+    ///
+    /// The entry is updated in both partition entry arrays, and both
+    /// headers are updated with the new CRC32s. No other fields of the
+    /// entry are changed.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range or refers to an unused entry.
+    /// * [`DiskError::InvalidPartitionName`] if `name` does not fit in
+    ///   the entry (35 UCS-2 characters, leaving room for a null
+    ///   terminator), or contains characters that can't be represented
+    ///   in UCS-2. Nothing is written in that case.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn rename_partition(
+        &mut self,
+        index: u32,
+        name: &str,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let name: GptPartitionName =
+            name.parse().map_err(|_| DiskError::InvalidPartitionName)?;
+        self.update_gpt_partition_entry(index, block_buf, |entry| {
+            if !entry.is_used() {
+                return Err(DiskError::InvalidPartitionIndex);
+            }
+            entry.name = name;
+            Ok(())
+        })
+    }
+
+    /// Change the disk GUID in both GPT headers, similar to
+    /// `sgdisk --disk-guid`.
+    ///
+    /// Both headers are read and verified, their `disk_guid` and header
+    /// CRC32 are updated, and they are written back. The secondary
+    /// header is written first so that if the update is interrupted,
+    /// the primary header (which is what most readers use) is still
+    /// the old, self-consistent header.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidDiskGuid`] if `disk_guid` is zero.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn set_disk_guid(
+        &mut self,
+        disk_guid: Guid,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        if disk_guid == Guid::ZERO {
+            return Err(DiskError::InvalidDiskGuid);
+        }
+
+        let (mut primary, mut secondary) =
+            self.read_gpt_headers_for_update(block_buf)?;
+        for header in [&mut primary, &mut secondary] {
+            header.disk_guid = disk_guid;
+            header.update_header_crc32();
+        }
+
+        self.write_secondary_gpt_header(&secondary, block_buf)?;
+        self.write_primary_gpt_header(&primary, block_buf)
+    }
+
+    /// Create a new, empty partition table, similar to `sgdisk -o`.
+    ///
+    /// The steps are:
+    /// 1. Write a protective MBR, unless the first block contains data
+    ///    other than a protective MBR and [`InitGptOptions::force`] is
+    ///    not set.
+    /// 2. Create primary and secondary headers in the standard
+    ///    locations based on [`BlockIo::num_blocks`]; see
+    ///    [`GptHeader::new_primary`].
+    /// 3. Write both partition entry arrays, with every entry zeroed.
+    /// 4. Write both headers.
+    ///
+    /// Any existing partition table is overwritten. Partition data is
+    /// not modified.
+    ///
+    /// Errors:
+    /// * [`DiskError::InvalidDiskGuid`] if `disk_guid` is zero.
+    /// * [`DiskError::MbrInUse`] if the first block is in use and
+    ///   [`InitGptOptions::force`] is not set.
+    /// * [`DiskError::NoFreeSpace`] if the disk is too small to hold
+    ///   both headers, both partition entry arrays, and at least one
+    ///   usable block.
+    ///
+    /// Nothing is written if an error is returned before the first
+    /// write.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn init_gpt(
+        &mut self,
+        disk_guid: Guid,
+        options: InitGptOptions,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        if disk_guid == Guid::ZERO {
+            return Err(DiskError::InvalidDiskGuid);
+        }
+        let primary = GptHeader::new_primary(
+            self.num_blocks()?,
+            self.block_size(),
+            disk_guid,
+            options.num_partition_entries,
+        )
+        .ok_or(DiskError::NoFreeSpace)?;
+        let secondary = primary.to_secondary();
+
+        self.write_protective_mbr_guarded(options.force, block_buf)?;
+        for header in [&primary, &secondary] {
+            self.write_gpt_partition_entry_array_from_iter(
+                header_layout(header)?,
+                iter::empty(),
+                block_buf,
+            )?;
+        }
+        self.write_secondary_gpt_header(&secondary, block_buf)?;
+        self.write_primary_gpt_header(&primary, block_buf)
+    }
+
+    /// Replace the disk GUID and the unique GUID of every used
+    /// partition with new random GUIDs, similar to `sgdisk -G`. This
+    /// should be done after cloning a disk image, so that the copies
+    /// can be told apart.
+    ///
+    /// `rng` is called once per new GUID to get 16 random bytes, which
+    /// are converted with [`Guid::from_random_bytes`]. For example, with
+    /// the `rand` crate this could be `|| rand::random()`.
+    ///
+    /// The updated array is written to both the primary and secondary
+    /// locations, and both headers are updated with the new CRC32s.
+    /// The disk GUID is then changed as in [`set_disk_guid`].
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionGuid`] if `rng` returns bytes that
+    ///   produce a duplicate GUID. Nothing is written in that case.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    /// [`set_disk_guid`]: Self::set_disk_guid
+    pub fn randomize_guids<R>(
+        &mut self,
+        mut rng: R,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        R: FnMut() -> [u8; 16],
+    {
+        let disk_guid = Guid::from_random_bytes(rng());
+        self.update_gpt_partition_entry_arrays(storage, block_buf, |array| {
+            let partition_guid = |array: &GptPartitionEntryArray, index| {
+                // OK to unwrap: `index` is always in bounds.
+                let entry = array.get_partition_entry(index).unwrap();
+                entry.unique_partition_guid
+            };
+
+            for i in 0..array.layout().num_entries {
+                // OK to unwrap: `i` is always in bounds.
+                if !array.get_partition_entry(i).unwrap().is_used() {
+                    continue;
+                }
+                // Random GUIDs are never zero, so they can't match an
+                // unused entry.
+                let guid = Guid::from_random_bytes(rng());
+                if guid == disk_guid
+                    || (0..i).any(|j| partition_guid(array, j) == guid)
+                {
+                    return Err(DiskError::InvalidPartitionGuid);
+                }
+                // OK to unwrap: `i` is always in bounds.
+                array
+                    .get_partition_entry_mut(i)
+                    .unwrap()
+                    .unique_partition_guid = guid;
+            }
+            Ok(())
+        })?;
+        self.set_disk_guid(disk_guid, block_buf)
+    }
+
+    /// Move the secondary partition entry array and header to the end
+    /// of the disk, similar to the "relocate backup data structures to
+    /// the end of the disk" command in `gdisk`.
+    ///
+    /// This fixes [`Finding::SecondaryHeaderNotAtEnd`], which typically
+    /// occurs after a disk image is written to a larger disk. It can
+    /// also be used after a disk has shrunk, as long as no partition
+    /// extends past the new end of the usable range.
+    ///
+    /// The primary header and partition entry array must be valid; the
+    /// existing secondary structures are not read. The steps are:
+    /// 1. Copy the primary partition entry array to the blocks just
+    ///    before the last block.
+    /// 2. Set the last usable LBA of both headers to the block before
+    ///    the new secondary array.
+    /// 3. Write the new secondary header to the last block, then update
+    ///    the primary header to point at it.
+    /// 4. Zero the old secondary header block, if it is still within
+    ///    the disk and was not overwritten, so that it is not mistaken
+    ///    for a valid header.
+    ///
+    /// The protective MBR is not updated.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if the primary header fails
+    ///   verification.
+    /// * [`DiskError::CorruptPartitionEntryArray`] if the primary
+    ///   partition entry array does not match the header's CRC32. The
+    ///   disk is not modified, so the old secondary copy can still be
+    ///   used for recovery.
+    /// * [`DiskError::NoFreeSpace`] if a used partition or the primary
+    ///   partition entry array would overlap the new secondary array.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`Finding::SecondaryHeaderNotAtEnd`]: crate::validate::Finding::SecondaryHeaderNotAtEnd
+    pub fn move_secondary_gpt_to_end(
+        &mut self,
+        mut block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (mut primary, array_blocks) =
+            self.read_gpt_for_repair(Lba(1), block_buf)?;
+        let layout = header_layout(&primary)?;
+
+        let num_blocks = self.num_blocks()?;
+        let last_lba = num_blocks.checked_sub(1).ok_or(DiskError::Overflow)?;
+        let array_start = last_lba
+            .checked_sub(array_blocks)
+            .ok_or(DiskError::NoFreeSpace)?;
+        let last_usable =
+            array_start.checked_sub(1).ok_or(DiskError::NoFreeSpace)?;
+        let first_usable = primary.first_usable_lba.to_u64();
+        let primary_array_end = layout
+            .start_lba
+            .to_u64()
+            .checked_add(array_blocks)
+            .ok_or(DiskError::Overflow)?;
+        if last_usable < first_usable || primary_array_end > array_start {
+            return Err(DiskError::NoFreeSpace);
+        }
+        for entry in self.gpt_partition_entry_array_iter(layout, block_buf)? {
+            let entry = entry?;
+            if entry.is_used() && entry.ending_lba.to_u64() > last_usable {
+                return Err(DiskError::NoFreeSpace);
+            }
+        }
+
+        // Copy the array. The source and destination can't overlap
+        // since the destination is after the primary array.
+        self.copy_blocks(
+            layout.start_lba,
+            Lba(array_start),
+            array_blocks,
+            block_buf,
+        )?;
+        block_buf = self.clip_block_buf_size(block_buf)?;
+
+        let old_secondary_lba = primary.alternate_lba.to_u64();
+        let old_last_usable = primary.last_usable_lba.to_u64();
+        primary.alternate_lba = LbaLe::from_u64(last_lba);
+        primary.last_usable_lba = LbaLe::from_u64(last_usable);
+        primary.update_header_crc32();
+        let mut secondary = primary;
+        secondary.my_lba = LbaLe::from_u64(last_lba);
+        secondary.alternate_lba = LbaLe::from_u64(1);
+        secondary.partition_entry_lba = LbaLe::from_u64(array_start);
+        secondary.update_header_crc32();
+
+        self.write_secondary_gpt_header(&secondary, block_buf)?;
+        self.write_primary_gpt_header(&primary, block_buf)?;
+
+        // Only zero the old header if it's where a secondary header
+        // belongs (after the usable range and the primary array), and
+        // wasn't overwritten by the new secondary structures.
+        if old_secondary_lba > old_last_usable
+            && old_secondary_lba >= primary_array_end
+            && old_secondary_lba < array_start
+        {
+            block_buf.fill(0);
+            self.write_blocks(Lba(old_secondary_lba), block_buf)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the secondary header and partition entry array from the
+    /// primary copy, similar to the "use main GPT header (rebuilding
+    /// backup)" command in `gdisk`'s recovery menu.
+    ///
+    /// The primary header and array must be valid; the existing
+    /// secondary structures are not read. The steps are:
+    /// 1. Read and verify the primary header, and check the CRC32 of
+    ///    the primary partition entry array.
+    /// 2. Copy the primary array to the blocks just after the last
+    ///    usable LBA.
+    /// 3. Write a secondary header at the primary header's
+    ///    `alternate_lba`. See [`GptHeader::to_secondary`].
+    ///
+    /// The primary header and the protective MBR are not modified.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if the primary header fails
+    ///   verification.
+    /// * [`DiskError::CorruptPartitionEntryArray`] if the primary
+    ///   partition entry array does not match its CRC32.
+    /// * [`DiskError::NoFreeSpace`] if the secondary array would not
+    ///   fit between the last usable LBA and the secondary header, or
+    ///   the secondary header is past the end of the disk.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn repair_from_primary(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (primary, array_blocks) =
+            self.read_gpt_for_repair(Lba(1), block_buf)?;
+        let secondary = primary.to_secondary();
+
+        let secondary_lba = secondary.my_lba.to_u64();
+        let array_end = secondary
+            .partition_entry_lba
+            .checked_add(array_blocks)
+            .ok_or(DiskError::Overflow)?;
+        if secondary_lba >= self.num_blocks()?
+            || array_end.to_u64() > secondary_lba
+        {
+            return Err(DiskError::NoFreeSpace);
+        }
+
+        self.copy_blocks(
+            primary.partition_entry_lba.into(),
+            secondary.partition_entry_lba.into(),
+            array_blocks,
+            block_buf,
+        )?;
+        self.write_secondary_gpt_header(&secondary, block_buf)
+    }
+
+    /// Rebuild the primary header and partition entry array from the
+    /// secondary copy, similar to the "use backup GPT header
+    /// (rebuilding main)" command in `gdisk`'s recovery menu.
+    ///
+    /// The secondary header (in the last block) and array must be
+    /// valid; the existing primary structures are not read. The steps
+    /// are:
+    /// 1. Read and verify the secondary header, and check the CRC32 of
+    ///    the secondary partition entry array.
+    /// 2. Copy the secondary array to the block after the primary
+    ///    header (LBA 2).
+    /// 3. Write a primary header at the secondary header's
+    ///    `alternate_lba` (LBA 1), with the `my_lba`, `alternate_lba`,
+    ///    and `partition_entry_lba` fields updated.
+    ///
+    /// The secondary header and the protective MBR are not modified.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if the secondary header fails
+    ///   verification, or its `alternate_lba` is not 1.
+    /// * [`DiskError::CorruptPartitionEntryArray`] if the secondary
+    ///   partition entry array does not match its CRC32.
+    /// * [`DiskError::NoFreeSpace`] if the primary array would not fit
+    ///   before the first usable LBA.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    pub fn repair_from_secondary(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let last_lba = self
+            .num_blocks()?
+            .checked_sub(1)
+            .ok_or(DiskError::Overflow)?;
+        let (secondary, array_blocks) =
+            self.read_gpt_for_repair(Lba(last_lba), block_buf)?;
+        if secondary.alternate_lba != 1 {
+            return Err(DiskError::CorruptGptHeader);
+        }
+
+        let mut primary = GptHeader {
+            my_lba: secondary.alternate_lba,
+            alternate_lba: secondary.my_lba,
+            partition_entry_lba: LbaLe::from_u64(2),
+            ..secondary
+        };
+        primary.update_header_crc32();
+        let array_end = primary
+            .partition_entry_lba
+            .checked_add(array_blocks)
+            .ok_or(DiskError::Overflow)?;
+        if array_end.to_u64() > primary.first_usable_lba.to_u64() {
+            return Err(DiskError::NoFreeSpace);
+        }
+
+        self.copy_blocks(
+            secondary.partition_entry_lba.into(),
+            primary.partition_entry_lba.into(),
+            array_blocks,
+            block_buf,
+        )?;
+        self.write_primary_gpt_header(&primary, block_buf)
+    }
+
+    /// Move the primary and secondary partition entry arrays to
+    /// `primary_start` and `secondary_start`, similar to the "relocate
+    /// the main partition table" command in `gdisk`.
+    ///
+    /// The arrays do not need to be adjacent to their headers. Some
+    /// firmware leaves a gap between the primary header and its array,
+    /// for example, and this can also be used to move the arrays back
+    /// to their usual locations on such disks. The usable range of the
+    /// disk is not changed, so the new locations must be outside it.
+    ///
+    /// The primary array is read into `storage` and written to both new
+    /// locations, then both headers are updated to point at the new
+    /// arrays, with new CRC32s.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::GptHeaderMismatch`] if the headers describe
+    ///   arrays with a different number of entries or entry size.
+    /// * [`DiskError::NoFreeSpace`] if either new array would overlap
+    ///   the MBR, either header, the usable range of either header, or
+    ///   the other new array, or would extend past the end of the disk.
+    ///   Nothing is written in that case.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    pub fn move_partition_entry_arrays(
+        &mut self,
+        primary_start: Lba,
+        secondary_start: Lba,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        let (mut primary, mut secondary) =
+            self.read_gpt_headers_for_update(block_buf)?;
+        let layout = header_layout(&primary)?;
+        let secondary_layout = header_layout(&secondary)?;
+        if layout.entry_size != secondary_layout.entry_size
+            || layout.num_entries != secondary_layout.num_entries
+        {
+            return Err(DiskError::GptHeaderMismatch);
+        }
+
+        // Check the new locations, using half-open ranges of blocks.
+        let array_blocks = layout
+            .num_blocks(self.block_size())
+            .ok_or(DiskError::Overflow)?;
+        let array_range = |start: Lba| {
+            let start = start.to_u64();
+            start
+                .checked_add(array_blocks)
+                .map(|end| start..end)
+                .ok_or(DiskError::Overflow)
+        };
+        let overlaps =
+            |a: &Range<u64>, b: &Range<u64>| a.start < b.end && b.start < a.end;
+        let usable_range = |header: &GptHeader| {
+            header.first_usable_lba.to_u64()
+                ..header.last_usable_lba.to_u64().saturating_add(1)
+        };
+        let new_primary = array_range(primary_start)?;
+        let new_secondary = array_range(secondary_start)?;
+        let num_blocks = self.num_blocks()?;
+        for range in [&new_primary, &new_secondary] {
+            // Blocks 0 and 1 are the MBR and primary header.
+            if range.start < 2
+                || range.end > num_blocks
+                || range.contains(&secondary.my_lba.to_u64())
+                || overlaps(range, &usable_range(&primary))
+                || overlaps(range, &usable_range(&secondary))
+            {
+                return Err(DiskError::NoFreeSpace);
+            }
+        }
+        if overlaps(&new_primary, &new_secondary) {
+            return Err(DiskError::NoFreeSpace);
+        }
+
+        // The whole array is read before anything is written, so the
+        // new locations may overlap the old ones.
+        let mut array = self.read_gpt_partition_entry_array(layout, storage)?;
+        array.set_start_lba(secondary_start);
+        self.write_gpt_partition_entry_array(&array)?;
+        array.set_start_lba(primary_start);
+        self.write_gpt_partition_entry_array(&array)?;
+
+        primary.partition_entry_lba = primary_start.into();
+        secondary.partition_entry_lba = secondary_start.into();
+        self.write_gpt_headers_after_update(
+            &mut primary,
+            &mut secondary,
+            block_buf,
+        )
+    }
+
+    /// Read entry `index` from the primary partition entry array,
+    /// modify it with `f`, then write it to both the primary and
+    /// secondary arrays and update both headers. Nothing is written if
+    /// `f` returns an error.
+    pub(crate) fn update_gpt_partition_entry<F>(
+        &mut self,
+        index: u32,
+        block_buf: &mut [u8],
+        f: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnOnce(&mut GptPartitionEntry) -> Result<(), DiskError<Io::Error>>,
+    {
+        let (mut primary, mut secondary) =
+            self.read_gpt_headers_for_update(block_buf)?;
+        let layout = header_layout(&primary)?;
+        let secondary_layout = header_layout(&secondary)?;
+        if layout.entry_size != secondary_layout.entry_size
+            || layout.num_entries != secondary_layout.num_entries
+        {
+            return Err(DiskError::GptHeaderMismatch);
+        }
+
+        let mut entry =
+            self.read_gpt_partition_entry(layout, index, block_buf)?;
+        f(&mut entry)?;
+        self.write_gpt_partition_entry(layout, index, &entry, block_buf)?;
+        self.write_gpt_partition_entry(
+            secondary_layout,
+            index,
+            &entry,
+            block_buf,
+        )?;
+
+        self.write_gpt_headers_after_update(
+            &mut primary,
+            &mut secondary,
+            block_buf,
+        )
+    }
+
+    /// Read the primary partition entry array into `storage`, modify
+    /// it with `f`, then write it to both the primary and secondary
+    /// locations and update both headers. Nothing is written if `f`
+    /// returns an error.
+    fn update_gpt_partition_entry_arrays<F>(
+        &mut self,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+        f: F,
+    ) -> Result<(), DiskError<Io::Error>>
+    where
+        F: FnOnce(
+            &mut GptPartitionEntryArray,
+        ) -> Result<(), DiskError<Io::Error>>,
+    {
+        let (mut primary, mut secondary) =
+            self.read_gpt_headers_for_update(block_buf)?;
+        let layout = header_layout(&primary)?;
+        let secondary_layout = header_layout(&secondary)?;
+        if layout.entry_size != secondary_layout.entry_size
+            || layout.num_entries != secondary_layout.num_entries
+        {
+            return Err(DiskError::GptHeaderMismatch);
+        }
+
+        let mut array = self.read_gpt_partition_entry_array(layout, storage)?;
+        f(&mut array)?;
+        self.write_gpt_partition_entry_array(&array)?;
+        array.set_start_lba(secondary_layout.start_lba);
+        self.write_gpt_partition_entry_array(&array)?;
+
+        self.write_gpt_headers_after_update(
+            &mut primary,
+            &mut secondary,
+            block_buf,
+        )
+    }
+
+    /// Sort the partition entries by starting LBA, similar to
+    /// `sgdisk --sort`.
+    ///
+    /// Used entries are moved to the start of the array in order of
+    /// their starting LBA, and unused entries are moved after them. The
+    /// sorted array is written to both the primary and secondary
+    /// locations, and both headers are updated with the new CRC32s.
+    /// Partition data is not moved.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    pub fn sort_partitions(
+        &mut self,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_gpt_partition_entry_arrays(storage, block_buf, |array| {
+            let sort_key = |array: &GptPartitionEntryArray, index| {
+                // OK to unwrap: `index` is always in bounds.
+                let entry = array.get_partition_entry(index).unwrap();
+                (!entry.is_used(), entry.starting_lba.to_u64())
+            };
+
+            // Insertion sort, since entries are not contiguous `T`s in
+            // storage. This is stable, so unused entries keep their
+            // relative order.
+            for i in 1..array.layout().num_entries {
+                let mut j = i;
+                while j > 0 && sort_key(array, j - 1) > sort_key(array, j) {
+                    swap_entries(array, j - 1, j);
+                    j -= 1;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Move all used partition entries to the lowest indices of the
+    /// partition entry array, keeping their relative order.
+    ///
+    /// This removes holes in the slot numbering left by deleted
+    /// partitions. Partition data is not moved. The compacted array is
+    /// written to both the primary and secondary locations, and both
+    /// headers are updated with the new CRC32s.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    pub fn compact_partitions(
+        &mut self,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_gpt_partition_entry_arrays(storage, block_buf, |array| {
+            // All entries in `next_free..i` are unused, so swapping
+            // entry `i` into `next_free` preserves the order of the used
+            // entries.
+            let mut next_free = 0;
+            for i in 0..array.layout().num_entries {
+                // OK to unwrap: `i` is always in bounds.
+                if array.get_partition_entry(i).unwrap().is_used() {
+                    swap_entries(array, next_free, i);
+                    next_free += 1;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Exchange the partition entries at indices `a` and `b`, similar
+    /// to `sgdisk --transpose`.
+    ///
+    /// Either entry may be unused, in which case this moves the other
+    /// entry to the unused slot. Partition data is not moved. The
+    /// updated array is written to both the primary and secondary
+    /// locations, and both headers are updated with the new CRC32s.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionIndex`] if `a` or `b` is out of
+    ///   range.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    pub fn swap_partition_indices(
+        &mut self,
+        a: u32,
+        b: u32,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_gpt_partition_entry_arrays(storage, block_buf, |array| {
+            let num_entries = array.layout().num_entries;
+            if a >= num_entries || b >= num_entries {
+                return Err(DiskError::InvalidPartitionIndex);
+            }
+            swap_entries(array, a, b);
+            Ok(())
+        })
+    }
+
+    /// Move the partition entry at index `from` to index `to`, shifting
+    /// the entries in between by one slot to make room.
+    ///
+    /// For example, with entries `[A, B, C, D]`, moving from 3 to 1
+    /// gives `[A, D, B, C]`. To exchange two entries without shifting
+    /// the others, use [`swap_partition_indices`]. Partition data is
+    /// not moved. The updated array is written to both the primary and
+    /// secondary locations, and both headers are updated with the new
+    /// CRC32s.
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::InvalidPartitionIndex`] if `from` or `to` is out
+    ///   of range.
+    ///
+    /// The `storage` buffer must be large enough to hold the whole
+    /// partition entry array (see [`read_gpt_partition_entry_array`]).
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    ///
+    /// [`read_gpt_partition_entry_array`]: Self::read_gpt_partition_entry_array
+    /// [`swap_partition_indices`]: Self::swap_partition_indices
+    pub fn move_partition_to_index(
+        &mut self,
+        from: u32,
+        to: u32,
+        storage: &mut [u8],
+        block_buf: &mut [u8],
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.update_gpt_partition_entry_arrays(storage, block_buf, |array| {
+            let num_entries = array.layout().num_entries;
+            if from >= num_entries || to >= num_entries {
+                return Err(DiskError::InvalidPartitionIndex);
+            }
+            // Bubble the entry one slot at a time, which shifts the
+            // entries in between.
+            if from < to {
+                for i in from..to {
+                    swap_entries(array, i, i + 1);
+                }
+            } else {
+                for i in (to..from).rev() {
+                    swap_entries(array, i, i + 1);
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::edit::{header_layout, header_usable_range};
+use crate::{
+    BlockIo, CreatePartitionOptions, Disk, DiskError, PartitionExtent,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+use core::num::NonZeroU64;
+use gpt_disk_types::{
+    GptHeader, GptPartitionEntry, GptPartitionEntryArrayBuf,
+    GptPartitionEntryArrayError, GptPartitionName, GptPartitionType, Guid,
+    LbaRangeInclusive, PartitionAlignment,
+};
+
+/// In-memory copy of a disk's partition table.
+///
+/// [`GptDisk::open`] reads and verifies both GPT headers and the
+/// primary partition entry array. The partition table can then be
+/// inspected and edited in memory with methods such as
+/// [`add_partition`], [`remove_partition`], [`set_name`], and
+/// [`set_type`]. Nothing is written until [`write`] is called, which
+/// writes both partition entry arrays and both headers with updated
+/// CRC32s.
+///
+/// This is a higher-level alternative to the editing methods on
+/// [`Disk`], which read and write the disk on every call. Requires the
+/// `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// use gpt_disk_io::gpt_disk_types::{guid, BlockSize, GptPartitionType};
+/// use gpt_disk_io::{
+///     CreatePartitionOptions, Disk, GptDisk, InitGptOptions, PartitionExtent,
+///     VecBlockIo,
+/// };
+/// use core::num::NonZeroU64;
+///
+/// // Create a disk with an empty partition table.
+/// let bs = BlockSize::BS_512;
+/// let mut disk = Disk::new(VecBlockIo::new(bs, 8192))?;
+/// let mut block_buf = vec![0; bs.to_usize().unwrap()];
+/// disk.init_gpt(
+///     guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
+///     InitGptOptions::default(),
+///     &mut block_buf,
+/// )?;
+///
+/// // Add a partition and write the partition table.
+/// let mut gpt = GptDisk::from_disk(disk)?;
+/// let index = gpt.add_partition(
+///     GptPartitionType::LINUX_FILESYSTEM,
+///     PartitionExtent::Blocks(NonZeroU64::new(2048).unwrap()),
+///     "data".parse().unwrap(),
+///     CreatePartitionOptions::new(guid!(
+///         "37c75ffd-8932-467a-9c56-8cf1f0456b12"
+///     )),
+/// )?;
+/// gpt.write()?;
+///
+/// let (_, entry) = gpt.partitions().next().unwrap();
+/// assert_eq!(entry.starting_lba.to_u64(), 2048);
+/// assert_eq!(entry.ending_lba.to_u64(), 4095);
+/// # Ok::<(), gpt_disk_io::DiskError<gpt_disk_io::SliceBlockIoError>>(())
+/// ```
+///
+/// [`add_partition`]: Self::add_partition
+/// [`remove_partition`]: Self::remove_partition
+/// [`set_name`]: Self::set_name
+/// [`set_type`]: Self::set_type
+/// [`write`]: Self::write
+pub struct GptDisk<Io: BlockIo> {
+    disk: Disk<Io>,
+    primary: GptHeader,
+    secondary: GptHeader,
+    entries: GptPartitionEntryArrayBuf,
+    block_buf: Vec<u8>,
+}
+
+impl<Io: BlockIo> GptDisk<Io> {
+    /// Create a `Disk` from `io` and read its partition table. See
+    /// [`from_disk`].
+    ///
+    /// [`from_disk`]: Self::from_disk
+    pub fn open(io: Io) -> Result<Self, DiskError<Io::Error>> {
+        Self::from_disk(Disk::new(io)?)
+    }
+
+    /// Read the partition table of `disk`.
+    ///
+    /// Both headers are read and verified, and the primary partition
+    /// entry array is read into memory. The secondary partition entry
+    /// array is not read; it is overwritten with the primary array by
+    /// [`write`].
+    ///
+    /// Errors:
+    /// * [`DiskError::CorruptGptHeader`] if either header fails
+    ///   verification.
+    /// * [`DiskError::GptHeaderMismatch`] if the headers describe
+    ///   partition entry arrays with a different number of entries or
+    ///   entry size.
+    /// * [`DiskError::CorruptPartitionEntryArray`] if the primary
+    ///   partition entry array does not match its CRC32.
+    ///
+    /// [`write`]: Self::write
+    pub fn from_disk(mut disk: Disk<Io>) -> Result<Self, DiskError<Io::Error>> {
+        let block_size = disk.block_size();
+        let mut block_buf =
+            vec![0; block_size.to_usize().ok_or(DiskError::Overflow)?];
+        let (primary, secondary) =
+            disk.read_gpt_headers_for_update(&mut block_buf)?;
+
+        let layout = header_layout(&primary)?;
+        let secondary_layout = header_layout(&secondary)?;
+        if layout.num_entries != secondary_layout.num_entries
+            || layout.entry_size != secondary_layout.entry_size
+        {
+            return Err(DiskError::GptHeaderMismatch);
+        }
+
+        let mut entries = GptPartitionEntryArrayBuf::new(layout, block_size)
+            .map_err(|err| match err {
+                GptPartitionEntryArrayError::BufferTooSmall
+                | GptPartitionEntryArrayError::Overflow => DiskError::Overflow,
+                GptPartitionEntryArrayError::InvalidIndex => {
+                    DiskError::InvalidPartitionIndex
+                }
+            })?;
+        disk.read_gpt_partition_entry_array(layout, entries.storage_mut())?;
+        if entries.calculate_crc32() != primary.partition_entry_array_crc32 {
+            return Err(DiskError::CorruptPartitionEntryArray);
+        }
+
+        Ok(Self {
+            disk,
+            primary,
+            secondary,
+            entries,
+            block_buf,
+        })
+    }
+
+    /// Get a reference to the underlying [`Disk`].
+    pub fn disk(&self) -> &Disk<Io> {
+        &self.disk
+    }
+
+    /// Get a mutable reference to the underlying [`Disk`].
+    ///
+    /// Changes made to the partition table through the [`Disk`] are
+    /// not reflected in the `GptDisk`, and are overwritten by
+    /// [`write`].
+    ///
+    /// [`write`]: Self::write
+    pub fn disk_mut(&mut self) -> &mut Disk<Io> {
+        &mut self.disk
+    }
+
+    /// Consume the `GptDisk` and return the underlying [`Disk`]. Any
+    /// changes that have not been written are discarded.
+    pub fn into_disk(self) -> Disk<Io> {
+        self.disk
+    }
+
+    /// Get the primary GPT header. The CRC32 fields are only up to date
+    /// after [`write`].
+    ///
+    /// [`write`]: Self::write
+    pub fn primary_header(&self) -> &GptHeader {
+        &self.primary
+    }
+
+    /// Get the secondary GPT header. The CRC32 fields are only up to
+    /// date after [`write`].
+    ///
+    /// [`write`]: Self::write
+    pub fn secondary_header(&self) -> &GptHeader {
+        &self.secondary
+    }
+
+    /// Get the number of entries in the partition entry array, including
+    /// unused entries.
+    pub fn num_entries(&self) -> u32 {
+        self.entries.layout().num_entries
+    }
+
+    /// Get the partition entry at `index`, which may be unused. Returns
+    /// `None` if `index` is out of range.
+    pub fn partition(&self, index: u32) -> Option<&GptPartitionEntry> {
+        self.entries.get_partition_entry(index)
+    }
+
+    /// Get an iterator over the used partition entries, along with
+    /// their indices in the partition entry array.
+    pub fn partitions(
+        &self,
+    ) -> impl Iterator<Item = (u32, &GptPartitionEntry)> + '_ {
+        (0..self.num_entries()).filter_map(|index| {
+            self.partition(index)
+                .filter(|entry| entry.is_used())
+                .map(|entry| (index, entry))
+        })
+    }
+
+    /// Add a new partition in the first unused entry of the partition
+    /// entry array. This is the in-memory equivalent of
+    /// [`Disk::create_partition`], and takes the same arguments.
+    ///
+    /// Returns the index of the new entry in the partition entry array.
+    ///
+    /// Errors:
+    /// * [`DiskError::NoFreePartitionEntry`] if every entry is in use.
+    /// * [`DiskError::InvalidPartitionGuid`] if the partition GUID is
+    ///   zero or already in use.
+    /// * [`DiskError::NoFreeSpace`] if no suitable free range exists.
+    pub fn add_partition(
+        &mut self,
+        partition_type: GptPartitionType,
+        extent: PartitionExtent,
+        name: GptPartitionName,
+        options: CreatePartitionOptions,
+    ) -> Result<u32, DiskError<Io::Error>> {
+        let guid = options.unique_partition_guid;
+        if guid == Guid::ZERO
+            || self
+                .partitions()
+                .any(|(_, entry)| { entry.unique_partition_guid } == guid)
+        {
+            return Err(DiskError::InvalidPartitionGuid);
+        }
+        let index = (0..self.num_entries())
+            .find(|index| {
+                self.partition(*index).is_some_and(|entry| !entry.is_used())
+            })
+            .ok_or(DiskError::NoFreePartitionEntry)?;
+
+        let range = match extent {
+            PartitionExtent::Blocks(num_blocks) => {
+                let alignment = options.alignment.unwrap_or_else(|| {
+                    PartitionAlignment::DEFAULT
+                        .with_physical_block_size(
+                            self.disk.physical_block_size(),
+                        )
+                        .num_blocks(self.disk.block_size())
+                });
+                self.find_free_range(num_blocks, alignment)?
+                    .ok_or(DiskError::NoFreeSpace)?
+            }
+            PartitionExtent::Range(range) => {
+                let usable = header_usable_range(&self.primary)?;
+                if !usable.contains_range(range) || self.is_used(range) {
+                    return Err(DiskError::NoFreeSpace);
+                }
+                range
+            }
+        };
+
+        let entry = GptPartitionEntry {
+            partition_type_guid: partition_type,
+            unique_partition_guid: guid,
+            starting_lba: range.start().into(),
+            ending_lba: range.end().into(),
+            attributes: options.attributes,
+            name,
+        };
+        self.entries
+            .set_partition_entry(index, &entry)
+            .map_err(|_| DiskError::InvalidPartitionIndex)?;
+        Ok(index)
+    }
+
+    /// Remove the partition at `index` by zeroing its entry. Removing an
+    /// entry that is already unused is not an error.
+    ///
+    /// Returns [`DiskError::InvalidPartitionIndex`] if `index` is out
+    /// of range.
+    pub fn remove_partition(
+        &mut self,
+        index: u32,
+    ) -> Result<(), DiskError<Io::Error>> {
+        self.entries
+            .set_partition_entry(index, &GptPartitionEntry::default())
+            .map_err(|_| DiskError::InvalidPartitionIndex)
+    }
+
+    /// Change the name of the partition at `index`.
+    ///
+    /// Errors:
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range or refers to an unused entry.
+    /// * [`DiskError::InvalidPartitionName`] if `name` does not fit in
+    ///   the entry (35 UCS-2 characters, leaving room for a null
+    ///   terminator), or contains characters that can't be represented
+    ///   in UCS-2.
+    pub fn set_name(
+        &mut self,
+        index: u32,
+        name: &str,
+    ) -> Result<(), DiskError<Io::Error>> {
+        let name: GptPartitionName =
+            name.parse().map_err(|_| DiskError::InvalidPartitionName)?;
+        self.used_partition_mut(index)?.name = name;
+        Ok(())
+    }
+
+    /// Change the type of the partition at `index`.
+    ///
+    /// Errors:
+    /// * [`DiskError::InvalidPartitionIndex`] if `index` is out of
+    ///   range or refers to an unused entry.
+    /// * [`DiskError::InvalidPartitionType`] if `partition_type` is
+    ///   [`GptPartitionType::UNUSED`]. Use [`remove_partition`] to
+    ///   remove a partition.
+    ///
+    /// [`remove_partition`]: Self::remove_partition
+    pub fn set_type(
+        &mut self,
+        index: u32,
+        partition_type: GptPartitionType,
+    ) -> Result<(), DiskError<Io::Error>> {
+        if partition_type == GptPartitionType::UNUSED {
+            return Err(DiskError::InvalidPartitionType);
+        }
+        self.used_partition_mut(index)?.partition_type_guid = partition_type;
+        Ok(())
+    }
+
+    /// Write the partition table to the disk.
+    ///
+    /// The partition entry array is written to both the primary and
+    /// secondary locations. Then the partition entry array CRC32 and
+    /// header CRC32 of both headers are updated, and both headers are
+    /// written. The protective MBR is not modified.
+    ///
+    /// The disk is not flushed; call [`Disk::flush`] on [`disk_mut`] if
+    /// needed.
+    ///
+    /// [`disk_mut`]: Self::disk_mut
+    pub fn write(&mut self) -> Result<(), DiskError<Io::Error>> {
+        let crc32 = self.entries.calculate_crc32();
+        self.primary.set_partition_entry_array_crc32(crc32);
+        self.secondary.set_partition_entry_array_crc32(crc32);
+
+        self.entries
+            .set_start_lba(self.primary.partition_entry_lba.into());
+        self.disk
+            .write_gpt_partition_entry_array(&self.entries.as_array_mut())?;
+        self.entries
+            .set_start_lba(self.secondary.partition_entry_lba.into());
+        self.disk
+            .write_gpt_partition_entry_array(&self.entries.as_array_mut())?;
+
+        self.disk
+            .write_primary_gpt_header(&self.primary, &mut self.block_buf)?;
+        self.disk
+            .write_secondary_gpt_header(&self.secondary, &mut self.block_buf)
+    }
+
+    /// Get the used entry at `index`.
+    fn used_partition_mut(
+        &mut self,
+        index: u32,
+    ) -> Result<&mut GptPartitionEntry, DiskError<Io::Error>> {
+        self.entries
+            .get_partition_entry_mut(index)
+            .filter(|entry| entry.is_used())
+            .ok_or(DiskError::InvalidPartitionIndex)
+    }
+
+    /// Check if any block of `range` is used by a partition. Entries with
+    /// an invalid range (end before start) are ignored.
+    fn is_used(&self, range: LbaRangeInclusive) -> bool {
+        self.partitions().any(|(_, entry)| {
+            entry.lba_range().is_some_and(|r| r.overlaps(range))
+        })
+    }
+
+    /// Find the first free range of `num_blocks` blocks within the
+    /// usable range whose start is a multiple of `alignment`.
+    fn find_free_range(
+        &self,
+        num_blocks: NonZeroU64,
+        alignment: NonZeroU64,
+    ) -> Result<Option<LbaRangeInclusive>, DiskError<Io::Error>> {
+        let usable = header_usable_range(&self.primary)?;
+
+        // A free range can start at the beginning of the usable range,
+        // or just after a partition.
+        let candidates = iter::once(usable.start()).chain(
+            self.partitions().filter_map(|(_, entry)| {
+                entry.lba_range().and_then(|r| r.end().checked_add(1))
+            }),
+        );
+        let mut best: Option<LbaRangeInclusive> = None;
+        for start in candidates {
+            let Some(start) = start.align_up(alignment) else {
+                continue;
+            };
+            let Some(range) = start
+                .checked_add(num_blocks.get() - 1)
+                .and_then(|end| LbaRangeInclusive::new(start, end))
+            else {
+                continue;
+            };
+            if usable.contains_range(range)
+                && !self.is_used(range)
+                && best.map_or(true, |best| range.start() < best.start())
+            {
+                best = Some(range);
+            }
+        }
+        Ok(best)
+    }
+}
@@ -17,14 +17,35 @@
 //!
 //! The [`BlockIoAdapter`] type allows the disk to be backed by simple
 //! byte-oriented storage backends, such as `&mut [u8]` and `File` (the
-//! latter requires the `std` feature).
+//! latter requires the `std` feature). The read-only [`RangeBlockIo`]
+//! type reads blocks through a [`RangeReader`], such as a remote file
+//! accessed with HTTP range requests. [`ByteBlockIo`] adapts
+//! byte-addressed storage implementing [`ByteIo`], such as flash
+//! memory. The [`RetryBlockIo`] wrapper retries failed operations on
+//! unreliable media, and [`OffsetBlockIo`] presents part of a
+//! [`BlockIo`] as a standalone disk.
 //!
 //! # Features
 //!
-//! * `alloc`: Enables [`Vec`] implementation of [`BlockIoAdapter`].
+//! * `alloc`: Enables [`Vec`], `Box<[u8]>`, `Arc<[u8]>`, and
+//!   `Cow<[u8]>` implementations of [`BlockIoAdapter`], the growable
+//!   [`VecBlockIo`], the [`DryRunBlockIo`], [`JournaledBlockIo`], and
+//!   [`FaultInjectingBlockIo`] wrappers, the read-only
+//!   [`SeekableZstdBlockIo`] for compressed images, [`BufDisk`],
+//!   which manages its own scratch buffers, and [`GptDisk`], which
+//!   edits an in-memory copy of the partition table.
+//! * `sha2`: Enables `Disk::calculate_partition_sha256`, which hashes
+//!   the contents of a partition with SHA-256. Off by default.
 //! * `std`: Enables [`std::io`] implementations of [`BlockIoAdapter`],
-//!   as well as `std::error::Error` implementations for all of the
-//!   error types. Off by default.
+//!   `HttpRangeReader`, a minimal HTTP [`RangeReader`], and
+//!   `NbdBlockIo`, a network block device client. On Linux, also
+//!   enables `reread_partition_table`. Off by default.
+//! * `test_util`: Enables the `test_util` module, with helpers for
+//!   building GPT disk images to use in tests. Implies `alloc`. Off by
+//!   default.
+//!
+//! All of the error types implement [`core::error::Error`], regardless
+//! of which features are enabled.
 //!
 //! # Examples
 //!
@@ -33,8 +54,8 @@
 //! ```
 //! use gpt_disk_io::{BlockIoAdapter, BlockIo, Disk, DiskError};
 //! use gpt_disk_types::{
-//!     guid, BlockSize, Crc32, GptHeader, GptPartitionEntry,
-//!     GptPartitionEntryArray, GptPartitionType, LbaLe, U32Le,
+//!     guid, BlockSize, GptHeader, GptPartitionEntry, GptPartitionEntryArray,
+//!     GptPartitionType, LbaLe,
 //! };
 //!
 //! // Space for a 4MiB disk.
@@ -49,26 +70,17 @@
 //!
 //! let mut disk = Disk::new(block_io)?;
 //!
-//! // Manually construct the header and partition entries.
-//! let primary_header = GptHeader {
-//!     header_crc32: Crc32(U32Le::from_u32(0xa4877843)),
-//!     my_lba: LbaLe::from_u64(1),
-//!     alternate_lba: LbaLe::from_u64(8191),
-//!     first_usable_lba: LbaLe::from_u64(34),
-//!     last_usable_lba: LbaLe::from_u64(8158),
-//!     disk_guid: guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
-//!     partition_entry_lba: LbaLe::from_u64(2),
-//!     number_of_partition_entries: U32Le::from_u32(128),
-//!     partition_entry_array_crc32: Crc32(U32Le::from_u32(0x9206adff)),
-//!     ..Default::default()
-//! };
-//! let secondary_header = GptHeader {
-//!     header_crc32: Crc32(U32Le::from_u32(0xdbeb4c13)),
-//!     my_lba: LbaLe::from_u64(8191),
-//!     alternate_lba: LbaLe::from_u64(1),
-//!     partition_entry_lba: LbaLe::from_u64(8159),
-//!     ..primary_header
-//! };
+//! // Construct the primary header with room for 128 partition
+//! // entries. The LBAs are calculated from the size of the disk.
+//! let mut primary_header = GptHeader::new_primary(
+//!     disk.num_blocks()?,
+//!     bs,
+//!     guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
+//!     128,
+//! )
+//! .unwrap();
+//!
+//! // Manually construct a partition entry.
 //! let partition_entry = GptPartitionEntry {
 //!     partition_type_guid: GptPartitionType(guid!(
 //!         "ccf0994f-f7e0-4e26-a011-843e38aa2eac"
@@ -84,12 +96,6 @@
 //! // but any mutable byte slice with the right length will do.
 //! let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
 //!
-//! // Write out the protective MBR and GPT headers. Note that without
-//! // the protective MBR, some tools won't recognize the disk as GPT.
-//! disk.write_protective_mbr(&mut block_buf)?;
-//! disk.write_primary_gpt_header(&primary_header, &mut block_buf)?;
-//! disk.write_secondary_gpt_header(&secondary_header, &mut block_buf)?;
-//!
 //! // Construct the partition entry array.
 //! let layout = primary_header.get_partition_entry_array_layout().unwrap();
 //! let mut bytes =
@@ -98,6 +104,17 @@
 //!     GptPartitionEntryArray::new(layout, bs, &mut bytes).unwrap();
 //! *entry_array.get_partition_entry_mut(0).unwrap() = partition_entry;
 //!
+//! // Update the checksums to match the modified array, then create
+//! // the secondary header from the primary header.
+//! primary_header.set_partition_entry_array_crc32(entry_array.calculate_crc32());
+//! let secondary_header = primary_header.to_secondary();
+//!
+//! // Write out the protective MBR and GPT headers. Note that without
+//! // the protective MBR, some tools won't recognize the disk as GPT.
+//! disk.write_protective_mbr(&mut block_buf)?;
+//! disk.write_primary_gpt_header(&primary_header, &mut block_buf)?;
+//! disk.write_secondary_gpt_header(&secondary_header, &mut block_buf)?;
+//!
 //! // Write the primary partition entry array.
 //! disk.write_gpt_partition_entry_array(&entry_array)?;
 //!
@@ -135,16 +152,68 @@
 extern crate alloc;
 
 mod block_io;
+#[cfg(feature = "alloc")]
+mod buf_disk;
+mod chromeos;
 mod disk;
-#[cfg(feature = "std")]
-mod std_support;
+mod edit;
+#[cfg(feature = "alloc")]
+mod gpt_disk;
+#[cfg(all(feature = "std", target_os = "linux"))]
+mod linux;
+
+pub mod compare;
+#[cfg(feature = "test_util")]
+pub mod test_util;
+pub mod validate;
 
 // Re-export dependencies.
 pub use gpt_disk_types;
 
+pub use block_io::byte_block_io::{ByteBlockIo, ByteBlockIoError, ByteIo};
+pub use block_io::offset_block_io::{OffsetBlockIo, OffsetBlockIoError};
+pub use block_io::range_block_io::{
+    RangeBlockIo, RangeBlockIoError, RangeReader,
+};
+pub use block_io::retry_block_io::RetryBlockIo;
 pub use block_io::slice_block_io::SliceBlockIoError;
 pub use block_io::{BlockIo, BlockIoAdapter};
-pub use disk::{Disk, DiskError};
+pub use chromeos::ChromeOsKernelAttributes;
+pub use disk::{
+    Disk, DiskError, GptHeaderConsistency, GptHeaderCopy, GptHeaders, NestedGpt,
+};
+pub use edit::{
+    CreatePartitionOptions, FreeGapFilter, InitGptOptions, NewPartitionEnd,
+    PartitionExtent, ResizePartitionOptions,
+};
+
+#[cfg(feature = "alloc")]
+pub use block_io::dry_run_block_io::{DryRunBlockIo, RecordedWrite};
+#[cfg(feature = "alloc")]
+pub use block_io::fault_injecting_block_io::{
+    FaultInjectingBlockIo, FaultInjectionError, FaultSchedule, FaultyOperations,
+};
+#[cfg(feature = "alloc")]
+pub use block_io::journaled_block_io::{
+    JournalError, JournalRecovery, JournaledBlockIo,
+};
+#[cfg(feature = "alloc")]
+pub use block_io::seekable_zstd_block_io::{
+    SeekableZstdBlockIo, SeekableZstdError, ZstdFrameDecoder,
+};
+#[cfg(feature = "alloc")]
+pub use block_io::vec_block_io::VecBlockIo;
+#[cfg(feature = "alloc")]
+pub use buf_disk::BufDisk;
+#[cfg(feature = "alloc")]
+pub use gpt_disk::GptDisk;
 
+#[cfg(feature = "std")]
+pub use block_io::http_range_reader::HttpRangeReader;
+#[cfg(feature = "std")]
+pub use block_io::nbd_block_io::NbdBlockIo;
 #[cfg(feature = "std")]
 pub use block_io::std_block_io::ReadWriteSeek;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use linux::reread_partition_table;
@@ -0,0 +1,112 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fs::File;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+// The type of the `ioctl` request argument differs between libc
+// implementations.
+#[cfg(target_env = "musl")]
+type IoctlRequest = c_int;
+#[cfg(not(target_env = "musl"))]
+type IoctlRequest = std::os::raw::c_ulong;
+
+/// `_IO(0x12, 95)` from `linux/fs.h`.
+const BLKRRPART: IoctlRequest = 0x125f;
+
+/// `_IO(0x12, 94)` from `linux/fs.h`.
+const BLKROGET: IoctlRequest = 0x125e;
+
+/// `F_GETFL` from `fcntl.h`.
+const F_GETFL: c_int = 3;
+
+/// `O_ACCMODE` and `O_RDONLY` from `fcntl.h`.
+const O_ACCMODE: c_int = 0o3;
+const O_RDONLY: c_int = 0;
+
+#[allow(unsafe_code)]
+extern "C" {
+    fn ioctl(fd: c_int, request: IoctlRequest, ...) -> c_int;
+    fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+}
+
+/// Check whether `file` was opened without write access, or is a block
+/// device with the read-only flag set. Errors are treated as not
+/// read-only, since this is only a hint.
+pub(crate) fn file_is_read_only(file: &File) -> bool {
+    let fd = file.as_raw_fd();
+
+    // SAFETY: `F_GETFL` takes no argument, and the file descriptor is
+    // valid for the lifetime of `file`.
+    #[allow(unsafe_code)]
+    let flags = unsafe { fcntl(fd, F_GETFL) };
+    if flags >= 0 && flags & O_ACCMODE == O_RDONLY {
+        return true;
+    }
+
+    // This fails with `ENOTTY` if `file` is not a block device.
+    let mut read_only: c_int = 0;
+    // SAFETY: `BLKROGET` writes a single `int` to the pointer, which is
+    // valid for the duration of the call.
+    #[allow(unsafe_code)]
+    let ret = unsafe { ioctl(fd, BLKROGET, ptr::addr_of_mut!(read_only)) };
+    ret == 0 && read_only != 0
+}
+
+/// Ask the Linux kernel to re-read the partition table of a block
+/// device.
+///
+/// After writing a new GPT to a block device, the kernel keeps using
+/// the partition table it read previously until told otherwise. Call
+/// this (after [`Disk::flush`]) so that the partition device nodes
+/// match what is on disk, without needing to run `partprobe`.
+///
+/// This issues the `BLKRRPART` ioctl. It fails with `EBUSY` if any
+/// partition of the device is in use (e.g. mounted), and with `ENOTTY`
+/// if `device` is not a block device.
+///
+/// Only available on Linux with the `std` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpt_disk_io::gpt_disk_types::BlockSize;
+/// use gpt_disk_io::{reread_partition_table, BlockIoAdapter, Disk};
+/// use std::fs::OpenOptions;
+///
+/// let mut file = OpenOptions::new()
+///     .read(true)
+///     .write(true)
+///     .open("/dev/sdb")?;
+/// let mut disk =
+///     Disk::new(BlockIoAdapter::new(&mut file, BlockSize::BS_512))?;
+/// let mut block_buf = vec![0; 512];
+/// disk.write_protective_mbr(&mut block_buf)?;
+/// // ... write GPT headers and partition entries ...
+/// disk.flush()?;
+/// drop(disk);
+///
+/// reread_partition_table(&file)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`Disk::flush`]: crate::Disk::flush
+pub fn reread_partition_table<F: AsRawFd>(device: &F) -> io::Result<()> {
+    // SAFETY: `BLKRRPART` takes no argument, and the file descriptor
+    // is valid for the lifetime of `device`.
+    #[allow(unsafe_code)]
+    let ret = unsafe { ioctl(device.as_raw_fd(), BLKRRPART) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
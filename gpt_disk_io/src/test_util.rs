@@ -0,0 +1,297 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for building synthetic GPT disks in tests.
+//!
+//! [`build_test_disk`] creates an in-memory disk image with a
+//! protective MBR, both GPT headers, and both partition entry arrays.
+//! [`corrupt_test_disk`] then damages one of those structures, for
+//! testing error handling.
+//!
+//! These functions panic rather than returning errors, since they are
+//! intended for use in tests.
+//!
+//! # Example
+//!
+//! ```
+//! use gpt_disk_io::test_util::{
+//!     corrupt_test_disk, valid_test_disk, Corruption,
+//! };
+//! use gpt_disk_io::gpt_disk_types::BlockSize;
+//! use gpt_disk_io::{BlockIoAdapter, Disk, GptHeaderCopy};
+//!
+//! let bs = BlockSize::BS_512;
+//! let mut storage = valid_test_disk();
+//! corrupt_test_disk(&mut storage, bs, Corruption::PrimaryHeader);
+//!
+//! let mut disk = Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), bs))?;
+//! let mut block_buf = vec![0; 512];
+//! let (_, copy) = disk.read_gpt_header_with_fallback(&mut block_buf)?;
+//! assert_eq!(copy, GptHeaderCopy::Secondary);
+//! # Ok::<(), gpt_disk_io::DiskError<gpt_disk_io::SliceBlockIoError>>(())
+//! ```
+
+use crate::{BlockIoAdapter, Disk};
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use bytemuck::from_bytes;
+use core::mem;
+use gpt_disk_types::{
+    guid, BlockSize, GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntryArrayLayout, GptPartitionEntrySize, GptPartitionType,
+    Guid, Lba, LbaLe, U32Le,
+};
+
+/// Disk GUID used by [`TestDiskSpec::default`].
+pub const TEST_DISK_GUID: Guid = guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870");
+
+/// Description of a disk to create with [`build_test_disk`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestDiskSpec {
+    /// Block size of the disk.
+    pub block_size: BlockSize,
+
+    /// Total number of blocks in the disk.
+    pub num_blocks: u64,
+
+    /// Disk GUID written to both headers.
+    pub disk_guid: Guid,
+
+    /// Number of entries in each partition entry array. The entry size
+    /// is always 128 bytes.
+    pub num_partition_entries: u32,
+
+    /// Partition entries to write at the start of the arrays. The rest
+    /// of the entries are unused. The entries are written as-is, so
+    /// they may be invalid or overlap.
+    pub partitions: Vec<GptPartitionEntry>,
+
+    /// Start of the primary partition entry array. If `None`, the
+    /// array is placed directly after the primary header, at LBA 2.
+    /// The first usable LBA is the block after the array.
+    pub primary_array_lba: Option<Lba>,
+
+    /// Start of the secondary partition entry array. If `None`, the
+    /// array is placed directly before the secondary header. The last
+    /// usable LBA is the block before the array.
+    pub secondary_array_lba: Option<Lba>,
+}
+
+impl Default for TestDiskSpec {
+    /// A 4MiB disk with 512-byte blocks, 128 partition entries, and no
+    /// partitions.
+    fn default() -> Self {
+        Self {
+            block_size: BlockSize::BS_512,
+            num_blocks: 8192,
+            disk_guid: TEST_DISK_GUID,
+            num_partition_entries: 128,
+            partitions: Vec::new(),
+            primary_array_lba: None,
+            secondary_array_lba: None,
+        }
+    }
+}
+
+/// Create a basic data partition entry from `start_lba` to `end_lba`,
+/// with a unique GUID derived from `index` and a name of the form
+/// `"test<index>"`.
+#[must_use]
+pub fn test_partition_entry(
+    index: u8,
+    start_lba: Lba,
+    end_lba: Lba,
+) -> GptPartitionEntry {
+    let mut guid_bytes =
+        guid!("37c75ffd-8932-467a-9c56-8cf1f0456b00").to_bytes();
+    guid_bytes[15] = index;
+
+    GptPartitionEntry {
+        partition_type_guid: GptPartitionType::BASIC_DATA,
+        unique_partition_guid: Guid::from_bytes(guid_bytes),
+        starting_lba: start_lba.into(),
+        ending_lba: end_lba.into(),
+        attributes: GptPartitionAttributes::default(),
+        // OK to unwrap: the name is short and ASCII.
+        name: format!("test{index}").parse().unwrap(),
+    }
+}
+
+/// Build a disk image as described by `spec`.
+///
+/// Unless [`TestDiskSpec::primary_array_lba`] or
+/// [`TestDiskSpec::secondary_array_lba`] is set, both headers and
+/// partition entry arrays are placed in their usual locations, with the
+/// arrays adjacent to their headers. All CRC32s are correct.
+///
+/// # Panics
+///
+/// Panics if the disk is too small to hold the GPT structures, if the
+/// arrays are placed such that there is no usable range between them,
+/// or if the disk's size in bytes does not fit in a `usize`.
+#[must_use]
+pub fn build_test_disk(spec: &TestDiskSpec) -> Vec<u8> {
+    let block_size = spec.block_size.to_usize().unwrap();
+    let len = usize::try_from(spec.num_blocks)
+        .ok()
+        .and_then(|n| n.checked_mul(block_size))
+        .expect("disk size does not fit in a usize");
+    let mut storage = vec![0; len];
+    let mut block_buf = vec![0; block_size];
+
+    let primary_layout = GptPartitionEntryArrayLayout {
+        start_lba: spec.primary_array_lba.unwrap_or(Lba(2)),
+        entry_size: GptPartitionEntrySize::default(),
+        num_entries: spec.num_partition_entries,
+    };
+    let array_blocks = primary_layout.num_blocks(spec.block_size).unwrap();
+    let first_usable_lba = primary_layout.start_lba.to_u64() + array_blocks;
+    let last_lba = spec.num_blocks.checked_sub(1).unwrap();
+    let secondary_array_lba = spec
+        .secondary_array_lba
+        .map(Lba::to_u64)
+        .or_else(|| last_lba.checked_sub(array_blocks))
+        .filter(|lba| *lba > first_usable_lba)
+        .expect("disk is too small for the GPT");
+    let secondary_layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(secondary_array_lba),
+        ..primary_layout
+    };
+
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), spec.block_size))
+            .unwrap();
+    let entries = || spec.partitions.iter().copied();
+    let crc = disk
+        .write_gpt_partition_entry_array_from_iter(
+            primary_layout,
+            entries(),
+            &mut block_buf,
+        )
+        .unwrap();
+    disk.write_gpt_partition_entry_array_from_iter(
+        secondary_layout,
+        entries(),
+        &mut block_buf,
+    )
+    .unwrap();
+
+    let mut primary = GptHeader {
+        my_lba: LbaLe::from_u64(1),
+        alternate_lba: LbaLe::from_u64(last_lba),
+        first_usable_lba: LbaLe::from_u64(first_usable_lba),
+        last_usable_lba: LbaLe::from_u64(secondary_array_lba - 1),
+        disk_guid: spec.disk_guid,
+        partition_entry_lba: primary_layout.start_lba.into(),
+        number_of_partition_entries: U32Le::from_u32(
+            spec.num_partition_entries,
+        ),
+        partition_entry_array_crc32: crc,
+        ..Default::default()
+    };
+    primary.update_header_crc32();
+    let mut secondary = GptHeader {
+        my_lba: LbaLe::from_u64(last_lba),
+        alternate_lba: LbaLe::from_u64(1),
+        partition_entry_lba: LbaLe::from_u64(secondary_array_lba),
+        ..primary
+    };
+    secondary.update_header_crc32();
+
+    disk.write_protective_mbr(&mut block_buf).unwrap();
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+        .unwrap();
+    disk.flush().unwrap();
+    drop(disk);
+
+    storage
+}
+
+/// Build the [default] disk with two partitions: `test0` from LBA 2048
+/// to 4095, and `test1` from LBA 4096 to 6143.
+///
+/// [default]: TestDiskSpec::default
+#[must_use]
+pub fn valid_test_disk() -> Vec<u8> {
+    build_test_disk(&TestDiskSpec {
+        partitions: vec![
+            test_partition_entry(0, Lba(2048), Lba(4095)),
+            test_partition_entry(1, Lba(4096), Lba(6143)),
+        ],
+        ..TestDiskSpec::default()
+    })
+}
+
+/// Structure to damage with [`corrupt_test_disk`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Corruption {
+    /// Clear the MBR signature, so the first block is not a valid MBR.
+    ProtectiveMbr,
+
+    /// Change a byte of the primary header's disk GUID, so that its
+    /// CRC32 no longer matches.
+    PrimaryHeader,
+
+    /// Change a byte of the secondary header's disk GUID, so that its
+    /// CRC32 no longer matches.
+    SecondaryHeader,
+
+    /// Change the first byte of the primary partition entry array, so
+    /// that it no longer matches the CRC32 in the primary header.
+    PrimaryPartitionEntryArray,
+
+    /// Change the first byte of the secondary partition entry array, so
+    /// that it no longer matches the CRC32 in the secondary header.
+    SecondaryPartitionEntryArray,
+}
+
+/// Damage one structure of a disk image, such as one created with
+/// [`build_test_disk`].
+///
+/// # Panics
+///
+/// Panics if the disk is too small, or for the partition entry array
+/// corruptions, if the corresponding header's `partition_entry_lba`
+/// is out of range.
+pub fn corrupt_test_disk(
+    disk: &mut [u8],
+    block_size: BlockSize,
+    corruption: Corruption,
+) {
+    let block_size = block_size.to_usize().unwrap();
+    let primary_offset = block_size;
+    let secondary_offset = (disk.len() / block_size - 1) * block_size;
+    let disk_guid_offset = mem::offset_of!(GptHeader, disk_guid);
+
+    let array_offset = |disk: &[u8], header_offset: usize| {
+        let header: &GptHeader = from_bytes(
+            &disk[header_offset..header_offset + mem::size_of::<GptHeader>()],
+        );
+        usize::try_from(header.partition_entry_lba.to_u64()).unwrap()
+            * block_size
+    };
+
+    let offset = match corruption {
+        Corruption::ProtectiveMbr => {
+            disk[510..512].fill(0);
+            return;
+        }
+        Corruption::PrimaryHeader => primary_offset + disk_guid_offset,
+        Corruption::SecondaryHeader => secondary_offset + disk_guid_offset,
+        Corruption::PrimaryPartitionEntryArray => {
+            array_offset(disk, primary_offset)
+        }
+        Corruption::SecondaryPartitionEntryArray => {
+            array_offset(disk, secondary_offset)
+        }
+    };
+    disk[offset] ^= 0xff;
+}
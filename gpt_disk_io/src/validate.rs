@@ -0,0 +1,648 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cross-structure validation of a GPT disk.
+//!
+//! The types in [`gpt_disk_types`] can only check themselves in
+//! isolation. The [`check_disk`] function reads the MBR, both GPT
+//! headers, and the partition entry arrays from a [`Disk`] and checks
+//! them against each other and against the size of the device.
+//!
+//! Problems are reported as [`Finding`]s, each of which has a
+//! [`Severity`]. Checking continues after a problem is found, so a
+//! single call reports everything that could be determined.
+//!
+//! # Example
+//!
+//! ```
+//! use gpt_disk_io::validate::{check_disk, Finding};
+//! use gpt_disk_io::{BlockIoAdapter, Disk, GptHeaderCopy};
+//! use gpt_disk_types::BlockSize;
+//!
+//! // A blank disk has no MBR or GPT.
+//! let mut storage = vec![0; 1024 * 1024];
+//! let block_io =
+//!     BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+//! let mut disk = Disk::new(block_io).unwrap();
+//!
+//! let mut block_buf = [0; 512];
+//! let mut findings = Vec::new();
+//! check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+//! assert_eq!(
+//!     findings,
+//!     [
+//!         Finding::InvalidMbrSignature,
+//!         Finding::CorruptHeader(GptHeaderCopy::Primary),
+//!         Finding::CorruptHeader(GptHeaderCopy::Secondary),
+//!     ]
+//! );
+//! ```
+
+use crate::disk::gpt_headers_agree;
+use crate::{BlockIo, Disk, DiskError, GptHeaderCopy};
+use core::fmt::{self, Display, Formatter};
+use gpt_disk_types::{GptHeader, Lba};
+
+#[cfg(feature = "alloc")]
+use {alloc::vec::Vec, gpt_disk_types::LbaRangeInclusive};
+
+#[cfg(not(feature = "alloc"))]
+use gpt_disk_types::GptPartitionEntryArrayLayout;
+
+/// How serious a [`Finding`] is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Severity {
+    /// The disk is usable, but something is unusual. For example, the
+    /// disk is larger than the GPT describes.
+    Warning,
+
+    /// The disk does not conform to the UEFI Specification.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A problem reported by [`check_disk`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Finding {
+    /// The MBR in the first block does not end with the `0x55, 0xaa`
+    /// boot signature.
+    InvalidMbrSignature,
+
+    /// The MBR does not contain a protective partition record (OS type
+    /// `0xee`).
+    MissingProtectiveMbr,
+
+    /// The header has an invalid signature or CRC32. No further checks
+    /// are done on this copy of the header.
+    CorruptHeader(GptHeaderCopy),
+
+    /// The header's `my_lba` does not match the block it was read from.
+    MyLbaMismatch(GptHeaderCopy),
+
+    /// The header's `alternate_lba` does not point at the other header.
+    /// For the primary header this is reported if `alternate_lba` is
+    /// past the end of the disk.
+    AlternateLbaMismatch(GptHeaderCopy),
+
+    /// The secondary header is not in the last block of the disk. This
+    /// typically happens when a disk image is copied to a larger disk.
+    ///
+    /// Use [`Disk::move_secondary_gpt_to_end`] to fix this.
+    SecondaryHeaderNotAtEnd,
+
+    /// The usable LBA range is inverted, or overlaps one of the
+    /// headers.
+    InvalidUsableRange(GptHeaderCopy),
+
+    /// The partition entry size is not a power of two of at least 128.
+    InvalidEntrySize(GptHeaderCopy),
+
+    /// The partition entry array extends past the end of the disk, or
+    /// overlaps the MBR, one of the headers, or the usable LBA range.
+    InvalidEntryArrayPlacement(GptHeaderCopy),
+
+    /// The partition entry array does not match the header's
+    /// `partition_entry_array_crc32`.
+    EntryArrayCrcMismatch(GptHeaderCopy),
+
+    /// The primary and secondary headers describe different disks, for
+    /// example with a different disk GUID or usable range.
+    HeadersDisagree,
+
+    /// The partition entry at `index` is in use, but its ending LBA is
+    /// less than its starting LBA.
+    InvalidPartitionRange {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+    },
+
+    /// The partition entry at `index` is in use, but is not fully
+    /// contained within the usable LBA range.
+    PartitionOutsideUsableRange {
+        /// Index of the entry in the partition entry array.
+        index: u32,
+    },
+
+    /// The partition entries at `first` and `second` are both in use,
+    /// and share at least one block.
+    PartitionsOverlap {
+        /// Index of the earlier entry in the partition entry array.
+        first: u32,
+        /// Index of the later entry in the partition entry array.
+        second: u32,
+    },
+}
+
+impl Finding {
+    /// Get the [`Severity`] of the finding.
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::SecondaryHeaderNotAtEnd => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMbrSignature => f.write_str("invalid MBR signature"),
+            Self::MissingProtectiveMbr => {
+                f.write_str("MBR has no protective partition record")
+            }
+            Self::CorruptHeader(copy) => {
+                write!(f, "{copy} header has an invalid signature or CRC32")
+            }
+            Self::MyLbaMismatch(copy) => {
+                write!(f, "{copy} header is not at its own my_lba")
+            }
+            Self::AlternateLbaMismatch(copy) => write!(
+                f,
+                "{copy} header alternate_lba does not point to the other header"
+            ),
+            Self::SecondaryHeaderNotAtEnd => {
+                f.write_str("secondary header is not at the end of the disk")
+            }
+            Self::InvalidUsableRange(copy) => {
+                write!(f, "{copy} header has an invalid usable LBA range")
+            }
+            Self::InvalidEntrySize(copy) => {
+                write!(f, "{copy} header has an invalid partition entry size")
+            }
+            Self::InvalidEntryArrayPlacement(copy) => write!(
+                f,
+                "{copy} partition entry array overlaps other data or is past the end of the disk"
+            ),
+            Self::EntryArrayCrcMismatch(copy) => {
+                write!(f, "{copy} partition entry array CRC32 mismatch")
+            }
+            Self::HeadersDisagree => {
+                f.write_str("primary and secondary headers do not match")
+            }
+            Self::InvalidPartitionRange { index } => {
+                write!(f, "partition {index} ends before it starts")
+            }
+            Self::PartitionOutsideUsableRange { index } => {
+                write!(f, "partition {index} is outside the usable LBA range")
+            }
+            Self::PartitionsOverlap { first, second } => {
+                write!(f, "partitions {first} and {second} overlap")
+            }
+        }
+    }
+}
+
+/// Results of [`Disk::verify_gpt`], grouped by the kind of problem.
+///
+/// Each field is derived from the [`Finding`]s reported by
+/// [`check_disk`]. All of the findings, including those that do not
+/// have a dedicated field (such as MBR problems), are also available in
+/// [`findings`](Self::findings).
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct GptVerifyReport {
+    /// Headers with an invalid signature or CRC32.
+    pub corrupt_headers: Vec<GptHeaderCopy>,
+
+    /// Whether the primary and secondary headers describe different
+    /// disks.
+    pub headers_disagree: bool,
+
+    /// Headers whose partition entry array does not match the
+    /// header's `partition_entry_array_crc32`.
+    pub entry_array_crc_mismatches: Vec<GptHeaderCopy>,
+
+    /// Indices of used partitions that are not fully contained within
+    /// the usable LBA range.
+    pub partitions_outside_usable_range: Vec<u32>,
+
+    /// Pairs of indices of used partitions that overlap. The lower
+    /// index is first.
+    pub overlapping_partitions: Vec<(u32, u32)>,
+
+    /// Whether the secondary header is not in the last block of the
+    /// disk.
+    pub secondary_header_not_at_end: bool,
+
+    /// All findings, in the order they were reported.
+    pub findings: Vec<Finding>,
+}
+
+#[cfg(feature = "alloc")]
+impl GptVerifyReport {
+    /// Returns true if no problems of any severity were found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns true if any finding has [`Severity::Error`].
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity() == Severity::Error)
+    }
+
+    fn add(&mut self, finding: Finding) {
+        match finding {
+            Finding::CorruptHeader(copy) => self.corrupt_headers.push(copy),
+            Finding::HeadersDisagree => self.headers_disagree = true,
+            Finding::EntryArrayCrcMismatch(copy) => {
+                self.entry_array_crc_mismatches.push(copy);
+            }
+            Finding::PartitionOutsideUsableRange { index } => {
+                self.partitions_outside_usable_range.push(index);
+            }
+            Finding::PartitionsOverlap { first, second } => {
+                self.overlapping_partitions.push((first, second));
+            }
+            Finding::SecondaryHeaderNotAtEnd => {
+                self.secondary_header_not_at_end = true;
+            }
+            _ => {}
+        }
+        self.findings.push(finding);
+    }
+}
+
+/// Read a header with [`Disk::read_gpt_header_verified`], reporting
+/// corruption as a finding rather than an error.
+fn read_header<Io, F>(
+    disk: &mut Disk<Io>,
+    lba: Lba,
+    copy: GptHeaderCopy,
+    block_buf: &mut [u8],
+    report: &mut F,
+) -> Result<Option<GptHeader>, DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    match disk.read_gpt_header_verified(lba, block_buf) {
+        Ok(header) => Ok(Some(header)),
+        Err(DiskError::CorruptGptHeader) => {
+            report(Finding::CorruptHeader(copy));
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Check a single header against the size of the disk, and check its
+/// partition entry array CRC. Returns true if the entry array is
+/// readable.
+fn check_header<Io, F>(
+    disk: &mut Disk<Io>,
+    header: &GptHeader,
+    copy: GptHeaderCopy,
+    secondary_lba: Lba,
+    block_buf: &mut [u8],
+    report: &mut F,
+) -> Result<bool, DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    let num_blocks = disk.num_blocks()?;
+    let first_usable = header.first_usable_lba.to_u64();
+    let last_usable = header.last_usable_lba.to_u64();
+
+    if first_usable > last_usable
+        || first_usable <= 1
+        || last_usable >= secondary_lba.to_u64()
+    {
+        report(Finding::InvalidUsableRange(copy));
+    }
+
+    let Ok(layout) = header.get_partition_entry_array_layout() else {
+        report(Finding::InvalidEntrySize(copy));
+        return Ok(false);
+    };
+
+    let array_blocks = layout
+        .num_blocks(disk.block_size())
+        .ok_or(DiskError::Overflow)?;
+    if array_blocks == 0 {
+        return Ok(true);
+    }
+    let array_start = layout.start_lba.to_u64();
+    let Some(array_end) = array_start.checked_add(array_blocks - 1) else {
+        report(Finding::InvalidEntryArrayPlacement(copy));
+        return Ok(false);
+    };
+    let overlaps = |lba: u64| (array_start..=array_end).contains(&lba);
+    if array_end >= num_blocks
+        || overlaps(0)
+        || overlaps(1)
+        || overlaps(secondary_lba.to_u64())
+        || (array_start <= last_usable && first_usable <= array_end)
+    {
+        report(Finding::InvalidEntryArrayPlacement(copy));
+        return Ok(false);
+    }
+
+    let crc32 =
+        disk.calculate_gpt_partition_entry_array_crc32(layout, block_buf)?;
+    if crc32 != header.partition_entry_array_crc32 {
+        report(Finding::EntryArrayCrcMismatch(copy));
+    }
+
+    Ok(true)
+}
+
+/// Check the GPT structures on `disk` for consistency with each other
+/// and with the size of the device.
+///
+/// Each problem found is passed to `report`. The following checks are
+/// performed:
+/// * The MBR has a valid signature and a protective partition record.
+/// * Both headers have a valid signature and CRC32. The secondary
+///   header is read from the primary header's `alternate_lba` if that
+///   is within the disk, otherwise from the last block.
+/// * Each header's `my_lba` and `alternate_lba` point at the right
+///   blocks.
+/// * Each header's usable range and partition entry array fit on the
+///   disk without overlapping each other or the headers.
+/// * Each partition entry array matches its header's CRC32.
+/// * The two headers agree with each other.
+/// * Every used partition is within the usable range, and no two used
+///   partitions overlap. The entries are read from the primary array if
+///   it is valid, otherwise from the secondary array.
+///
+/// Errors are only returned for IO failures and other conditions that
+/// prevent checking from continuing, such as a `block_buf` that is
+/// too small.
+///
+/// [Strict mode] is disabled while checking, so that problems it would
+/// reject are reported as findings instead.
+///
+/// `block_buf` is a mutable byte buffer with a length of at least one block.
+///
+/// [Strict mode]: Disk::set_strict
+pub fn check_disk<Io, F>(
+    disk: &mut Disk<Io>,
+    block_buf: &mut [u8],
+    report: F,
+) -> Result<(), DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    let strict = disk.is_strict();
+    disk.set_strict(false);
+    let result = check_disk_impl(disk, block_buf, report);
+    disk.set_strict(strict);
+    result
+}
+
+fn check_disk_impl<Io, F>(
+    disk: &mut Disk<Io>,
+    block_buf: &mut [u8],
+    mut report: F,
+) -> Result<(), DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    let num_blocks = disk.num_blocks()?;
+    let last_lba = Lba(num_blocks.checked_sub(1).ok_or(DiskError::Overflow)?);
+
+    let mbr = disk.read_mbr(block_buf)?;
+    if mbr.signature != [0x55, 0xaa] {
+        report(Finding::InvalidMbrSignature);
+    } else if !mbr.partitions.iter().any(|p| p.os_indicator == 0xee) {
+        report(Finding::MissingProtectiveMbr);
+    }
+
+    let primary = read_header(
+        disk,
+        Lba(1),
+        GptHeaderCopy::Primary,
+        block_buf,
+        &mut report,
+    )?;
+
+    // Find the secondary header.
+    let mut secondary_lba = last_lba;
+    if let Some(primary) = &primary {
+        if primary.my_lba.to_u64() != 1 {
+            report(Finding::MyLbaMismatch(GptHeaderCopy::Primary));
+        }
+        let alternate_lba = primary.alternate_lba.to_u64();
+        if alternate_lba <= 1 || alternate_lba > last_lba.to_u64() {
+            report(Finding::AlternateLbaMismatch(GptHeaderCopy::Primary));
+        } else if alternate_lba < last_lba.to_u64() {
+            report(Finding::SecondaryHeaderNotAtEnd);
+            secondary_lba = Lba(alternate_lba);
+        }
+    }
+
+    let secondary = read_header(
+        disk,
+        secondary_lba,
+        GptHeaderCopy::Secondary,
+        block_buf,
+        &mut report,
+    )?;
+    if let Some(secondary) = &secondary {
+        if secondary.my_lba.to_u64() != secondary_lba.to_u64() {
+            report(Finding::MyLbaMismatch(GptHeaderCopy::Secondary));
+        }
+        if secondary.alternate_lba.to_u64() != 1 {
+            report(Finding::AlternateLbaMismatch(GptHeaderCopy::Secondary));
+        }
+    }
+
+    let mut entries_header = None;
+    for (header, copy) in [
+        (primary, GptHeaderCopy::Primary),
+        (secondary, GptHeaderCopy::Secondary),
+    ] {
+        if let Some(header) = header {
+            if check_header(
+                disk,
+                &header,
+                copy,
+                secondary_lba,
+                block_buf,
+                &mut report,
+            )? && entries_header.is_none()
+            {
+                entries_header = Some(header);
+            }
+        }
+    }
+
+    if let (Some(primary), Some(secondary)) = (&primary, &secondary) {
+        if !gpt_headers_agree(primary, secondary) {
+            report(Finding::HeadersDisagree);
+        }
+    }
+
+    if let Some(header) = entries_header {
+        check_partitions(disk, &header, block_buf, &mut report)?;
+    }
+
+    Ok(())
+}
+
+/// Check that every used partition in the array described by `header`
+/// is within the usable range, and that no two used partitions overlap.
+/// The header's partition entry array layout must be valid.
+///
+/// Range problems are reported in entry order, followed by overlapping
+/// pairs ordered by `first` and then `second`.
+fn check_partitions<Io, F>(
+    disk: &mut Disk<Io>,
+    header: &GptHeader,
+    block_buf: &mut [u8],
+    report: &mut F,
+) -> Result<(), DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    // OK to unwrap: `check_header` only returns true if the layout is
+    // valid.
+    let layout = header.get_partition_entry_array_layout().unwrap();
+    let first_usable = header.first_usable_lba.to_u64();
+    let last_usable = header.last_usable_lba.to_u64();
+
+    #[cfg(feature = "alloc")]
+    let mut ranges = Vec::new();
+    let iter = disk.gpt_partition_entry_array_iter(layout, block_buf)?;
+    for (index, entry) in (0u32..).zip(iter) {
+        let entry = entry?;
+        if !entry.is_used() {
+            continue;
+        }
+        let Some(range) = entry.lba_range() else {
+            report(Finding::InvalidPartitionRange { index });
+            continue;
+        };
+        if range.start().to_u64() < first_usable
+            || range.end().to_u64() > last_usable
+        {
+            report(Finding::PartitionOutsideUsableRange { index });
+        }
+        #[cfg(feature = "alloc")]
+        ranges.push((range, index));
+    }
+
+    #[cfg(feature = "alloc")]
+    report_overlaps(ranges, report);
+    #[cfg(not(feature = "alloc"))]
+    report_overlaps_no_alloc(disk, layout, block_buf, report)?;
+
+    Ok(())
+}
+
+/// Report every overlapping pair of `ranges`, each of which is paired
+/// with its entry index.
+///
+/// The ranges are sorted by starting LBA and swept once, keeping track
+/// of the ranges that are still open at each start.
+#[cfg(feature = "alloc")]
+fn report_overlaps<F>(mut ranges: Vec<(LbaRangeInclusive, u32)>, report: &mut F)
+where
+    F: FnMut(Finding),
+{
+    ranges.sort_unstable_by_key(|(range, index)| (range.start().0, *index));
+
+    let mut open: Vec<(u64, u32)> = Vec::new();
+    let mut pairs = Vec::new();
+    for (range, index) in ranges {
+        open.retain(|(end, _)| *end >= range.start().0);
+        for (_, other) in &open {
+            pairs.push(((*other).min(index), (*other).max(index)));
+        }
+        open.push((range.end().0, index));
+    }
+
+    pairs.sort_unstable();
+    for (first, second) in pairs {
+        report(Finding::PartitionsOverlap { first, second });
+    }
+}
+
+/// Report every overlapping pair of used entries in the array described
+/// by `layout`.
+///
+/// Without an allocator there is nowhere to store the ranges, so each
+/// used entry is compared against every later entry by rereading the
+/// array.
+#[cfg(not(feature = "alloc"))]
+fn report_overlaps_no_alloc<Io, F>(
+    disk: &mut Disk<Io>,
+    layout: GptPartitionEntryArrayLayout,
+    block_buf: &mut [u8],
+    report: &mut F,
+) -> Result<(), DiskError<Io::Error>>
+where
+    Io: BlockIo,
+    F: FnMut(Finding),
+{
+    for first in 0..layout.num_entries {
+        let entry = disk.read_gpt_partition_entry(layout, first, block_buf)?;
+        let Some(range) = entry.lba_range().filter(|_| entry.is_used()) else {
+            continue;
+        };
+        for second in first + 1..layout.num_entries {
+            let other =
+                disk.read_gpt_partition_entry(layout, second, block_buf)?;
+            if other.is_used()
+                && other.lba_range().is_some_and(|r| r.overlaps(range))
+            {
+                report(Finding::PartitionsOverlap { first, second });
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<Io: BlockIo> Disk<Io> {
+    /// Check the GPT structures on the disk for consistency and return
+    /// all problems found. See [`check_disk`] for details of the
+    /// checks performed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    #[cfg(feature = "alloc")]
+    pub fn verify(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<Vec<Finding>, DiskError<Io::Error>> {
+        let mut findings = Vec::new();
+        check_disk(self, block_buf, |finding| findings.push(finding))?;
+        Ok(findings)
+    }
+
+    /// Check the GPT structures on the disk for consistency and return
+    /// a [`GptVerifyReport`] that groups the problems found by kind.
+    /// See [`check_disk`] for details of the checks performed.
+    ///
+    /// `block_buf` is a mutable byte buffer with a length of at least one block.
+    #[cfg(feature = "alloc")]
+    pub fn verify_gpt(
+        &mut self,
+        block_buf: &mut [u8],
+    ) -> Result<GptVerifyReport, DiskError<Io::Error>> {
+        let mut report = GptVerifyReport::default();
+        check_disk(self, block_buf, |finding| report.add(finding))?;
+        Ok(report)
+    }
+}
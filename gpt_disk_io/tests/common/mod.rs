@@ -13,7 +13,7 @@ use gpt_disk_types::{
 };
 use std::collections::hash_map::DefaultHasher;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::eq_op)]
 pub fn check_derives<T>()
 where
     T: Clone
@@ -42,7 +42,7 @@ where
 
     // Debug/Display
     assert!(!format!("{a:?}").is_empty());
-    format!("{a}");
+    let _ = format!("{a}");
 
     // Hash
     let mut hasher = DefaultHasher::new();
@@ -88,3 +88,64 @@ pub fn create_partition_entry() -> GptPartitionEntry {
         name: "hello world!".parse().unwrap(),
     }
 }
+
+struct SparseChunk {
+    offset: usize,
+    data: [u8; 16],
+}
+
+impl SparseChunk {
+    const fn new(offset: usize, data: [u8; 16]) -> Self {
+        Self { offset, data }
+    }
+}
+
+#[rustfmt::skip]
+const SPARSE_DISK: &[SparseChunk] = &[
+// Test data generated as follows:
+//
+// truncate --size 4MiB disk.bin
+// sgdisk disk.bin \
+//   --disk-guid=57a7feb6-8cd5-4922-b7bd-c78b0914e870 \
+//   --new=1:2048:4096 \
+//   --change-name='1:hello world!' \
+//   --partition-guid=1:37c75ffd-8932-467a-9c56-8cf1f0456b12 \
+//   --typecode=1:ccf0994f-f7e0-4e26-a011-843e38aa2eac
+// hexdump -ve '"SparseChunk::new(0x%_ax, [" 16/1 "%u," "]),\n"' disk.bin \
+//   | grep -v '\[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,\]'
+SparseChunk::new(0x1c0, [2,0,238,130,2,0,1,0,0,0,255,31,0,0,0,0,]),
+SparseChunk::new(0x1f0, [0,0,0,0,0,0,0,0,0,0,0,0,0,0,85,170,]),
+SparseChunk::new(0x200, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
+SparseChunk::new(0x210, [67,120,135,164,0,0,0,0,1,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x220, [255,31,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x230, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
+SparseChunk::new(0x240, [183,189,199,139,9,20,232,112,2,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x250, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
+SparseChunk::new(0x400, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
+SparseChunk::new(0x410, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
+SparseChunk::new(0x420, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
+SparseChunk::new(0x430, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
+SparseChunk::new(0x440, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
+SparseChunk::new(0x3fbe00, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
+SparseChunk::new(0x3fbe10, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
+SparseChunk::new(0x3fbe20, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
+SparseChunk::new(0x3fbe30, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
+SparseChunk::new(0x3fbe40, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
+SparseChunk::new(0x3ffe00, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
+SparseChunk::new(0x3ffe10, [19,76,235,219,0,0,0,0,255,31,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe20, [1,0,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe30, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
+SparseChunk::new(0x3ffe40, [183,189,199,139,9,20,232,112,223,31,0,0,0,0,0,0,]),
+SparseChunk::new(0x3ffe50, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
+];
+
+/// Load a 4MiB test disk with a single partition.
+#[allow(dead_code)]
+pub fn load_test_disk() -> Vec<u8> {
+    let mut disk = vec![0; 4 * 1024 * 1024];
+    for chunk in SPARSE_DISK {
+        let end = chunk.offset + chunk.data.len();
+        disk[chunk.offset..end].copy_from_slice(&chunk.data);
+    }
+    disk
+}
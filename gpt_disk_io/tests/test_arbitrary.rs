@@ -0,0 +1,46 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use arbitrary::{Arbitrary, Unstructured};
+use core::mem;
+use gpt_disk_types::{
+    GptHeader, GptPartitionAttributes, GptPartitionEntry, Guid,
+    MasterBootRecord,
+};
+
+/// Check that `T` consumes exactly its in-memory size in bytes.
+fn assert_size_hint<'a, T: Arbitrary<'a>>() {
+    let size = mem::size_of::<T>();
+    assert_eq!(T::size_hint(0), (size, Some(size)));
+}
+
+#[test]
+fn test_size_hint() {
+    assert_size_hint::<Guid>();
+    assert_size_hint::<GptHeader>();
+    assert_size_hint::<GptPartitionAttributes>();
+    assert_size_hint::<GptPartitionEntry>();
+    assert_size_hint::<MasterBootRecord>();
+}
+
+#[test]
+fn test_arbitrary() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut u = Unstructured::new(&data);
+
+    let attrs = GptPartitionAttributes::arbitrary(&mut u).unwrap();
+    assert_eq!(attrs.0 .0, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+    let _header = GptHeader::arbitrary(&mut u).unwrap();
+    let _entry = GptPartitionEntry::arbitrary(&mut u).unwrap();
+    assert_eq!(u.len(), 256 - 8 - 92 - 128);
+
+    // Running out of input fills the rest with zeroes.
+    let mut u = Unstructured::new(&[]);
+    assert_eq!(Guid::arbitrary(&mut u).unwrap(), Guid::ZERO);
+}
@@ -9,11 +9,31 @@
 mod common;
 
 use common::check_derives;
-use gpt_disk_types::{BlockSize, Lba, LbaLe, LbaRangeInclusive, U64Le};
+use core::num::NonZeroU64;
+use gpt_disk_types::{
+    BlockSize, Lba, LbaLe, LbaRangeInclusive, PartitionAlignment, SizeDisplay,
+    U64Le,
+};
 
 #[test]
 fn test_lba() {
     check_derives::<Lba>();
+
+    assert_eq!(Lba::from(123), Lba(123));
+    assert_eq!(u64::from(Lba(123)), 123);
+
+    assert_eq!(Lba(123), 123);
+    assert_eq!(123, Lba(123));
+    assert!(Lba(123) < 124);
+    assert!(Lba(123) > 122);
+    assert!(122 < Lba(123));
+    assert_eq!(Lba(123), LbaLe::from_u64(123));
+    assert!(Lba(123) < LbaLe::from_u64(124));
+
+    assert_eq!(Lba(2).checked_add(32), Some(Lba(34)));
+    assert_eq!(Lba(u64::MAX).checked_add(1), None);
+    assert_eq!(Lba(34).checked_sub(32), Some(Lba(2)));
+    assert_eq!(Lba(0).checked_sub(1), None);
 }
 
 #[test]
@@ -21,6 +41,23 @@ fn test_lba_le() {
     check_derives::<LbaLe>();
 
     assert_eq!(LbaLe::from(Lba(123)), LbaLe(U64Le::from_u64(123)));
+    assert_eq!(LbaLe::from(123), LbaLe(U64Le::from_u64(123)));
+    assert_eq!(u64::from(LbaLe::from_u64(123)), 123);
+
+    // Comparisons use the integer value, not the byte order.
+    let lba = LbaLe::from_u64(0x100);
+    assert_eq!(lba, 0x100);
+    assert_eq!(0x100, lba);
+    assert!(lba > 0xff);
+    assert!(lba < 0x101);
+    assert!(0xff < lba);
+    assert_eq!(lba, Lba(0x100));
+    assert!(lba > Lba(0xff));
+
+    assert_eq!(lba.checked_add(1), Some(LbaLe::from_u64(0x101)));
+    assert_eq!(LbaLe::from_u64(u64::MAX).checked_add(1), None);
+    assert_eq!(lba.checked_sub(1), Some(LbaLe::from_u64(0xff)));
+    assert_eq!(LbaLe::from_u64(0).checked_sub(1), None);
 }
 
 #[test]
@@ -75,6 +112,14 @@ fn test_block_size() {
     assert!(BlockSize::new(0).is_none());
     assert!(BlockSize::new(511).is_none());
 
+    // Sizes that are not a power of two are allowed.
+    for size in [520, 528, 4160] {
+        let bs = BlockSize::new(size).unwrap();
+        assert_eq!(bs.to_u32(), size);
+        assert!(bs.is_multiple_of_block_size(size * 3));
+        assert!(!bs.is_multiple_of_block_size(512 * 3));
+    }
+
     assert_eq!(BlockSize::from_usize(512).unwrap().to_u64(), 512);
     assert!(BlockSize::from_usize(0).is_none());
 
@@ -108,3 +153,132 @@ fn test_block_size_assert_valid_block_buffer() {
 fn test_block_size_assert_valid_block_buffer_painc() {
     BlockSize::BS_512.assert_valid_block_buffer(&[0; 513]);
 }
+
+#[test]
+fn test_lba_range_overlap() {
+    let r = |start, end| LbaRangeInclusive::new(Lba(start), Lba(end)).unwrap();
+
+    let range = r(10, 20);
+    assert!(!range.contains(Lba(9)));
+    assert!(range.contains(Lba(10)));
+    assert!(range.contains(Lba(20)));
+    assert!(!range.contains(Lba(21)));
+
+    assert!(range.contains_range(range));
+    assert!(range.contains_range(r(15, 15)));
+    assert!(!range.contains_range(r(9, 15)));
+    assert!(!range.contains_range(r(15, 21)));
+
+    assert!(range.overlaps(r(0, 10)));
+    assert!(range.overlaps(r(20, 30)));
+    assert!(range.overlaps(r(12, 18)));
+    assert!(range.overlaps(r(0, 30)));
+    assert!(!range.overlaps(r(0, 9)));
+    assert!(!range.overlaps(r(21, 30)));
+
+    assert_eq!(range.intersection(r(0, 10)), Some(r(10, 10)));
+    assert_eq!(range.intersection(r(15, 30)), Some(r(15, 20)));
+    assert_eq!(range.intersection(r(0, 30)), Some(range));
+    assert_eq!(range.intersection(r(21, 30)), None);
+    assert_eq!(
+        r(0, u64::MAX).intersection(r(u64::MAX, u64::MAX)),
+        Some(r(u64::MAX, u64::MAX))
+    );
+}
+
+#[test]
+fn test_lba_align() {
+    let a = NonZeroU64::new(8).unwrap();
+
+    assert_eq!(Lba(0).align_up(a), Some(Lba(0)));
+    assert_eq!(Lba(1).align_up(a), Some(Lba(8)));
+    assert_eq!(Lba(8).align_up(a), Some(Lba(8)));
+    assert_eq!(Lba(u64::MAX).align_up(a), None);
+    assert_eq!(Lba(u64::MAX - 7).align_up(a), Some(Lba(u64::MAX - 7)));
+
+    assert_eq!(Lba(7).align_down(a), Lba(0));
+    assert_eq!(Lba(8).align_down(a), Lba(8));
+    assert_eq!(Lba(15).align_down(a), Lba(8));
+
+    assert!(Lba(0).is_aligned(a));
+    assert!(Lba(16).is_aligned(a));
+    assert!(!Lba(17).is_aligned(a));
+
+    assert_eq!(Lba(17).align_up(NonZeroU64::MIN), Some(Lba(17)));
+}
+
+#[test]
+fn test_partition_alignment() {
+    check_derives::<PartitionAlignment>();
+
+    let default = PartitionAlignment::default();
+    assert_eq!(default, PartitionAlignment::DEFAULT);
+    assert_eq!(default.to_bytes(), 1024 * 1024);
+    assert_eq!(default.to_string(), "1048576 bytes");
+    assert!(PartitionAlignment::from_bytes(0).is_none());
+
+    let bs512 = BlockSize::BS_512;
+    let bs4096 = BlockSize::BS_4096;
+    assert_eq!(default.num_blocks(bs512).get(), 2048);
+    assert_eq!(default.num_blocks(bs4096).get(), 256);
+    // Rounded down for block sizes that don't divide the alignment.
+    assert_eq!(default.num_blocks(BlockSize::new(520).unwrap()).get(), 2016);
+    // At least one block.
+    let small = PartitionAlignment::from_bytes(512).unwrap();
+    assert_eq!(small.num_blocks(bs4096).get(), 1);
+
+    // Physical block size.
+    let large_physical = BlockSize::new(4 * 1024 * 1024).unwrap();
+    assert_eq!(default.with_physical_block_size(bs4096), default);
+    assert_eq!(
+        default.with_physical_block_size(large_physical).to_bytes(),
+        4 * 1024 * 1024
+    );
+    assert_eq!(
+        small
+            .with_physical_block_size(bs4096)
+            .num_blocks(bs512)
+            .get(),
+        8
+    );
+
+    assert_eq!(default.align_up(Lba(34), bs512), Some(Lba(2048)));
+    assert_eq!(default.align_up(Lba(2049), bs512), Some(Lba(4096)));
+    assert_eq!(default.align_up(Lba(6), bs4096), Some(Lba(256)));
+    assert_eq!(default.align_up(Lba(u64::MAX), bs512), None);
+    assert_eq!(default.align_down(Lba(4095), bs512), Lba(2048));
+    assert!(default.is_aligned(Lba(4096), bs512));
+    assert!(!default.is_aligned(Lba(4096 + 256), bs512));
+    assert!(default.is_aligned(Lba(4096 + 256), bs4096));
+}
+
+#[test]
+fn test_size_display() {
+    let bytes = |n| SizeDisplay::from_bytes(n).to_string();
+    assert_eq!(bytes(0), "0 bytes");
+    assert_eq!(bytes(1023), "1023 bytes");
+    assert_eq!(bytes(1024), "1.0 KiB");
+    // Rounded to the nearest tenth.
+    assert_eq!(bytes(1024 + 51), "1.0 KiB");
+    assert_eq!(bytes(1024 + 52), "1.1 KiB");
+    assert_eq!(bytes(1024 * 1024 - 1), "1024.0 KiB");
+    assert_eq!(bytes(512 * 1024 * 1024), "512.0 MiB");
+    assert_eq!(bytes(4_000_787_030_016), "3.6 TiB");
+    assert_eq!(bytes(u64::MAX), "16.0 EiB");
+
+    let blocks = |n, bs| SizeDisplay::from_blocks(n, bs).to_string();
+    assert_eq!(blocks(1, BlockSize::BS_512), "512 bytes");
+    assert_eq!(blocks(2048, BlockSize::BS_512), "1.0 MiB");
+    assert_eq!(blocks(2048, BlockSize::BS_4096), "8.0 MiB");
+    // Sizes that do not fit in a u64 are still displayed.
+    assert_eq!(blocks(u64::MAX, BlockSize::BS_4096), "65536.0 EiB");
+    assert_eq!(
+        SizeDisplay::from_blocks(u64::MAX, BlockSize::BS_512).to_bytes(),
+        u128::from(u64::MAX) * 512
+    );
+
+    // Padding applies to the whole string.
+    let size = SizeDisplay::from_bytes(1024);
+    assert_eq!(format!("[{size:>9}]"), "[  1.0 KiB]");
+    assert_eq!(format!("[{size:<9}]"), "[1.0 KiB  ]");
+}
@@ -9,9 +9,27 @@
 mod common;
 
 use common::check_derives;
-use gpt_disk_io::{BlockIo, BlockIoAdapter, SliceBlockIoError};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, ByteBlockIo, ByteBlockIoError, ByteIo,
+    RetryBlockIo, SliceBlockIoError,
+};
 use gpt_disk_types::{BlockSize, Lba};
 
+#[cfg(feature = "alloc")]
+use {
+    common::load_test_disk,
+    core::num::NonZeroU64,
+    gpt_disk_io::{
+        CreatePartitionOptions, Disk, DryRunBlockIo, FaultInjectingBlockIo,
+        FaultInjectionError, FaultSchedule, FaultyOperations, JournalError,
+        JournalRecovery, JournaledBlockIo, PartitionExtent, RecordedWrite,
+        VecBlockIo,
+    },
+    gpt_disk_types::{crc, guid, Crc32, GptPartitionType, U32Le},
+    std::borrow::Cow,
+    std::sync::Arc,
+};
+
 #[cfg(feature = "std")]
 use {
     gpt_disk_io::ReadWriteSeek,
@@ -29,6 +47,45 @@ fn test_block_io_adapter() {
     assert_eq!(data, 123);
 }
 
+#[test]
+fn test_block_io_adapter_physical_block_size() {
+    let mut bio = BlockIoAdapter::new(&[0u8; 512][..], BlockSize::BS_512);
+    assert_eq!(BlockIo::physical_block_size(&bio), BlockSize::BS_512);
+    bio.set_physical_block_size(Some(BlockSize::BS_4096));
+    assert_eq!(BlockIo::physical_block_size(&bio), BlockSize::BS_4096);
+    assert_eq!(BlockIo::block_size(&bio), BlockSize::BS_512);
+    bio.set_physical_block_size(None);
+    assert_eq!(bio.physical_block_size(), BlockSize::BS_512);
+}
+
+#[test]
+fn test_block_io_adapter_read_only() {
+    let mut data = [0u8; 512];
+
+    // Immutable slices are always read-only.
+    let mut bio = BlockIoAdapter::new(&data[..], BlockSize::BS_512);
+    assert!(bio.is_read_only());
+    bio.set_read_only(false);
+    assert!(bio.is_read_only());
+
+    let mut bio = BlockIoAdapter::new(&mut data[..], BlockSize::BS_512);
+    assert!(!bio.is_read_only());
+    bio.set_read_only(true);
+    assert!(bio.is_read_only());
+    // Wrappers forward the hint.
+    assert!(RetryBlockIo::new(&mut bio, 1).is_read_only());
+    // Writes directly through the adapter are still attempted.
+    bio.write_blocks(Lba(0), &[1; 512]).unwrap();
+    assert_eq!(data, [1; 512]);
+}
+
+#[test]
+#[should_panic(expected = "multiple of the block size")]
+fn test_block_io_adapter_invalid_physical_block_size() {
+    let mut bio = BlockIoAdapter::new(&[0u8; 4096][..], BlockSize::BS_4096);
+    bio.set_physical_block_size(Some(BlockSize::BS_512));
+}
+
 #[test]
 fn test_slice_block_io_error() {
     check_derives::<SliceBlockIoError>();
@@ -186,6 +243,138 @@ fn test_block_io_vec() {
     check_read_and_write(storage, |bio| bio.storage().to_vec());
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn test_block_io_box() {
+    let storage: Box<[u8]> = get_read_data().into_boxed_slice();
+    check_read_and_write(storage, |bio| bio.storage().to_vec());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_block_io_arc() {
+    let storage: Arc<[u8]> = Arc::from(get_read_data());
+    let storage = check_read(storage);
+
+    let mut bio = BlockIoAdapter::new(storage, BlockSize::BS_512);
+    assert!(bio.is_read_only());
+    assert_eq!(
+        bio.write_blocks(Lba(0), &[0; 512]),
+        Err(SliceBlockIoError::ReadOnly)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_block_io_cow() {
+    let data = get_read_data();
+
+    // Borrowed data is copied on the first write.
+    let storage = check_read(Cow::Borrowed(data.as_slice()));
+    assert!(matches!(storage, Cow::Borrowed(_)));
+    check_write(storage, |bio| {
+        assert!(matches!(bio.storage(), Cow::Owned(_)));
+        bio.storage().to_vec()
+    });
+    assert_eq!(data, get_read_data());
+
+    let storage: Cow<[u8]> = Cow::Owned(get_read_data());
+    check_read_and_write(storage, |bio| bio.storage().to_vec());
+}
+
+#[test]
+fn test_block_io_slice_large_lba() {
+    let data = get_read_data();
+    let mut bio = BlockIoAdapter::new(data.as_slice(), BlockSize::BS_512);
+    let mut buf = vec![0; 512];
+
+    // Byte offset does not fit in a `u64`.
+    assert_eq!(
+        bio.read_blocks(Lba(u64::MAX), &mut buf),
+        Err(SliceBlockIoError::Overflow)
+    );
+
+    // Byte offset fits in a `u64`, but is past 4GiB (and therefore
+    // might not fit in a `usize`).
+    let lba = Lba((8 << 30) / 512);
+    assert_eq!(
+        bio.read_blocks(lba, &mut buf),
+        Err(SliceBlockIoError::OutOfBounds {
+            start_lba: lba,
+            length_in_bytes: 512
+        })
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vec_block_io() {
+    let mut bio = VecBlockIo::new(BlockSize::BS_512, 4);
+    assert_eq!(bio.num_blocks().unwrap(), 4);
+    assert_eq!(bio.block_size(), BlockSize::BS_512);
+    assert!(bio.storage().is_empty());
+
+    // Unwritten blocks read as zero.
+    let mut buf = vec![0xff; 1024];
+    bio.read_blocks(Lba(2), &mut buf).unwrap();
+    assert_eq!(buf, vec![0; 1024]);
+
+    // Writing grows the storage up to the end of the write.
+    bio.write_blocks(Lba(1), &[1; 512]).unwrap();
+    assert_eq!(bio.storage().len(), 1024);
+    assert_eq!(bio.storage()[..512], [0; 512]);
+    assert_eq!(bio.storage()[512..], [1; 512]);
+
+    // A read that straddles the end of the storage.
+    bio.read_blocks(Lba(1), &mut buf).unwrap();
+    assert_eq!(buf[..512], [1; 512]);
+    assert_eq!(buf[512..], [0; 512]);
+
+    // Writing inside the existing storage does not grow it.
+    bio.write_blocks(Lba(0), &[2; 512]).unwrap();
+    assert_eq!(bio.storage().len(), 1024);
+
+    // Writing the last block is allowed, but not past it.
+    bio.write_blocks(Lba(3), &[3; 512]).unwrap();
+    assert_eq!(bio.storage().len(), 2048);
+    assert_eq!(
+        bio.write_blocks(Lba(4), &[4; 512]),
+        Err(SliceBlockIoError::OutOfBounds {
+            start_lba: Lba(4),
+            length_in_bytes: 512
+        })
+    );
+    assert_eq!(
+        bio.read_blocks(Lba(3), &mut buf),
+        Err(SliceBlockIoError::OutOfBounds {
+            start_lba: Lba(3),
+            length_in_bytes: 1024
+        })
+    );
+    bio.flush().unwrap();
+
+    let storage = bio.take_storage();
+    assert_eq!(storage.len(), 2048);
+    assert_eq!(storage[..512], [2; 512]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vec_block_io_from_vec() {
+    let mut bio = VecBlockIo::from_vec(get_read_data(), BlockSize::BS_512, 4);
+    assert_eq!(bio.num_blocks().unwrap(), 4);
+
+    let mut buf = vec![0; 512];
+    bio.read_blocks(Lba(1), &mut buf).unwrap();
+    assert_eq!(buf[0], 3);
+    assert_eq!(buf[511], 4);
+
+    // The fourth block is not backed by the initial data.
+    bio.read_blocks(Lba(3), &mut buf).unwrap();
+    assert_eq!(buf, vec![0; 512]);
+    assert_eq!(bio.storage().len(), 512 * 3);
+}
+
 #[cfg(feature = "std")]
 #[test]
 fn test_block_io_file() {
@@ -194,6 +383,7 @@ fn test_block_io_file() {
         .read(true)
         .write(true)
         .create(true)
+        .truncate(true)
         .open(path)
         .unwrap();
     file.write_all(&get_read_data()).unwrap();
@@ -211,6 +401,7 @@ fn test_block_io_dyn_readwriteseek() {
         .read(true)
         .write(true)
         .create(true)
+        .truncate(true)
         .open(path)
         .unwrap();
     file.write_all(&get_read_data()).unwrap();
@@ -220,3 +411,650 @@ fn test_block_io_dyn_readwriteseek() {
 
     fs::remove_file(path).unwrap();
 }
+
+#[cfg(feature = "std")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_block_io_file_larger_than_4gib() {
+    let path = "/tmp/test_block_io_std_3.bin";
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .unwrap();
+    // Sparse file, so this doesn't actually use 8GiB of disk space.
+    file.set_len(8 << 30).unwrap();
+
+    let mut bio = BlockIoAdapter::new(file, BlockSize::BS_512);
+    let num_blocks = bio.num_blocks().unwrap();
+    assert_eq!(num_blocks, (8 << 30) / 512);
+
+    // Write and read back the last block.
+    let last_lba = Lba(num_blocks - 1);
+    bio.write_blocks(last_lba, &[0xab; 512]).unwrap();
+    bio.flush().unwrap();
+    let mut buf = vec![0; 512];
+    bio.read_blocks(last_lba, &mut buf).unwrap();
+    assert_eq!(buf, [0xab; 512]);
+
+    // Byte offset overflow is an error rather than a panic.
+    assert!(bio.read_blocks(Lba(u64::MAX), &mut buf).is_err());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_block_io_file_read_only() {
+    let path = "/tmp/test_block_io_std_5.bin";
+    fs::write(path, get_read_data()).unwrap();
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .unwrap();
+    assert!(!BlockIoAdapter::new(&file, BlockSize::BS_512).is_read_only());
+    assert!(!BlockIoAdapter::new(&mut file, BlockSize::BS_512).is_read_only());
+
+    let mut file = OpenOptions::new().read(true).open(path).unwrap();
+    assert!(BlockIoAdapter::new(&file, BlockSize::BS_512).is_read_only());
+    assert!(BlockIoAdapter::new(&mut file, BlockSize::BS_512).is_read_only());
+    assert!(
+        BlockIoAdapter::new(&mut &mut file, BlockSize::BS_512).is_read_only()
+    );
+
+    let storage: &mut dyn ReadWriteSeek = &mut file;
+    assert!(BlockIoAdapter::new(storage, BlockSize::BS_512).is_read_only());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_reread_partition_table_not_block_device() {
+    let path = "/tmp/test_block_io_std_4.bin";
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .unwrap();
+
+    // ENOTTY: the ioctl isn't supported on regular files.
+    let err = gpt_disk_io::reread_partition_table(&file).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(25));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_dry_run_block_io() {
+    let mut bio = DryRunBlockIo::new(VecBlockIo::new(BlockSize::BS_512, 4));
+    assert_eq!(bio.num_blocks().unwrap(), 4);
+
+    let crc32 = |data: &[u8]| {
+        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+        Crc32(U32Le::from_u32(crc.checksum(data)))
+    };
+
+    let mut data = vec![0; 1024];
+    data[0] = 1;
+    data[512] = 2;
+    bio.write_blocks(Lba(1), &data).unwrap();
+    bio.write_blocks(Lba(2), &[3; 512]).unwrap();
+    assert_eq!(
+        bio.writes(),
+        [
+            RecordedWrite {
+                start_lba: Lba(1),
+                length_in_bytes: 1024,
+                crc32: crc32(&data),
+            },
+            RecordedWrite {
+                start_lba: Lba(2),
+                length_in_bytes: 512,
+                crc32: crc32(&[3; 512]),
+            },
+        ]
+    );
+
+    // Reads see the pending writes, but the underlying storage is
+    // unchanged.
+    let mut buf = vec![0xff; 512 * 4];
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    assert_eq!(buf[..512], [0; 512]);
+    assert_eq!(buf[512], 1);
+    assert_eq!(buf[1024..1536], [3; 512]);
+    assert_eq!(buf[1536..], [0; 512]);
+    assert!(bio.inner().storage().is_empty());
+
+    // Committing applies the writes in order.
+    let inner = bio.commit().unwrap();
+    assert_eq!(inner.storage()[512], 1);
+    assert_eq!(inner.storage()[1024..1536], [3; 512]);
+
+    // Clearing discards the writes.
+    let mut bio = DryRunBlockIo::new(inner);
+    bio.write_blocks(Lba(0), &[4; 512]).unwrap();
+    bio.clear();
+    assert_eq!(bio.writes(), []);
+    bio.read_blocks(Lba(0), &mut buf[..512]).unwrap();
+    assert_eq!(buf[..512], [0; 512]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_dry_run_disk_operation() {
+    fn create<Io: BlockIo>(disk: &mut Disk<Io>) {
+        let mut block_buf = vec![0; 512];
+        disk.create_partition(
+            GptPartitionType::BASIC_DATA,
+            PartitionExtent::Blocks(NonZeroU64::new(100).unwrap()),
+            "new".parse().unwrap(),
+            CreatePartitionOptions::new(guid!(
+                "c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1"
+            )),
+            &mut block_buf,
+        )
+        .unwrap();
+    }
+
+    // Run the operation for real to get the expected result.
+    let mut expected = load_test_disk();
+    create(
+        &mut Disk::new(BlockIoAdapter::new(
+            expected.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap(),
+    );
+
+    let mut storage = load_test_disk();
+    let mut bio = DryRunBlockIo::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ));
+    create(&mut Disk::new(&mut bio).unwrap());
+    let written: Vec<u64> =
+        bio.writes().iter().map(|w| w.start_lba.to_u64()).collect();
+    // Both entries, then both headers.
+    assert_eq!(written, [2, 8159, 1, 8191]);
+    bio.commit().unwrap();
+    assert!(storage == expected);
+}
+
+/// `BlockIo` that fails the first `failures` operations.
+struct FlakyBlockIo<Io> {
+    io: Io,
+    failures: u32,
+}
+
+impl<Io: BlockIo<Error = SliceBlockIoError>> FlakyBlockIo<Io> {
+    fn fail(&mut self) -> Result<(), SliceBlockIoError> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            Err(SliceBlockIoError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<Io: BlockIo<Error = SliceBlockIoError>> BlockIo for FlakyBlockIo<Io> {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.fail()?;
+        self.io.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.io.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.io.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.io.flush()
+    }
+}
+
+#[test]
+fn test_retry_block_io() {
+    let data = get_read_data();
+    let mut buf = vec![0; 512];
+    let mut bio = RetryBlockIo::new(
+        FlakyBlockIo {
+            io: BlockIoAdapter::new(data.as_slice(), BlockSize::BS_512),
+            failures: 2,
+        },
+        3,
+    );
+    assert_eq!(bio.max_retries(), 3);
+
+    // Succeeds after two retries.
+    bio.read_blocks(Lba(1), &mut buf).unwrap();
+    assert_eq!(buf, data[512..1024]);
+    assert_eq!(bio.last_retries(), 2);
+    assert_eq!(bio.total_retries(), 2);
+
+    // No failures.
+    assert_eq!(bio.num_blocks().unwrap(), 3);
+    assert_eq!(bio.last_retries(), 0);
+    assert_eq!(bio.total_retries(), 2);
+
+    bio.reset_retries();
+    assert_eq!(bio.total_retries(), 0);
+
+    // Too many failures.
+    let mut bio = RetryBlockIo::new(
+        FlakyBlockIo {
+            io: bio.into_inner().io,
+            failures: 3,
+        },
+        2,
+    );
+    bio.set_max_retries(1);
+    bio.set_backoff(|attempt| assert_eq!(attempt, 1));
+    assert_eq!(bio.flush(), Err(SliceBlockIoError::ReadOnly));
+    assert_eq!(bio.last_retries(), 1);
+    assert_eq!(bio.inner().failures, 1);
+
+    // Errors rejected by the filter are not retried.
+    bio.set_retry_filter(|err| *err != SliceBlockIoError::ReadOnly);
+    assert_eq!(bio.flush(), Err(SliceBlockIoError::ReadOnly));
+    assert_eq!(bio.last_retries(), 0);
+    assert_eq!(bio.total_retries(), 1);
+    bio.flush().unwrap();
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_journaled_block_io() {
+    let bs = BlockSize::BS_512;
+    let journal_lba = Lba(32);
+    let old: Vec<u8> = (0..512 * 64).map(|i| (i / 512) as u8).collect();
+    let mut new = old.clone();
+    new[512..512 * 3].fill(0xaa);
+    new[512 * 5..512 * 6].fill(0xbb);
+
+    fn write_new<Io: BlockIo>(bio: &mut JournaledBlockIo<Io>) {
+        bio.write_blocks(Lba(1), &[0xaa; 512 * 2]).unwrap();
+        bio.write_blocks(Lba(5), &[0xbb; 512]).unwrap();
+    }
+
+    // Pending writes are visible through the wrapper, but not written
+    // until committed.
+    let mut storage = old.clone();
+    let mut bio = JournaledBlockIo::new(
+        BlockIoAdapter::new(storage.as_mut_slice(), bs),
+        journal_lba,
+        32,
+    );
+    assert!(!bio.recover(JournalRecovery::Replay).unwrap());
+    write_new(&mut bio);
+    assert!(bio.has_pending_writes());
+    // Header, one descriptor block, and two copies of three blocks.
+    assert_eq!(bio.required_journal_blocks(), Some(8));
+    let mut buf = vec![0; 512 * 6];
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    assert_eq!(buf, new[..512 * 6]);
+    assert_eq!(**bio.inner().storage(), old[..]);
+
+    // Discarding drops the writes.
+    bio.discard();
+    assert!(!bio.has_pending_writes());
+    bio.commit().unwrap();
+    assert_eq!(**bio.inner().storage(), old[..]);
+
+    // Errors that are detected before anything is written.
+    let mut bio = JournaledBlockIo::new(
+        BlockIoAdapter::new(storage.as_mut_slice(), bs),
+        journal_lba,
+        7,
+    );
+    write_new(&mut bio);
+    assert_eq!(
+        bio.commit(),
+        Err(JournalError::JournalTooSmall { required_blocks: 8 })
+    );
+    bio.write_blocks(Lba(35), &[0; 512]).unwrap();
+    assert_eq!(bio.commit(), Err(JournalError::JournalOverlap));
+    assert_eq!(**bio.inner().storage(), old[..]);
+
+    // Successful commit.
+    let mut bio = JournaledBlockIo::new(
+        BlockIoAdapter::new(storage.as_mut_slice(), bs),
+        journal_lba,
+        32,
+    );
+    write_new(&mut bio);
+    bio.commit().unwrap();
+    assert!(!bio.has_pending_writes());
+    assert!(!bio.recover(JournalRecovery::Replay).unwrap());
+    assert_eq!(storage[..512 * 32], new[..512 * 32]);
+
+    // Crash at every possible point. The data is never left partially
+    // updated. If a journal is found, replaying gives the new data and
+    // rolling back gives the old data.
+    for crash_after in 0.. {
+        let crash = |crash_after| {
+            let mut storage = old.clone();
+            let mut bio = JournaledBlockIo::new(
+                FaultInjectingBlockIo::new(
+                    BlockIoAdapter::new(storage.as_mut_slice(), bs),
+                    FaultSchedule::CrashAfter(crash_after),
+                ),
+                journal_lba,
+                32,
+            );
+            write_new(&mut bio);
+            let committed = bio.commit().is_ok();
+            drop(bio);
+            (storage, committed)
+        };
+
+        let (mut storage, committed) = crash(crash_after);
+        if committed {
+            assert_eq!(storage[..512 * 32], new[..512 * 32]);
+            break;
+        }
+        let mut bio = JournaledBlockIo::new(
+            BlockIoAdapter::new(storage.as_mut_slice(), bs),
+            journal_lba,
+            32,
+        );
+        let replayed = bio.recover(JournalRecovery::Replay).unwrap();
+        let state = &storage[..512 * 32];
+        if replayed {
+            assert_eq!(state, &new[..512 * 32]);
+        } else {
+            assert!(state == &old[..512 * 32] || state == &new[..512 * 32]);
+        }
+
+        let (mut storage, _) = crash(crash_after);
+        let mut bio = JournaledBlockIo::new(
+            BlockIoAdapter::new(storage.as_mut_slice(), bs),
+            journal_lba,
+            32,
+        );
+        assert_eq!(bio.recover(JournalRecovery::RollBack).unwrap(), replayed);
+        let state = &storage[..512 * 32];
+        if replayed {
+            assert_eq!(state, &old[..512 * 32]);
+        } else {
+            assert!(state == &old[..512 * 32] || state == &new[..512 * 32]);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_journaled_block_io_inconsistent_journal() {
+    let bs = BlockSize::BS_512;
+    let journal_lba = 32;
+    let old: Vec<u8> = (0..512 * 64).map(|i| (i / 512) as u8).collect();
+    let crc32 =
+        |data: &[u8]| crc::Crc::<u32>::new(&Crc32::ALGORITHM).checksum(data);
+
+    // Write a journal with valid checksums, but whose record
+    // descriptors do not match the length of the payload.
+    let write_journal = |storage: &mut [u8],
+                         num_records: u32,
+                         descriptors: &[(u64, u64)]| {
+        let payload_blocks: u64 = 4;
+        let payload =
+            &mut storage[512 * (journal_lba + 1)..512 * (journal_lba + 5)];
+        payload.fill(0xcc);
+        payload[..512].fill(0);
+        for (i, (lba, num_blocks)) in descriptors.iter().enumerate() {
+            payload[i * 16..i * 16 + 8].copy_from_slice(&lba.to_le_bytes());
+            payload[i * 16 + 8..i * 16 + 16]
+                .copy_from_slice(&num_blocks.to_le_bytes());
+        }
+        let payload_crc32 = crc32(payload);
+
+        let header = &mut storage[512 * journal_lba..512 * (journal_lba + 1)];
+        header.fill(0);
+        header[0..8].copy_from_slice(b"GPTJOURN");
+        header[8..12].copy_from_slice(&1u32.to_le_bytes());
+        header[12..16].copy_from_slice(&num_records.to_le_bytes());
+        header[16..24].copy_from_slice(&payload_blocks.to_le_bytes());
+        header[24..28].copy_from_slice(&payload_crc32.to_le_bytes());
+        let header_crc32 = crc32(&header[..28]);
+        header[28..32].copy_from_slice(&header_crc32.to_le_bytes());
+    };
+
+    let cases: [(u32, &[(u64, u64)]); 3] = [
+        // More descriptors than fit in the payload.
+        (1000, &[]),
+        // The first record fits, but the second does not.
+        (2, &[(1, 1), (5, 1)]),
+        // The data is shorter than the payload.
+        (0, &[]),
+    ];
+    for (num_records, descriptors) in cases {
+        for action in [JournalRecovery::Replay, JournalRecovery::RollBack] {
+            let mut storage = old.clone();
+            write_journal(&mut storage, num_records, descriptors);
+            let before = storage.clone();
+
+            let mut bio = JournaledBlockIo::new(
+                BlockIoAdapter::new(storage.as_mut_slice(), bs),
+                Lba(journal_lba as u64),
+                32,
+            );
+            assert!(!bio.recover(action).unwrap());
+            drop(bio);
+
+            // The journal header is cleared, and nothing else changes.
+            let header = 512 * journal_lba..512 * (journal_lba + 1);
+            assert!(storage[header.clone()].iter().all(|b| *b == 0));
+            assert_eq!(storage[..header.start], before[..header.start]);
+            assert_eq!(storage[header.end..], before[header.end..]);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_fault_injecting_block_io() {
+    let mut buf = vec![0; 512];
+    let mut bio = FaultInjectingBlockIo::new(
+        VecBlockIo::new(BlockSize::BS_512, 4),
+        FaultSchedule::Script(vec![1, 3]),
+    );
+    assert_eq!(bio.num_blocks().unwrap(), 4);
+    bio.write_blocks(Lba(0), &[1; 512]).unwrap();
+    assert_eq!(
+        bio.write_blocks(Lba(1), &[2; 512]),
+        Err(FaultInjectionError::Injected { operation: 1 })
+    );
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    assert_eq!(buf, [1; 512]);
+    assert!(bio.flush().is_err());
+    bio.flush().unwrap();
+    assert_eq!(bio.operations(), 5);
+    assert_eq!(bio.injected_faults(), 2);
+    // The failed write had no effect.
+    assert_eq!(bio.inner().storage().len(), 512);
+
+    // Errors from the underlying `BlockIo` are passed through.
+    assert!(matches!(
+        bio.read_blocks(Lba(4), &mut buf),
+        Err(FaultInjectionError::Io(
+            SliceBlockIoError::OutOfBounds { .. }
+        ))
+    ));
+
+    // Only writes and flushes are counted.
+    bio.set_schedule(FaultSchedule::CrashAfter(1));
+    bio.set_faulty_operations(FaultyOperations::WRITES);
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    bio.flush().unwrap();
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    assert!(bio.write_blocks(Lba(0), &buf).is_err());
+    assert!(bio.flush().is_err());
+    assert_eq!(bio.operations(), 3);
+
+    // A seeded schedule is reproducible.
+    let run = |seed| {
+        let mut bio = FaultInjectingBlockIo::new(
+            VecBlockIo::new(BlockSize::BS_512, 1),
+            FaultSchedule::Seeded { seed, one_in: 4 },
+        );
+        (0..64).map(|_| bio.flush().is_err()).collect::<Vec<_>>()
+    };
+    let failures = run(1);
+    assert_eq!(failures, run(1));
+    assert_ne!(failures, run(2));
+    assert!(failures.contains(&true));
+    assert!(failures.contains(&false));
+}
+
+/// Byte-addressed storage with a configurable write unit that records
+/// each write.
+struct UnitByteIo {
+    data: Vec<u8>,
+    write_unit: u64,
+    writes: Vec<(u64, usize)>,
+}
+
+impl ByteIo for UnitByteIo {
+    type Error = SliceBlockIoError;
+
+    fn size(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.data.len() as u64)
+    }
+
+    fn write_unit(&self) -> u64 {
+        self.write_unit
+    }
+
+    fn read_bytes(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let offset = offset as usize;
+        dst.copy_from_slice(&self.data[offset..offset + dst.len()]);
+        Ok(())
+    }
+
+    fn write_bytes(
+        &mut self,
+        offset: u64,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        assert_eq!(offset % self.write_unit, 0);
+        assert_eq!(src.len() as u64 % self.write_unit, 0);
+        self.writes.push((offset, src.len()));
+        let offset = offset as usize;
+        self.data[offset..offset + src.len()].copy_from_slice(src);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_byte_block_io() {
+    let new_io = |write_unit| UnitByteIo {
+        data: (0..4096 * 5).map(|i| (i / 512) as u8).collect(),
+        write_unit,
+        writes: Vec::new(),
+    };
+
+    // Write units that divide the block size don't need a scratch
+    // buffer.
+    for write_unit in [1, 512] {
+        let mut bio = ByteBlockIo::new(new_io(write_unit), BlockSize::BS_512);
+        assert_eq!(bio.num_blocks().unwrap(), 40);
+        bio.write_blocks(Lba(3), &[0xaa; 1024]).unwrap();
+        let mut buf = vec![0; 1536];
+        bio.read_blocks(Lba(2), &mut buf).unwrap();
+        assert_eq!(buf[..512], [2; 512]);
+        assert_eq!(buf[512..], [0xaa; 1024]);
+        assert_eq!(bio.inner().writes, [(1536, 1024)]);
+    }
+
+    // Larger write units use read-modify-write.
+    let mut scratch = [0; 4096];
+    let mut bio = ByteBlockIo::with_scratch(
+        new_io(4096),
+        BlockSize::BS_512,
+        &mut scratch,
+    );
+    // Partial unit.
+    bio.write_blocks(Lba(1), &[0xaa; 1024]).unwrap();
+    // Full unit.
+    bio.write_blocks(Lba(8), &[0xbb; 4096]).unwrap();
+    // Partial, full, and partial.
+    bio.write_blocks(Lba(23), &[0xcc; 512 * 10]).unwrap();
+    bio.flush().unwrap();
+    let io = bio.into_inner();
+    assert_eq!(
+        io.writes,
+        [
+            (0, 4096),
+            (4096, 4096),
+            (8192, 4096),
+            (12288, 4096),
+            (16384, 4096)
+        ]
+    );
+    let block = |lba: usize| &io.data[lba * 512..(lba + 1) * 512];
+    assert_eq!(block(0), [0; 512]);
+    assert_eq!(block(1), [0xaa; 512]);
+    assert_eq!(block(2), [0xaa; 512]);
+    assert_eq!(block(3), [3; 512]);
+    assert_eq!(block(8), [0xbb; 512]);
+    assert_eq!(block(22), [22; 512]);
+    for lba in 23..33 {
+        assert_eq!(block(lba), [0xcc; 512]);
+    }
+
+    // Read-modify-write without a large enough scratch buffer.
+    let mut bio = ByteBlockIo::new(new_io(4096), BlockSize::BS_512);
+    assert_eq!(
+        bio.write_blocks(Lba(1), &[0; 512]),
+        Err(ByteBlockIoError::ScratchTooSmall)
+    );
+    bio.write_blocks(Lba(8), &[0; 4096]).unwrap();
+
+    // Invalid write units.
+    for write_unit in [0, 768] {
+        let mut bio = ByteBlockIo::new(new_io(write_unit), BlockSize::BS_512);
+        assert_eq!(
+            bio.write_blocks(Lba(0), &[0; 512]),
+            Err(ByteBlockIoError::InvalidWriteUnit)
+        );
+    }
+}
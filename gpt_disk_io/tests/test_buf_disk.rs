@@ -0,0 +1,103 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{create_partition_entry, create_primary_header, load_test_disk};
+use core::num::NonZeroU64;
+use gpt_disk_io::{
+    BlockIoAdapter, BufDisk, CreatePartitionOptions, FreeGapFilter,
+    GptHeaderCopy, PartitionExtent,
+};
+use gpt_disk_types::{
+    guid, BlockSize, GptPartitionType, Lba, LbaRangeInclusive,
+};
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_buf_disk_read() {
+    let mut storage = load_test_disk();
+    let mut disk = BufDisk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+
+    assert_eq!(disk.block_size(), BlockSize::BS_512);
+    assert_eq!(disk.read_mbr().unwrap().partitions[0].os_indicator, 0xee);
+    assert_eq!(
+        disk.read_gpt_header_with_fallback().unwrap(),
+        (create_primary_header(), GptHeaderCopy::Primary)
+    );
+
+    let header = disk.read_primary_gpt_header_verified().unwrap();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+    let entries = disk.read_gpt_partition_entries(layout).unwrap();
+    assert_eq!(entries.len(), 128);
+    assert_eq!(entries[0], create_partition_entry());
+    assert!(!entries[1].is_used());
+    assert_eq!(
+        disk.calculate_gpt_partition_entry_array_crc32(layout)
+            .unwrap(),
+        header.partition_entry_array_crc32
+    );
+    assert_eq!(disk.verify().unwrap(), []);
+    assert!(disk.verify_gpt().unwrap().is_clean());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_buf_disk_edit() {
+    let mut storage = load_test_disk();
+    let mut disk = BufDisk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+
+    let header = disk.read_primary_gpt_header().unwrap();
+    let gaps = disk.free_gaps(&header, FreeGapFilter::default()).unwrap();
+    assert_eq!(
+        gaps,
+        [
+            LbaRangeInclusive::new(Lba(34), Lba(2047)).unwrap(),
+            LbaRangeInclusive::new(Lba(4097), Lba(8158)).unwrap(),
+        ]
+    );
+
+    let index = disk
+        .create_partition(
+            GptPartitionType::BASIC_DATA,
+            PartitionExtent::Blocks(NonZeroU64::new(100).unwrap()),
+            "new".parse().unwrap(),
+            CreatePartitionOptions::new(guid!(
+                "c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1"
+            )),
+        )
+        .unwrap();
+    assert_eq!(index, 1);
+
+    disk.sort_partitions().unwrap();
+    disk.compact_partitions().unwrap();
+    disk.flush().unwrap();
+    assert_eq!(disk.verify().unwrap(), []);
+
+    let mut disk = disk.into_disk();
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    let entry = disk
+        .read_gpt_partition_entry(
+            header.get_partition_entry_array_layout().unwrap(),
+            1,
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(entry.starting_lba.to_u64(), 6144);
+}
@@ -0,0 +1,63 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use bytemuck::{bytes_of, from_bytes, Pod};
+use common::{create_partition_entry, create_primary_header, load_test_disk};
+use core::mem;
+use gpt_disk_types::{
+    Chs, Crc32, GptHeader, GptHeaderRevision, GptHeaderSignature,
+    GptPartitionAttributes, GptPartitionEntry, GptPartitionName,
+    GptPartitionType, Guid, Lba, LbaLe, LbaRangeInclusive, MasterBootRecord,
+    MbrPartitionRecord, U16Le, U32Le, U64Le,
+};
+
+fn assert_pod<T: Pod>() {}
+
+/// Check that the on-disk types can be cast to and from bytes.
+#[test]
+fn test_pod() {
+    assert_pod::<Guid>();
+    assert_pod::<U16Le>();
+    assert_pod::<U32Le>();
+    assert_pod::<U64Le>();
+    assert_pod::<Lba>();
+    assert_pod::<LbaLe>();
+    assert_pod::<LbaRangeInclusive>();
+    assert_pod::<Crc32>();
+    assert_pod::<GptHeaderSignature>();
+    assert_pod::<GptHeaderRevision>();
+    assert_pod::<GptHeader>();
+    assert_pod::<GptPartitionType>();
+    assert_pod::<GptPartitionAttributes>();
+    assert_pod::<GptPartitionName>();
+    assert_pod::<GptPartitionEntry>();
+    assert_pod::<Chs>();
+    assert_pod::<MbrPartitionRecord>();
+    assert_pod::<MasterBootRecord>();
+}
+
+/// Cast structures directly from the bytes of a disk image.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_cast_from_disk() {
+    let disk = load_test_disk();
+
+    let header: &GptHeader =
+        from_bytes(&disk[512..512 + mem::size_of::<GptHeader>()]);
+    assert_eq!(*header, create_primary_header());
+    assert_eq!(bytes_of(header), &disk[512..512 + 92]);
+
+    // The partition entry array starts at LBA 2.
+    let entry: &GptPartitionEntry = from_bytes(&disk[1024..1024 + 128]);
+    assert_eq!(*entry, create_partition_entry());
+
+    let mbr: &MasterBootRecord = from_bytes(&disk[..512]);
+    assert_eq!(mbr.signature, [0x55, 0xaa]);
+}
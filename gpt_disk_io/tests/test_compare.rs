@@ -0,0 +1,178 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_partition_entry, create_primary_header, load_test_disk};
+use gpt_disk_io::compare::{compare_gpt, CompareError, Difference};
+use gpt_disk_io::{BlockIoAdapter, Disk, DiskError};
+use gpt_disk_types::{
+    guid, BlockSize, GptPartitionAttributes, GptPartitionType, Lba, LbaLe,
+    U64Le,
+};
+
+fn compare(a: &mut [u8], b: &mut [u8]) -> Vec<Difference> {
+    let mut disk_a =
+        Disk::new(BlockIoAdapter::new(a, BlockSize::BS_512)).unwrap();
+    let mut disk_b =
+        Disk::new(BlockIoAdapter::new(b, BlockSize::BS_512)).unwrap();
+    let mut block_buf = vec![0; 512];
+    let mut differences = Vec::new();
+    compare_gpt(&mut disk_a, &mut disk_b, &mut block_buf, |d| {
+        differences.push(d);
+    })
+    .unwrap();
+    differences
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_compare_identical() {
+    let mut a = load_test_disk();
+    let mut b = load_test_disk();
+    assert_eq!(compare(&mut a, &mut b), []);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_compare_differences() {
+    let mut a = load_test_disk();
+    let mut b = load_test_disk();
+    let layout = create_primary_header()
+        .get_partition_entry_array_layout()
+        .unwrap();
+    let new_guid = guid!("c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1");
+
+    {
+        let mut disk =
+            Disk::new(BlockIoAdapter::new(b.as_mut_slice(), BlockSize::BS_512))
+                .unwrap();
+        let mut block_buf = vec![0; 512];
+        disk.set_disk_guid(new_guid, &mut block_buf).unwrap();
+
+        // Change every field of the existing partition.
+        let mut entry = create_partition_entry();
+        entry.partition_type_guid = GptPartitionType::EFI_SYSTEM;
+        entry.unique_partition_guid = new_guid;
+        entry.ending_lba = LbaLe::from_u64(5000);
+        entry.attributes = GptPartitionAttributes(U64Le::from_u64(1));
+        entry.name = "changed".parse().unwrap();
+        disk.write_gpt_partition_entry(layout, 0, &entry, &mut block_buf)
+            .unwrap();
+
+        // Add a partition.
+        disk.write_gpt_partition_entry(
+            layout,
+            5,
+            &create_partition_entry(),
+            &mut block_buf,
+        )
+        .unwrap();
+    }
+
+    let original = create_partition_entry();
+    let differences = compare(&mut a, &mut b);
+    assert_eq!(
+        differences,
+        [
+            Difference::DiskGuid {
+                a: create_primary_header().disk_guid,
+                b: new_guid,
+            },
+            Difference::PartitionType {
+                index: 0,
+                a: original.partition_type_guid,
+                b: GptPartitionType::EFI_SYSTEM,
+            },
+            Difference::PartitionGuid {
+                index: 0,
+                a: original.unique_partition_guid,
+                b: new_guid,
+            },
+            Difference::PartitionRange {
+                index: 0,
+                a: (Lba(2048), Lba(4096)),
+                b: (Lba(2048), Lba(5000)),
+            },
+            Difference::PartitionAttributes {
+                index: 0,
+                a: GptPartitionAttributes::default(),
+                b: GptPartitionAttributes(U64Le::from_u64(1)),
+            },
+            Difference::PartitionName {
+                index: 0,
+                a: original.name,
+                b: "changed".parse().unwrap(),
+            },
+            Difference::PartitionAdded { index: 5 },
+        ]
+    );
+    assert_eq!(
+        differences[3].to_string(),
+        "partition 0 range differs: 2048..=4096 != 2048..=5000"
+    );
+
+    // Swapping the disks reports a removal instead of an addition.
+    assert_eq!(
+        compare(&mut b, &mut a).last(),
+        Some(&Difference::PartitionRemoved { index: 5 })
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_compare_fallback_and_errors() {
+    let mut a = load_test_disk();
+    let mut b = load_test_disk();
+
+    // A corrupt primary header falls back to the secondary header.
+    b[512..1024].fill(0);
+    assert_eq!(compare(&mut a, &mut b), []);
+
+    // With both headers corrupt, the error says which disk failed.
+    let len = b.len();
+    b[len - 512..].fill(0);
+    let mut disk_a =
+        Disk::new(BlockIoAdapter::new(a.as_mut_slice(), BlockSize::BS_512))
+            .unwrap();
+    let mut disk_b =
+        Disk::new(BlockIoAdapter::new(b.as_mut_slice(), BlockSize::BS_512))
+            .unwrap();
+    let mut block_buf = vec![0; 512];
+    let err = compare_gpt(&mut disk_a, &mut disk_b, &mut block_buf, |_| {})
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        CompareError::DiskB(DiskError::CorruptGptHeader)
+    ));
+    assert_eq!(
+        err.to_string(),
+        "second disk: GPT header has an invalid signature or CRC32"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_compare_gpt() {
+    let mut a = load_test_disk();
+    let mut b = load_test_disk();
+    b[0x400..0x480].fill(0);
+
+    let mut disk_a =
+        Disk::new(BlockIoAdapter::new(a.as_mut_slice(), BlockSize::BS_512))
+            .unwrap();
+    let mut disk_b =
+        Disk::new(BlockIoAdapter::new(b.as_mut_slice(), BlockSize::BS_512))
+            .unwrap();
+    let mut block_buf = vec![0; 512];
+    assert_eq!(
+        disk_a.compare_gpt(&mut disk_b, &mut block_buf).unwrap(),
+        [Difference::PartitionRemoved { index: 0 }]
+    );
+}
@@ -10,71 +10,32 @@ mod common;
 
 use common::{
     create_partition_entry, create_primary_header, create_secondary_header,
+    load_test_disk,
+};
+use core::cell::Cell;
+use core::error::Error;
+use core::num::NonZeroU64;
+use gpt_disk_io::validate::{check_disk, Finding};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, CreatePartitionOptions, Disk, DiskError,
+    GptHeaderConsistency, GptHeaderCopy, GptHeaders, NewPartitionEnd,
+    PartitionExtent, ResizePartitionOptions, SliceBlockIoError,
+};
+use gpt_disk_types::{
+    guid, BlockSize, Crc32, GptHeader, GptHeaderSignature, GptPartitionEntry,
+    GptPartitionEntryArray, GptPartitionEntryArrayLayout,
+    GptPartitionEntrySize, GptPartitionType, Guid, HybridMbr,
+    HybridMbrPartition, Lba, LbaLe, LbaRangeInclusive, MasterBootRecord, U32Le,
+    U64Le,
 };
-use gpt_disk_io::{BlockIo, BlockIoAdapter, Disk};
-use gpt_disk_types::{BlockSize, GptPartitionEntryArray};
 
+use std::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::env;
 #[cfg(feature = "std")]
 use std::fs::{self, File, OpenOptions};
-
-struct SparseChunk {
-    offset: usize,
-    data: [u8; 16],
-}
-
-impl SparseChunk {
-    const fn new(offset: usize, data: [u8; 16]) -> Self {
-        Self { offset, data }
-    }
-}
-
-#[rustfmt::skip]
-const SPARSE_DISK: &'static [SparseChunk] = &[
-// Test data generated as follows:
-//
-// truncate --size 4MiB disk.bin
-// sgdisk disk.bin \
-//   --disk-guid=57a7feb6-8cd5-4922-b7bd-c78b0914e870 \
-//   --new=1:2048:4096 \
-//   --change-name='1:hello world!' \
-//   --partition-guid=1:37c75ffd-8932-467a-9c56-8cf1f0456b12 \
-//   --typecode=1:ccf0994f-f7e0-4e26-a011-843e38aa2eac
-// hexdump -ve '"SparseChunk::new(0x%_ax, [" 16/1 "%u," "]),\n"' disk.bin \
-//   | grep -v '\[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,\]'
-SparseChunk::new(0x1c0, [2,0,238,130,2,0,1,0,0,0,255,31,0,0,0,0,]),
-SparseChunk::new(0x1f0, [0,0,0,0,0,0,0,0,0,0,0,0,0,0,85,170,]),
-SparseChunk::new(0x200, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
-SparseChunk::new(0x210, [67,120,135,164,0,0,0,0,1,0,0,0,0,0,0,0,]),
-SparseChunk::new(0x220, [255,31,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
-SparseChunk::new(0x230, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
-SparseChunk::new(0x240, [183,189,199,139,9,20,232,112,2,0,0,0,0,0,0,0,]),
-SparseChunk::new(0x250, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
-SparseChunk::new(0x400, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
-SparseChunk::new(0x410, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
-SparseChunk::new(0x420, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
-SparseChunk::new(0x430, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
-SparseChunk::new(0x440, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
-SparseChunk::new(0x3fbe00, [79,153,240,204,224,247,38,78,160,17,132,62,56,170,46,172,]),
-SparseChunk::new(0x3fbe10, [253,95,199,55,50,137,122,70,156,86,140,241,240,69,107,18,]),
-SparseChunk::new(0x3fbe20, [0,8,0,0,0,0,0,0,0,16,0,0,0,0,0,0,]),
-SparseChunk::new(0x3fbe30, [0,0,0,0,0,0,0,0,104,0,101,0,108,0,108,0,]),
-SparseChunk::new(0x3fbe40, [111,0,32,0,119,0,111,0,114,0,108,0,100,0,33,0,]),
-SparseChunk::new(0x3ffe00, [69,70,73,32,80,65,82,84,0,0,1,0,92,0,0,0,]),
-SparseChunk::new(0x3ffe10, [19,76,235,219,0,0,0,0,255,31,0,0,0,0,0,0,]),
-SparseChunk::new(0x3ffe20, [1,0,0,0,0,0,0,0,34,0,0,0,0,0,0,0,]),
-SparseChunk::new(0x3ffe30, [222,31,0,0,0,0,0,0,182,254,167,87,213,140,34,73,]),
-SparseChunk::new(0x3ffe40, [183,189,199,139,9,20,232,112,223,31,0,0,0,0,0,0,]),
-SparseChunk::new(0x3ffe50, [128,0,0,0,128,0,0,0,255,173,6,146,0,0,0,0,]),
-];
-
-fn load_test_disk() -> Vec<u8> {
-    let mut disk = vec![0; 4 * 1024 * 1024];
-    for chunk in SPARSE_DISK {
-        let end = chunk.offset + chunk.data.len();
-        disk[chunk.offset..end].copy_from_slice(&chunk.data);
-    }
-    disk
-}
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
 fn test_disk_read<Io>(block_io: Io)
 where
@@ -84,8 +45,20 @@ where
     let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
     let mut disk = Disk::new(block_io).unwrap();
 
+    let mbr = disk.read_mbr(&mut block_buf).unwrap();
+    assert_eq!(mbr.partitions[0].os_indicator, 0xee);
+    assert_eq!(mbr.signature, [0x55, 0xaa]);
+
     let primary_header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
     assert_eq!(primary_header, create_primary_header());
+    assert_eq!(
+        disk.calculate_gpt_partition_entry_array_crc32(
+            primary_header.get_partition_entry_array_layout().unwrap(),
+            &mut block_buf
+        )
+        .unwrap(),
+        primary_header.partition_entry_array_crc32
+    );
 
     let secondary_header =
         disk.read_secondary_gpt_header(&mut block_buf).unwrap();
@@ -222,3 +195,1252 @@ fn test_disk() {
     #[cfg(feature = "std")]
     test_with_file(&test_disk);
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_read_verified() {
+    let mut contents = load_test_disk();
+    let mut block_buf = vec![0u8; 512];
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_primary_header()
+    );
+    assert_eq!(
+        disk.read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_secondary_header()
+    );
+
+    // Corrupt the disk GUID of the primary header, so the CRC no longer
+    // matches.
+    let mut header = create_primary_header();
+    header.disk_guid = Guid::ZERO;
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.read_primary_gpt_header_verified(&mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+
+    // Corrupt the signature of the secondary header, but keep the CRC
+    // valid.
+    let mut header = create_secondary_header();
+    header.signature = GptHeaderSignature(U64Le::from_u64(0));
+    header.update_header_crc32();
+    disk.write_secondary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.read_secondary_gpt_header_verified(&mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_read_header_with_fallback() {
+    let mut contents = load_test_disk();
+    let mut block_buf = vec![0u8; 512];
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    assert_eq!(
+        disk.read_gpt_header_with_fallback(&mut block_buf).unwrap(),
+        (create_primary_header(), GptHeaderCopy::Primary)
+    );
+
+    // Corrupt the primary header.
+    let mut header = create_primary_header();
+    header.disk_guid = Guid::ZERO;
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_gpt_header_with_fallback(&mut block_buf).unwrap(),
+        (create_secondary_header(), GptHeaderCopy::Secondary)
+    );
+
+    // Corrupt the secondary header as well.
+    let mut header = create_secondary_header();
+    header.disk_guid = Guid::ZERO;
+    disk.write_secondary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.read_gpt_header_with_fallback(&mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_read_gpt_headers() {
+    let mut contents = load_test_disk();
+    let mut block_buf = vec![0u8; 512];
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let headers = disk.read_gpt_headers(&mut block_buf).unwrap();
+    assert_eq!(
+        headers,
+        GptHeaders {
+            primary: Some(create_primary_header()),
+            secondary: Some(create_secondary_header()),
+        }
+    );
+    assert_eq!(headers.consistency(), GptHeaderConsistency::Match);
+    assert_eq!(
+        headers.preferred(),
+        Some((create_primary_header(), GptHeaderCopy::Primary))
+    );
+
+    // Valid headers that disagree.
+    let mut header = create_primary_header();
+    header.disk_guid = Guid::ZERO;
+    header.update_header_crc32();
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_gpt_headers(&mut block_buf).unwrap().consistency(),
+        GptHeaderConsistency::Mismatch
+    );
+    let mut header = create_primary_header();
+    header.alternate_lba = LbaLe::from_u64(100);
+    header.update_header_crc32();
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_gpt_headers(&mut block_buf).unwrap().consistency(),
+        GptHeaderConsistency::Mismatch
+    );
+
+    // Corrupt the primary header.
+    header.update_header_crc32();
+    header.header_crc32 = Crc32::default();
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    let headers = disk.read_gpt_headers(&mut block_buf).unwrap();
+    assert_eq!(headers.primary, None);
+    assert_eq!(headers.consistency(), GptHeaderConsistency::SecondaryOnly);
+    assert_eq!(
+        headers.preferred(),
+        Some((create_secondary_header(), GptHeaderCopy::Secondary))
+    );
+
+    // Corrupt the secondary header as well.
+    let mut header = create_secondary_header();
+    header.disk_guid = Guid::ZERO;
+    disk.write_secondary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    let headers = disk.read_gpt_headers(&mut block_buf).unwrap();
+    assert_eq!(headers.consistency(), GptHeaderConsistency::NeitherValid);
+    assert_eq!(headers.preferred(), None);
+
+    // Restore the primary header.
+    disk.write_primary_gpt_header(&create_primary_header(), &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_gpt_headers(&mut block_buf).unwrap().consistency(),
+        GptHeaderConsistency::PrimaryOnly
+    );
+    assert_eq!(
+        GptHeaderConsistency::PrimaryOnly.to_string(),
+        "only primary header is valid"
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_strict() {
+    let mut contents = load_test_disk();
+    let mut block_buf = vec![0u8; 512];
+    let mut array_buf = vec![0u8; 512 * 32];
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    assert!(!disk.is_strict());
+    disk.set_strict(true);
+    assert!(disk.is_strict());
+
+    // A valid disk passes the strict checks.
+    let primary = create_primary_header();
+    let layout = primary.get_partition_entry_array_layout().unwrap();
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        primary
+    );
+    assert_eq!(
+        disk.read_secondary_gpt_header(&mut block_buf).unwrap(),
+        create_secondary_header()
+    );
+    assert_eq!(
+        disk.read_gpt_partition_entry(layout, 0, &mut block_buf)
+            .unwrap(),
+        create_partition_entry()
+    );
+    disk.read_gpt_partition_entry_array(layout, &mut array_buf)
+        .unwrap();
+
+    // Primary header with a usable range past the end of the disk.
+    let mut header = primary;
+    header.last_usable_lba = LbaLe::from_u64(8192);
+    header.update_header_crc32();
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.read_primary_gpt_header(&mut block_buf),
+        Err(DiskError::OutOfSpecGptHeader)
+    ));
+    assert!(matches!(
+        disk.read_primary_gpt_header_verified(&mut block_buf),
+        Err(DiskError::OutOfSpecGptHeader)
+    ));
+    assert_eq!(
+        disk.read_gpt_header_with_fallback(&mut block_buf).unwrap(),
+        (create_secondary_header(), GptHeaderCopy::Secondary)
+    );
+    disk.set_strict(false);
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        header
+    );
+    disk.set_strict(true);
+
+    // Partition entry that ends past the end of the disk.
+    let mut entry = create_partition_entry();
+    entry.ending_lba = LbaLe::from_u64(10_000);
+    disk.write_gpt_partition_entry(layout, 0, &entry, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.read_gpt_partition_entry(layout, 0, &mut block_buf),
+        Err(DiskError::OutOfSpecPartitionEntry)
+    ));
+    assert!(matches!(
+        disk.read_gpt_partition_entry_array(layout, &mut array_buf),
+        Err(DiskError::OutOfSpecPartitionEntry)
+    ));
+    {
+        let mut iter = disk
+            .gpt_partition_entry_array_iter(layout, &mut block_buf)
+            .unwrap();
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DiskError::OutOfSpecPartitionEntry))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    // Validation reports problems rather than failing, and leaves
+    // strict mode enabled.
+    let mut findings = Vec::new();
+    check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+    assert!(!findings.is_empty());
+    assert!(disk.is_strict());
+}
+
+/// Wrapper that counts calls to `read_blocks`.
+struct CountingBlockIo<'a, Io> {
+    io: Io,
+    reads: &'a Cell<usize>,
+}
+
+impl<Io: BlockIo> BlockIo for CountingBlockIo<'_, Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.io.block_size()
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.io.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.reads.set(self.reads.get() + 1);
+        self.io.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.io.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush()
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_header_cache() {
+    let mut contents = load_test_disk();
+    let mut block_buf = vec![0u8; 512];
+    let reads = Cell::new(0);
+
+    let mut disk = Disk::new(CountingBlockIo {
+        io: BlockIoAdapter::new(contents.as_mut_slice(), BlockSize::BS_512),
+        reads: &reads,
+    })
+    .unwrap();
+
+    // Without the cache, every read goes to the disk.
+    assert!(!disk.is_header_cache_enabled());
+    disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(reads.replace(0), 2);
+
+    // With the cache, each header is read once.
+    disk.set_header_cache_enabled(true);
+    assert!(disk.is_header_cache_enabled());
+    for _ in 0..3 {
+        assert_eq!(
+            disk.read_gpt_header_with_fallback(&mut block_buf).unwrap(),
+            (create_primary_header(), GptHeaderCopy::Primary)
+        );
+        assert_eq!(
+            disk.read_secondary_gpt_header_verified(&mut block_buf)
+                .unwrap(),
+            create_secondary_header()
+        );
+    }
+    assert_eq!(reads.replace(0), 2);
+
+    // Writing a header updates the cache.
+    let mut header = create_primary_header();
+    header.disk_guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    header.update_header_crc32();
+    disk.write_primary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        header
+    );
+    assert_eq!(reads.replace(0), 0);
+
+    // Other writes that overlap a cached header invalidate it.
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(0),
+        entry_size: GptPartitionEntrySize::new(128).unwrap(),
+        num_entries: 8,
+    };
+    disk.write_gpt_partition_entry(
+        layout,
+        7,
+        &create_partition_entry(),
+        &mut block_buf,
+    )
+    .unwrap();
+    assert_eq!(reads.replace(0), 1);
+    // The entry is past the end of the header, so the header is still
+    // valid.
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        header
+    );
+    assert_eq!(reads.replace(0), 1);
+    disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(reads.replace(0), 0);
+
+    // Clearing the cache forces a re-read.
+    disk.clear_header_cache();
+    disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(reads.replace(0), 1);
+
+    // Disabling the cache discards it.
+    disk.set_header_cache_enabled(false);
+    disk.set_header_cache_enabled(true);
+    disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(reads.replace(0), 1);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_entry_array_iter_from() {
+    let mut storage = create_disk_with_layout(BlockSize::BS_512, 128, 128, 128);
+    let mut block_buf = vec![0u8; 512];
+    let reads = Cell::new(0);
+    let mut disk = Disk::new(CountingBlockIo {
+        io: BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512),
+        reads: &reads,
+    })
+    .unwrap();
+    let layout = disk
+        .read_primary_gpt_header(&mut block_buf)
+        .unwrap()
+        .get_partition_entry_array_layout()
+        .unwrap();
+    reads.set(0);
+
+    // Start partway through the array. The last four entries are all
+    // in one block.
+    let mut iter = disk
+        .gpt_partition_entry_array_iter_from(layout, 124, &mut block_buf)
+        .unwrap();
+    assert_eq!(iter.size_hint(), (0, Some(4)));
+    let used: Vec<_> = iter.by_ref().map(|e| e.unwrap().is_used()).collect();
+    assert_eq!(used, [false, false, false, true]);
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    drop(iter);
+    assert_eq!(reads.replace(0), 1);
+
+    // `nth` skips directly to the requested entry.
+    let mut iter = disk
+        .gpt_partition_entry_array_iter(layout, &mut block_buf)
+        .unwrap();
+    assert!(iter.nth(127).unwrap().unwrap().is_used());
+    assert!(iter.next().is_none());
+    drop(iter);
+    assert_eq!(reads.replace(0), 1);
+
+    // `nth` past the end.
+    let mut iter = disk
+        .gpt_partition_entry_array_iter(layout, &mut block_buf)
+        .unwrap();
+    assert!(iter.nth(usize::MAX).is_none());
+    assert!(iter.next().is_none());
+    drop(iter);
+    assert_eq!(reads.replace(0), 0);
+
+    // Starting at the end gives an empty iterator.
+    assert_eq!(
+        disk.gpt_partition_entry_array_iter_from(layout, 128, &mut block_buf)
+            .unwrap()
+            .count(),
+        0
+    );
+    assert!(matches!(
+        disk.gpt_partition_entry_array_iter_from(layout, 129, &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_hash_partition() {
+    let mut contents = load_test_disk();
+    // Partition 0 covers blocks 2048 through 4096.
+    let part = 2048 * 512..4097 * 512;
+    for (i, byte) in contents[part.clone()].iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+    let expected = contents[part].to_vec();
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+
+    // Buffers of one block, many blocks, and a partial block extra.
+    for (buf_len, num_reads) in [(512, 2049), (512 * 100, 21), (1000, 2049)] {
+        let mut buf = vec![0; buf_len];
+        let mut data = Vec::new();
+        let mut reads = 0;
+        disk.hash_partition(
+            0,
+            |chunk| {
+                data.extend_from_slice(chunk);
+                reads += 1;
+            },
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(data, expected);
+        assert_eq!(reads, num_reads);
+    }
+
+    let mut block_buf = vec![0; 512];
+    let crc = gpt_disk_types::crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+    assert_eq!(
+        disk.calculate_partition_crc32(0, &mut block_buf).unwrap(),
+        Crc32(U32Le(crc.checksum(&expected).to_le_bytes()))
+    );
+    #[cfg(feature = "sha2")]
+    {
+        use sha2::{Digest, Sha256};
+        let digest: [u8; 32] = Sha256::digest(&expected).into();
+        assert_eq!(
+            disk.calculate_partition_sha256(0, &mut block_buf).unwrap(),
+            digest
+        );
+    }
+
+    // Unused and out-of-range entries.
+    for index in [1, 128] {
+        assert!(matches!(
+            disk.hash_partition(index, |_| {}, &mut block_buf),
+            Err(DiskError::InvalidPartitionIndex)
+        ));
+    }
+
+    // Partition extending past the end of the disk.
+    let header = create_primary_header();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+    let entry = GptPartitionEntry {
+        ending_lba: LbaLe::from_u64(8192),
+        ..create_partition_entry()
+    };
+    disk.write_gpt_partition_entry(layout, 0, &entry, &mut block_buf)
+        .unwrap();
+    assert!(matches!(
+        disk.hash_partition(0, |_| {}, &mut block_buf),
+        Err(DiskError::OutOfSpecPartitionEntry)
+    ));
+    assert!(matches!(
+        disk.hash_partition(0, |_| {}, &mut []),
+        Err(DiskError::BufferTooSmall)
+    ));
+}
+
+#[test]
+fn test_disk_write_protective_mbr_guarded() {
+    let mut contents = vec![0u8; 512 * 64];
+    let mut block_buf = vec![0u8; 512];
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+
+    // A blank disk can be written.
+    disk.write_protective_mbr_guarded(false, &mut block_buf)
+        .unwrap();
+    let protective = disk.read_mbr(&mut block_buf).unwrap();
+    assert_eq!(protective, MasterBootRecord::protective_mbr(64));
+
+    // An existing protective MBR can be overwritten.
+    disk.write_protective_mbr_guarded(false, &mut block_buf)
+        .unwrap();
+
+    // A legacy partition table is refused unless forced.
+    let mut legacy = protective;
+    legacy.partitions[0].os_indicator = 0x83;
+    disk.write_mbr(&legacy, &mut block_buf).unwrap();
+    assert!(matches!(
+        disk.write_protective_mbr_guarded(false, &mut block_buf),
+        Err(DiskError::MbrInUse)
+    ));
+    assert_eq!(disk.read_mbr(&mut block_buf).unwrap(), legacy);
+    disk.write_protective_mbr_guarded(true, &mut block_buf)
+        .unwrap();
+    assert_eq!(disk.read_mbr(&mut block_buf).unwrap(), protective);
+
+    // So is non-blank data without an MBR signature.
+    let mut garbage = MasterBootRecord::default();
+    garbage.boot_strap_code[0] = 1;
+    disk.write_mbr(&garbage, &mut block_buf).unwrap();
+    assert!(matches!(
+        disk.write_protective_mbr_guarded(false, &mut block_buf),
+        Err(DiskError::MbrInUse)
+    ));
+}
+
+#[test]
+fn test_disk_write_hybrid_mbr() {
+    let mut contents = vec![0u8; 512 * 64];
+    let mut block_buf = vec![0u8; 512];
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+
+    let mut hybrid = HybridMbr::new();
+    hybrid
+        .add_partition(HybridMbrPartition {
+            range: LbaRangeInclusive::new(Lba(34), Lba(47)).unwrap(),
+            os_indicator: 0x0c,
+            bootable: true,
+        })
+        .unwrap();
+    disk.write_hybrid_mbr(&hybrid, &mut block_buf).unwrap();
+    let mbr = disk.read_mbr(&mut block_buf).unwrap();
+    assert_eq!(mbr, hybrid.build(64).unwrap());
+    assert_eq!(mbr.partitions[0].os_indicator, 0x0c);
+    assert_eq!(mbr.partitions[1].os_indicator, 0xee);
+
+    // A partition past the end of the disk is an error, and nothing is
+    // written.
+    hybrid
+        .add_partition(HybridMbrPartition {
+            range: LbaRangeInclusive::new(Lba(48), Lba(64)).unwrap(),
+            os_indicator: 0x83,
+            bootable: false,
+        })
+        .unwrap();
+    assert_eq!(
+        disk.write_hybrid_mbr(&hybrid, &mut block_buf),
+        Err(DiskError::InvalidHybridMbr)
+    );
+    assert_eq!(disk.read_mbr(&mut block_buf).unwrap(), mbr);
+}
+
+/// Wrapper that requires 64-byte aligned buffers.
+struct AlignedBlockIo<Io>(Io);
+
+impl<Io: BlockIo> BlockIo for AlignedBlockIo<Io> {
+    type Error = Io::Error;
+
+    fn block_size(&self) -> BlockSize {
+        self.0.block_size()
+    }
+
+    fn buffer_alignment(&self) -> usize {
+        64
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        self.0.num_blocks()
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        assert_eq!(dst.as_ptr().align_offset(64), 0);
+        self.0.read_blocks(start_lba, dst)
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        assert_eq!(src.as_ptr().align_offset(64), 0);
+        self.0.write_blocks(start_lba, src)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.0.flush()
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_buffer_validation() {
+    let mut contents = load_test_disk();
+    let mut disk = Disk::new(AlignedBlockIo(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    )))
+    .unwrap();
+
+    let mut storage = vec![0u8; 512 * 2 + 64];
+    let offset = storage.as_ptr().align_offset(64);
+    let aligned = &mut storage[offset..];
+
+    // An aligned buffer works.
+    assert_eq!(
+        disk.read_primary_gpt_header(&mut aligned[..512]).unwrap(),
+        create_primary_header()
+    );
+
+    // An unaligned buffer is rejected before reaching the `BlockIo`.
+    assert!(matches!(
+        disk.read_primary_gpt_header(&mut aligned[1..513]),
+        Err(DiskError::UnalignedBuffer)
+    ));
+    assert!(matches!(
+        disk.write_protective_mbr(&mut aligned[1..513]),
+        Err(DiskError::UnalignedBuffer)
+    ));
+
+    // An entry array for a different block size has a storage length
+    // that isn't a multiple of the disk's block size.
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(128).unwrap(),
+        num_entries: 2,
+    };
+    let mut array_storage = vec![0u8; 512];
+    let mut contents_4k = vec![0u8; 4096 * 8];
+    let mut disk_4k = Disk::new(BlockIoAdapter::new(
+        contents_4k.as_mut_slice(),
+        BlockSize::BS_4096,
+    ))
+    .unwrap();
+    let entry_array = GptPartitionEntryArray::new(
+        layout,
+        BlockSize::BS_512,
+        &mut array_storage,
+    )
+    .unwrap();
+    assert!(matches!(
+        disk_4k.write_gpt_partition_entry_array(&entry_array),
+        Err(DiskError::InvalidBufferSize)
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_bounce_buffer() {
+    let mut contents = load_test_disk();
+    let mut disk = Disk::new(AlignedBlockIo(BlockIoAdapter::new(
+        contents.as_mut_slice(),
+        BlockSize::BS_512,
+    )))
+    .unwrap();
+    assert!(!disk.is_bounce_buffer_enabled());
+    disk.set_bounce_buffer_enabled(true);
+    assert!(disk.is_bounce_buffer_enabled());
+
+    let mut storage = vec![0u8; 512 + 64];
+    let offset = storage.as_ptr().align_offset(64);
+    let unaligned = &mut storage[offset + 1..offset + 513];
+
+    // Reads and writes with an unaligned buffer go through an aligned
+    // copy.
+    assert_eq!(
+        disk.read_primary_gpt_header(unaligned).unwrap(),
+        create_primary_header()
+    );
+    disk.write_protective_mbr(unaligned).unwrap();
+    assert_eq!(
+        disk.read_mbr(unaligned).unwrap(),
+        MasterBootRecord::protective_mbr(8192)
+    );
+}
+
+#[test]
+fn test_disk_error_source() {
+    let err: DiskError<SliceBlockIoError> =
+        DiskError::Io(SliceBlockIoError::ReadOnly);
+    let source = err.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<SliceBlockIoError>(),
+        Some(&SliceBlockIoError::ReadOnly)
+    );
+
+    let err: DiskError<SliceBlockIoError> = DiskError::Overflow;
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn test_disk_error_clone_eq_code() {
+    let err: DiskError<SliceBlockIoError> =
+        DiskError::Io(SliceBlockIoError::ReadOnly);
+    assert_eq!(err.clone(), err);
+    assert_ne!(err, DiskError::Io(SliceBlockIoError::Overflow));
+    assert_ne!(err, DiskError::ReadOnly);
+
+    assert_eq!(err.code(), 100);
+    assert_eq!(DiskError::<SliceBlockIoError>::BufferTooSmall.code(), 1);
+    assert_eq!(DiskError::<SliceBlockIoError>::ReadOnly.code(), 12);
+    assert_eq!(DiskError::<SliceBlockIoError>::UnalignedBuffer.code(), 18);
+    assert_eq!(
+        DiskError::<SliceBlockIoError>::CorruptPartitionEntryArray.code(),
+        21
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_larger_than_4gib() {
+    /// Removes the file when dropped, so that it is cleaned up even if
+    /// the test fails.
+    struct RemoveOnDrop(PathBuf);
+
+    impl Drop for RemoveOnDrop {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    let path = RemoveOnDrop(env::temp_dir().join("tmp_test_disk_large.bin"));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path.0)
+        .unwrap();
+    // Sparse file, so this doesn't actually use 8GiB of disk space.
+    file.set_len(8 << 30).unwrap();
+
+    let bs = BlockSize::BS_512;
+    let mut block_buf = vec![0u8; bs.to_usize().unwrap()];
+    let mut disk = Disk::new(BlockIoAdapter::new(file, bs)).unwrap();
+
+    let last_lba = (8 << 30) / 512 - 1;
+    let mut header = create_secondary_header();
+    header.my_lba = LbaLe::from_u64(last_lba);
+    header.partition_entry_lba = LbaLe::from_u64(last_lba - 32);
+    disk.write_secondary_gpt_header(&header, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.read_gpt_header(Lba(last_lba), &mut block_buf).unwrap(),
+        header
+    );
+    assert_eq!(
+        disk.read_secondary_gpt_header(&mut block_buf).unwrap(),
+        header
+    );
+    disk.flush().unwrap();
+}
+
+/// In-memory [`BlockIo`] that only stores blocks that have been
+/// written, so that very large disks can be tested.
+struct SparseBlockIo {
+    block_size: BlockSize,
+    physical_block_size: BlockSize,
+    num_blocks: u64,
+    blocks: BTreeMap<u64, Vec<u8>>,
+}
+
+impl BlockIo for SparseBlockIo {
+    type Error = SliceBlockIoError;
+
+    fn block_size(&self) -> BlockSize {
+        self.block_size
+    }
+
+    fn physical_block_size(&self) -> BlockSize {
+        self.physical_block_size
+    }
+
+    fn num_blocks(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.num_blocks)
+    }
+
+    fn read_blocks(
+        &mut self,
+        start_lba: Lba,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let bs = self.block_size.to_usize().unwrap();
+        for (lba, chunk) in (start_lba.to_u64()..).zip(dst.chunks_mut(bs)) {
+            if lba >= self.num_blocks {
+                return Err(SliceBlockIoError::OutOfBounds {
+                    start_lba: Lba(lba),
+                    length_in_bytes: bs,
+                });
+            }
+            match self.blocks.get(&lba) {
+                Some(data) => chunk.copy_from_slice(data),
+                None => chunk.fill(0),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_blocks(
+        &mut self,
+        start_lba: Lba,
+        src: &[u8],
+    ) -> Result<(), Self::Error> {
+        let bs = self.block_size.to_usize().unwrap();
+        for (lba, chunk) in (start_lba.to_u64()..).zip(src.chunks(bs)) {
+            if lba >= self.num_blocks {
+                return Err(SliceBlockIoError::OutOfBounds {
+                    start_lba: Lba(lba),
+                    length_in_bytes: bs,
+                });
+            }
+            self.blocks.insert(lba, chunk.to_vec());
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Write a GPT with no partitions to a disk of `num_blocks` blocks.
+fn write_empty_gpt(disk: &mut Disk<&mut SparseBlockIo>, num_blocks: u64) {
+    let bs = disk.block_size();
+    let mut block_buf = vec![0; bs.to_usize().unwrap()];
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(128).unwrap(),
+        num_entries: 128,
+    };
+    let array_blocks = layout.num_blocks(bs).unwrap();
+    let last_lba = num_blocks - 1;
+    let secondary_array_lba = last_lba - array_blocks;
+    let crc = disk
+        .write_gpt_partition_entry_array_from_iter(layout, [], &mut block_buf)
+        .unwrap();
+
+    let mut primary = GptHeader {
+        my_lba: LbaLe::from_u64(1),
+        alternate_lba: LbaLe::from_u64(last_lba),
+        first_usable_lba: LbaLe::from_u64(2 + array_blocks),
+        last_usable_lba: LbaLe::from_u64(secondary_array_lba - 1),
+        disk_guid: guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
+        partition_entry_lba: LbaLe::from_u64(2),
+        number_of_partition_entries: U32Le::from_u32(128),
+        partition_entry_array_crc32: crc,
+        ..Default::default()
+    };
+    primary.update_header_crc32();
+    let mut secondary = GptHeader {
+        my_lba: LbaLe::from_u64(last_lba),
+        alternate_lba: LbaLe::from_u64(1),
+        partition_entry_lba: LbaLe::from_u64(secondary_array_lba),
+        ..primary
+    };
+    secondary.update_header_crc32();
+
+    disk.write_gpt_partition_entry_array_from_iter(
+        GptPartitionEntryArrayLayout {
+            start_lba: Lba(secondary_array_lba),
+            ..layout
+        },
+        [],
+        &mut block_buf,
+    )
+    .unwrap();
+    disk.write_protective_mbr(&mut block_buf).unwrap();
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+        .unwrap();
+}
+
+/// Test a 20TB disk with 512e (512-byte logical, 4096-byte physical)
+/// and 4Kn (4096-byte logical and physical) blocks, where LBAs and
+/// sizes don't fit in 32 bits.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_20tb() {
+    const SIZE: u64 = 20_000_000_000_000;
+    for block_size in [BlockSize::BS_512, BlockSize::BS_4096] {
+        let bs = block_size.to_u64();
+        let num_blocks = SIZE / bs;
+        // Start with a smaller disk that has grown, as if an image was
+        // written to the array.
+        let old_num_blocks = num_blocks / 2;
+        let mut io = SparseBlockIo {
+            block_size,
+            physical_block_size: BlockSize::BS_4096,
+            num_blocks: old_num_blocks,
+            blocks: BTreeMap::new(),
+        };
+        let mut disk = Disk::new(&mut io).unwrap();
+        write_empty_gpt(&mut disk, old_num_blocks);
+        drop(disk);
+        io.num_blocks = num_blocks;
+
+        let mut disk = Disk::new(&mut io).unwrap();
+        let mut block_buf = vec![0; block_size.to_usize().unwrap()];
+        let mut findings = Vec::new();
+        check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+        assert_eq!(findings, [Finding::SecondaryHeaderNotAtEnd]);
+
+        // Move the secondary structures to the end.
+        disk.move_secondary_gpt_to_end(&mut block_buf).unwrap();
+        let header = disk
+            .read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        let array_blocks = 128 * 128 / bs;
+        assert_eq!(header.my_lba.to_u64(), num_blocks - 1);
+        assert_eq!(
+            header.partition_entry_lba.to_u64(),
+            num_blocks - 1 - array_blocks
+        );
+        assert_eq!(
+            header.last_usable_lba.to_u64(),
+            num_blocks - 2 - array_blocks
+        );
+
+        // The protective MBR saturates.
+        disk.write_protective_mbr(&mut block_buf).unwrap();
+        let mbr = disk.read_mbr(&mut block_buf).unwrap();
+        assert_eq!(mbr.partitions[0].size_in_lba.to_u32(), 0xffff_ffff);
+
+        // Create a partition larger than 2TiB, then grow one to the
+        // end of the disk.
+        let mut create = |disk: &mut Disk<_>, n, guid| {
+            disk.create_partition(
+                GptPartitionType::BASIC_DATA,
+                PartitionExtent::Blocks(NonZeroU64::new(n).unwrap()),
+                Default::default(),
+                CreatePartitionOptions::new(guid),
+                &mut block_buf,
+            )
+            .unwrap()
+        };
+        let big = (3 << 40) / bs;
+        let guid0 = guid!("c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1");
+        assert_eq!(create(&mut disk, big, guid0), 0);
+        let guid1 = guid!("a1a2b3b4-c5c6-4d7d-8e9e-f0f1f2f3f4f5");
+        assert_eq!(create(&mut disk, 1, guid1), 1);
+
+        // The first partition is aligned to 1MiB.
+        let layout = header.get_partition_entry_array_layout().unwrap();
+        let entry = disk
+            .read_gpt_partition_entry(layout, 0, &mut block_buf)
+            .unwrap();
+        let range = entry.lba_range().unwrap();
+        assert_eq!(range.start().to_u64(), (1 << 20) / bs);
+        assert_eq!(range.num_blocks(), big);
+
+        let range = disk
+            .resize_partition(
+                1,
+                NewPartitionEnd::Max,
+                ResizePartitionOptions::default(),
+                &mut block_buf,
+            )
+            .unwrap();
+        assert_eq!(range.end().to_u64(), header.last_usable_lba.to_u64());
+
+        findings.clear();
+        check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+        assert_eq!(findings, []);
+    }
+}
+
+/// Create a disk with a valid GPT using the given array parameters.
+/// The last entry in the array is used, all others are unused. The
+/// secondary partition entry array is placed directly before the
+/// secondary header.
+fn create_disk_with_layout(
+    block_size: BlockSize,
+    num_blocks: u64,
+    num_entries: u32,
+    entry_size: u32,
+) -> Vec<u8> {
+    let bs = block_size.to_usize().unwrap();
+    let mut storage = vec![0; bs * usize::try_from(num_blocks).unwrap()];
+    let mut block_buf = vec![0; bs];
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), block_size))
+            .unwrap();
+
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(entry_size).unwrap(),
+        num_entries,
+    };
+    let array_blocks = layout.num_blocks(block_size).unwrap();
+    let last_lba = num_blocks - 1;
+    let secondary_array_lba = last_lba - array_blocks;
+
+    let mut array_buf = vec![
+        0;
+        layout
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .unwrap()
+    ];
+    let mut array =
+        GptPartitionEntryArray::new(layout, block_size, &mut array_buf)
+            .unwrap();
+    let first_usable = 2 + array_blocks;
+    let last_usable = secondary_array_lba - 1;
+    *array.get_partition_entry_mut(num_entries - 1).unwrap() =
+        GptPartitionEntry {
+            starting_lba: LbaLe::from_u64(first_usable),
+            ending_lba: LbaLe::from_u64(first_usable + 9),
+            ..create_partition_entry()
+        };
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+    array.set_start_lba(Lba(secondary_array_lba));
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+
+    let mut primary = GptHeader {
+        my_lba: LbaLe::from_u64(1),
+        alternate_lba: LbaLe::from_u64(last_lba),
+        first_usable_lba: LbaLe::from_u64(first_usable),
+        last_usable_lba: LbaLe::from_u64(last_usable),
+        disk_guid: guid!("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
+        partition_entry_lba: LbaLe::from_u64(2),
+        number_of_partition_entries: U32Le::from_u32(num_entries),
+        size_of_partition_entry: U32Le::from_u32(entry_size),
+        partition_entry_array_crc32: array.calculate_crc32(),
+        ..Default::default()
+    };
+    primary.update_header_crc32();
+    let mut secondary = GptHeader {
+        my_lba: LbaLe::from_u64(last_lba),
+        alternate_lba: LbaLe::from_u64(1),
+        partition_entry_lba: LbaLe::from_u64(secondary_array_lba),
+        ..primary
+    };
+    secondary.update_header_crc32();
+
+    disk.write_protective_mbr(&mut block_buf).unwrap();
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+        .unwrap();
+    drop(disk);
+    storage
+}
+
+fn check_disk_with_layout(
+    block_size: BlockSize,
+    num_blocks: u64,
+    num_entries: u32,
+    entry_size: u32,
+) {
+    let mut storage = create_disk_with_layout(
+        block_size,
+        num_blocks,
+        num_entries,
+        entry_size,
+    );
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage.as_mut_slice(), block_size))
+            .unwrap();
+    let mut block_buf = vec![0; block_size.to_usize().unwrap()];
+
+    let assert_valid = |disk: &mut Disk<_>, block_buf: &mut [u8]| {
+        let mut findings = Vec::new();
+        check_disk(disk, block_buf, |f| findings.push(f)).unwrap();
+        assert_eq!(findings, []);
+    };
+    assert_valid(&mut disk, &mut block_buf);
+
+    let header = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+
+    // Iterate over the secondary array.
+    let used: Vec<_> = disk
+        .gpt_partition_entry_array_iter(layout, &mut block_buf)
+        .unwrap()
+        .map(|entry| entry.unwrap().is_used())
+        .collect();
+    assert_eq!(used.len(), usize::try_from(num_entries).unwrap());
+    assert_eq!(used.iter().filter(|u| **u).count(), 1);
+    assert!(used[used.len() - 1]);
+
+    // Read the whole array.
+    let mut array_buf = vec![
+        0;
+        layout
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .unwrap()
+    ];
+    let array = disk
+        .read_gpt_partition_entry_array(layout, &mut array_buf)
+        .unwrap();
+    let last_entry = *array.get_partition_entry(num_entries - 1).unwrap();
+    assert!(last_entry.is_used());
+
+    // Rewriting the array from an iterator gives the same CRC32.
+    let entries: Vec<_> = (0..num_entries)
+        .map(|i| *array.get_partition_entry(i).unwrap())
+        .collect();
+    assert_eq!(
+        disk.write_gpt_partition_entry_array_from_iter(
+            layout,
+            entries,
+            &mut block_buf
+        )
+        .unwrap(),
+        header.partition_entry_array_crc32
+    );
+    assert_eq!(
+        disk.read_gpt_partition_entry(layout, num_entries - 1, &mut block_buf)
+            .unwrap(),
+        last_entry
+    );
+
+    // Add a partition, then compact.
+    let index = disk
+        .create_partition(
+            GptPartitionType::EFI_SYSTEM,
+            PartitionExtent::Blocks(NonZeroU64::new(10).unwrap()),
+            Default::default(),
+            CreatePartitionOptions::new(guid!(
+                "c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1"
+            )),
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(index, 0);
+    disk.compact_partitions(&mut array_buf, &mut block_buf)
+        .unwrap();
+    let entry = disk
+        .read_gpt_partition_entry(layout, 1, &mut block_buf)
+        .unwrap();
+    assert_eq!(entry, last_entry);
+    assert_valid(&mut disk, &mut block_buf);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_write_entry_array_from_iter() {
+    let mut storage = load_test_disk();
+    let expected = storage.clone();
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let mut block_buf = vec![0; 512];
+    let header = create_primary_header();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+
+    // Too many entries. The array is still written.
+    assert!(matches!(
+        disk.write_gpt_partition_entry_array_from_iter(
+            layout,
+            core::iter::repeat(create_partition_entry()).take(129),
+            &mut block_buf,
+        ),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+
+    // Write the array with just the one used entry. The rest of the
+    // array is zeroed.
+    assert_eq!(
+        disk.write_gpt_partition_entry_array_from_iter(
+            layout,
+            [create_partition_entry()],
+            &mut block_buf,
+        )
+        .unwrap(),
+        header.partition_entry_array_crc32
+    );
+    drop(disk);
+    assert!(storage == expected);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_disk_entry_array_layouts() {
+    // More than 128 entries.
+    check_disk_with_layout(BlockSize::BS_512, 16384, 256, 128);
+    check_disk_with_layout(BlockSize::BS_512, 16384, 512, 128);
+    check_disk_with_layout(BlockSize::BS_4096, 4096, 1000, 128);
+
+    // Larger entries, including entries larger than a block.
+    check_disk_with_layout(BlockSize::BS_512, 16384, 300, 256);
+    check_disk_with_layout(BlockSize::BS_512, 16384, 64, 1024);
+    check_disk_with_layout(BlockSize::BS_4096, 4096, 128, 512);
+
+    // A block size that isn't a multiple of the entry size, so some
+    // entries span two blocks.
+    check_disk_with_layout(BlockSize::new(520).unwrap(), 16384, 200, 128);
+    check_disk_with_layout(BlockSize::new(4160).unwrap(), 4096, 128, 128);
+}
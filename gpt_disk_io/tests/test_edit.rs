@@ -0,0 +1,1253 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{
+    create_partition_entry, create_primary_header, create_secondary_header,
+    load_test_disk,
+};
+use core::num::NonZeroU64;
+use gpt_disk_io::validate::{check_disk, Finding};
+use gpt_disk_io::{
+    BlockIoAdapter, ChromeOsKernelAttributes, CreatePartitionOptions, Disk,
+    DiskError, FreeGapFilter, InitGptOptions, NewPartitionEnd, PartitionExtent,
+    ResizePartitionOptions,
+};
+use gpt_disk_types::{
+    guid, BlockSize, GptHeader, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionType, Guid, Lba, LbaRangeInclusive, U64Le,
+};
+
+type TestDisk<'a> = Disk<BlockIoAdapter<&'a mut [u8]>>;
+
+fn new_disk(storage: &mut [u8]) -> TestDisk<'_> {
+    Disk::new(BlockIoAdapter::new(storage, BlockSize::BS_512)).unwrap()
+}
+
+fn read_entry(disk: &mut TestDisk, index: u32) -> GptPartitionEntry {
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    disk.read_gpt_partition_entry(
+        header.get_partition_entry_array_layout().unwrap(),
+        index,
+        &mut block_buf,
+    )
+    .unwrap()
+}
+
+fn assert_disk_valid(disk: &mut TestDisk) {
+    let mut block_buf = vec![0; 512];
+    let mut findings = Vec::new();
+    check_disk(disk, &mut block_buf, |f| findings.push(f)).unwrap();
+    assert_eq!(findings, []);
+}
+
+fn blocks(n: u64) -> PartitionExtent {
+    PartitionExtent::Blocks(NonZeroU64::new(n).unwrap())
+}
+
+fn range(start: u64, end: u64) -> PartitionExtent {
+    PartitionExtent::Range(
+        LbaRangeInclusive::new(Lba(start), Lba(end)).unwrap(),
+    )
+}
+
+const GUID_A: Guid = guid!("c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1");
+const GUID_B: Guid = guid!("a1a2b3b4-c5c6-4d7d-8e9e-f0f1f2f3f4f5");
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_read_write_partition_entry() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    let layout = header.get_partition_entry_array_layout().unwrap();
+
+    assert_eq!(read_entry(&mut disk, 0), create_partition_entry());
+    assert!(!read_entry(&mut disk, 1).is_used());
+
+    let entry = GptPartitionEntry {
+        unique_partition_guid: GUID_A,
+        ..create_partition_entry()
+    };
+    disk.write_gpt_partition_entry(layout, 5, &entry, &mut block_buf)
+        .unwrap();
+    assert_eq!(read_entry(&mut disk, 5), entry);
+    assert_eq!(read_entry(&mut disk, 0), create_partition_entry());
+
+    assert!(matches!(
+        disk.read_gpt_partition_entry(layout, 128, &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_create_partition() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let ptype = GptPartitionType::EFI_SYSTEM;
+
+    // The existing partition covers 2048..=4096. The gap before it is
+    // too small once the start is aligned to 1MiB, so the new
+    // partition goes after it.
+    let index = disk
+        .create_partition(
+            ptype,
+            blocks(1024),
+            "new".parse().unwrap(),
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(index, 1);
+    let entry = read_entry(&mut disk, 1);
+    assert_eq!({ entry.partition_type_guid }, ptype);
+    assert_eq!({ entry.unique_partition_guid }, GUID_A);
+    assert_eq!(entry.lba_range().unwrap().start(), Lba(6144));
+    assert_eq!(entry.lba_range().unwrap().end(), Lba(7167));
+    assert_eq!({ entry.name }, "new".parse().unwrap());
+    assert_disk_valid(&mut disk);
+
+    // With no alignment, the gap at the start of the usable range is
+    // used.
+    let mut options = CreatePartitionOptions::new(GUID_B);
+    options.alignment = NonZeroU64::new(1);
+    let index = disk
+        .create_partition(
+            ptype,
+            blocks(100),
+            Default::default(),
+            options,
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(index, 2);
+    let range = read_entry(&mut disk, 2).lba_range().unwrap();
+    assert_eq!((range.start(), range.end()), (Lba(34), Lba(133)));
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_create_partition_physical_block_size() {
+    let mut storage = load_test_disk();
+    let mut bio =
+        BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+    // 2MiB physical blocks, which is larger than the 1MiB default.
+    bio.set_physical_block_size(BlockSize::new(2 * 1024 * 1024));
+    let mut disk = Disk::new(bio).unwrap();
+    assert_eq!(disk.physical_block_size().to_u64(), 2 * 1024 * 1024);
+    let mut block_buf = vec![0; 512];
+
+    disk.delete_partition(0, &mut block_buf).unwrap();
+    let index = disk
+        .create_partition(
+            GptPartitionType::EFI_SYSTEM,
+            blocks(100),
+            Default::default(),
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        )
+        .unwrap();
+    let range = read_entry(&mut disk, index).lba_range().unwrap();
+    assert_eq!(range.start(), Lba(4096));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_create_partition_range() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let ptype = GptPartitionType::EFI_SYSTEM;
+    let name = Default::default();
+
+    // Overlaps the existing partition.
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            range(1000, 2048),
+            name,
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        ),
+        Err(DiskError::NoFreeSpace)
+    ));
+
+    // Outside the usable range.
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            range(8000, 8159),
+            name,
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        ),
+        Err(DiskError::NoFreeSpace)
+    ));
+
+    assert_eq!(
+        disk.create_partition(
+            ptype,
+            range(4097, 8158),
+            name,
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        )
+        .unwrap(),
+        1
+    );
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_create_partition_errors() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let ptype = GptPartitionType::EFI_SYSTEM;
+    let name = Default::default();
+
+    // Too big.
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            blocks(8000),
+            name,
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        ),
+        Err(DiskError::NoFreeSpace)
+    ));
+
+    // GUID already in use.
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            blocks(1),
+            name,
+            CreatePartitionOptions::new(
+                create_partition_entry().unique_partition_guid
+            ),
+            &mut block_buf,
+        ),
+        Err(DiskError::InvalidPartitionGuid)
+    ));
+
+    // Zero GUID.
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            blocks(1),
+            name,
+            CreatePartitionOptions::new(Guid::ZERO),
+            &mut block_buf,
+        ),
+        Err(DiskError::InvalidPartitionGuid)
+    ));
+
+    // Corrupt header.
+    drop(disk);
+    storage[0x210] ^= 1;
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.create_partition(
+            ptype,
+            blocks(1),
+            name,
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        ),
+        Err(DiskError::CorruptGptHeader)
+    ));
+}
+
+fn collect_gaps(disk: &mut TestDisk, filter: FreeGapFilter) -> Vec<(u64, u64)> {
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    disk.free_gaps(&header, filter, &mut block_buf)
+        .unwrap()
+        .map(|gap| {
+            let gap = gap.unwrap();
+            (gap.start().to_u64(), gap.end().to_u64())
+        })
+        .collect()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_delete_partition() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+
+    assert!(read_entry(&mut disk, 0).is_used());
+    disk.delete_partition(0, &mut block_buf).unwrap();
+    assert_eq!(read_entry(&mut disk, 0), GptPartitionEntry::default());
+    assert_disk_valid(&mut disk);
+
+    // Deleting an unused entry is allowed.
+    disk.delete_partition(0, &mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+
+    assert!(matches!(
+        disk.delete_partition(128, &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_rename_partition() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+
+    disk.rename_partition(0, "renamed", &mut block_buf).unwrap();
+    let entry = read_entry(&mut disk, 0);
+    assert_eq!(entry.name, "renamed".parse().unwrap());
+    assert_eq!(
+        GptPartitionEntry {
+            name: create_partition_entry().name,
+            ..entry
+        },
+        create_partition_entry()
+    );
+
+    // The secondary array is updated too.
+    let header = disk
+        .read_secondary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    let secondary_entry = disk
+        .read_gpt_partition_entry(
+            header.get_partition_entry_array_layout().unwrap(),
+            0,
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(secondary_entry, entry);
+    assert_disk_valid(&mut disk);
+
+    // Too long, or not representable in UCS-2.
+    let long = "a".repeat(36);
+    assert_eq!(
+        disk.rename_partition(0, &long, &mut block_buf),
+        Err(DiskError::InvalidPartitionName)
+    );
+    assert_eq!(
+        disk.rename_partition(0, "\u{1f600}", &mut block_buf),
+        Err(DiskError::InvalidPartitionName)
+    );
+    disk.rename_partition(0, &long[..35], &mut block_buf)
+        .unwrap();
+
+    // Unused or out of range entries.
+    assert_eq!(
+        disk.rename_partition(1, "x", &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    );
+    assert_eq!(
+        disk.rename_partition(128, "x", &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    );
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_edit_read_only() {
+    let mut storage = load_test_disk();
+    let original = storage.clone();
+    let mut bio =
+        BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+    bio.set_read_only(true);
+    let mut disk = Disk::new(bio).unwrap();
+    let mut block_buf = vec![0; 512];
+    assert!(disk.is_read_only());
+
+    // Reads work.
+    disk.read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+
+    // Multi-step updates fail before anything is written.
+    assert!(matches!(
+        disk.create_partition(
+            GptPartitionType::EFI_SYSTEM,
+            blocks(100),
+            Default::default(),
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        ),
+        Err(DiskError::ReadOnly)
+    ));
+    assert!(matches!(
+        disk.delete_partition(0, &mut block_buf),
+        Err(DiskError::ReadOnly)
+    ));
+    assert!(matches!(
+        disk.write_protective_mbr(&mut block_buf),
+        Err(DiskError::ReadOnly)
+    ));
+    drop(disk);
+    assert_eq!(storage, original);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_resize_partition() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    // Partition 0 is 2048..=4096. Add partition 1 at 6000..=6999.
+    create_partitions(&mut disk, &[(6000, 6999)]);
+
+    let mut resize = |disk: &mut TestDisk, index, end, options| {
+        disk.resize_partition(index, end, options, &mut block_buf)
+            .map(|range| (range.start().to_u64(), range.end().to_u64()))
+    };
+    let lba = |lba| NewPartitionEnd::Lba(Lba(lba));
+    let no_shrink = ResizePartitionOptions::default();
+    let allow_shrink = ResizePartitionOptions {
+        allow_shrink: true,
+        ..Default::default()
+    };
+
+    // Grow.
+    assert_eq!(
+        resize(&mut disk, 0, lba(5000), no_shrink).unwrap(),
+        (2048, 5000)
+    );
+    assert_eq!(read_entry(&mut disk, 0).ending_lba.to_u64(), 5000);
+    assert_disk_valid(&mut disk);
+
+    // Shrinking must be allowed explicitly.
+    let size = NewPartitionEnd::Blocks(NonZeroU64::new(2049).unwrap());
+    assert!(matches!(
+        resize(&mut disk, 0, size, no_shrink),
+        Err(DiskError::InvalidPartitionSize)
+    ));
+    assert_eq!(
+        resize(&mut disk, 0, size, allow_shrink).unwrap(),
+        (2048, 4096)
+    );
+    assert_eq!(read_entry(&mut disk, 0), create_partition_entry());
+    assert_disk_valid(&mut disk);
+
+    // The end can't be before the start.
+    assert!(matches!(
+        resize(&mut disk, 0, lba(2047), allow_shrink),
+        Err(DiskError::InvalidPartitionSize)
+    ));
+
+    // Growing into another partition or past the usable range fails.
+    assert!(matches!(
+        resize(&mut disk, 0, lba(6000), no_shrink),
+        Err(DiskError::NoFreeSpace)
+    ));
+    assert!(matches!(
+        resize(&mut disk, 1, lba(8159), no_shrink),
+        Err(DiskError::NoFreeSpace)
+    ));
+    assert_eq!(
+        resize(&mut disk, 0, lba(5999), no_shrink).unwrap(),
+        (2048, 5999)
+    );
+
+    // No free space after the partition, so nothing changes.
+    assert_eq!(
+        resize(&mut disk, 0, NewPartitionEnd::Max, no_shrink).unwrap(),
+        (2048, 5999)
+    );
+
+    // Grow to fill the space, with and without end alignment.
+    let aligned = |alignment| ResizePartitionOptions {
+        end_alignment: NonZeroU64::new(alignment),
+        ..Default::default()
+    };
+    assert_eq!(
+        resize(&mut disk, 1, NewPartitionEnd::Max, aligned(1024)).unwrap(),
+        (6000, 7167)
+    );
+    // Alignment never shrinks the partition.
+    assert_eq!(
+        resize(&mut disk, 1, NewPartitionEnd::Max, aligned(4096)).unwrap(),
+        (6000, 7167)
+    );
+    assert_eq!(
+        resize(&mut disk, 1, NewPartitionEnd::Max, no_shrink).unwrap(),
+        (6000, 8158)
+    );
+    assert_disk_valid(&mut disk);
+
+    // Unused and out-of-range entries.
+    for index in [2, 128] {
+        assert!(matches!(
+            resize(&mut disk, index, lba(100), no_shrink),
+            Err(DiskError::InvalidPartitionIndex)
+        ));
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_free_gaps() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+
+    assert_eq!(
+        collect_gaps(&mut disk, FreeGapFilter::default()),
+        [(34, 2047), (4097, 8158)]
+    );
+
+    // Add partitions at the end of the usable range and in the middle
+    // of the second gap. These are added out of order to check that
+    // gaps are still sorted.
+    let ptype = GptPartitionType::EFI_SYSTEM;
+    for (guid, extent) in
+        [(GUID_A, range(8000, 8158)), (GUID_B, range(5000, 5999))]
+    {
+        disk.create_partition(
+            ptype,
+            extent,
+            Default::default(),
+            CreatePartitionOptions::new(guid),
+            &mut block_buf,
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        collect_gaps(&mut disk, FreeGapFilter::default()),
+        [(34, 2047), (4097, 4999), (6000, 7999)]
+    );
+
+    // Filter by size.
+    let filter = FreeGapFilter {
+        min_blocks: 2000,
+        ..Default::default()
+    };
+    assert_eq!(collect_gaps(&mut disk, filter), [(34, 2047), (6000, 7999)]);
+
+    // Filter by alignment and size.
+    let filter = FreeGapFilter {
+        min_blocks: 1000,
+        alignment: NonZeroU64::new(2048).unwrap(),
+    };
+    assert_eq!(collect_gaps(&mut disk, filter), [(6144, 7999)]);
+}
+
+/// Create a partition for each of `ranges`, in order.
+fn create_partitions(disk: &mut TestDisk, ranges: &[(u64, u64)]) {
+    let mut block_buf = vec![0; 512];
+    for (i, (start, end)) in ranges.iter().enumerate() {
+        let mut guid_bytes = GUID_A.to_bytes();
+        guid_bytes[0] = u8::try_from(i).unwrap();
+        disk.create_partition(
+            GptPartitionType::EFI_SYSTEM,
+            range(*start, *end),
+            Default::default(),
+            CreatePartitionOptions::new(Guid::from_bytes(guid_bytes)),
+            &mut block_buf,
+        )
+        .unwrap();
+    }
+}
+
+/// Get the starting LBA of the first `n` entries, or `None` for unused
+/// entries.
+fn entry_starts(disk: &mut TestDisk, n: u32) -> Vec<Option<u64>> {
+    (0..n)
+        .map(|i| {
+            let entry = read_entry(disk, i);
+            entry.is_used().then(|| entry.starting_lba.to_u64())
+        })
+        .collect()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_sort_partitions() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let mut array_buf = vec![0; 512 * 32];
+
+    create_partitions(&mut disk, &[(5000, 5999), (100, 199), (7000, 7999)]);
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(2048), Some(5000), Some(100), Some(7000), None]
+    );
+
+    disk.sort_partitions(&mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(100), Some(2048), Some(5000), Some(7000), None]
+    );
+    assert_eq!(read_entry(&mut disk, 1), create_partition_entry());
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_compact_partitions() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let mut array_buf = vec![0; 512 * 32];
+
+    create_partitions(&mut disk, &[(5000, 5999), (100, 199), (7000, 7999)]);
+
+    // Delete the first and third partitions by writing unused entries
+    // directly to both arrays.
+    let (primary, secondary) = (
+        disk.read_primary_gpt_header(&mut block_buf).unwrap(),
+        disk.read_secondary_gpt_header(&mut block_buf).unwrap(),
+    );
+    for header in [primary, secondary] {
+        for index in [0, 2] {
+            disk.write_gpt_partition_entry(
+                header.get_partition_entry_array_layout().unwrap(),
+                index,
+                &GptPartitionEntry::default(),
+                &mut block_buf,
+            )
+            .unwrap();
+        }
+    }
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [None, Some(5000), None, Some(7000), None]
+    );
+
+    disk.compact_partitions(&mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(5000), Some(7000), None, None, None]
+    );
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_swap_and_move_partition_indices() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let mut array_buf = vec![0; 512 * 32];
+
+    // Entry 0 starts at 2048.
+    create_partitions(&mut disk, &[(100, 199), (5000, 5999), (7000, 7999)]);
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(2048), Some(100), Some(5000), Some(7000), None]
+    );
+
+    disk.swap_partition_indices(0, 2, &mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(5000), Some(100), Some(2048), Some(7000), None]
+    );
+    assert_disk_valid(&mut disk);
+
+    // Swapping with an unused entry moves the partition there.
+    disk.swap_partition_indices(1, 4, &mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(5000), None, Some(2048), Some(7000), Some(100)]
+    );
+    assert_disk_valid(&mut disk);
+
+    // Move backward, shifting the entries in between forward.
+    disk.move_partition_to_index(4, 0, &mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(100), Some(5000), None, Some(2048), Some(7000)]
+    );
+    assert_disk_valid(&mut disk);
+
+    // Move forward, shifting the entries in between backward.
+    disk.move_partition_to_index(1, 3, &mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(100), None, Some(2048), Some(5000), Some(7000)]
+    );
+
+    // Moving or swapping an entry with itself is a no-op.
+    disk.move_partition_to_index(2, 2, &mut array_buf, &mut block_buf)
+        .unwrap();
+    disk.swap_partition_indices(2, 2, &mut array_buf, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(100), None, Some(2048), Some(5000), Some(7000)]
+    );
+    assert_disk_valid(&mut disk);
+
+    // Out of range indices fail without modifying the disk.
+    assert!(matches!(
+        disk.swap_partition_indices(0, 128, &mut array_buf, &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+    assert!(matches!(
+        disk.move_partition_to_index(128, 0, &mut array_buf, &mut block_buf),
+        Err(DiskError::InvalidPartitionIndex)
+    ));
+    assert_eq!(
+        entry_starts(&mut disk, 5),
+        [Some(100), None, Some(2048), Some(5000), Some(7000)]
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_init_gpt() {
+    let mut storage = vec![0; 4 * 1024 * 1024];
+    let mut block_buf = vec![0; 512];
+    let disk_guid = create_primary_header().disk_guid;
+
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.init_gpt(Guid::ZERO, InitGptOptions::default(), &mut block_buf),
+        Err(DiskError::InvalidDiskGuid)
+    ));
+    disk.init_gpt(disk_guid, InitGptOptions::default(), &mut block_buf)
+        .unwrap();
+    assert_disk_valid(&mut disk);
+
+    // The headers match the test disk's headers, apart from the
+    // partition entry array CRC32.
+    let mut primary = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    let mut secondary = disk
+        .read_secondary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    primary.set_partition_entry_array_crc32(
+        create_primary_header().partition_entry_array_crc32,
+    );
+    secondary.set_partition_entry_array_crc32(
+        create_primary_header().partition_entry_array_crc32,
+    );
+    assert_eq!(primary, create_primary_header());
+    assert_eq!(secondary, create_secondary_header());
+
+    // An existing partition table is replaced.
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let options = InitGptOptions {
+        num_partition_entries: 4,
+        ..Default::default()
+    };
+    disk.init_gpt(GUID_A, options, &mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+    let primary = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!({ primary.disk_guid }, GUID_A);
+    assert_eq!(primary.number_of_partition_entries.to_u32(), 4);
+    assert_eq!(primary.first_usable_lba.to_u64(), 3);
+    assert!(!read_entry(&mut disk, 0).is_used());
+
+    // A legacy MBR is only overwritten with `force`.
+    let mut legacy = disk.read_mbr(&mut block_buf).unwrap();
+    legacy.partitions[0].os_indicator = 0x83;
+    disk.write_mbr(&legacy, &mut block_buf).unwrap();
+    assert!(matches!(
+        disk.init_gpt(GUID_B, InitGptOptions::default(), &mut block_buf),
+        Err(DiskError::MbrInUse)
+    ));
+    let options = InitGptOptions {
+        force: true,
+        ..Default::default()
+    };
+    disk.init_gpt(GUID_B, options, &mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+
+    // The disk must be large enough for the GPT.
+    let mut storage = vec![0; 67 * 512];
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.init_gpt(GUID_A, InitGptOptions::default(), &mut block_buf),
+        Err(DiskError::NoFreeSpace)
+    ));
+    drop(disk);
+    assert!(storage.iter().all(|b| *b == 0));
+    let mut storage = vec![0; 68 * 512];
+    let mut disk = new_disk(&mut storage);
+    disk.init_gpt(GUID_A, InitGptOptions::default(), &mut block_buf)
+        .unwrap();
+    assert_disk_valid(&mut disk);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_repair_from_primary() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    // Wipe the secondary header and array.
+    storage[8159 * 512..].fill(0);
+    let mut disk = new_disk(&mut storage);
+    disk.repair_from_primary(&mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+    assert_eq!(
+        disk.read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_secondary_header()
+    );
+    let header = create_secondary_header();
+    assert_eq!(
+        disk.read_gpt_partition_entry(
+            header.get_partition_entry_array_layout().unwrap(),
+            0,
+            &mut block_buf
+        )
+        .unwrap(),
+        create_partition_entry()
+    );
+
+    // Fails if the primary array is corrupt.
+    let mut storage = load_test_disk();
+    storage[2 * 512] ^= 1;
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.repair_from_primary(&mut block_buf),
+        Err(DiskError::CorruptPartitionEntryArray)
+    ));
+
+    // Fails if the primary header is corrupt.
+    let mut storage = load_test_disk();
+    storage[512] ^= 1;
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.repair_from_primary(&mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_repair_from_secondary() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    // Wipe the primary header and array.
+    storage[512..34 * 512].fill(0);
+    let mut disk = new_disk(&mut storage);
+    disk.repair_from_secondary(&mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_primary_header()
+    );
+    assert_eq!(read_entry(&mut disk, 0), create_partition_entry());
+
+    // Fails if the secondary array is corrupt.
+    let mut storage = load_test_disk();
+    storage[8159 * 512] ^= 1;
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.repair_from_secondary(&mut block_buf),
+        Err(DiskError::CorruptPartitionEntryArray)
+    ));
+
+    // Fails if the secondary header is corrupt.
+    let mut storage = load_test_disk();
+    storage[8191 * 512] ^= 1;
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.repair_from_secondary(&mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_set_disk_guid() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    {
+        let mut disk = new_disk(&mut storage);
+        assert!(matches!(
+            disk.set_disk_guid(Guid::ZERO, &mut block_buf),
+            Err(DiskError::InvalidDiskGuid)
+        ));
+
+        disk.set_disk_guid(GUID_A, &mut block_buf).unwrap();
+        let primary = disk
+            .read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        let secondary = disk
+            .read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        assert_eq!({ secondary.disk_guid }, GUID_A);
+        assert_eq!(
+            primary,
+            GptHeader {
+                disk_guid: GUID_A,
+                header_crc32: primary.header_crc32,
+                ..create_primary_header()
+            }
+        );
+        assert_disk_valid(&mut disk);
+    }
+
+    // Fails without writing anything if a header is corrupt.
+    let secondary_start = storage.len() - 512;
+    storage[secondary_start..].fill(0);
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.set_disk_guid(GUID_B, &mut block_buf),
+        Err(DiskError::CorruptGptHeader)
+    ));
+    let primary = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!({ primary.disk_guid }, GUID_A);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_randomize_guids() {
+    let mut storage = load_test_disk();
+    let mut array_storage = vec![0; 512 * 32];
+    let mut block_buf = vec![0; 512];
+    let mut disk = new_disk(&mut storage);
+    create_partitions(&mut disk, &[(5000, 5999), (6000, 6999)]);
+    // Leave a hole so that an unused entry is between used ones.
+    disk.delete_partition(1, &mut block_buf).unwrap();
+    let before: Vec<_> = (0..3).map(|i| read_entry(&mut disk, i)).collect();
+
+    let mut n = 0;
+    disk.randomize_guids(
+        || {
+            n += 1;
+            [n; 16]
+        },
+        &mut array_storage,
+        &mut block_buf,
+    )
+    .unwrap();
+    let header = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!({ header.disk_guid }, Guid::from_random_bytes([1; 16]));
+    for (i, n) in [(0, 2), (2, 3)] {
+        assert_eq!(
+            read_entry(&mut disk, i),
+            GptPartitionEntry {
+                unique_partition_guid: Guid::from_random_bytes([n; 16]),
+                ..before[usize::try_from(i).unwrap()]
+            }
+        );
+    }
+    assert!(!read_entry(&mut disk, 1).is_used());
+    assert_disk_valid(&mut disk);
+
+    // A duplicate GUID fails without writing anything.
+    assert!(matches!(
+        disk.randomize_guids(|| [4; 16], &mut array_storage, &mut block_buf),
+        Err(DiskError::InvalidPartitionGuid)
+    ));
+    let header = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!({ header.disk_guid }, Guid::from_random_bytes([1; 16]));
+    assert_eq!(
+        { read_entry(&mut disk, 0).unique_partition_guid },
+        Guid::from_random_bytes([2; 16])
+    );
+}
+
+#[test]
+fn test_chrome_os_kernel_attributes() {
+    let mut attributes =
+        GptPartitionAttributes(U64Le::from_u64(0xf000_0000_0000_0004));
+    assert_eq!(
+        ChromeOsKernelAttributes::from_attributes(attributes),
+        ChromeOsKernelAttributes::default()
+    );
+
+    let attrs = ChromeOsKernelAttributes {
+        priority: 2,
+        tries: 15,
+        successful: true,
+    };
+    assert!(attrs.update_attributes(&mut attributes));
+    assert_eq!(attributes.0.to_u64(), 0xf1f2_0000_0000_0004);
+    assert_eq!(ChromeOsKernelAttributes::from_attributes(attributes), attrs);
+
+    let invalid = ChromeOsKernelAttributes {
+        priority: 16,
+        ..attrs
+    };
+    assert!(!invalid.update_attributes(&mut attributes));
+    assert_eq!(attributes.0.to_u64(), 0xf1f2_0000_0000_0004);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_set_chrome_os_kernel_attributes() {
+    let mut storage = load_test_disk();
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+
+    let index = disk
+        .create_partition(
+            GptPartitionType::CHROME_OS_KERNEL,
+            blocks(100),
+            "KERN-A".parse().unwrap(),
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(
+        disk.chrome_os_kernel_attributes(index, &mut block_buf)
+            .unwrap(),
+        ChromeOsKernelAttributes::default()
+    );
+
+    let attrs = ChromeOsKernelAttributes {
+        priority: 1,
+        tries: 6,
+        successful: false,
+    };
+    disk.set_chrome_os_kernel_attributes(index, attrs, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.chrome_os_kernel_attributes(index, &mut block_buf)
+            .unwrap(),
+        attrs
+    );
+    assert_disk_valid(&mut disk);
+
+    disk.set_chrome_os_kernel_priority(index, 2, &mut block_buf)
+        .unwrap();
+    disk.set_chrome_os_kernel_tries(index, 0, &mut block_buf)
+        .unwrap();
+    disk.set_chrome_os_kernel_successful(index, true, &mut block_buf)
+        .unwrap();
+    assert_eq!(
+        disk.chrome_os_kernel_attributes(index, &mut block_buf)
+            .unwrap(),
+        ChromeOsKernelAttributes {
+            priority: 2,
+            tries: 0,
+            successful: true,
+        }
+    );
+    assert_disk_valid(&mut disk);
+
+    // The secondary array is updated too.
+    let header = disk.read_secondary_gpt_header(&mut block_buf).unwrap();
+    let entry = disk
+        .read_gpt_partition_entry(
+            header.get_partition_entry_array_layout().unwrap(),
+            index,
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(entry.attributes.0.to_u64(), 0x0102_0000_0000_0000);
+
+    assert!(matches!(
+        disk.set_chrome_os_kernel_tries(index, 16, &mut block_buf),
+        Err(DiskError::InvalidAttributeValue)
+    ));
+
+    // Partition 0 is not a kernel partition.
+    assert!(matches!(
+        disk.chrome_os_kernel_attributes(0, &mut block_buf),
+        Err(DiskError::InvalidPartitionType)
+    ));
+    assert!(matches!(
+        disk.set_chrome_os_kernel_priority(0, 1, &mut block_buf),
+        Err(DiskError::InvalidPartitionType)
+    ));
+    assert_eq!(read_entry(&mut disk, 0), create_partition_entry());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_move_secondary_gpt_to_end() {
+    let mut block_buf = vec![0; 512];
+
+    // Disk image copied to a larger disk.
+    let mut storage = load_test_disk();
+    storage.resize(storage.len() * 2, 0);
+    {
+        let mut disk = new_disk(&mut storage);
+        let mut findings = Vec::new();
+        check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+        assert_eq!(findings, [Finding::SecondaryHeaderNotAtEnd]);
+
+        disk.move_secondary_gpt_to_end(&mut block_buf).unwrap();
+        assert_disk_valid(&mut disk);
+        let secondary = disk
+            .read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        assert_eq!(secondary.my_lba.to_u64(), 16383);
+        assert_eq!(secondary.partition_entry_lba.to_u64(), 16351);
+        assert_eq!(secondary.last_usable_lba.to_u64(), 16350);
+        assert_eq!(
+            disk.read_gpt_partition_entry(
+                secondary.get_partition_entry_array_layout().unwrap(),
+                0,
+                &mut block_buf,
+            )
+            .unwrap(),
+            create_partition_entry()
+        );
+    }
+    // The old secondary header is gone.
+    assert!(storage[8191 * 512..8192 * 512].iter().all(|b| *b == 0));
+
+    // Disk image truncated, but still large enough for the partitions.
+    let mut storage = load_test_disk();
+    storage.truncate(8000 * 512);
+    let mut disk = new_disk(&mut storage);
+    disk.move_secondary_gpt_to_end(&mut block_buf).unwrap();
+    assert_disk_valid(&mut disk);
+    let primary = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!(primary.alternate_lba.to_u64(), 7999);
+    assert_eq!(primary.last_usable_lba.to_u64(), 7966);
+
+    // Disk image truncated so that the partition no longer fits.
+    let mut storage = load_test_disk();
+    storage.truncate(4100 * 512);
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.move_secondary_gpt_to_end(&mut block_buf),
+        Err(DiskError::NoFreeSpace)
+    ));
+
+    // Corrupt primary array on a grown disk. Nothing is written, so the
+    // intact secondary copy is preserved.
+    let mut storage = load_test_disk();
+    storage.resize(storage.len() * 2, 0);
+    storage[2 * 512] ^= 1;
+    let original = storage.clone();
+    let mut disk = new_disk(&mut storage);
+    assert!(matches!(
+        disk.move_secondary_gpt_to_end(&mut block_buf),
+        Err(DiskError::CorruptPartitionEntryArray)
+    ));
+    drop(disk);
+    assert!(storage == original);
+}
+
+/// Test a disk whose partition entry arrays are not adjacent to their
+/// headers, as created by some firmware.
+#[cfg(feature = "test_util")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_non_adjacent_partition_entry_arrays() {
+    use gpt_disk_io::test_util::{
+        build_test_disk, test_partition_entry, TestDiskSpec,
+    };
+
+    let mut storage = build_test_disk(&TestDiskSpec {
+        partitions: vec![test_partition_entry(0, Lba(2048), Lba(4095))],
+        primary_array_lba: Some(Lba(40)),
+        secondary_array_lba: Some(Lba(8149)),
+        ..TestDiskSpec::default()
+    });
+    let mut disk = new_disk(&mut storage);
+    let mut block_buf = vec![0; 512];
+    let mut array_buf = vec![0; 128 * 128];
+    assert_disk_valid(&mut disk);
+
+    // Edits update the arrays where the headers point.
+    disk.rename_partition(0, "renamed", &mut block_buf).unwrap();
+    let index = disk
+        .create_partition(
+            GptPartitionType::BASIC_DATA,
+            blocks(1024),
+            "new".parse().unwrap(),
+            CreatePartitionOptions::new(GUID_A),
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(index, 1);
+    assert_disk_valid(&mut disk);
+    let entry0 = read_entry(&mut disk, 0);
+    let entry1 = read_entry(&mut disk, 1);
+    assert_eq!(entry0.name, "renamed".parse().unwrap());
+
+    // Invalid locations.
+    for (primary, secondary) in [
+        // MBR or primary header.
+        (1, 8159),
+        // Overlaps the usable range, which starts at 72.
+        (50, 8159),
+        // Overlaps the secondary header.
+        (2, 8160),
+        // Overlaps the other array.
+        (2, 20),
+        // Past the end of the disk.
+        (2, 8192),
+    ] {
+        assert_eq!(
+            disk.move_partition_entry_arrays(
+                Lba(primary),
+                Lba(secondary),
+                &mut array_buf,
+                &mut block_buf,
+            ),
+            Err(DiskError::NoFreeSpace)
+        );
+    }
+    assert_disk_valid(&mut disk);
+
+    // Move the arrays back next to the headers.
+    disk.move_partition_entry_arrays(
+        Lba(2),
+        Lba(8159),
+        &mut array_buf,
+        &mut block_buf,
+    )
+    .unwrap();
+    assert_disk_valid(&mut disk);
+    let primary = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    let secondary = disk
+        .read_secondary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!(primary.partition_entry_lba.to_u64(), 2);
+    assert_eq!(secondary.partition_entry_lba.to_u64(), 8159);
+    assert_eq!(primary.first_usable_lba.to_u64(), 72);
+    assert_eq!(secondary.last_usable_lba.to_u64(), 8148);
+    for (index, entry) in [(0, entry0), (1, entry1)] {
+        for header in [primary, secondary] {
+            assert_eq!(
+                disk.read_gpt_partition_entry(
+                    header.get_partition_entry_array_layout().unwrap(),
+                    index,
+                    &mut block_buf,
+                )
+                .unwrap(),
+                entry
+            );
+        }
+    }
+}
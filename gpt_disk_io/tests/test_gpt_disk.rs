@@ -0,0 +1,204 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{
+    create_partition_entry, create_primary_header, create_secondary_header,
+    load_test_disk,
+};
+use core::num::NonZeroU64;
+use gpt_disk_io::validate::check_disk;
+use gpt_disk_io::{
+    BlockIoAdapter, CreatePartitionOptions, Disk, DiskError, GptDisk,
+    PartitionExtent,
+};
+use gpt_disk_types::{
+    guid, BlockSize, GptPartitionEntry, GptPartitionType, Guid, Lba,
+    LbaRangeInclusive,
+};
+
+const GUID_A: Guid = guid!("c3c2a4e8-41c6-4bb6-94cc-8d62cbe6a2b1");
+const GUID_B: Guid = guid!("a1a2b3b4-c5c6-4d7d-8e9e-f0f1f2f3f4f5");
+
+fn open(storage: &mut [u8]) -> GptDisk<BlockIoAdapter<&mut [u8]>> {
+    GptDisk::open(BlockIoAdapter::new(storage, BlockSize::BS_512)).unwrap()
+}
+
+fn blocks(n: u64) -> PartitionExtent {
+    PartitionExtent::Blocks(NonZeroU64::new(n).unwrap())
+}
+
+fn range(start: u64, end: u64) -> PartitionExtent {
+    PartitionExtent::Range(
+        LbaRangeInclusive::new(Lba(start), Lba(end)).unwrap(),
+    )
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_gpt_disk_read() {
+    let mut storage = load_test_disk();
+    let gpt = open(&mut storage);
+
+    assert_eq!(*gpt.primary_header(), create_primary_header());
+    assert_eq!(*gpt.secondary_header(), create_secondary_header());
+    assert_eq!(gpt.num_entries(), 128);
+    assert_eq!(gpt.partition(0), Some(&create_partition_entry()));
+    assert!(!gpt.partition(1).unwrap().is_used());
+    assert_eq!(gpt.partition(128), None);
+    assert_eq!(
+        gpt.partitions().collect::<Vec<_>>(),
+        [(0, &create_partition_entry())]
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_gpt_disk_edit() {
+    let mut storage = load_test_disk();
+    let mut gpt = open(&mut storage);
+
+    // The first free aligned range is after the existing partition,
+    // which ends at 4096.
+    let index = gpt
+        .add_partition(
+            GptPartitionType::LINUX_SWAP,
+            blocks(100),
+            "swap".parse().unwrap(),
+            CreatePartitionOptions::new(GUID_A),
+        )
+        .unwrap();
+    assert_eq!(index, 1);
+    let entry = *gpt.partition(1).unwrap();
+    assert_eq!(entry.starting_lba.to_u64(), 6144);
+    assert_eq!(entry.ending_lba.to_u64(), 6243);
+    assert_eq!(entry.name.to_string(), "swap");
+
+    // Without alignment, the free space before the first partition is
+    // used.
+    let options = CreatePartitionOptions {
+        alignment: Some(NonZeroU64::MIN),
+        ..CreatePartitionOptions::new(GUID_B)
+    };
+    let index = gpt
+        .add_partition(
+            GptPartitionType::BASIC_DATA,
+            blocks(10),
+            "b".parse().unwrap(),
+            options,
+        )
+        .unwrap();
+    assert_eq!(index, 2);
+    assert_eq!(gpt.partition(2).unwrap().starting_lba.to_u64(), 34);
+
+    gpt.set_name(2, "renamed").unwrap();
+    gpt.set_type(2, GptPartitionType::LINUX_FILESYSTEM).unwrap();
+    gpt.remove_partition(0).unwrap();
+    assert_eq!(gpt.partitions().map(|(i, _)| i).collect::<Vec<_>>(), [1, 2]);
+
+    // Nothing is written until `write`.
+    let mut disk = gpt.into_disk();
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(header, create_primary_header());
+    let mut gpt = GptDisk::from_disk(disk).unwrap();
+    assert_eq!(gpt.partitions().count(), 1);
+
+    gpt.add_partition(
+        GptPartitionType::LINUX_SWAP,
+        range(5000, 5099),
+        "swap".parse().unwrap(),
+        CreatePartitionOptions::new(GUID_A),
+    )
+    .unwrap();
+    gpt.write().unwrap();
+    assert!(gpt.primary_header().is_header_crc32_valid());
+    assert!(gpt.secondary_header().is_header_crc32_valid());
+
+    // Both copies of the partition table are valid and updated.
+    let mut disk = gpt.into_disk();
+    let mut findings = Vec::new();
+    check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+    assert_eq!(findings, []);
+    let gpt = GptDisk::from_disk(disk).unwrap();
+    let entries: Vec<GptPartitionEntry> =
+        gpt.partitions().map(|(_, entry)| *entry).collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].starting_lba.to_u64(), 5000);
+    assert_eq!(entries[1].ending_lba.to_u64(), 5099);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_gpt_disk_errors() {
+    let mut storage = load_test_disk();
+    let mut gpt = open(&mut storage);
+    let swap = GptPartitionType::LINUX_SWAP;
+    let name = || "x".parse().unwrap();
+
+    // GUIDs must be unique and nonzero.
+    let existing = create_partition_entry().unique_partition_guid;
+    assert_eq!(
+        gpt.add_partition(
+            swap,
+            blocks(1),
+            name(),
+            CreatePartitionOptions::new(existing)
+        ),
+        Err(DiskError::InvalidPartitionGuid)
+    );
+    assert_eq!(
+        gpt.add_partition(
+            swap,
+            blocks(1),
+            name(),
+            CreatePartitionOptions::new(Guid::ZERO)
+        ),
+        Err(DiskError::InvalidPartitionGuid)
+    );
+
+    // Ranges must be free and usable.
+    let options = CreatePartitionOptions::new(GUID_A);
+    for extent in [range(4000, 5000), range(33, 100), range(8100, 8159)] {
+        assert_eq!(
+            gpt.add_partition(swap, extent, name(), options),
+            Err(DiskError::NoFreeSpace)
+        );
+    }
+    assert_eq!(
+        gpt.add_partition(swap, blocks(8000), name(), options),
+        Err(DiskError::NoFreeSpace)
+    );
+
+    assert_eq!(gpt.set_name(1, "x"), Err(DiskError::InvalidPartitionIndex));
+    assert_eq!(
+        gpt.set_name(0, &"x".repeat(36)),
+        Err(DiskError::InvalidPartitionName)
+    );
+    assert_eq!(
+        gpt.set_type(0, GptPartitionType::UNUSED),
+        Err(DiskError::InvalidPartitionType)
+    );
+    assert_eq!(gpt.set_type(1, swap), Err(DiskError::InvalidPartitionIndex));
+    assert_eq!(
+        gpt.remove_partition(128),
+        Err(DiskError::InvalidPartitionIndex)
+    );
+
+    // A corrupt partition entry array is detected when opening.
+    drop(gpt);
+    storage[2 * 512] ^= 1;
+    let io = BlockIoAdapter::new(storage.as_mut_slice(), BlockSize::BS_512);
+    assert_eq!(
+        GptDisk::from_disk(Disk::new(io).unwrap()).err(),
+        Some(DiskError::CorruptPartitionEntryArray)
+    );
+}
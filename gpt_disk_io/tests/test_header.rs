@@ -8,11 +8,11 @@
 
 mod common;
 
-use common::{check_derives, create_primary_header};
+use common::{check_derives, create_primary_header, create_secondary_header};
 use gpt_disk_types::{
-    Crc32, GptHeader, GptHeaderRevision, GptHeaderSignature,
-    GptPartitionEntryArrayLayout, GptPartitionEntrySize,
-    GptPartitionEntrySizeError, Lba, U32Le,
+    BlockSize, Crc32, GptHeader, GptHeaderError, GptHeaderRevision,
+    GptHeaderSignature, GptPartitionEntryArray, GptPartitionEntryArrayLayout,
+    GptPartitionEntrySize, GptPartitionEntrySizeError, Lba, LbaLe, U32Le,
 };
 
 #[test]
@@ -53,6 +53,23 @@ fn test_header_crc32() {
 
     header.update_header_crc32();
     assert_eq!(header.header_crc32, Crc32(U32Le::from_u32(0xa4877843)));
+    assert!(header.is_header_crc32_valid());
+
+    // The header CRC does not depend on the current value of the CRC
+    // field.
+    header.header_crc32 = Crc32(U32Le::from_u32(0x1234_5678));
+    assert!(!header.is_header_crc32_valid());
+    assert_eq!(
+        header.calculate_header_crc32(),
+        Crc32(U32Le::from_u32(0xa4877843))
+    );
+
+    // Any other field change alters the CRC.
+    header.partition_entry_array_crc32 = Crc32(U32Le::from_u32(0));
+    assert_ne!(
+        header.calculate_header_crc32(),
+        Crc32(U32Le::from_u32(0xa4877843))
+    );
 }
 
 #[test]
@@ -77,6 +94,13 @@ fn test_partition_entry_size() {
 
     assert_eq!(GptPartitionEntrySize::new(128).unwrap().to_u32(), 128);
     assert_eq!(GptPartitionEntrySize::default().to_u32(), 128);
+    assert_eq!(GptPartitionEntrySize::default().num_reserved_bytes(), 0);
+    assert_eq!(
+        GptPartitionEntrySize::new(512)
+            .unwrap()
+            .num_reserved_bytes(),
+        384
+    );
     assert!(GptPartitionEntrySize::new(0).is_err());
     assert!(GptPartitionEntrySize::new(64).is_err());
     assert!(GptPartitionEntrySize::new(130).is_err());
@@ -99,3 +123,180 @@ fn test_header_partition_layout() {
     header.size_of_partition_entry = U32Le::from_u32(64);
     assert!(header.get_partition_entry_array_layout().is_err());
 }
+
+#[test]
+fn test_header_validate() {
+    let bs = BlockSize::BS_512;
+    assert_eq!(create_primary_header().validate(bs), Ok(()));
+    assert_eq!(create_secondary_header().validate(bs), Ok(()));
+
+    // Apply `f` to a valid header, update the CRC, and validate.
+    let check = |f: fn(&mut GptHeader)| {
+        let mut header = create_primary_header();
+        f(&mut header);
+        header.update_header_crc32();
+        header.validate(bs)
+    };
+
+    assert_eq!(
+        check(|h| h.signature.0 .0[0] = 0),
+        Err(GptHeaderError::InvalidSignature)
+    );
+    assert_eq!(
+        check(|h| h.revision = GptHeaderRevision(U32Le::from_u32(0x0002_0000))),
+        Err(GptHeaderError::InvalidRevision(0x0002_0000))
+    );
+    assert_eq!(
+        check(|h| h.header_size = U32Le::from_u32(91)),
+        Err(GptHeaderError::InvalidHeaderSize(91))
+    );
+    assert_eq!(
+        check(|h| h.header_size = U32Le::from_u32(513)),
+        Err(GptHeaderError::InvalidHeaderSize(513))
+    );
+    assert_eq!(
+        check(|h| h.reserved = U32Le::from_u32(1)),
+        Err(GptHeaderError::ReservedNotZero)
+    );
+    assert_eq!(
+        check(|h| h.my_lba = LbaLe::from_u64(0)),
+        Err(GptHeaderError::InvalidMyLba)
+    );
+    assert_eq!(
+        check(|h| h.my_lba = h.alternate_lba),
+        Err(GptHeaderError::InvalidMyLba)
+    );
+    assert_eq!(
+        check(|h| h.alternate_lba = LbaLe::from_u64(0)),
+        Err(GptHeaderError::InvalidAlternateLba)
+    );
+    assert_eq!(
+        check(|h| h.first_usable_lba = LbaLe::from_u64(8159)),
+        Err(GptHeaderError::InvalidUsableRange)
+    );
+    assert_eq!(
+        check(|h| h.last_usable_lba = LbaLe::from_u64(8191)),
+        Err(GptHeaderError::InvalidUsableRange)
+    );
+    assert_eq!(
+        check(|h| h.size_of_partition_entry = U32Le::from_u32(100)),
+        Err(GptHeaderError::InvalidPartitionEntrySize(100))
+    );
+    assert_eq!(
+        check(|h| h.partition_entry_lba = LbaLe::from_u64(1)),
+        Err(GptHeaderError::InvalidPartitionEntryArray)
+    );
+    assert_eq!(
+        check(|h| h.partition_entry_lba = LbaLe::from_u64(3)),
+        Err(GptHeaderError::InvalidPartitionEntryArray)
+    );
+    assert_eq!(
+        check(|h| h.partition_entry_lba = LbaLe::from_u64(u64::MAX)),
+        Err(GptHeaderError::InvalidPartitionEntryArray)
+    );
+
+    // An empty partition entry array can go anywhere.
+    assert_eq!(
+        check(|h| h.number_of_partition_entries = U32Le::from_u32(0)),
+        Ok(())
+    );
+
+    // A larger block size allows a larger header.
+    let mut header = create_primary_header();
+    header.header_size = U32Le::from_u32(513);
+    header.update_header_crc32();
+    assert_eq!(header.validate(BlockSize::BS_4096), Ok(()));
+
+    // Invalid CRC.
+    let mut header = create_primary_header();
+    header.header_crc32 = Crc32(U32Le::from_u32(0x1234_5678));
+    assert_eq!(
+        header.validate(bs),
+        Err(GptHeaderError::InvalidHeaderCrc32 {
+            expected: 0xa4877843,
+            actual: 0x1234_5678,
+        })
+    );
+    assert_eq!(
+        GptHeaderError::InvalidHeaderCrc32 {
+            expected: 0xa4877843,
+            actual: 0x1234_5678,
+        }
+        .to_string(),
+        "invalid header CRC32: expected 0xa4877843, got 0x12345678"
+    );
+}
+
+#[test]
+fn test_header_summary() {
+    let header = create_primary_header();
+    assert_eq!(
+        header.display_summary(BlockSize::BS_512).to_string(),
+        "Sector size: 512 bytes
+Disk identifier (GUID): 57a7feb6-8cd5-4922-b7bd-c78b0914e870
+Partition table holds up to 128 entries
+Partition table begins at sector 2 and ends at sector 33
+First usable sector is 34, last usable sector is 8158"
+    );
+
+    // The end of the partition entry array is omitted if the layout is
+    // invalid.
+    let mut header = create_primary_header();
+    header.size_of_partition_entry = U32Le::from_u32(100);
+    assert!(header
+        .display_summary(BlockSize::BS_4096)
+        .to_string()
+        .contains("Partition table begins at sector 2\n"));
+}
+
+#[test]
+fn test_header_new_primary() {
+    let bs = BlockSize::BS_512;
+    let disk_guid = create_primary_header().disk_guid;
+
+    // The array CRC32 is calculated for an array of empty entries.
+    let mut primary = GptHeader::new_primary(8192, bs, disk_guid, 128).unwrap();
+    let layout = primary.get_partition_entry_array_layout().unwrap();
+    let mut bytes =
+        vec![0; layout.num_bytes_rounded_to_block_as_usize(bs).unwrap()];
+    let entry_array =
+        GptPartitionEntryArray::new(layout, bs, &mut bytes).unwrap();
+    assert_eq!(
+        primary.partition_entry_array_crc32,
+        entry_array.calculate_crc32()
+    );
+    assert!(primary.is_header_crc32_valid());
+    assert_eq!(primary.validate(bs), Ok(()));
+
+    // With the array CRC32 of the test array, the headers match the
+    // test headers exactly.
+    primary.set_partition_entry_array_crc32(
+        create_primary_header().partition_entry_array_crc32,
+    );
+    assert_eq!(primary, create_primary_header());
+    let secondary = primary.to_secondary();
+    assert_eq!(secondary, create_secondary_header());
+    assert_eq!(secondary.validate(bs), Ok(()));
+
+    // Larger block sizes need fewer blocks for the array.
+    let primary =
+        GptHeader::new_primary(8192, BlockSize::BS_4096, disk_guid, 128)
+            .unwrap();
+    assert_eq!(primary.first_usable_lba, LbaLe::from_u64(6));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(8186));
+    assert_eq!(
+        primary.to_secondary().partition_entry_lba,
+        LbaLe::from_u64(8187)
+    );
+
+    // An array with no entries takes no space.
+    let primary = GptHeader::new_primary(4, bs, disk_guid, 0).unwrap();
+    assert_eq!(primary.first_usable_lba, LbaLe::from_u64(2));
+    assert_eq!(primary.last_usable_lba, LbaLe::from_u64(2));
+
+    // The disk must have at least one usable block.
+    assert!(GptHeader::new_primary(68, bs, disk_guid, 128).is_some());
+    assert!(GptHeader::new_primary(67, bs, disk_guid, 128).is_none());
+    assert!(GptHeader::new_primary(0, bs, disk_guid, 128).is_none());
+    assert!(GptHeader::new_primary(3, bs, disk_guid, 0).is_none());
+}
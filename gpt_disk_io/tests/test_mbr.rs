@@ -10,7 +10,9 @@ mod common;
 
 use common::check_derives;
 use gpt_disk_types::{
-    Chs, DiskGeometry, Lba, MasterBootRecord, MbrPartitionRecord, U32Le,
+    Chs, DiskGeometry, GptPartitionEntry, HybridMbr, HybridMbrError,
+    HybridMbrPartition, Lba, LbaLe, LbaRangeInclusive, MasterBootRecord,
+    MbrPartitionRecord, U32Le,
 };
 
 #[test]
@@ -27,6 +29,48 @@ fn test_chs() {
     // Out of range errors.
     assert!(Chs::new(0xf000, 1, 1).is_none());
     assert!(Chs::new(1, 1, 0xf0).is_none());
+
+    // Packed on-disk format.
+    let chs = Chs::new(0x3ff, 0xfe, 0x3f).unwrap();
+    assert_eq!(chs.to_bytes(), [0xfe, 0xff, 0xff]);
+    assert_eq!(Chs::from_bytes([0xfe, 0xff, 0xff]), chs);
+    assert_eq!(Chs::MAX.as_tuple(), (0x3ff, 0xff, 0x3f));
+}
+
+#[test]
+fn test_chs_lba_conversion() {
+    let geom = DiskGeometry::UNKNOWN;
+    for lba in [0, 1, 62, 63, 8191, 16_064_999, 16_450_559] {
+        let chs = Chs::from_lba(Lba(lba), geom).unwrap();
+        assert_eq!(chs.to_lba(geom), Some(Lba(lba)));
+    }
+    assert_eq!(Chs::from_lba(Lba(1), geom).unwrap().as_tuple(), (0, 0, 2));
+    assert_eq!(
+        Chs::from_lba(Lba(16_450_559), geom).unwrap().as_tuple(),
+        (1023, 254, 63)
+    );
+
+    // Too large for CHS.
+    assert!(Chs::from_lba(Lba(16_450_560), geom).is_none());
+    assert!(Chs::from_lba(Lba(0x1_0000_0000), geom).is_none());
+
+    // Invalid geometry.
+    let zero = DiskGeometry {
+        heads_per_cylinder: 0,
+        sectors_per_track: 0,
+    };
+    assert!(Chs::from_lba(Lba(1), zero).is_none());
+    assert!(Chs::new(0, 0, 1).unwrap().to_lba(zero).is_none());
+
+    // Out of range for the geometry.
+    let geom = DiskGeometry {
+        heads_per_cylinder: 16,
+        sectors_per_track: 32,
+    };
+    assert_eq!(Chs::new(2, 3, 4).unwrap().to_lba(geom), Some(Lba(1123)));
+    assert!(Chs::new(0, 16, 1).unwrap().to_lba(geom).is_none());
+    assert!(Chs::new(0, 0, 33).unwrap().to_lba(geom).is_none());
+    assert!(Chs::new(0, 0, 0).unwrap().to_lba(geom).is_none());
 }
 
 #[test]
@@ -98,3 +142,270 @@ signature: 0x3412
         .to_string()
         .starts_with("MasterBootRecord { boot_strap_code: <non-zero>,"));
 }
+
+#[test]
+fn test_protective_mbr() {
+    let partition =
+        |num_blocks| MasterBootRecord::protective_mbr(num_blocks).partitions[0];
+
+    let record = partition(8192);
+    assert_eq!(record.os_indicator, 0xee);
+    assert_eq!(record.start_chs.as_tuple(), (0, 0, 2));
+    assert_eq!(record.starting_lba.to_u32(), 1);
+    assert_eq!(record.size_in_lba.to_u32(), 8191);
+    assert_eq!(record.end_chs.as_tuple(), (0, 130, 2));
+
+    // The largest disk whose size fits.
+    let record = partition(0x1_0000_0000);
+    assert_eq!(record.size_in_lba.to_u32(), 0xffff_ffff);
+
+    // Larger disks saturate. 20TB with 512-byte and 4096-byte blocks:
+    for num_blocks in [0x1_0000_0001, 39_062_500_000, 4_882_812_500] {
+        let record = partition(num_blocks);
+        assert_eq!(record.size_in_lba.to_u32(), 0xffff_ffff);
+        assert_eq!(record.end_chs, Chs::MAX);
+    }
+
+    // Custom geometry.
+    let geom = DiskGeometry {
+        heads_per_cylinder: 16,
+        sectors_per_track: 32,
+    };
+    let record = MasterBootRecord::protective_mbr_with_geometry(8192, geom)
+        .partitions[0];
+    assert_eq!(record.start_chs.as_tuple(), (0, 0, 2));
+    assert_eq!(record.end_chs.as_tuple(), (15, 15, 32));
+    assert_eq!(record.end_chs.to_lba(geom), Some(Lba(8191)));
+}
+
+#[test]
+fn test_mbr_partition_record() {
+    let record = MbrPartitionRecord {
+        boot_indicator: 0x80,
+        start_chs: Chs::new(1, 2, 3).unwrap(),
+        os_indicator: 0x83,
+        end_chs: Chs::new(4, 5, 6).unwrap(),
+        starting_lba: U32Le::from_u32(2048),
+        size_in_lba: U32Le::from_u32(4096),
+    };
+    #[rustfmt::skip]
+    let bytes = [
+        0x80,
+        0x02, 0x03, 0x01,
+        0x83,
+        0x05, 0x06, 0x04,
+        0x00, 0x08, 0x00, 0x00,
+        0x00, 0x10, 0x00, 0x00,
+    ];
+    assert_eq!(record.to_bytes(), bytes);
+    assert_eq!(MbrPartitionRecord::from_bytes(bytes), record);
+
+    assert!(record.is_bootable());
+    assert!(record.is_used());
+    assert!(!record.is_protective());
+    assert_eq!(
+        record.lba_range(),
+        LbaRangeInclusive::new(Lba(2048), Lba(6143))
+    );
+
+    let record = MbrPartitionRecord::default();
+    assert!(!record.is_bootable());
+    assert!(!record.is_used());
+    assert_eq!(record.lba_range(), None);
+}
+
+#[test]
+fn test_mbr_bytes() {
+    let mut bytes = [0; 512];
+    bytes[0] = 0xeb;
+    bytes[440..444].copy_from_slice(&[0x78, 0x56, 0x34, 0x12]);
+    bytes[446 + 16 * 2] = 0x80;
+    bytes[446 + 16 * 2 + 4] = 0x07;
+    bytes[446 + 16 * 2 + 8] = 0x3f;
+    bytes[446 + 16 * 2 + 12] = 0x01;
+    bytes[510] = 0x55;
+    bytes[511] = 0xaa;
+
+    let mut mbr = MasterBootRecord::from_bytes(&bytes);
+    assert_eq!(mbr.to_bytes(), bytes);
+    assert!(mbr.is_signature_valid());
+    assert!(!mbr.is_protective());
+    assert!(!mbr.is_boot_strap_code_zero());
+    assert_eq!(mbr.boot_strap_code[0], 0xeb);
+    assert_eq!(mbr.disk_signature(), 0x1234_5678);
+
+    let used: Vec<_> = mbr.used_partitions().collect();
+    assert_eq!(used.len(), 1);
+    assert_eq!(used[0].0, 2);
+    assert!(used[0].1.is_bootable());
+    assert_eq!(used[0].1.os_indicator, 0x07);
+    assert_eq!(
+        used[0].1.lba_range(),
+        LbaRangeInclusive::new(Lba(0x3f), Lba(0x3f))
+    );
+
+    mbr.set_disk_signature(0xaabb_ccdd);
+    assert_eq!(mbr.unique_mbr_disk_signature, [0xdd, 0xcc, 0xbb, 0xaa]);
+
+    mbr.signature = [0; 2];
+    assert!(!mbr.is_signature_valid());
+
+    // The protective MBR round trips too.
+    let mbr = MasterBootRecord::protective_mbr(8192);
+    assert!(mbr.is_signature_valid());
+    assert!(mbr.is_protective());
+    assert_eq!(MasterBootRecord::from_bytes(&mbr.to_bytes()), mbr);
+
+    // The byte layout matches the in-memory layout.
+    assert_eq!(bytemuck::bytes_of(&mbr), mbr.to_bytes());
+}
+
+fn hybrid_partition(
+    start: u64,
+    end: u64,
+    os_indicator: u8,
+) -> HybridMbrPartition {
+    HybridMbrPartition {
+        range: LbaRangeInclusive::new(Lba(start), Lba(end)).unwrap(),
+        os_indicator,
+        bootable: false,
+    }
+}
+
+#[test]
+fn test_hybrid_mbr() {
+    // Without any partitions, this is a normal protective MBR.
+    assert_eq!(
+        HybridMbr::new().build(8192).unwrap(),
+        MasterBootRecord::protective_mbr(8192)
+    );
+
+    let mut hybrid = HybridMbr::new();
+    hybrid
+        .add_partition(HybridMbrPartition {
+            bootable: true,
+            ..hybrid_partition(4096, 6143, 0x0c)
+        })
+        .unwrap();
+    let entry = GptPartitionEntry {
+        starting_lba: LbaLe::from_u64(2048),
+        ending_lba: LbaLe::from_u64(4095),
+        ..Default::default()
+    };
+    hybrid.add_gpt_partition(&entry, 0x83, false).unwrap();
+    hybrid.set_disk_signature(0x1234_5678);
+    assert_eq!(hybrid.partitions().count(), 2);
+
+    let mbr = hybrid.build(8192).unwrap();
+    assert!(mbr.is_signature_valid());
+    assert!(mbr.is_protective());
+    assert_eq!(mbr.disk_signature(), 0x1234_5678);
+
+    // Mirrored partitions are in the order they were added.
+    let p = mbr.partitions[0];
+    assert!(p.is_bootable());
+    assert_eq!(p.os_indicator, 0x0c);
+    assert_eq!(p.starting_lba.to_u32(), 4096);
+    assert_eq!(p.size_in_lba.to_u32(), 2048);
+    assert_eq!(p.start_chs.to_lba(DiskGeometry::UNKNOWN), Some(Lba(4096)));
+    assert_eq!(p.end_chs.to_lba(DiskGeometry::UNKNOWN), Some(Lba(6143)));
+    let p = mbr.partitions[1];
+    assert!(!p.is_bootable());
+    assert_eq!(p.os_indicator, 0x83);
+    assert_eq!(p.lba_range(), entry.lba_range());
+
+    // The protective partition ends before the first mirrored
+    // partition.
+    let p = mbr.partitions[2];
+    assert!(p.is_protective());
+    assert_eq!(p.lba_range(), LbaRangeInclusive::new(Lba(1), Lba(2047)));
+    assert_eq!(p.start_chs.as_tuple(), (0, 0, 2));
+    assert!(!mbr.partitions[3].is_used());
+
+    // The protective partition can come first instead.
+    hybrid.set_protective_first(true);
+    let mbr = hybrid.build(8192).unwrap();
+    assert!(mbr.partitions[0].is_protective());
+    assert_eq!(mbr.partitions[1].os_indicator, 0x0c);
+    assert_eq!(mbr.partitions[2].os_indicator, 0x83);
+    assert!(!mbr.partitions[3].is_used());
+
+    // Custom geometry.
+    let geom = DiskGeometry {
+        heads_per_cylinder: 16,
+        sectors_per_track: 32,
+    };
+    hybrid.set_geometry(geom);
+    let mbr = hybrid.build(8192).unwrap();
+    assert_eq!(mbr.partitions[1].end_chs.to_lba(geom), Some(Lba(6143)));
+
+    // Partition past the end of the disk.
+    assert_eq!(hybrid.build(6143), Err(HybridMbrError::PartitionOutOfRange));
+
+    // At most three partitions.
+    hybrid
+        .add_partition(hybrid_partition(6144, 6200, 0x07))
+        .unwrap();
+    assert_eq!(
+        hybrid.add_partition(hybrid_partition(7000, 7100, 0x07)),
+        Err(HybridMbrError::TooManyPartitions)
+    );
+    let mbr = hybrid.build(8192).unwrap();
+    assert!(mbr.partitions.iter().all(MbrPartitionRecord::is_used));
+}
+
+#[test]
+fn test_hybrid_mbr_invalid_partition() {
+    let mut hybrid = HybridMbr::new();
+    hybrid
+        .add_partition(hybrid_partition(100, 200, 0x83))
+        .unwrap();
+
+    // Overlaps.
+    for (start, end) in [(50, 100), (200, 300), (120, 130), (50, 300)] {
+        assert_eq!(
+            hybrid.add_partition(hybrid_partition(start, end, 0x83)),
+            Err(HybridMbrError::PartitionOverlap)
+        );
+    }
+
+    // No room for the protective partition.
+    for start in [0, 1] {
+        assert_eq!(
+            hybrid.add_partition(hybrid_partition(start, 10, 0x83)),
+            Err(HybridMbrError::PartitionOutOfRange)
+        );
+    }
+
+    // Doesn't fit in 32 bits.
+    assert_eq!(
+        hybrid.add_partition(hybrid_partition(
+            0x1_0000_0000,
+            0x1_0000_0001,
+            0x83
+        )),
+        Err(HybridMbrError::PartitionOutOfRange)
+    );
+    assert_eq!(
+        hybrid.add_partition(hybrid_partition(1000, 0x1_0000_1000, 0x83)),
+        Err(HybridMbrError::PartitionOutOfRange)
+    );
+
+    // Invalid GPT partition range.
+    let entry = GptPartitionEntry {
+        starting_lba: LbaLe::from_u64(2000),
+        ending_lba: LbaLe::from_u64(1000),
+        ..Default::default()
+    };
+    assert_eq!(
+        hybrid.add_gpt_partition(&entry, 0x83, false),
+        Err(HybridMbrError::PartitionOutOfRange)
+    );
+
+    // Failed calls don't add anything.
+    assert_eq!(hybrid.partitions().count(), 1);
+    assert_eq!(
+        HybridMbrError::PartitionOverlap.to_string(),
+        "partition overlaps another partition"
+    );
+}
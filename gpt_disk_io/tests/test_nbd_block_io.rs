@@ -0,0 +1,217 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::{create_primary_header, load_test_disk};
+use gpt_disk_io::{BlockIo, Disk, NbdBlockIo};
+use gpt_disk_types::{BlockSize, Lba};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+
+/// Final data of the export, and the commands received.
+type ServerResult = (Vec<u8>, Vec<u16>);
+
+struct ServerOptions {
+    no_zeroes: bool,
+    transmission_flags: u16,
+}
+
+fn read_array<const N: usize>(stream: &mut TcpStream) -> [u8; N] {
+    let mut buf = [0; N];
+    stream.read_exact(&mut buf).unwrap();
+    buf
+}
+
+/// Serve `data` as the NBD export "disk" for a single connection.
+/// Returns the final data and the list of commands received.
+fn serve(
+    mut data: Vec<u8>,
+    options: ServerOptions,
+) -> (SocketAddr, JoinHandle<ServerResult>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut commands = Vec::new();
+
+        stream.write_all(b"NBDMAGICIHAVEOPT").unwrap();
+        let flags: u16 = if options.no_zeroes { 0b11 } else { 0b01 };
+        stream.write_all(&flags.to_be_bytes()).unwrap();
+
+        let client_flags = u32::from_be_bytes(read_array(&mut stream));
+        assert_eq!(client_flags, u32::from(flags));
+        assert_eq!(&read_array::<8>(&mut stream), b"IHAVEOPT");
+        assert_eq!(u32::from_be_bytes(read_array(&mut stream)), 1);
+        let name_len = u32::from_be_bytes(read_array(&mut stream));
+        let mut name = vec![0; usize::try_from(name_len).unwrap()];
+        stream.read_exact(&mut name).unwrap();
+        if name != b"disk" {
+            // Unknown export: hang up.
+            return (data, commands);
+        }
+        let size = u64::try_from(data.len()).unwrap();
+        stream.write_all(&size.to_be_bytes()).unwrap();
+        stream
+            .write_all(&options.transmission_flags.to_be_bytes())
+            .unwrap();
+        if !options.no_zeroes {
+            stream.write_all(&[0; 124]).unwrap();
+        }
+
+        loop {
+            let request = read_array::<28>(&mut stream);
+            assert_eq!(request[0..4], [0x25, 0x60, 0x95, 0x13]);
+            let command = u16::from_be_bytes([request[6], request[7]]);
+            let handle = &request[8..16];
+            let offset = usize::try_from(u64::from_be_bytes(
+                request[16..24].try_into().unwrap(),
+            ))
+            .unwrap();
+            let len = usize::try_from(u32::from_be_bytes(
+                request[24..28].try_into().unwrap(),
+            ))
+            .unwrap();
+            commands.push(command);
+
+            let mut reply = vec![0x67, 0x44, 0x66, 0x98, 0, 0, 0, 0];
+            reply.extend(handle);
+            match command {
+                // Read.
+                0 => reply.extend(&data[offset..offset + len]),
+                // Write.
+                1 => {
+                    stream.read_exact(&mut data[offset..offset + len]).unwrap()
+                }
+                // Disconnect.
+                2 => return (data, commands),
+                // Flush.
+                3 => {}
+                // Return EINVAL for anything else.
+                _ => reply[7] = 22,
+            }
+            stream.write_all(&reply).unwrap();
+        }
+    });
+    (addr, handle)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_nbd_block_io() {
+    for no_zeroes in [false, true] {
+        let (addr, server) = serve(
+            load_test_disk(),
+            ServerOptions {
+                no_zeroes,
+                // Has flags, and supports flush.
+                transmission_flags: 0b101,
+            },
+        );
+
+        let mut bio =
+            NbdBlockIo::connect(addr, "disk", BlockSize::BS_512).unwrap();
+        assert_eq!(bio.export_size(), 4 * 1024 * 1024);
+        assert!(!bio.is_read_only());
+
+        // Access past the end is rejected without being sent.
+        let mut block_buf = vec![0; 512];
+        assert_eq!(
+            bio.read_blocks(Lba(8192), &mut block_buf)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidInput
+        );
+
+        let mut disk = Disk::new(bio).unwrap();
+        assert_eq!(disk.num_blocks().unwrap(), 8192);
+        let header = disk
+            .read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        assert_eq!(header, create_primary_header());
+
+        // Write and read back.
+        disk.write_protective_mbr(&mut block_buf).unwrap();
+        disk.flush().unwrap();
+        assert_eq!(
+            disk.read_mbr(&mut block_buf).unwrap().signature,
+            [0x55, 0xaa]
+        );
+        drop(disk);
+
+        let (data, commands) = server.join().unwrap();
+        // Read of the primary header, write and flush of the MBR, read
+        // of the MBR, flush when the `Disk` is dropped, then disconnect.
+        assert_eq!(commands, [0, 1, 3, 0, 3, 2]);
+        assert_eq!(data[510..512], [0x55, 0xaa]);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_nbd_block_io_read_only() {
+    let (addr, server) = serve(
+        vec![0; 512 * 4],
+        ServerOptions {
+            no_zeroes: true,
+            // Has flags, and is read-only. Flush is not supported.
+            transmission_flags: 0b011,
+        },
+    );
+    let mut bio = NbdBlockIo::connect(addr, "disk", BlockSize::BS_512).unwrap();
+    assert!(bio.is_read_only());
+    assert_eq!(
+        bio.write_blocks(Lba(0), &[1; 512]).unwrap_err().kind(),
+        ErrorKind::PermissionDenied
+    );
+    // Flush is not sent if the server doesn't support it.
+    bio.flush().unwrap();
+    let mut buf = vec![1; 512 * 4];
+    bio.read_blocks(Lba(0), &mut buf).unwrap();
+    assert_eq!(buf, [0; 512 * 4]);
+    drop(bio);
+
+    let (data, commands) = server.join().unwrap();
+    assert_eq!(commands, [0, 2]);
+    assert_eq!(data, [0; 512 * 4]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_nbd_block_io_export_not_found() {
+    let (addr, server) = serve(
+        vec![0; 512],
+        ServerOptions {
+            no_zeroes: false,
+            transmission_flags: 0b001,
+        },
+    );
+    let err =
+        NbdBlockIo::connect(addr, "missing", BlockSize::BS_512).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    server.join().unwrap();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_nbd_block_io_not_nbd() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n")
+            .unwrap();
+    });
+    let err = NbdBlockIo::connect(addr, "disk", BlockSize::BS_512).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    server.join().unwrap();
+}
@@ -52,3 +52,40 @@ fn test_num_display() {
         "1311768467463790320 123456789abcdef0 0x123456789abcdef0"
     );
 }
+
+#[test]
+fn test_num_native_int() {
+    // Conversions.
+    assert_eq!(U16Le::from(0x1234u16), U16Le::from_u16(0x1234));
+    assert_eq!(u16::from(U16Le::from_u16(0x1234)), 0x1234);
+    assert_eq!(U32Le::from(0x1234u32), U32Le::from_u32(0x1234));
+    assert_eq!(u32::from(U32Le::from_u32(0x1234)), 0x1234);
+    assert_eq!(U64Le::from(0x1234u64), U64Le::from_u64(0x1234));
+    assert_eq!(u64::from(U64Le::from_u64(0x1234)), 0x1234);
+
+    // Comparisons use the integer value, not the byte order.
+    let n = U32Le::from_u32(0x100);
+    assert_eq!(n, 0x100);
+    assert_eq!(0x100, n);
+    assert_ne!(n, 0x1);
+    assert!(n > 0xff);
+    assert!(n < 0x101);
+    assert!(0xff < n);
+    assert!(0x101 > n);
+    assert!(U16Le::from_u16(0x100) > 0xffu16);
+    assert!(U64Le::from_u64(0x100) > 0xffu64);
+
+    // Checked arithmetic.
+    assert_eq!(U16Le::from_u16(1).checked_add(2), Some(U16Le::from_u16(3)));
+    assert_eq!(U16Le::from_u16(u16::MAX).checked_add(1), None);
+    assert_eq!(U16Le::from_u16(3).checked_sub(2), Some(U16Le::from_u16(1)));
+    assert_eq!(U16Le::from_u16(0).checked_sub(1), None);
+    assert_eq!(U32Le::from_u32(1).checked_add(2), Some(U32Le::from_u32(3)));
+    assert_eq!(U32Le::from_u32(u32::MAX).checked_add(1), None);
+    assert_eq!(U32Le::from_u32(3).checked_sub(2), Some(U32Le::from_u32(1)));
+    assert_eq!(U32Le::from_u32(0).checked_sub(1), None);
+    assert_eq!(U64Le::from_u64(1).checked_add(2), Some(U64Le::from_u64(3)));
+    assert_eq!(U64Le::from_u64(u64::MAX).checked_add(1), None);
+    assert_eq!(U64Le::from_u64(3).checked_sub(2), Some(U64Le::from_u64(1)));
+    assert_eq!(U64Le::from_u64(0).checked_sub(1), None);
+}
@@ -0,0 +1,160 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_primary_header, create_secondary_header, load_test_disk};
+use gpt_disk_io::{
+    BlockIo, BlockIoAdapter, Disk, GptHeaderCopy, NestedGpt, OffsetBlockIo,
+    OffsetBlockIoError, SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba};
+
+/// Create a container with the test disk at block 100, surrounded by
+/// non-zero data.
+fn create_container() -> Vec<u8> {
+    let mut container = vec![0xaa; 512 * 100];
+    container.extend(load_test_disk());
+    container.extend(vec![0x55; 512 * 50]);
+    container
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_offset_block_io() {
+    let mut container = create_container();
+    let bs = BlockSize::BS_512;
+
+    let mut bio = OffsetBlockIo::new(
+        BlockIoAdapter::new(container.as_mut_slice(), bs),
+        Lba(100),
+        8192,
+    )
+    .unwrap();
+    assert_eq!(bio.start_lba(), Lba(100));
+    assert_eq!(bio.num_blocks().unwrap(), 8192);
+
+    // Accesses past the end of the range are rejected, even though
+    // they are within the container.
+    let mut block = vec![0; 512];
+    assert_eq!(
+        bio.read_blocks(Lba(8192), &mut block),
+        Err(OffsetBlockIoError::OutOfBounds)
+    );
+    assert_eq!(
+        bio.write_blocks(Lba(8191), &[0; 1024]),
+        Err(OffsetBlockIoError::OutOfBounds)
+    );
+
+    let mut disk = Disk::new(bio).unwrap();
+    let mut block_buf = vec![0; 512];
+    assert_eq!(
+        disk.read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_primary_header()
+    );
+    assert_eq!(
+        disk.read_secondary_gpt_header_verified(&mut block_buf)
+            .unwrap(),
+        create_secondary_header()
+    );
+
+    // Writes go to the right place in the container.
+    disk.write_protective_mbr(&mut block_buf).unwrap();
+    drop(disk);
+    assert_eq!(container[512 * 100 + 510..512 * 101], [0x55, 0xaa]);
+    assert_eq!(container[510..512], [0xaa, 0xaa]);
+}
+
+#[test]
+fn test_offset_block_io_constructors() {
+    let container = vec![0; 512 * 16];
+    let bs = BlockSize::BS_512;
+    let adapter = || BlockIoAdapter::new(container.as_slice(), bs);
+
+    let mut bio = OffsetBlockIo::to_end(adapter(), Lba(4)).unwrap();
+    assert_eq!(bio.num_blocks().unwrap(), 12);
+    assert!(bio.is_read_only());
+    assert_eq!(
+        bio.write_blocks(Lba(0), &[0; 512]),
+        Err(OffsetBlockIoError::Io(SliceBlockIoError::ReadOnly))
+    );
+
+    let mut bio =
+        OffsetBlockIo::from_byte_range(adapter(), 512 * 2, 512 * 3).unwrap();
+    assert_eq!(bio.start_lba(), Lba(2));
+    assert_eq!(bio.num_blocks().unwrap(), 3);
+
+    assert!(matches!(
+        OffsetBlockIo::from_byte_range(adapter(), 100, 512),
+        Err(OffsetBlockIoError::Unaligned)
+    ));
+    assert!(matches!(
+        OffsetBlockIo::new(adapter(), Lba(8), 9),
+        Err(OffsetBlockIoError::OutOfBounds)
+    ));
+    assert!(matches!(
+        OffsetBlockIo::to_end(adapter(), Lba(17)),
+        Err(OffsetBlockIoError::OutOfBounds)
+    ));
+    assert!(matches!(
+        OffsetBlockIo::new(adapter(), Lba(u64::MAX), 2),
+        Err(OffsetBlockIoError::Overflow)
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_find_nested_gpts() {
+    let mut container = create_container();
+    let bs = BlockSize::BS_512;
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(container.as_mut_slice(), bs)).unwrap();
+
+    // Use a buffer that isn't a multiple of the block size, and doesn't
+    // evenly divide the disk.
+    let mut buf = vec![0; 512 * 7 + 100];
+    let mut found = Vec::new();
+    disk.find_nested_gpts(&mut buf, |nested| found.push(nested))
+        .unwrap();
+    assert_eq!(
+        found,
+        [
+            NestedGpt {
+                header: create_primary_header(),
+                header_lba: Lba(101),
+                start_lba: Lba(100),
+                num_blocks: 8192,
+            },
+            NestedGpt {
+                header: create_secondary_header(),
+                header_lba: Lba(100 + 8191),
+                start_lba: Lba(100),
+                num_blocks: 8192,
+            },
+        ]
+    );
+    assert_eq!(found[0].copy(), GptHeaderCopy::Primary);
+    assert_eq!(found[1].copy(), GptHeaderCopy::Secondary);
+
+    // Open the nested disk.
+    drop(disk);
+    let bio = OffsetBlockIo::new(
+        BlockIoAdapter::new(container.as_mut_slice(), bs),
+        found[0].start_lba,
+        found[0].num_blocks,
+    )
+    .unwrap();
+    let mut disk = Disk::new(bio).unwrap();
+    let mut block_buf = vec![0; 512];
+    assert!(disk
+        .read_gpt_headers(&mut block_buf)
+        .unwrap()
+        .primary
+        .is_some());
+}
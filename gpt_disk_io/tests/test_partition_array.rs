@@ -8,9 +8,11 @@
 
 mod common;
 
-use common::check_derives;
+use common::{check_derives, create_partition_entry};
 use gpt_disk_types::{
-    BlockSize, GptPartitionEntryArrayLayout, GptPartitionEntrySize, Lba,
+    BlockSize, GptPartitionEntryArray, GptPartitionEntryArrayBuf,
+    GptPartitionEntryArrayError, GptPartitionEntryArrayLayout,
+    GptPartitionEntrySize, Lba,
 };
 
 #[test]
@@ -43,3 +45,99 @@ fn test_partition_entry_array_layout() {
     );
     assert_eq!(layout.num_bytes_exact_as_usize().unwrap(), 256 * 128);
 }
+
+#[test]
+fn test_partition_entry_array_large_entries() {
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(256).unwrap(),
+        num_entries: 4,
+    };
+    assert_eq!(layout.entry_size.num_reserved_bytes(), 128);
+
+    // Fill the storage with non-zero bytes to check that the reserved
+    // bytes are handled correctly.
+    let mut storage = vec![0xff; 1024];
+    let mut array =
+        GptPartitionEntryArray::new(layout, BlockSize::BS_512, &mut storage)
+            .unwrap();
+
+    let entry = create_partition_entry();
+    array.set_partition_entry(1, &entry).unwrap();
+    assert_eq!(*array.get_partition_entry(1).unwrap(), entry);
+    let bytes = array.get_partition_entry_bytes(1).unwrap();
+    assert_eq!(bytes.len(), 256);
+    assert_eq!(&bytes[..128], bytemuck::bytes_of(&entry));
+    assert!(bytes[128..].iter().all(|b| *b == 0));
+
+    // The neighboring entries are not modified.
+    assert!(array
+        .get_partition_entry_bytes(0)
+        .unwrap()
+        .iter()
+        .all(|b| *b == 0xff));
+    assert!(array
+        .get_partition_entry_bytes(2)
+        .unwrap()
+        .iter()
+        .all(|b| *b == 0xff));
+
+    // Writing through `get_partition_entry_mut` leaves the reserved
+    // bytes alone.
+    *array.get_partition_entry_mut(2).unwrap() = entry;
+    assert!(array.get_partition_entry_bytes(2).unwrap()[128..]
+        .iter()
+        .all(|b| *b == 0xff));
+
+    // The CRC covers the reserved bytes.
+    let crc = array.calculate_crc32();
+    array.get_partition_entry_bytes_mut(2).unwrap()[255] = 0;
+    assert_ne!(array.calculate_crc32(), crc);
+
+    assert_eq!(
+        array.set_partition_entry(4, &entry),
+        Err(GptPartitionEntryArrayError::InvalidIndex)
+    );
+    assert!(array.get_partition_entry_bytes(4).is_none());
+    assert!(array.get_partition_entry_bytes_mut(4).is_none());
+}
+
+#[test]
+fn test_partition_entry_array_buf() {
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size: GptPartitionEntrySize::new(256).unwrap(),
+        num_entries: 3,
+    };
+    let bs = BlockSize::BS_512;
+    let mut buf = GptPartitionEntryArrayBuf::new(layout, bs).unwrap();
+    assert_eq!(buf.storage().len(), 1024);
+    assert!(buf.storage().iter().all(|b| *b == 0));
+    assert_eq!(buf.layout(), &layout);
+
+    let entry = create_partition_entry();
+    buf.set_partition_entry(2, &entry).unwrap();
+    assert_eq!(*buf.get_partition_entry(2).unwrap(), entry);
+    buf.get_partition_entry_mut(0).unwrap().starting_lba = entry.ending_lba;
+    buf.get_partition_entry_bytes_mut(1).unwrap()[255] = 1;
+    assert_eq!(buf.get_partition_entry_bytes(1).unwrap()[255], 1);
+    assert!(buf.get_partition_entry(3).is_none());
+    assert!(buf.get_partition_entry_bytes(3).is_none());
+    assert_eq!(
+        buf.set_partition_entry(3, &entry),
+        Err(GptPartitionEntryArrayError::InvalidIndex)
+    );
+
+    // Same results as a borrowed array with the same contents.
+    let mut storage = buf.storage().to_vec();
+    let array = GptPartitionEntryArray::new(layout, bs, &mut storage).unwrap();
+    assert_eq!(buf.calculate_crc32(), array.calculate_crc32());
+    assert_eq!(
+        buf.as_array_mut().calculate_crc32(),
+        array.calculate_crc32()
+    );
+
+    buf.set_start_lba(Lba(100));
+    assert_eq!(buf.as_array_mut().layout().start_lba, Lba(100));
+    assert_eq!(buf.into_storage(), storage);
+}
@@ -10,9 +10,12 @@ mod common;
 
 use common::check_derives;
 use gpt_disk_types::{
-    GptPartitionAttributes, GptPartitionEntry, GptPartitionName,
-    GptPartitionType, Guid, U16Le, U64Le,
+    guid, BlockSize, GptPartitionAttributes, GptPartitionEntry,
+    GptPartitionEntrySummary, GptPartitionName, GptPartitionNameFromStrError,
+    GptPartitionNameSetCharError, GptPartitionType, Guid, Lba, LbaLe, U16Le,
+    U64Le,
 };
+use std::collections::BTreeSet;
 
 #[test]
 fn test_partition_type() {
@@ -34,6 +37,83 @@ fn test_partition_type() {
     );
 }
 
+#[test]
+fn test_partition_type_lookup() {
+    let all = [
+        GptPartitionType::EFI_SYSTEM,
+        GptPartitionType::LEGACY_MBR,
+        GptPartitionType::BASIC_DATA,
+        GptPartitionType::CHROME_OS_KERNEL,
+        GptPartitionType::CHROME_OS_ROOT_FS,
+        GptPartitionType::MICROSOFT_RESERVED,
+        GptPartitionType::WINDOWS_RECOVERY,
+        GptPartitionType::WINDOWS_LDM_METADATA,
+        GptPartitionType::WINDOWS_LDM_DATA,
+        GptPartitionType::CHROME_OS_RESERVED,
+        GptPartitionType::BIOS_BOOT,
+        GptPartitionType::XBOOTLDR,
+        GptPartitionType::LINUX_FILESYSTEM,
+        GptPartitionType::LINUX_SWAP,
+        GptPartitionType::LINUX_ROOT_X86_64,
+        GptPartitionType::LINUX_ROOT_ARM64,
+        GptPartitionType::LINUX_HOME,
+        GptPartitionType::LINUX_SRV,
+        GptPartitionType::LINUX_VAR,
+        GptPartitionType::LINUX_LVM,
+        GptPartitionType::LINUX_RAID,
+        GptPartitionType::LINUX_LUKS,
+        GptPartitionType::LINUX_DM_CRYPT,
+        GptPartitionType::LINUX_RESERVED,
+        GptPartitionType::FREEBSD_BOOT,
+        GptPartitionType::FREEBSD_DISKLABEL,
+        GptPartitionType::FREEBSD_SWAP,
+        GptPartitionType::FREEBSD_UFS,
+        GptPartitionType::FREEBSD_ZFS,
+        GptPartitionType::APPLE_HFS_PLUS,
+        GptPartitionType::APPLE_APFS,
+        GptPartitionType::APPLE_BOOT,
+    ];
+
+    // Every constant has a unique GUID, name, and code, and the code
+    // maps back to the constant.
+    let mut names = BTreeSet::new();
+    let mut codes = BTreeSet::new();
+    let guids: BTreeSet<_> = all.iter().collect();
+    assert_eq!(guids.len(), all.len());
+    for ptype in all {
+        assert!(names.insert(ptype.name().unwrap()));
+        let code = ptype.sgdisk_code().unwrap();
+        assert!(codes.insert(code));
+        assert_eq!(GptPartitionType::from_sgdisk_code(code), Some(ptype));
+    }
+
+    assert_eq!(
+        GptPartitionType::EFI_SYSTEM.name(),
+        Some("EFI system partition")
+    );
+    assert_eq!(GptPartitionType::EFI_SYSTEM.sgdisk_code(), Some(0xef00));
+    assert_eq!(
+        GptPartitionType::from_sgdisk_code(0x8300),
+        Some(GptPartitionType::LINUX_FILESYSTEM)
+    );
+    assert_eq!(
+        GptPartitionType::from_sgdisk_code(0xef02),
+        Some(GptPartitionType::BIOS_BOOT)
+    );
+    assert_eq!(
+        GptPartitionType::LINUX_FILESYSTEM.to_string(),
+        "0fc63daf-8483-4772-8e79-3d69d8477de4"
+    );
+
+    // Unknown types.
+    assert_eq!(GptPartitionType::UNUSED.name(), None);
+    assert_eq!(GptPartitionType::UNUSED.sgdisk_code(), None);
+    let unknown = GptPartitionType(Guid::from_bytes([1; 16]));
+    assert_eq!(unknown.name(), None);
+    assert_eq!(unknown.sgdisk_code(), None);
+    assert_eq!(GptPartitionType::from_sgdisk_code(0x1234), None);
+}
+
 #[test]
 fn test_required_partition_attribute() {
     check_derives::<GptPartitionAttributes>();
@@ -86,6 +166,108 @@ fn test_type_specific_attributes() {
     assert_eq!(attr.type_specific_attributes().to_u16(), 0xabcd);
 }
 
+#[test]
+fn test_type_specific_bit() {
+    let mut attr =
+        GptPartitionAttributes(U64Le::from_u64(0x8001_0000_0000_0007));
+    assert!(attr.type_specific_bit(0));
+    assert!(!attr.type_specific_bit(1));
+    assert!(attr.type_specific_bit(15));
+
+    attr.update_type_specific_bit(0, false);
+    attr.update_type_specific_bit(9, true);
+    assert_eq!(attr.0.to_u64(), 0x8200_0000_0000_0007);
+
+    // Setting a bit that is already set has no effect.
+    attr.update_type_specific_bit(9, true);
+    assert_eq!(attr.0.to_u64(), 0x8200_0000_0000_0007);
+}
+
+#[test]
+#[should_panic(expected = "type-specific bit out of range")]
+fn test_type_specific_bit_out_of_range() {
+    let _ = GptPartitionAttributes::default().type_specific_bit(16);
+}
+
+#[test]
+fn test_cros_attributes() {
+    let mut attr =
+        GptPartitionAttributes(U64Le::from_u64(0x8000_0000_0000_0001));
+    assert_eq!(attr.cros_priority(), 0);
+    assert_eq!(attr.cros_tries(), 0);
+    assert!(!attr.cros_successful());
+
+    assert!(attr.update_cros_priority(15));
+    assert!(attr.update_cros_tries(6));
+    attr.update_cros_successful(true);
+    assert_eq!(attr.cros_priority(), 15);
+    assert_eq!(attr.cros_tries(), 6);
+    assert!(attr.cros_successful());
+    // Other bits are unchanged.
+    assert_eq!(attr.0.to_u64(), 0x816f_0000_0000_0001);
+
+    // Out of range.
+    assert!(!attr.update_cros_priority(16));
+    assert!(!attr.update_cros_tries(16));
+    assert_eq!(attr.0.to_u64(), 0x816f_0000_0000_0001);
+
+    assert!(attr.update_cros_priority(1));
+    attr.update_cros_successful(false);
+    assert_eq!(attr.0.to_u64(), 0x8061_0000_0000_0001);
+}
+
+#[test]
+fn test_android_attributes() {
+    let mut attr = GptPartitionAttributes::default();
+    assert_eq!(attr.android_priority(), 0);
+    assert!(!attr.android_active());
+    assert_eq!(attr.android_retry_count(), 0);
+    assert!(!attr.android_successful());
+    assert!(!attr.android_unbootable());
+
+    assert!(attr.update_android_priority(3));
+    attr.update_android_active(true);
+    assert!(attr.update_android_retry_count(7));
+    assert_eq!(attr.0.to_u64(), 0x003f_0000_0000_0000);
+    assert_eq!(attr.android_priority(), 3);
+    assert!(attr.android_active());
+    assert_eq!(attr.android_retry_count(), 7);
+
+    attr.update_android_successful(true);
+    attr.update_android_unbootable(true);
+    assert!(attr.android_successful());
+    assert!(attr.android_unbootable());
+    assert_eq!(attr.0.to_u64(), 0x00ff_0000_0000_0000);
+
+    // Out of range.
+    assert!(!attr.update_android_priority(4));
+    assert!(!attr.update_android_retry_count(8));
+    assert_eq!(attr.0.to_u64(), 0x00ff_0000_0000_0000);
+
+    assert!(attr.update_android_retry_count(2));
+    attr.update_android_active(false);
+    assert_eq!(attr.0.to_u64(), 0x00d3_0000_0000_0000);
+}
+
+#[test]
+fn test_partition_attributes_builder() {
+    let attr = GptPartitionAttributes::default()
+        .with_required_partition(true)
+        .with_no_block_io_protocol(true)
+        .with_legacy_bios_bootable(true);
+    assert_eq!(attr.0.to_u64(), 0x7);
+
+    let attr = attr
+        .with_no_block_io_protocol(false)
+        .with_type_specific_attributes(U16Le::from_u16(0x1234))
+        .with_type_specific_bit(15, true)
+        .with_type_specific_bit(2, false);
+    assert_eq!(attr.0.to_u64(), 0x9230_0000_0000_0005);
+    assert!(attr.required_partition());
+    assert!(!attr.no_block_io_protocol());
+    assert!(attr.legacy_bios_bootable());
+}
+
 #[test]
 fn test_partition_attribute_display() {
     let mut attr = GptPartitionAttributes(U64Le::from_u64(0));
@@ -140,9 +322,172 @@ fn test_partition_name() {
         name.0[i] = if (i % 2) == 0 { b'a' } else { 0 };
     }
     assert_eq!(name.to_string(), "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    assert_eq!(name.len_chars(), 36);
+
+    // The last character can be set, but not one past the end.
+    name.set_char(35, 'z').unwrap();
+    assert_eq!(name.chars().last(), Some('z'));
+    assert_eq!(
+        name.set_char(36, 'z'),
+        Err(GptPartitionNameSetCharError::Index)
+    );
+}
+
+#[test]
+fn test_partition_name_len_chars() {
+    assert_eq!(GptPartitionName::default().len_chars(), 0);
+
+    let name: GptPartitionName = "hello".parse().unwrap();
+    assert_eq!(name.len_chars(), 5);
+
+    // Data after the null terminator is ignored.
+    let mut name = name;
+    name.0[12] = b'x';
+    assert_eq!(name.len_chars(), 5);
+    assert_eq!(name.to_string(), "hello");
+}
+
+#[test]
+fn test_partition_name_chars_utf16_lossy() {
+    let mut name = GptPartitionName::default();
+    // "a", then U+1F600 as a surrogate pair, then an unpaired low
+    // surrogate, then "b".
+    for (i, c) in [0x0061, 0xd83d, 0xde00, 0xde00, 0x0062].iter().enumerate() {
+        name.0[i * 2..i * 2 + 2].copy_from_slice(&u16::to_le_bytes(*c));
+    }
+
+    assert_eq!(
+        name.chars_utf16_lossy().collect::<String>(),
+        "a\u{1f600}\u{fffd}b"
+    );
+    // UCS-2 decoding replaces each surrogate.
+    assert_eq!(name.to_string(), "a\u{fffd}\u{fffd}\u{fffd}b");
+    assert_eq!(name.len_chars(), 5);
+}
+
+#[test]
+fn test_partition_name_set_from_str() {
+    let mut name = GptPartitionName::default();
+
+    assert_eq!(name.set_from_str("abc"), Ok(false));
+    assert_eq!(name.to_string(), "abc");
+    assert_eq!(name, "abc".parse().unwrap());
+
+    // Setting a shorter name clears the rest.
+    assert_eq!(name.set_from_str("d"), Ok(false));
+    assert_eq!(name, "d".parse().unwrap());
+
+    // Maximum length.
+    let s = "a".repeat(35);
+    assert_eq!(name.set_from_str(&s), Ok(false));
+    assert_eq!(name.to_string(), s);
+    assert_eq!(name, s.parse().unwrap());
+
+    // Too long.
+    let s = "b".repeat(40);
+    assert_eq!(name.set_from_str(&s), Ok(true));
+    assert_eq!(name.to_string(), "b".repeat(35));
+    assert_eq!(name.len_chars(), 35);
+
+    // Invalid characters leave the name unchanged, unless they are
+    // past the truncation point.
+    assert_eq!(
+        name.set_from_str("abc\u{1f600}"),
+        Err(GptPartitionNameFromStrError::InvalidChar)
+    );
+    assert_eq!(name.to_string(), "b".repeat(35));
+    let s = format!("{}\u{1f600}", "c".repeat(35));
+    assert_eq!(name.set_from_str(&s), Ok(true));
+    assert_eq!(name.to_string(), "c".repeat(35));
 }
 
 #[test]
 fn test_partition_entry() {
     check_derives::<GptPartitionEntry>();
 }
+
+#[test]
+fn test_partition_entry_size() {
+    let entry = |start, end| GptPartitionEntry {
+        starting_lba: LbaLe::from_u64(start),
+        ending_lba: LbaLe::from_u64(end),
+        ..Default::default()
+    };
+
+    // The ending LBA is inclusive.
+    let e = entry(2048, 4095);
+    assert_eq!(e.num_blocks(), Some(2048));
+    assert_eq!(e.num_bytes(BlockSize::BS_512), Some(1024 * 1024));
+    assert_eq!(e.num_bytes(BlockSize::BS_4096), Some(8 * 1024 * 1024));
+    assert!(!e.contains_lba(Lba(2047)));
+    assert!(e.contains_lba(Lba(2048)));
+    assert!(e.contains_lba(Lba(4095)));
+    assert!(!e.contains_lba(Lba(4096)));
+
+    // A single-block partition.
+    let e = entry(34, 34);
+    assert_eq!(e.num_blocks(), Some(1));
+    assert_eq!(e.num_bytes(BlockSize::BS_512), Some(512));
+    assert!(e.contains_lba(Lba(34)));
+
+    // Invalid range.
+    let e = entry(10, 9);
+    assert_eq!(e.num_blocks(), None);
+    assert_eq!(e.num_bytes(BlockSize::BS_512), None);
+    assert!(!e.contains_lba(Lba(9)));
+    assert!(!e.contains_lba(Lba(10)));
+
+    // Overflow.
+    let e = entry(0, u64::MAX);
+    assert_eq!(e.num_blocks(), None);
+    assert!(e.contains_lba(Lba(u64::MAX)));
+    let e = entry(1, u64::MAX);
+    assert_eq!(e.num_blocks(), Some(u64::MAX));
+    assert_eq!(e.num_bytes(BlockSize::BS_512), None);
+}
+
+#[test]
+fn test_partition_entry_summary() {
+    let summary = |start, end, partition_type, bs| {
+        let entry = GptPartitionEntry {
+            partition_type_guid: partition_type,
+            starting_lba: LbaLe::from_u64(start),
+            ending_lba: LbaLe::from_u64(end),
+            name: "data".parse().unwrap(),
+            ..Default::default()
+        };
+        entry.display_summary(12, bs).to_string()
+    };
+
+    assert_eq!(
+        GptPartitionEntrySummary::HEADER,
+        "Number  Start (sector)    End (sector)        Size  Type                   Name"
+    );
+    assert_eq!(
+        summary(34, 34, GptPartitionType::LINUX_SWAP, BlockSize::BS_512),
+        "    12              34              34   512 bytes  Linux swap             data"
+    );
+    assert_eq!(
+        summary(2048, 4095, GptPartitionType::LINUX_FILESYSTEM, BlockSize::BS_512),
+        "    12            2048            4095     1.0 MiB  Linux filesystem       data"
+    );
+    assert_eq!(
+        summary(256, 262399, GptPartitionType::BASIC_DATA, BlockSize::BS_4096),
+        "    12             256          262399     1.0 GiB  Microsoft basic data   data"
+    );
+    // Sizes are rounded to the nearest tenth.
+    assert_eq!(
+        summary(0, 3071, GptPartitionType::BASIC_DATA, BlockSize::BS_512),
+        "    12               0            3071     1.5 MiB  Microsoft basic data   data"
+    );
+    // Unknown types are shown as a GUID.
+    assert_eq!(
+        summary(
+            10,
+            9,
+            GptPartitionType(guid!("01234567-89ab-cdef-0123-456789abcdef")),
+            BlockSize::BS_512
+        ),
+        "    12              10               9     invalid  01234567-89ab-cdef-0123-456789abcdef  data"
+    );
+}
@@ -0,0 +1,46 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use gpt_disk_types::strategy::valid_gpt_header;
+use gpt_disk_types::{
+    BlockSize, GptHeader, GptHeaderRevision, GptPartitionEntry,
+};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_valid_gpt_header(header in valid_gpt_header()) {
+        prop_assert!(header.is_signature_valid());
+        prop_assert_eq!(header.revision, GptHeaderRevision::VERSION_1_0);
+        prop_assert_eq!(header.header_size.to_u32(), 92);
+        prop_assert_eq!(header.reserved.to_u32(), 0);
+        prop_assert_eq!(header.calculate_header_crc32(), header.header_crc32);
+
+        let layout = header.get_partition_entry_array_layout().unwrap();
+        let array_blocks = layout.num_blocks(BlockSize::BS_512).unwrap();
+        let first_usable = header.first_usable_lba.to_u64();
+        let last_usable = header.last_usable_lba.to_u64();
+        let alternate = header.alternate_lba.to_u64();
+        prop_assert_eq!(header.my_lba.to_u64(), 1);
+        prop_assert_eq!(layout.start_lba.to_u64() + array_blocks, first_usable);
+        prop_assert!(first_usable <= last_usable);
+        prop_assert_eq!(last_usable + array_blocks + 1, alternate);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_arbitrary_header_and_entry(
+        header in any::<GptHeader>(),
+        entry in any::<GptPartitionEntry>(),
+    ) {
+        // Formatting arbitrary data must not panic.
+        let _ = header.to_string();
+        let _ = entry.to_string();
+    }
+}
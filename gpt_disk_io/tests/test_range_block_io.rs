@@ -0,0 +1,375 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_primary_header, load_test_disk};
+use gpt_disk_io::{
+    BlockIo, Disk, DiskError, RangeBlockIo, RangeBlockIoError, RangeReader,
+    SliceBlockIoError,
+};
+use gpt_disk_types::{BlockSize, Lba};
+
+#[cfg(feature = "alloc")]
+use gpt_disk_io::{SeekableZstdBlockIo, SeekableZstdError, ZstdFrameDecoder};
+
+#[cfg(feature = "std")]
+use {
+    gpt_disk_io::HttpRangeReader,
+    std::io::{BufRead, BufReader, Write},
+    std::net::TcpListener,
+    std::thread,
+};
+
+/// In-memory reader that counts the number of reads.
+struct TestReader {
+    data: Vec<u8>,
+    reads: usize,
+}
+
+impl RangeReader for TestReader {
+    type Error = SliceBlockIoError;
+
+    fn size(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.data.len().try_into().unwrap())
+    }
+
+    fn read_range(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.reads += 1;
+        let start = usize::try_from(offset).unwrap();
+        dst.copy_from_slice(&self.data[start..start + dst.len()]);
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_range_block_io() {
+    let reader = TestReader {
+        data: load_test_disk(),
+        reads: 0,
+    };
+    let mut bio = RangeBlockIo::new(reader, BlockSize::BS_512);
+    assert_eq!(bio.num_blocks().unwrap(), 8192);
+    assert_eq!(
+        bio.write_blocks(Lba(0), &[0; 512]),
+        Err(RangeBlockIoError::ReadOnly)
+    );
+
+    let mut disk = Disk::new(&mut bio).unwrap();
+    let mut block_buf = vec![0; 512];
+    let header = disk.read_primary_gpt_header(&mut block_buf).unwrap();
+    assert_eq!(header, create_primary_header());
+    let mut array_buf = vec![0; 512 * 32];
+    disk.read_gpt_partition_entry_array(
+        header.get_partition_entry_array_layout().unwrap(),
+        &mut array_buf,
+    )
+    .unwrap();
+    // The disk reports that it is read-only, so `Disk` doesn't attempt
+    // the write.
+    assert!(disk.is_read_only());
+    assert!(matches!(
+        disk.write_protective_mbr(&mut block_buf),
+        Err(DiskError::ReadOnly)
+    ));
+    drop(disk);
+
+    // One read for the header, and one for the whole partition entry
+    // array.
+    assert_eq!(bio.reader().reads, 2);
+}
+
+/// Serve `data` over HTTP, handling `num_requests` range requests.
+///
+/// Requests with a `Host` header that does not include the port are
+/// rejected. Each response starts `shift` bytes after the requested
+/// range, as a misbehaving server might.
+#[cfg(feature = "std")]
+fn serve(data: Vec<u8>, num_requests: usize, shift: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let url = format!("http://{addr}/disk.img");
+    thread::spawn(move || {
+        for stream in listener.incoming().take(num_requests) {
+            let mut stream = stream.unwrap();
+            let mut range = None;
+            let mut host = None;
+            let mut reader = BufReader::new(&mut stream);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Range: bytes=") {
+                    let (first, last) = value.split_once('-').unwrap();
+                    let first: usize = first.parse().unwrap();
+                    let last: usize = last.parse().unwrap();
+                    range = Some(first + shift..last + 1 + shift);
+                } else if let Some(value) = line.strip_prefix("Host: ") {
+                    host = Some(value.to_owned());
+                }
+            }
+            if host != Some(addr.to_string()) {
+                write!(stream, "HTTP/1.1 400 Bad Request\r\n\r\n").unwrap();
+                continue;
+            }
+            let range = range.unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                range.len(),
+                range.start,
+                range.end - 1,
+                data.len()
+            )
+            .unwrap();
+            stream.write_all(&data[range]).unwrap();
+        }
+    });
+    url
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_http_range_reader() {
+    // One request for the size, one for the header, one for the
+    // partition entry. The server checks that the `Host` header
+    // includes the non-default port.
+    let url = serve(load_test_disk(), 3, 0);
+
+    let reader = HttpRangeReader::new(&url).unwrap();
+    let mut disk =
+        Disk::new(RangeBlockIo::new(reader, BlockSize::BS_512)).unwrap();
+    assert_eq!(disk.num_blocks().unwrap(), 8192);
+
+    let mut block_buf = vec![0; 512];
+    let header = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!(header, create_primary_header());
+    let entry = disk
+        .read_gpt_partition_entry(
+            header.get_partition_entry_array_layout().unwrap(),
+            0,
+            &mut block_buf,
+        )
+        .unwrap();
+    assert_eq!(entry, common::create_partition_entry());
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_http_range_reader_wrong_range() {
+    // The server responds with the right length, but from a different
+    // offset than requested.
+    let url = serve(load_test_disk(), 1, 512);
+
+    let mut reader = HttpRangeReader::new(&url).unwrap();
+    let mut buf = vec![0; 512];
+    let err = reader.read_range(512, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "Content-Range does not match the requested range"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_http_range_reader_url() {
+    assert!(HttpRangeReader::new("https://example.com/disk.img").is_err());
+    assert!(HttpRangeReader::new("http://:80/disk.img").is_err());
+    assert!(HttpRangeReader::new("http://example.com:x/").is_err());
+    assert!(HttpRangeReader::new("http://example.com").is_ok());
+}
+
+/// Test "decoder" for frames that are stored XORed with 0x5a rather
+/// than compressed.
+#[cfg(feature = "alloc")]
+struct XorDecoder;
+
+#[cfg(feature = "alloc")]
+impl ZstdFrameDecoder for XorDecoder {
+    type Error = SliceBlockIoError;
+
+    fn decode_frame(
+        &mut self,
+        src: &[u8],
+        dst: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        if src.len() != dst.len() {
+            return Err(SliceBlockIoError::Overflow);
+        }
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = s ^ 0x5a;
+        }
+        Ok(())
+    }
+}
+
+/// Split `data` into frames of the given sizes and append a seek table.
+#[cfg(feature = "alloc")]
+fn create_seekable(
+    data: &[u8],
+    frame_sizes: &[usize],
+    checksums: bool,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    for size in frame_sizes {
+        out.extend(data[offset..offset + size].iter().map(|b| b ^ 0x5a));
+        let size = u32::try_from(*size).unwrap();
+        entries.extend(size.to_le_bytes());
+        entries.extend(size.to_le_bytes());
+        if checksums {
+            entries.extend([0; 4]);
+        }
+        offset += usize::try_from(size).unwrap();
+    }
+    assert_eq!(offset, data.len());
+
+    let num_frames = u32::try_from(frame_sizes.len()).unwrap();
+    let table_size = u32::try_from(entries.len() + 9).unwrap();
+    out.extend(0x184d_2a5e_u32.to_le_bytes());
+    out.extend(table_size.to_le_bytes());
+    out.extend(entries);
+    out.extend(num_frames.to_le_bytes());
+    out.push(if checksums { 0x80 } else { 0 });
+    out.extend(0x8f92_eab1_u32.to_le_bytes());
+    out
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_seekable_zstd_block_io() {
+    let data = load_test_disk();
+    // Frames that don't line up with blocks, including an empty frame.
+    let frame_sizes = [1000, 0, 100_000, 3_000_000, 1_093_304];
+
+    for checksums in [false, true] {
+        let reader = TestReader {
+            data: create_seekable(&data, &frame_sizes, checksums),
+            reads: 0,
+        };
+        let mut bio =
+            SeekableZstdBlockIo::new(reader, XorDecoder, BlockSize::BS_512)
+                .unwrap();
+        assert_eq!(bio.num_frames(), 5);
+        assert_eq!(bio.decompressed_size(), 4 * 1024 * 1024);
+        assert_eq!(bio.num_blocks().unwrap(), 8192);
+        assert_eq!(
+            bio.write_blocks(Lba(0), &[0; 512]),
+            Err(SeekableZstdError::ReadOnly)
+        );
+
+        // The header straddles the first two non-empty frames, and the
+        // partition entry array is in the second.
+        let mut disk = Disk::new(&mut bio).unwrap();
+        let mut block_buf = vec![0; 512];
+        let header = disk
+            .read_primary_gpt_header_verified(&mut block_buf)
+            .unwrap();
+        assert_eq!(header, create_primary_header());
+        let mut array_buf = vec![0; 512 * 32];
+        disk.read_gpt_partition_entry_array(
+            header.get_partition_entry_array_layout().unwrap(),
+            &mut array_buf,
+        )
+        .unwrap();
+        drop(disk);
+        assert_eq!(bio.frames_decoded(), 2);
+
+        // Read across frame boundaries.
+        let mut buf = vec![0; 512 * 300];
+        bio.read_blocks(Lba(1), &mut buf).unwrap();
+        assert_eq!(buf, data[512..512 * 301]);
+        let mut buf = vec![0; 512 * 8192];
+        bio.read_blocks(Lba(0), &mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        // Reading the cached frame does not decode it again.
+        let decoded = bio.frames_decoded();
+        bio.read_blocks(Lba(8191), &mut block_buf).unwrap();
+        assert_eq!(bio.frames_decoded(), decoded);
+
+        assert_eq!(
+            bio.read_blocks(Lba(8192), &mut block_buf),
+            Err(SeekableZstdError::OutOfBounds)
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_seekable_zstd_block_io_invalid() {
+    let new_bio = |data| {
+        SeekableZstdBlockIo::new(
+            TestReader { data, reads: 0 },
+            XorDecoder,
+            BlockSize::BS_512,
+        )
+    };
+    let valid = create_seekable(&[1; 1024], &[512, 512], false);
+    assert_eq!(new_bio(valid.clone()).unwrap().num_frames(), 2);
+
+    // Too short for a footer.
+    assert!(matches!(
+        new_bio(vec![0; 8]),
+        Err(SeekableZstdError::InvalidSeekTable)
+    ));
+
+    // Bad magic numbers and reserved bits.
+    for (index, value) in [
+        (valid.len() - 1, 0),
+        (valid.len() - 5, 0x04),
+        (1024, 0),
+        (1028, 0),
+    ] {
+        let mut data = valid.clone();
+        data[index] = value;
+        assert!(matches!(
+            new_bio(data),
+            Err(SeekableZstdError::InvalidSeekTable)
+        ));
+    }
+
+    // Frames don't fill the space before the seek table.
+    let mut data = vec![0];
+    data.extend(&valid);
+    assert!(matches!(
+        new_bio(data),
+        Err(SeekableZstdError::InvalidSeekTable)
+    ));
+
+    // Decoder errors are passed through.
+    let mut data = valid;
+    // Claim the first frame decompresses to 513 bytes.
+    data[1024 + 8 + 5] = 0x02;
+    data[1024 + 8 + 4] = 0x01;
+    let mut bio = new_bio(data).unwrap();
+    let mut buf = vec![0; 512];
+    assert_eq!(
+        bio.read_blocks(Lba(0), &mut buf),
+        Err(SeekableZstdError::Decoder(SliceBlockIoError::Overflow))
+    );
+    assert_eq!(
+        bio.read_blocks(Lba(2), &mut buf),
+        Err(SeekableZstdError::OutOfBounds)
+    );
+}
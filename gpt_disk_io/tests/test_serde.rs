@@ -0,0 +1,189 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_partition_entry, create_primary_header};
+use gpt_disk_types::{
+    Chs, Crc32, GptPartitionAttributes, GptPartitionName, GptPartitionType,
+    Lba, LbaLe, MbrPartitionRecord, U16Le, U32Le, U64Le,
+};
+use serde_test::{Configure, Token};
+
+#[test]
+fn test_serde_numbers() {
+    serde_test::assert_tokens(&U16Le::from_u16(0x1234), &[Token::U16(0x1234)]);
+    serde_test::assert_tokens(
+        &U32Le::from_u32(0x1234_5678),
+        &[Token::U32(0x1234_5678)],
+    );
+    serde_test::assert_tokens(
+        &U64Le::from_u64(1 << 40),
+        &[Token::U64(1 << 40)],
+    );
+    serde_test::assert_tokens(&Lba(34), &[Token::U64(34)]);
+    serde_test::assert_tokens(&LbaLe::from_u64(34), &[Token::U64(34)]);
+    serde_test::assert_tokens(
+        &Crc32(U32Le::from_u32(0xa4877843)),
+        &[Token::U32(0xa4877843)],
+    );
+    serde_test::assert_tokens(
+        &GptPartitionAttributes(U64Le::from_u64(1 << 60)),
+        &[Token::U64(1 << 60)],
+    );
+}
+
+#[test]
+fn test_serde_partition_name() {
+    let name: GptPartitionName = "hello world!".parse().unwrap();
+
+    serde_test::assert_tokens(&name.readable(), &[Token::Str("hello world!")]);
+    // The tokens must be `'static`.
+    let bytes: &'static [u8] = Box::leak(Box::new(name.0));
+    serde_test::assert_tokens(&name.compact(), &[Token::Bytes(bytes)]);
+
+    // Bytes as a sequence.
+    let mut tokens = vec![Token::Seq { len: Some(72) }];
+    tokens.extend(name.0.iter().map(|b| Token::U8(*b)));
+    tokens.push(Token::SeqEnd);
+    serde_test::assert_de_tokens(&name.compact(), &tokens);
+
+    serde_test::assert_de_tokens_error::<serde_test::Readable<GptPartitionName>>(
+        &[Token::Str("abcdefghijklmnopqrstuvwxyzabcdefghijk")],
+        "input string is too long",
+    );
+    serde_test::assert_de_tokens_error::<serde_test::Compact<GptPartitionName>>(
+        &[Token::Bytes(&[1, 2, 3])],
+        "invalid length 3, expected a partition name string or 72 bytes",
+    );
+}
+
+#[test]
+fn test_serde_partition_entry() {
+    let entry = create_partition_entry();
+
+    serde_test::assert_tokens(
+        &entry.readable(),
+        &[
+            Token::Struct {
+                name: "GptPartitionEntry",
+                len: 6,
+            },
+            Token::Str("partition_type_guid"),
+            Token::Str("ccf0994f-f7e0-4e26-a011-843e38aa2eac"),
+            Token::Str("unique_partition_guid"),
+            Token::Str("37c75ffd-8932-467a-9c56-8cf1f0456b12"),
+            Token::Str("starting_lba"),
+            Token::U64(2048),
+            Token::Str("ending_lba"),
+            Token::U64(4096),
+            Token::Str("attributes"),
+            Token::U64(0),
+            Token::Str("name"),
+            Token::Str("hello world!"),
+            Token::StructEnd,
+        ],
+    );
+
+    serde_test::assert_tokens(
+        &GptPartitionType::EFI_SYSTEM.readable(),
+        &[Token::Str("c12a7328-f81f-11d2-ba4b-00a0c93ec93b")],
+    );
+    const BYTES: [u8; 16] = GptPartitionType::EFI_SYSTEM.0.to_bytes();
+    serde_test::assert_tokens(
+        &GptPartitionType::EFI_SYSTEM.compact(),
+        &[Token::Bytes(&BYTES)],
+    );
+}
+
+#[test]
+fn test_serde_header() {
+    let header = create_primary_header();
+
+    serde_test::assert_tokens(
+        &header.readable(),
+        &[
+            Token::Struct {
+                name: "GptHeader",
+                len: 14,
+            },
+            Token::Str("signature"),
+            Token::U64(0x5452415020494645),
+            Token::Str("revision"),
+            Token::U32(0x0001_0000),
+            Token::Str("header_size"),
+            Token::U32(92),
+            Token::Str("header_crc32"),
+            Token::U32(0xa4877843),
+            Token::Str("reserved"),
+            Token::U32(0),
+            Token::Str("my_lba"),
+            Token::U64(1),
+            Token::Str("alternate_lba"),
+            Token::U64(8191),
+            Token::Str("first_usable_lba"),
+            Token::U64(34),
+            Token::Str("last_usable_lba"),
+            Token::U64(8158),
+            Token::Str("disk_guid"),
+            Token::Str("57a7feb6-8cd5-4922-b7bd-c78b0914e870"),
+            Token::Str("partition_entry_lba"),
+            Token::U64(2),
+            Token::Str("number_of_partition_entries"),
+            Token::U32(128),
+            Token::Str("size_of_partition_entry"),
+            Token::U32(128),
+            Token::Str("partition_entry_array_crc32"),
+            Token::U32(0x9206adff),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_mbr_partition_record() {
+    let record = MbrPartitionRecord {
+        boot_indicator: 0x80,
+        start_chs: Chs([0, 2, 0]),
+        os_indicator: 0x0c,
+        end_chs: Chs::MAX,
+        starting_lba: U32Le::from_u32(2048),
+        size_in_lba: U32Le::from_u32(4096),
+    };
+
+    serde_test::assert_tokens(
+        &record,
+        &[
+            Token::Struct {
+                name: "MbrPartitionRecord",
+                len: 6,
+            },
+            Token::Str("boot_indicator"),
+            Token::U8(0x80),
+            Token::Str("start_chs"),
+            Token::Tuple { len: 3 },
+            Token::U8(0),
+            Token::U8(2),
+            Token::U8(0),
+            Token::TupleEnd,
+            Token::Str("os_indicator"),
+            Token::U8(0x0c),
+            Token::Str("end_chs"),
+            Token::Tuple { len: 3 },
+            Token::U8(0xff),
+            Token::U8(0xff),
+            Token::U8(0xff),
+            Token::TupleEnd,
+            Token::Str("starting_lba"),
+            Token::U32(2048),
+            Token::Str("size_in_lba"),
+            Token::U32(4096),
+            Token::StructEnd,
+        ],
+    );
+}
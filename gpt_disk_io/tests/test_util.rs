@@ -0,0 +1,104 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "test_util")]
+
+use gpt_disk_io::test_util::{
+    build_test_disk, corrupt_test_disk, test_partition_entry, valid_test_disk,
+    Corruption, TestDiskSpec, TEST_DISK_GUID,
+};
+use gpt_disk_io::validate::{check_disk, Finding};
+use gpt_disk_io::{BlockIoAdapter, Disk, GptHeaderCopy};
+use gpt_disk_types::{BlockSize, Lba};
+
+fn findings(storage: &mut [u8], block_size: BlockSize) -> Vec<Finding> {
+    let mut disk = Disk::new(BlockIoAdapter::new(storage, block_size)).unwrap();
+    let mut block_buf = vec![0; block_size.to_usize().unwrap()];
+    let mut findings = Vec::new();
+    check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+    findings
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_build_test_disk() {
+    let mut storage = valid_test_disk();
+    assert_eq!(storage.len(), 4 * 1024 * 1024);
+    assert_eq!(findings(&mut storage, BlockSize::BS_512), []);
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let mut block_buf = vec![0; 512];
+    let header = disk
+        .read_primary_gpt_header_verified(&mut block_buf)
+        .unwrap();
+    assert_eq!({ header.disk_guid }, TEST_DISK_GUID);
+    assert_eq!(header.first_usable_lba.to_u64(), 34);
+    assert_eq!(header.last_usable_lba.to_u64(), 8158);
+    let layout = header.get_partition_entry_array_layout().unwrap();
+    assert_eq!(
+        disk.read_gpt_partition_entry(layout, 1, &mut block_buf)
+            .unwrap(),
+        test_partition_entry(1, Lba(4096), Lba(6143))
+    );
+    assert!(!disk
+        .read_gpt_partition_entry(layout, 2, &mut block_buf)
+        .unwrap()
+        .is_used());
+
+    // Other block sizes and array sizes.
+    let spec = TestDiskSpec {
+        block_size: BlockSize::BS_4096,
+        num_blocks: 64,
+        num_partition_entries: 4,
+        partitions: vec![test_partition_entry(0, Lba(8), Lba(15))],
+        ..TestDiskSpec::default()
+    };
+    let mut storage = build_test_disk(&spec);
+    assert_eq!(storage.len(), 4096 * 64);
+    assert_eq!(findings(&mut storage, BlockSize::BS_4096), []);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_corrupt_test_disk() {
+    for corruption in [
+        Corruption::ProtectiveMbr,
+        Corruption::PrimaryHeader,
+        Corruption::SecondaryHeader,
+        Corruption::PrimaryPartitionEntryArray,
+        Corruption::SecondaryPartitionEntryArray,
+    ] {
+        let mut storage = valid_test_disk();
+        corrupt_test_disk(&mut storage, BlockSize::BS_512, corruption);
+        assert_ne!(
+            findings(&mut storage, BlockSize::BS_512),
+            [],
+            "{corruption:?}"
+        );
+    }
+
+    // The secondary header is used if the primary header is corrupt.
+    let mut storage = valid_test_disk();
+    corrupt_test_disk(
+        &mut storage,
+        BlockSize::BS_512,
+        Corruption::PrimaryHeader,
+    );
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let mut block_buf = vec![0; 512];
+    let (_, copy) = disk.read_gpt_header_with_fallback(&mut block_buf).unwrap();
+    assert_eq!(copy, GptHeaderCopy::Secondary);
+}
@@ -0,0 +1,359 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_primary_header, create_secondary_header, load_test_disk};
+use gpt_disk_io::validate::{check_disk, Finding, Severity};
+use gpt_disk_io::{BlockIoAdapter, Disk, GptHeaderCopy};
+use gpt_disk_types::{BlockSize, GptPartitionEntryArray, Guid, LbaLe};
+
+fn check(storage: &mut [u8]) -> Vec<Finding> {
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage, BlockSize::BS_512)).unwrap();
+    let mut block_buf = vec![0; 512];
+    let mut findings = Vec::new();
+    check_disk(&mut disk, &mut block_buf, |f| findings.push(f)).unwrap();
+    findings
+}
+
+/// Modify the partition entries in both arrays, updating the array
+/// CRCs in both headers.
+fn edit_entries<F>(storage: &mut [u8], f: F)
+where
+    F: FnOnce(&mut GptPartitionEntryArray),
+{
+    let mut disk =
+        Disk::new(BlockIoAdapter::new(storage, BlockSize::BS_512)).unwrap();
+    let mut block_buf = vec![0; 512];
+    let mut primary = create_primary_header();
+    let mut secondary = create_secondary_header();
+    let mut array_buf = vec![0; 512 * 32];
+    let mut array = disk
+        .read_gpt_partition_entry_array(
+            primary.get_partition_entry_array_layout().unwrap(),
+            &mut array_buf,
+        )
+        .unwrap();
+    f(&mut array);
+
+    primary.set_partition_entry_array_crc32(array.calculate_crc32());
+    secondary.set_partition_entry_array_crc32(array.calculate_crc32());
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+    array.set_start_lba(secondary.partition_entry_lba.into());
+    disk.write_gpt_partition_entry_array(&array).unwrap();
+    disk.write_primary_gpt_header(&primary, &mut block_buf)
+        .unwrap();
+    disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+        .unwrap();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_valid_disk() {
+    let mut storage = load_test_disk();
+    assert_eq!(check(&mut storage), []);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_mbr() {
+    let mut storage = load_test_disk();
+
+    // Remove the protective partition record.
+    storage[0x1c2] = 0;
+    assert_eq!(check(&mut storage), [Finding::MissingProtectiveMbr]);
+
+    // Remove the boot signature.
+    storage[0x1fe] = 0;
+    assert_eq!(check(&mut storage), [Finding::InvalidMbrSignature]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_grown_disk() {
+    let mut storage = load_test_disk();
+    storage.resize(storage.len() * 2, 0);
+
+    let findings = check(&mut storage);
+    assert_eq!(findings, [Finding::SecondaryHeaderNotAtEnd]);
+    assert_eq!(findings[0].severity(), Severity::Warning);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_corrupt_headers() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    {
+        let mut disk = Disk::new(BlockIoAdapter::new(
+            storage.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap();
+        let mut header = create_primary_header();
+        header.disk_guid = Guid::ZERO;
+        disk.write_primary_gpt_header(&header, &mut block_buf)
+            .unwrap();
+    }
+    assert_eq!(
+        check(&mut storage),
+        [Finding::CorruptHeader(GptHeaderCopy::Primary)]
+    );
+
+    // Blank out the secondary header too.
+    let secondary_start = storage.len() - 512;
+    storage[secondary_start..].fill(0);
+    assert_eq!(
+        check(&mut storage),
+        [
+            Finding::CorruptHeader(GptHeaderCopy::Primary),
+            Finding::CorruptHeader(GptHeaderCopy::Secondary),
+        ]
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_header_fields() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    {
+        let mut disk = Disk::new(BlockIoAdapter::new(
+            storage.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap();
+
+        // Primary array overlaps the usable range.
+        let mut header = create_primary_header();
+        header.first_usable_lba = LbaLe::from_u64(10);
+        header.update_header_crc32();
+        disk.write_primary_gpt_header(&header, &mut block_buf)
+            .unwrap();
+
+        // Secondary header points at the wrong primary header and
+        // array CRC.
+        let mut header = create_secondary_header();
+        header.alternate_lba = LbaLe::from_u64(2);
+        header.partition_entry_array_crc32.0 .0[0] ^= 1;
+        header.update_header_crc32();
+        disk.write_secondary_gpt_header(&header, &mut block_buf)
+            .unwrap();
+    }
+    assert_eq!(
+        check(&mut storage),
+        [
+            Finding::AlternateLbaMismatch(GptHeaderCopy::Secondary),
+            Finding::InvalidEntryArrayPlacement(GptHeaderCopy::Primary),
+            Finding::EntryArrayCrcMismatch(GptHeaderCopy::Secondary),
+            Finding::HeadersDisagree,
+        ]
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_partition_bounds() {
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+
+    {
+        let mut disk = Disk::new(BlockIoAdapter::new(
+            storage.as_mut_slice(),
+            BlockSize::BS_512,
+        ))
+        .unwrap();
+
+        let mut primary = create_primary_header();
+        let mut secondary = create_secondary_header();
+        let mut array_buf = vec![0; 512 * 32];
+        let mut array = disk
+            .read_gpt_partition_entry_array(
+                primary.get_partition_entry_array_layout().unwrap(),
+                &mut array_buf,
+            )
+            .unwrap();
+
+        // Make the first partition extend past the usable range, and
+        // make the second partition inverted.
+        let entry = array.get_partition_entry_mut(0).unwrap();
+        entry.ending_lba = LbaLe::from_u64(8159);
+        let mut inverted = *entry;
+        inverted.starting_lba = LbaLe::from_u64(4000);
+        inverted.ending_lba = LbaLe::from_u64(3000);
+        *array.get_partition_entry_mut(1).unwrap() = inverted;
+
+        primary.partition_entry_array_crc32 = array.calculate_crc32();
+        primary.update_header_crc32();
+        secondary.partition_entry_array_crc32 = array.calculate_crc32();
+        secondary.update_header_crc32();
+
+        disk.write_gpt_partition_entry_array(&array).unwrap();
+        array.set_start_lba(secondary.partition_entry_lba.into());
+        disk.write_gpt_partition_entry_array(&array).unwrap();
+        disk.write_primary_gpt_header(&primary, &mut block_buf)
+            .unwrap();
+        disk.write_secondary_gpt_header(&secondary, &mut block_buf)
+            .unwrap();
+    }
+    assert_eq!(
+        check(&mut storage),
+        [
+            Finding::PartitionOutsideUsableRange { index: 0 },
+            Finding::InvalidPartitionRange { index: 1 },
+        ]
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_check_overlapping_partitions() {
+    let mut storage = load_test_disk();
+    edit_entries(&mut storage, |array| {
+        // The first partition covers 2048..=4096. Add one partition
+        // that overlaps it, one that overlaps the new one, and one
+        // that is adjacent but does not overlap.
+        let entry = *array.get_partition_entry(0).unwrap();
+        for (index, start, end) in
+            [(1, 4096, 5000), (3, 4500, 4600), (4, 5001, 6000)]
+        {
+            let mut other = entry;
+            other.starting_lba = LbaLe::from_u64(start);
+            other.ending_lba = LbaLe::from_u64(end);
+            *array.get_partition_entry_mut(index).unwrap() = other;
+        }
+    });
+    let overlap = |first, second| Finding::PartitionsOverlap { first, second };
+    assert_eq!(check(&mut storage), [overlap(0, 1), overlap(1, 3)]);
+
+    // Add a partition that starts first and spans all of the others.
+    // Overlaps are reported in index order, not LBA order.
+    edit_entries(&mut storage, |array| {
+        let mut entry = *array.get_partition_entry(0).unwrap();
+        entry.starting_lba = LbaLe::from_u64(100);
+        entry.ending_lba = LbaLe::from_u64(7000);
+        *array.get_partition_entry_mut(2).unwrap() = entry;
+    });
+    assert_eq!(
+        check(&mut storage),
+        [
+            overlap(0, 1),
+            overlap(0, 2),
+            overlap(1, 2),
+            overlap(1, 3),
+            overlap(2, 3),
+            overlap(2, 4),
+        ]
+    );
+}
+
+#[test]
+fn test_finding_display() {
+    assert_eq!(
+        Finding::CorruptHeader(GptHeaderCopy::Secondary).to_string(),
+        "secondary header has an invalid signature or CRC32"
+    );
+    assert_eq!(
+        Finding::PartitionOutsideUsableRange { index: 3 }.to_string(),
+        "partition 3 is outside the usable LBA range"
+    );
+    assert_eq!(
+        Finding::PartitionsOverlap {
+            first: 1,
+            second: 2
+        }
+        .to_string(),
+        "partitions 1 and 2 overlap"
+    );
+    assert_eq!(Severity::Warning.to_string(), "warning");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_verify() {
+    let mut storage = load_test_disk();
+    storage[0x1fe] = 0;
+
+    let mut disk = Disk::new(BlockIoAdapter::new(
+        storage.as_mut_slice(),
+        BlockSize::BS_512,
+    ))
+    .unwrap();
+    let mut block_buf = vec![0; 512];
+    assert_eq!(
+        disk.verify(&mut block_buf).unwrap(),
+        [Finding::InvalidMbrSignature]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_verify_gpt() {
+    use gpt_disk_io::validate::GptVerifyReport;
+
+    let mut storage = load_test_disk();
+    let mut block_buf = vec![0; 512];
+    let verify = |storage: &mut [u8], block_buf: &mut [u8]| {
+        Disk::new(BlockIoAdapter::new(storage, BlockSize::BS_512))
+            .unwrap()
+            .verify_gpt(block_buf)
+            .unwrap()
+    };
+
+    let report = verify(&mut storage, &mut block_buf);
+    assert!(report.is_clean());
+    assert!(!report.has_errors());
+    assert_eq!(report, GptVerifyReport::default());
+
+    edit_entries(&mut storage, |array| {
+        let entry = array.get_partition_entry_mut(0).unwrap();
+        entry.ending_lba = LbaLe::from_u64(8159);
+        let mut other = *entry;
+        other.starting_lba = LbaLe::from_u64(8000);
+        *array.get_partition_entry_mut(1).unwrap() = other;
+    });
+    // Corrupt the secondary partition entry array, then grow the disk.
+    storage[8159 * 512] ^= 1;
+    storage.resize(storage.len() * 2, 0);
+
+    let report = verify(&mut storage, &mut block_buf);
+    assert!(!report.is_clean());
+    assert!(report.has_errors());
+    assert_eq!(
+        report,
+        GptVerifyReport {
+            corrupt_headers: vec![],
+            headers_disagree: false,
+            entry_array_crc_mismatches: vec![GptHeaderCopy::Secondary],
+            partitions_outside_usable_range: vec![0, 1],
+            overlapping_partitions: vec![(0, 1)],
+            secondary_header_not_at_end: true,
+            findings: vec![
+                Finding::SecondaryHeaderNotAtEnd,
+                Finding::EntryArrayCrcMismatch(GptHeaderCopy::Secondary),
+                Finding::PartitionOutsideUsableRange { index: 0 },
+                Finding::PartitionOutsideUsableRange { index: 1 },
+                Finding::PartitionsOverlap {
+                    first: 0,
+                    second: 1
+                },
+            ],
+        }
+    );
+
+    // A corrupt header is reported by copy.
+    let mut storage = load_test_disk();
+    storage[512] ^= 1;
+    let report = verify(&mut storage, &mut block_buf);
+    assert_eq!(report.corrupt_headers, [GptHeaderCopy::Primary]);
+}
@@ -0,0 +1,52 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod common;
+
+use common::{create_partition_entry, create_primary_header, load_test_disk};
+use gpt_disk_types::{
+    GptHeader, GptPartitionEntry, GptPartitionName, MasterBootRecord, U32Le,
+};
+use zerocopy::{FromBytes, IntoBytes, Unaligned};
+
+fn assert_unaligned<T: Unaligned>() {}
+
+#[test]
+fn test_unaligned() {
+    assert_unaligned::<U32Le>();
+    assert_unaligned::<GptHeader>();
+    assert_unaligned::<GptPartitionEntry>();
+    assert_unaligned::<GptPartitionName>();
+    assert_unaligned::<MasterBootRecord>();
+}
+
+/// Parse structures directly from the bytes of a disk image.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_parse_from_disk() {
+    let disk = load_test_disk();
+
+    // The header is followed by padding to the end of the block.
+    let (header, _) = GptHeader::ref_from_prefix(&disk[512..1024]).unwrap();
+    assert_eq!(*header, create_primary_header());
+    assert_eq!(header.as_bytes(), &disk[512..512 + 92]);
+
+    // The partition entry array starts at LBA 2. Since the types are
+    // unaligned, the entries can be parsed from any offset.
+    let entries =
+        <[GptPartitionEntry]>::ref_from_bytes(&disk[1024..2048]).unwrap();
+    assert_eq!(entries.len(), 8);
+    assert_eq!(entries[0], create_partition_entry());
+    assert!(!entries[1].is_used());
+
+    let mbr = MasterBootRecord::read_from_bytes(&disk[..512]).unwrap();
+    assert_eq!(mbr.signature, [0x55, 0xaa]);
+
+    // Wrong size.
+    assert!(MasterBootRecord::ref_from_bytes(&disk[..511]).is_err());
+}
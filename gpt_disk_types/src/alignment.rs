@@ -0,0 +1,123 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockSize, Lba};
+use core::fmt::{self, Display, Formatter};
+use core::num::NonZeroU64;
+
+/// Alignment of partition boundaries, in bytes.
+///
+/// Partitioning tools such as `sgdisk` and `parted` align partitions to
+/// 1 MiB by default (see [`PartitionAlignment::DEFAULT`]). This keeps
+/// partitions aligned to the physical blocks of 4Kn disks and to the
+/// erase blocks of most SSDs, whatever the logical block size.
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{BlockSize, Lba, PartitionAlignment};
+///
+/// let alignment = PartitionAlignment::DEFAULT;
+/// assert_eq!(alignment.num_blocks(BlockSize::BS_512).get(), 2048);
+/// assert_eq!(alignment.num_blocks(BlockSize::BS_4096).get(), 256);
+///
+/// // First aligned LBA after the primary partition entry array.
+/// assert_eq!(
+///     alignment.align_up(Lba(34), BlockSize::BS_512),
+///     Some(Lba(2048))
+/// );
+/// assert_eq!(
+///     alignment.align_up(Lba(6), BlockSize::BS_4096),
+///     Some(Lba(256))
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PartitionAlignment(NonZeroU64);
+
+impl PartitionAlignment {
+    /// 1 MiB alignment, the default used by most partitioning tools.
+    pub const DEFAULT: Self =
+        Self(if let Some(nz) = NonZeroU64::new(1024 * 1024) {
+            nz
+        } else {
+            unreachable!()
+        });
+
+    /// Create a `PartitionAlignment` from a number of bytes. Returns
+    /// `None` if `num_bytes` is zero.
+    #[must_use]
+    pub const fn from_bytes(num_bytes: u64) -> Option<Self> {
+        if let Some(nz) = NonZeroU64::new(num_bytes) {
+            Some(Self(nz))
+        } else {
+            None
+        }
+    }
+
+    /// Get the alignment in bytes.
+    #[must_use]
+    pub const fn to_bytes(self) -> u64 {
+        self.0.get()
+    }
+
+    /// Increase the alignment to the `physical_block_size` if that is
+    /// larger, so that partitions never start in the middle of a
+    /// physical block.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn with_physical_block_size(
+        self,
+        physical_block_size: BlockSize,
+    ) -> Self {
+        // OK to unwrap: both values are nonzero.
+        Self::from_bytes(self.to_bytes().max(physical_block_size.to_u64()))
+            .unwrap()
+    }
+
+    /// Get the alignment in blocks of `block_size`. If the alignment is
+    /// not a multiple of the block size, it is rounded down, with a
+    /// minimum of one block.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn num_blocks(self, block_size: BlockSize) -> NonZeroU64 {
+        // OK to unwrap: the value is at least one.
+        NonZeroU64::new((self.to_bytes() / block_size.to_u64()).max(1)).unwrap()
+    }
+
+    /// Round `lba` up to the alignment. Returns `None` if overflow
+    /// occurs. See [`Lba::align_up`].
+    #[must_use]
+    pub fn align_up(self, lba: Lba, block_size: BlockSize) -> Option<Lba> {
+        lba.align_up(self.num_blocks(block_size))
+    }
+
+    /// Round `lba` down to the alignment. See [`Lba::align_down`].
+    #[must_use]
+    pub fn align_down(self, lba: Lba, block_size: BlockSize) -> Lba {
+        lba.align_down(self.num_blocks(block_size))
+    }
+
+    /// Check if `lba` is aligned. See [`Lba::is_aligned`].
+    #[must_use]
+    pub fn is_aligned(self, lba: Lba, block_size: BlockSize) -> bool {
+        lba.is_aligned(self.num_blocks(block_size))
+    }
+}
+
+impl Default for PartitionAlignment {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Display for PartitionAlignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes", self.0)
+    }
+}
@@ -7,16 +7,36 @@
 // except according to those terms.
 
 use crate::U64Le;
+use core::cmp::Ordering;
 use core::fmt::{self, Display, Formatter};
-use core::num::{NonZeroU32, TryFromIntError};
+use core::num::{NonZeroU32, NonZeroU64, TryFromIntError};
 use core::ops::RangeInclusive;
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "bytemuck")]
 use bytemuck::{Pod, Zeroable};
 
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
 /// Logical block address.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout)
+)]
 #[repr(transparent)]
 pub struct Lba(pub u64);
 
@@ -26,6 +46,88 @@ impl Lba {
     pub fn to_u64(self) -> u64 {
         self.0
     }
+
+    /// Round up to the next multiple of `alignment` (in blocks). Returns
+    /// `None` if overflow occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::num::NonZeroU64;
+    /// use gpt_disk_types::Lba;
+    ///
+    /// let alignment = NonZeroU64::new(2048).unwrap();
+    /// assert_eq!(Lba(34).align_up(alignment), Some(Lba(2048)));
+    /// assert_eq!(Lba(2048).align_up(alignment), Some(Lba(2048)));
+    /// ```
+    #[must_use]
+    pub const fn align_up(self, alignment: NonZeroU64) -> Option<Self> {
+        match self.0 % alignment.get() {
+            0 => Some(self),
+            rem => match self.0.checked_add(alignment.get() - rem) {
+                Some(lba) => Some(Self(lba)),
+                None => None,
+            },
+        }
+    }
+
+    /// Round down to the previous multiple of `alignment` (in blocks).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::num::NonZeroU64;
+    /// use gpt_disk_types::Lba;
+    ///
+    /// let alignment = NonZeroU64::new(2048).unwrap();
+    /// assert_eq!(Lba(4000).align_down(alignment), Lba(2048));
+    /// ```
+    #[must_use]
+    pub const fn align_down(self, alignment: NonZeroU64) -> Self {
+        Self(self.0 - self.0 % alignment.get())
+    }
+
+    /// Check if the LBA is a multiple of `alignment` (in blocks).
+    #[must_use]
+    pub const fn is_aligned(self, alignment: NonZeroU64) -> bool {
+        self.0 % alignment.get() == 0
+    }
+
+    /// Add `num_blocks`, returning `None` if overflow occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::Lba;
+    ///
+    /// assert_eq!(Lba(2).checked_add(32), Some(Lba(34)));
+    /// assert_eq!(Lba(u64::MAX).checked_add(1), None);
+    /// ```
+    #[must_use]
+    pub const fn checked_add(self, num_blocks: u64) -> Option<Self> {
+        match self.0.checked_add(num_blocks) {
+            Some(lba) => Some(Self(lba)),
+            None => None,
+        }
+    }
+
+    /// Subtract `num_blocks`, returning `None` if overflow occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::Lba;
+    ///
+    /// assert_eq!(Lba(8191).checked_sub(33), Some(Lba(8158)));
+    /// assert_eq!(Lba(0).checked_sub(1), None);
+    /// ```
+    #[must_use]
+    pub const fn checked_sub(self, num_blocks: u64) -> Option<Self> {
+        match self.0.checked_sub(num_blocks) {
+            Some(lba) => Some(Self(lba)),
+            None => None,
+        }
+    }
 }
 
 impl PartialEq<u64> for Lba {
@@ -34,6 +136,36 @@ impl PartialEq<u64> for Lba {
     }
 }
 
+impl PartialEq<Lba> for u64 {
+    fn eq(&self, other: &Lba) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialOrd<u64> for Lba {
+    fn partial_cmp(&self, other: &u64) -> Option<Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl PartialOrd<Lba> for u64 {
+    fn partial_cmp(&self, other: &Lba) -> Option<Ordering> {
+        self.partial_cmp(&other.0)
+    }
+}
+
+impl PartialEq<LbaLe> for Lba {
+    fn eq(&self, other: &LbaLe) -> bool {
+        self.0 == other.to_u64()
+    }
+}
+
+impl PartialOrd<LbaLe> for Lba {
+    fn partial_cmp(&self, other: &LbaLe) -> Option<Ordering> {
+        self.0.partial_cmp(&other.to_u64())
+    }
+}
+
 impl Display for Lba {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -54,9 +186,31 @@ impl From<LbaLe> for Lba {
     }
 }
 
+impl From<u64> for Lba {
+    fn from(lba: u64) -> Self {
+        Self(lba)
+    }
+}
+
+impl From<Lba> for u64 {
+    fn from(lba: Lba) -> Self {
+        lba.0
+    }
+}
+
 /// Logical block address stored as a [`U64Le`].
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct LbaLe(pub U64Le);
 
@@ -72,6 +226,60 @@ impl LbaLe {
     pub const fn to_u64(self) -> u64 {
         self.0.to_u64()
     }
+
+    /// Add `num_blocks`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_add(self, num_blocks: u64) -> Option<Self> {
+        match self.0.checked_add(num_blocks) {
+            Some(lba) => Some(Self(lba)),
+            None => None,
+        }
+    }
+
+    /// Subtract `num_blocks`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_sub(self, num_blocks: u64) -> Option<Self> {
+        match self.0.checked_sub(num_blocks) {
+            Some(lba) => Some(Self(lba)),
+            None => None,
+        }
+    }
+}
+
+impl PartialEq<u64> for LbaLe {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<LbaLe> for u64 {
+    fn eq(&self, other: &LbaLe) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialOrd<u64> for LbaLe {
+    fn partial_cmp(&self, other: &u64) -> Option<Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl PartialOrd<LbaLe> for u64 {
+    fn partial_cmp(&self, other: &LbaLe) -> Option<Ordering> {
+        self.partial_cmp(&other.0)
+    }
+}
+
+impl PartialEq<Lba> for LbaLe {
+    fn eq(&self, other: &Lba) -> bool {
+        self.to_u64() == other.0
+    }
+}
+
+impl PartialOrd<Lba> for LbaLe {
+    fn partial_cmp(&self, other: &Lba) -> Option<Ordering> {
+        self.to_u64().partial_cmp(&other.0)
+    }
 }
 
 impl Display for LbaLe {
@@ -86,9 +294,26 @@ impl From<Lba> for LbaLe {
     }
 }
 
+impl From<u64> for LbaLe {
+    fn from(lba: u64) -> Self {
+        Self::from_u64(lba)
+    }
+}
+
+impl From<LbaLe> for u64 {
+    fn from(lba: LbaLe) -> Self {
+        lba.to_u64()
+    }
+}
+
 /// Inclusive range of logical block addresses.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout)
+)]
 #[repr(C)]
 pub struct LbaRangeInclusive {
     start: Lba,
@@ -216,6 +441,75 @@ impl LbaRangeInclusive {
         // Add one here since the range is inclusive.
         self.end().to_u64() - self.start.to_u64() + 1
     }
+
+    /// Check if `lba` is within the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{Lba, LbaRangeInclusive};
+    ///
+    /// let r = LbaRangeInclusive::new(Lba(1), Lba(2)).unwrap();
+    /// assert!(r.contains(Lba(2)));
+    /// assert!(!r.contains(Lba(3)));
+    /// ```
+    #[must_use]
+    pub const fn contains(self, lba: Lba) -> bool {
+        self.start.0 <= lba.0 && lba.0 <= self.end.0
+    }
+
+    /// Check if every block of `other` is within the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{Lba, LbaRangeInclusive};
+    ///
+    /// let r = LbaRangeInclusive::new(Lba(1), Lba(10)).unwrap();
+    /// assert!(r.contains_range(LbaRangeInclusive::new(Lba(2), Lba(10)).unwrap()));
+    /// assert!(!r.contains_range(LbaRangeInclusive::new(Lba(2), Lba(11)).unwrap()));
+    /// ```
+    #[must_use]
+    pub const fn contains_range(self, other: Self) -> bool {
+        self.start.0 <= other.start.0 && other.end.0 <= self.end.0
+    }
+
+    /// Check if the range has at least one block in common with
+    /// `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{Lba, LbaRangeInclusive};
+    ///
+    /// let r = LbaRangeInclusive::new(Lba(1), Lba(10)).unwrap();
+    /// assert!(r.overlaps(LbaRangeInclusive::new(Lba(10), Lba(20)).unwrap()));
+    /// assert!(!r.overlaps(LbaRangeInclusive::new(Lba(11), Lba(20)).unwrap()));
+    /// ```
+    #[must_use]
+    pub const fn overlaps(self, other: Self) -> bool {
+        self.start.0 <= other.end.0 && other.start.0 <= self.end.0
+    }
+
+    /// Get the blocks that are in both the range and `other`. Returns
+    /// `None` if the ranges do not overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::{Lba, LbaRangeInclusive};
+    ///
+    /// let r1 = LbaRangeInclusive::new(Lba(1), Lba(10)).unwrap();
+    /// let r2 = LbaRangeInclusive::new(Lba(5), Lba(20)).unwrap();
+    /// assert_eq!(
+    ///     r1.intersection(r2),
+    ///     LbaRangeInclusive::new(Lba(5), Lba(10))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
 }
 
 impl Display for LbaRangeInclusive {
@@ -227,7 +521,9 @@ impl Display for LbaRangeInclusive {
 /// Size of a block in bytes.
 ///
 /// This type enforces some restrictions on the block size: it must be
-/// at least 512 bytes and fit within a [`u32`].
+/// at least 512 bytes and fit within a [`u32`]. The size does not need
+/// to be a power of two or a multiple of 512, so devices with sectors
+/// such as 520, 528, or 4160 bytes can be described.
 ///
 /// # Minimum size
 ///
@@ -254,7 +550,8 @@ impl BlockSize {
         unreachable!()
     });
 
-    /// Create a `BlockSize`.
+    /// Create a `BlockSize`. Returns `None` if `num_bytes` is less
+    /// than 512.
     #[must_use]
     pub const fn new(num_bytes: u32) -> Option<Self> {
         if let Some(nz) = NonZeroU32::new(num_bytes) {
@@ -333,3 +630,10 @@ impl Display for BlockSize {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for BlockSize {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=u32}", self.to_u32());
+    }
+}
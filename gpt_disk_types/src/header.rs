@@ -7,19 +7,39 @@
 // except according to those terms.
 
 use crate::{
-    Crc32, GptPartitionEntry, GptPartitionEntryArrayLayout,
-    GptPartitionEntrySize, GptPartitionEntrySizeError, Guid, LbaLe, U32Le,
-    U64Le,
+    BlockSize, Crc32, GptHeaderSummary, GptPartitionEntry,
+    GptPartitionEntryArrayLayout, GptPartitionEntrySize,
+    GptPartitionEntrySizeError, Guid, Lba, LbaLe, U32Le, U64Le,
 };
+use core::error::Error;
 use core::fmt::{self, Display, Formatter};
 use core::mem;
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "bytemuck")]
-use bytemuck::{bytes_of, Pod, Zeroable};
+use bytemuck::{Pod, Zeroable};
+
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
 /// GPT header signature.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct GptHeaderSignature(pub U64Le);
 
@@ -56,7 +76,17 @@ impl Default for GptHeaderSignature {
 
 /// GPT header revision.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct GptHeaderRevision(pub U32Le);
 
@@ -91,9 +121,120 @@ impl Display for GptHeaderRevision {
     }
 }
 
+/// Errors returned by [`GptHeader::validate`].
+///
+/// This type implements the [`Error`] trait.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GptHeaderError {
+    /// The [`signature`] is not
+    /// [`GptHeaderSignature::EFI_COMPATIBLE_PARTITION_TABLE_HEADER`].
+    ///
+    /// [`signature`]: GptHeader::signature
+    InvalidSignature,
+
+    /// The [`revision`] is not [`GptHeaderRevision::VERSION_1_0`]. The
+    /// invalid revision is included.
+    ///
+    /// [`revision`]: GptHeader::revision
+    InvalidRevision(u32),
+
+    /// The [`header_size`] is less than 92 or greater than the block
+    /// size. The invalid size is included.
+    ///
+    /// [`header_size`]: GptHeader::header_size
+    InvalidHeaderSize(u32),
+
+    /// The [`reserved`] field is not zero.
+    ///
+    /// [`reserved`]: GptHeader::reserved
+    ReservedNotZero,
+
+    /// The [`header_crc32`] does not match the checksum of the header.
+    ///
+    /// [`header_crc32`]: GptHeader::header_crc32
+    InvalidHeaderCrc32 {
+        /// Checksum calculated from the header's contents.
+        expected: u32,
+
+        /// Checksum stored in the header.
+        actual: u32,
+    },
+
+    /// The [`my_lba`] is zero (which is reserved for the MBR) or the
+    /// same as the [`alternate_lba`].
+    ///
+    /// [`alternate_lba`]: GptHeader::alternate_lba
+    /// [`my_lba`]: GptHeader::my_lba
+    InvalidMyLba,
+
+    /// The [`alternate_lba`] is zero (which is reserved for the MBR).
+    ///
+    /// [`alternate_lba`]: GptHeader::alternate_lba
+    InvalidAlternateLba,
+
+    /// The [`first_usable_lba`] is greater than the
+    /// [`last_usable_lba`], or the usable range contains LBA zero or
+    /// one of the headers.
+    ///
+    /// [`first_usable_lba`]: GptHeader::first_usable_lba
+    /// [`last_usable_lba`]: GptHeader::last_usable_lba
+    InvalidUsableRange,
+
+    /// The [`size_of_partition_entry`] is not a valid
+    /// [`GptPartitionEntrySize`]. The invalid size is included.
+    ///
+    /// [`size_of_partition_entry`]: GptHeader::size_of_partition_entry
+    InvalidPartitionEntrySize(u32),
+
+    /// The partition entry array overflows, or overlaps LBA zero, one
+    /// of the headers, or the usable range.
+    InvalidPartitionEntryArray,
+}
+
+impl Display for GptHeaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => f.write_str("invalid header signature"),
+            Self::InvalidRevision(revision) => {
+                write!(f, "invalid header revision: {revision:#010x}")
+            }
+            Self::InvalidHeaderSize(size) => {
+                write!(f, "invalid header size: {size}")
+            }
+            Self::ReservedNotZero => {
+                f.write_str("reserved header field is not zero")
+            }
+            Self::InvalidHeaderCrc32 { expected, actual } => write!(
+                f,
+                "invalid header CRC32: expected {expected:#010x}, got {actual:#010x}"
+            ),
+            Self::InvalidMyLba => f.write_str("invalid header LBA"),
+            Self::InvalidAlternateLba => {
+                f.write_str("invalid alternate header LBA")
+            }
+            Self::InvalidUsableRange => f.write_str("invalid usable LBA range"),
+            Self::InvalidPartitionEntrySize(size) => {
+                write!(f, "invalid partition entry size: {size}")
+            }
+            Self::InvalidPartitionEntryArray => {
+                f.write_str("invalid partition entry array location")
+            }
+        }
+    }
+}
+
+impl Error for GptHeaderError {}
+
 /// GPT header that appears near the start and end of a GPT-formatted disk.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(C, packed)]
 pub struct GptHeader {
     /// Magic signature for the header. In a valid header this must be
@@ -148,6 +289,102 @@ pub struct GptHeader {
 }
 
 impl GptHeader {
+    /// Create a primary header for a disk with `num_blocks` blocks of
+    /// `block_size` bytes, using the standard layout:
+    /// * The primary header is at LBA 1 and the secondary header is at
+    ///   the last LBA of the disk.
+    /// * The primary partition entry array starts at LBA 2, and the
+    ///   secondary partition entry array ends just before the
+    ///   secondary header.
+    /// * The usable range is everything in between the two arrays.
+    ///
+    /// The partition entry array has `num_partition_entries` entries of
+    /// the default size (128 bytes). Both checksums are filled in, with
+    /// the array checksum calculated for an array of empty entries. If
+    /// the array is later modified, update its checksum with
+    /// [`set_partition_entry_array_crc32`].
+    ///
+    /// Use [`to_secondary`] to create the matching secondary header.
+    ///
+    /// Returns `None` if the disk is too small to hold the headers, both
+    /// partition entry arrays, and at least one usable block.
+    ///
+    /// [`set_partition_entry_array_crc32`]: Self::set_partition_entry_array_crc32
+    /// [`to_secondary`]: Self::to_secondary
+    #[must_use]
+    pub fn new_primary(
+        num_blocks: u64,
+        block_size: BlockSize,
+        disk_guid: Guid,
+        num_partition_entries: u32,
+    ) -> Option<Self> {
+        let layout = GptPartitionEntryArrayLayout {
+            start_lba: Lba(2),
+            entry_size: GptPartitionEntrySize::default(),
+            num_entries: num_partition_entries,
+        };
+        let array_blocks = layout.num_blocks(block_size)?;
+        let first_usable_lba =
+            layout.start_lba.to_u64().checked_add(array_blocks)?;
+        let last_lba = num_blocks.checked_sub(1)?;
+        let last_usable_lba =
+            last_lba.checked_sub(array_blocks)?.checked_sub(1)?;
+        if first_usable_lba > last_usable_lba {
+            return None;
+        }
+
+        let mut header = Self {
+            my_lba: LbaLe::from_u64(1),
+            alternate_lba: LbaLe::from_u64(last_lba),
+            first_usable_lba: LbaLe::from_u64(first_usable_lba),
+            last_usable_lba: LbaLe::from_u64(last_usable_lba),
+            disk_guid,
+            partition_entry_lba: layout.start_lba.into(),
+            number_of_partition_entries: U32Le::from_u32(num_partition_entries),
+            ..Default::default()
+        };
+        header.set_partition_entry_array_crc32(empty_array_crc32(
+            num_partition_entries,
+        ));
+        Some(header)
+    }
+
+    /// Create the secondary header that matches this primary header.
+    ///
+    /// The [`my_lba`] and [`alternate_lba`] fields are swapped, the
+    /// [`partition_entry_lba`] is set to the block just after the
+    /// [`last_usable_lba`], and the header checksum is updated. All
+    /// other fields are copied unchanged.
+    ///
+    /// [`alternate_lba`]: Self::alternate_lba
+    /// [`last_usable_lba`]: Self::last_usable_lba
+    /// [`my_lba`]: Self::my_lba
+    /// [`partition_entry_lba`]: Self::partition_entry_lba
+    #[must_use]
+    pub fn to_secondary(&self) -> Self {
+        let mut secondary = Self {
+            my_lba: self.alternate_lba,
+            alternate_lba: self.my_lba,
+            partition_entry_lba: LbaLe::from_u64(
+                self.last_usable_lba.to_u64().saturating_add(1),
+            ),
+            ..*self
+        };
+        secondary.update_header_crc32();
+        secondary
+    }
+
+    /// Set the [`partition_entry_array_crc32`] field and update the
+    /// header's checksum to match. See
+    /// [`GptPartitionEntryArray::calculate_crc32`].
+    ///
+    /// [`GptPartitionEntryArray::calculate_crc32`]: crate::GptPartitionEntryArray::calculate_crc32
+    /// [`partition_entry_array_crc32`]: Self::partition_entry_array_crc32
+    pub fn set_partition_entry_array_crc32(&mut self, crc32: Crc32) {
+        self.partition_entry_array_crc32 = crc32;
+        self.update_header_crc32();
+    }
+
     /// Check if the header's signature matches
     /// [`GptHeaderSignature::EFI_COMPATIBLE_PARTITION_TABLE_HEADER`].
     #[must_use]
@@ -158,34 +395,164 @@ impl GptHeader {
 
     /// Calculate the header's CRC32 checksum. This returns the checksum
     /// but does not update the checksum field in the header.
-    #[cfg(feature = "bytemuck")]
+    ///
+    /// The [`partition_entry_array_crc32`] field is included in the
+    /// checksum, so it should be set first. See
+    /// [`GptPartitionEntryArray::calculate_crc32`].
+    ///
+    /// [`GptPartitionEntryArray::calculate_crc32`]: crate::GptPartitionEntryArray::calculate_crc32
+    /// [`partition_entry_array_crc32`]: Self::partition_entry_array_crc32
     #[must_use]
     pub fn calculate_header_crc32(&self) -> Crc32 {
         let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
         let mut digest = crc.digest();
-        digest.update(bytes_of(&self.signature));
-        digest.update(bytes_of(&self.revision));
-        digest.update(bytes_of(&self.header_size));
+        digest.update(&self.signature.0 .0);
+        digest.update(&self.revision.0 .0);
+        digest.update(&self.header_size.0);
         digest.update(&[0u8; 4]); // Zeroes for the `header_crc32` field.
-        digest.update(bytes_of(&self.reserved));
-        digest.update(bytes_of(&self.my_lba));
-        digest.update(bytes_of(&self.alternate_lba));
-        digest.update(bytes_of(&self.first_usable_lba));
-        digest.update(bytes_of(&self.last_usable_lba));
-        digest.update(bytes_of(&{ self.disk_guid }));
-        digest.update(bytes_of(&self.partition_entry_lba));
-        digest.update(bytes_of(&self.number_of_partition_entries));
-        digest.update(bytes_of(&self.size_of_partition_entry));
-        digest.update(bytes_of(&self.partition_entry_array_crc32));
+        digest.update(&self.reserved.0);
+        digest.update(&self.my_lba.0 .0);
+        digest.update(&self.alternate_lba.0 .0);
+        digest.update(&self.first_usable_lba.0 .0);
+        digest.update(&self.last_usable_lba.0 .0);
+        digest.update(&{ self.disk_guid }.to_bytes());
+        digest.update(&self.partition_entry_lba.0 .0);
+        digest.update(&self.number_of_partition_entries.0);
+        digest.update(&self.size_of_partition_entry.0);
+        digest.update(&self.partition_entry_array_crc32.0 .0);
         Crc32(U32Le(digest.finalize().to_le_bytes()))
     }
 
-    /// Update the header's CRC32 checksum.
-    #[cfg(feature = "bytemuck")]
+    /// Update the header's CRC32 checksum. See
+    /// [`calculate_header_crc32`].
+    ///
+    /// [`calculate_header_crc32`]: Self::calculate_header_crc32
     pub fn update_header_crc32(&mut self) {
         self.header_crc32 = self.calculate_header_crc32();
     }
 
+    /// Check if the [`header_crc32`] field matches the checksum
+    /// calculated by [`calculate_header_crc32`].
+    ///
+    /// [`calculate_header_crc32`]: Self::calculate_header_crc32
+    /// [`header_crc32`]: Self::header_crc32
+    #[must_use]
+    pub fn is_header_crc32_valid(&self) -> bool {
+        self.header_crc32 == self.calculate_header_crc32()
+    }
+
+    /// Check that the header is valid for a disk with the given block
+    /// size. This checks:
+    /// * The signature, revision, and reserved fields.
+    /// * The header size is at least 92 bytes and no larger than a
+    ///   block.
+    /// * The header CRC32.
+    /// * Neither header is at LBA zero, and the two headers are at
+    ///   different LBAs.
+    /// * The usable range is not empty and does not contain LBA zero
+    ///   or either header.
+    /// * The partition entry size is valid, and the partition entry
+    ///   array does not overlap LBA zero, either header, or the usable
+    ///   range.
+    ///
+    /// The first failed check is returned as a [`GptHeaderError`]. The
+    /// partition entry array itself is not read, so its CRC32 is not
+    /// checked.
+    pub fn validate(
+        &self,
+        block_size: BlockSize,
+    ) -> Result<(), GptHeaderError> {
+        if !self.is_signature_valid() {
+            return Err(GptHeaderError::InvalidSignature);
+        }
+        if self.revision != GptHeaderRevision::VERSION_1_0 {
+            return Err(GptHeaderError::InvalidRevision(
+                self.revision.0.to_u32(),
+            ));
+        }
+        let header_size = self.header_size.to_u32();
+        if usize::try_from(header_size)
+            .map_or(true, |size| size < mem::size_of::<Self>())
+            || header_size > block_size.to_u32()
+        {
+            return Err(GptHeaderError::InvalidHeaderSize(header_size));
+        }
+        if self.reserved.to_u32() != 0 {
+            return Err(GptHeaderError::ReservedNotZero);
+        }
+        let expected_crc32 = self.calculate_header_crc32();
+        if self.header_crc32 != expected_crc32 {
+            return Err(GptHeaderError::InvalidHeaderCrc32 {
+                expected: expected_crc32.0.to_u32(),
+                actual: self.header_crc32.0.to_u32(),
+            });
+        }
+
+        let my_lba = self.my_lba.to_u64();
+        let alternate_lba = self.alternate_lba.to_u64();
+        if my_lba == 0 || my_lba == alternate_lba {
+            return Err(GptHeaderError::InvalidMyLba);
+        }
+        if alternate_lba == 0 {
+            return Err(GptHeaderError::InvalidAlternateLba);
+        }
+
+        let first_usable_lba = self.first_usable_lba.to_u64();
+        let last_usable_lba = self.last_usable_lba.to_u64();
+        let is_usable =
+            |lba: u64| (first_usable_lba..=last_usable_lba).contains(&lba);
+        if first_usable_lba > last_usable_lba
+            || is_usable(0)
+            || is_usable(my_lba)
+            || is_usable(alternate_lba)
+        {
+            return Err(GptHeaderError::InvalidUsableRange);
+        }
+
+        let layout = self.get_partition_entry_array_layout().map_err(|_| {
+            GptHeaderError::InvalidPartitionEntrySize(
+                self.size_of_partition_entry.to_u32(),
+            )
+        })?;
+        let array_start = layout.start_lba.to_u64();
+        let array_blocks = layout
+            .num_blocks(block_size)
+            .ok_or(GptHeaderError::InvalidPartitionEntryArray)?;
+        if array_blocks != 0 {
+            let array_end = array_start
+                .checked_add(array_blocks - 1)
+                .ok_or(GptHeaderError::InvalidPartitionEntryArray)?;
+            let in_array = |lba: u64| (array_start..=array_end).contains(&lba);
+            if in_array(0)
+                || in_array(my_lba)
+                || in_array(alternate_lba)
+                || (array_start <= last_usable_lba
+                    && first_usable_lba <= array_end)
+            {
+                return Err(GptHeaderError::InvalidPartitionEntryArray);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get an adapter for displaying a multi-line summary of the
+    /// header, similar to the output of `gdisk -p`. The `block_size` is
+    /// used to calculate where the partition entry array ends.
+    ///
+    /// The [`Display`] implementation of `GptHeader` shows the raw
+    /// fields instead.
+    #[must_use]
+    pub fn display_summary(
+        &self,
+        block_size: BlockSize,
+    ) -> GptHeaderSummary<'_> {
+        GptHeaderSummary {
+            header: self,
+            block_size,
+        }
+    }
+
     /// Get the [`GptPartitionEntryArrayLayout`] for this header.
     pub fn get_partition_entry_array_layout(
         &self,
@@ -200,6 +567,18 @@ impl GptHeader {
     }
 }
 
+/// Calculate the CRC32 of a partition entry array containing
+/// `num_entries` empty entries of the default size.
+fn empty_array_crc32(num_entries: u32) -> Crc32 {
+    let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+    let mut digest = crc.digest();
+    let empty_entry = [0u8; mem::size_of::<GptPartitionEntry>()];
+    for _ in 0..num_entries {
+        digest.update(&empty_entry);
+    }
+    Crc32(U32Le(digest.finalize().to_le_bytes()))
+}
+
 impl Default for GptHeader {
     fn default() -> Self {
         Self {
@@ -255,3 +634,33 @@ impl Display for GptHeader {
         f.write_str(" }")
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for GptHeader {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "GptHeader {{ signature: {=u64:#x}, revision: {=u32:#x}, \
+             header_size: {=u32}, header_crc32: {=u32:#x}, my_lba: {=u64}, \
+             alternate_lba: {=u64}, first_usable_lba: {=u64}, \
+             last_usable_lba: {=u64}, disk_guid: {}, \
+             partition_entry_lba: {=u64}, \
+             number_of_partition_entries: {=u32}, \
+             size_of_partition_entry: {=u32}, \
+             partition_entry_array_crc32: {=u32:#x} }}",
+            self.signature.to_u64(),
+            self.revision.0.to_u32(),
+            self.header_size.to_u32(),
+            self.header_crc32.0.to_u32(),
+            self.my_lba.to_u64(),
+            self.alternate_lba.to_u64(),
+            self.first_usable_lba.to_u64(),
+            self.last_usable_lba.to_u64(),
+            { self.disk_guid },
+            self.partition_entry_lba.to_u64(),
+            self.number_of_partition_entries.to_u32(),
+            self.size_of_partition_entry.to_u32(),
+            self.partition_entry_array_crc32.0.to_u32(),
+        );
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{
+    Chs, DiskGeometry, GptPartitionEntry, Lba, LbaRangeInclusive,
+    MasterBootRecord, MbrPartitionRecord, U32Le,
+};
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+/// Errors used by [`HybridMbr`].
+///
+/// This type implements the [`Error`] trait.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HybridMbrError {
+    /// The hybrid MBR already contains [`HybridMbr::MAX_PARTITIONS`]
+    /// partitions.
+    TooManyPartitions,
+
+    /// A partition starts at LBA 0 or 1, extends past the end of the
+    /// disk, or does not fit in the 32-bit fields of an MBR partition
+    /// record.
+    PartitionOutOfRange,
+
+    /// A partition overlaps another partition in the hybrid MBR.
+    PartitionOverlap,
+}
+
+impl Display for HybridMbrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyPartitions => {
+                f.write_str("too many partitions in the hybrid MBR")
+            }
+            Self::PartitionOutOfRange => {
+                f.write_str("partition cannot be represented in the MBR")
+            }
+            Self::PartitionOverlap => {
+                f.write_str("partition overlaps another partition")
+            }
+        }
+    }
+}
+
+impl Error for HybridMbrError {}
+
+/// GPT partition mirrored into a [`HybridMbr`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct HybridMbrPartition {
+    /// Blocks covered by the partition. This is typically the range of
+    /// a GPT partition (see [`GptPartitionEntry::lba_range`]).
+    pub range: LbaRangeInclusive,
+
+    /// MBR partition type, such as `0x0c` for FAT32 or `0x83` for
+    /// Linux. See [`MbrPartitionRecord::os_indicator`].
+    pub os_indicator: u8,
+
+    /// Whether the partition is marked as legacy bootable. See
+    /// [`MbrPartitionRecord::boot_indicator`].
+    pub bootable: bool,
+}
+
+impl HybridMbrPartition {
+    fn to_record(self, geom: DiskGeometry) -> MbrPartitionRecord {
+        let start = self.range.start();
+        let end = self.range.end();
+        // OK to unwrap: the range is checked in `HybridMbr::add_partition`.
+        let starting_lba = u32::try_from(start.to_u64()).unwrap();
+        let size_in_lba = u32::try_from(self.range.num_blocks()).unwrap();
+
+        MbrPartitionRecord {
+            boot_indicator: if self.bootable { 0x80 } else { 0 },
+            start_chs: Chs::from_lba(start, geom).unwrap_or(Chs::MAX),
+            os_indicator: self.os_indicator,
+            end_chs: Chs::from_lba(end, geom).unwrap_or(Chs::MAX),
+            starting_lba: U32Le::from_u32(starting_lba),
+            size_in_lba: U32Le::from_u32(size_in_lba),
+        }
+    }
+}
+
+/// Hybrid MBR, in which some GPT partitions are also described by MBR
+/// partition records so that legacy BIOS firmware and operating systems
+/// can access them. This is similar to the `h` command in the recovery
+/// and transformation menu of `gdisk`.
+///
+/// Up to three partitions can be mirrored. The remaining record is a
+/// protective partition of type `0xee` that covers the GPT header and
+/// partition entry array, starting at LBA 1 and ending just before the
+/// first mirrored partition.
+///
+/// Hybrid MBRs are not part of the UEFI Specification, and the
+/// partition tables must be kept in sync manually. Only use one if
+/// legacy firmware requires it.
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{HybridMbr, HybridMbrPartition, Lba, LbaRangeInclusive};
+///
+/// let mut hybrid = HybridMbr::new();
+/// hybrid.add_partition(HybridMbrPartition {
+///     range: LbaRangeInclusive::new(Lba(2048), Lba(4095)).unwrap(),
+///     os_indicator: 0x0c,
+///     bootable: true,
+/// })?;
+/// let mbr = hybrid.build(8192)?;
+/// assert_eq!(mbr.partitions[0].os_indicator, 0x0c);
+/// assert_eq!(mbr.partitions[1].os_indicator, 0xee);
+/// assert_eq!(mbr.partitions[1].size_in_lba.to_u32(), 2047);
+/// # Ok::<(), gpt_disk_types::HybridMbrError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct HybridMbr {
+    partitions: [Option<HybridMbrPartition>; HybridMbr::MAX_PARTITIONS],
+    protective_first: bool,
+    geometry: DiskGeometry,
+    disk_signature: u32,
+}
+
+impl HybridMbr {
+    /// Maximum number of mirrored partitions. The fourth MBR partition
+    /// record is used for the protective partition.
+    pub const MAX_PARTITIONS: usize = 3;
+
+    /// Create an empty hybrid MBR. Building it without adding any
+    /// partitions produces a normal protective MBR.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a mirrored partition. Partitions are placed in the MBR in
+    /// the order they are added.
+    ///
+    /// The partition must not overlap a partition that was already
+    /// added, must start at LBA 2 or later to leave room for the
+    /// protective partition, and its start and size must fit in 32
+    /// bits.
+    pub fn add_partition(
+        &mut self,
+        partition: HybridMbrPartition,
+    ) -> Result<(), HybridMbrError> {
+        let range = partition.range;
+        if range.start().to_u64() < 2
+            || u32::try_from(range.start().to_u64()).is_err()
+            || u32::try_from(range.num_blocks()).is_err()
+        {
+            return Err(HybridMbrError::PartitionOutOfRange);
+        }
+        if self.partitions().any(|other| range.overlaps(other.range)) {
+            return Err(HybridMbrError::PartitionOverlap);
+        }
+
+        let slot = self
+            .partitions
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(HybridMbrError::TooManyPartitions)?;
+        *slot = Some(partition);
+        Ok(())
+    }
+
+    /// Add a mirrored partition covering the same blocks as a GPT
+    /// partition entry. See [`add_partition`].
+    ///
+    /// [`add_partition`]: Self::add_partition
+    pub fn add_gpt_partition(
+        &mut self,
+        entry: &GptPartitionEntry,
+        os_indicator: u8,
+        bootable: bool,
+    ) -> Result<(), HybridMbrError> {
+        self.add_partition(HybridMbrPartition {
+            range: entry
+                .lba_range()
+                .ok_or(HybridMbrError::PartitionOutOfRange)?,
+            os_indicator,
+            bootable,
+        })
+    }
+
+    /// Iterate over the mirrored partitions in the order they were added.
+    pub fn partitions(&self) -> impl Iterator<Item = HybridMbrPartition> + '_ {
+        self.partitions.iter().flatten().copied()
+    }
+
+    /// Put the protective partition in the first MBR partition record
+    /// rather than after the mirrored partitions. Some firmware only
+    /// boots from a disk with this layout. Defaults to `false`.
+    pub fn set_protective_first(&mut self, protective_first: bool) {
+        self.protective_first = protective_first;
+    }
+
+    /// Set the geometry used to calculate the CHS fields of the
+    /// partition records. Defaults to [`DiskGeometry::UNKNOWN`].
+    pub fn set_geometry(&mut self, geometry: DiskGeometry) {
+        self.geometry = geometry;
+    }
+
+    /// Set the MBR disk signature. Defaults to zero. See
+    /// [`MasterBootRecord::set_disk_signature`].
+    pub fn set_disk_signature(&mut self, disk_signature: u32) {
+        self.disk_signature = disk_signature;
+    }
+
+    /// Create the [`MasterBootRecord`] for a disk with `num_blocks`
+    /// blocks.
+    ///
+    /// The protective partition starts at LBA 1 and ends just before
+    /// the first mirrored partition. If there are no mirrored
+    /// partitions, this is the same as
+    /// [`MasterBootRecord::protective_mbr_with_geometry`].
+    ///
+    /// Returns [`HybridMbrError::PartitionOutOfRange`] if any mirrored
+    /// partition extends past the end of the disk.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn build(
+        &self,
+        num_blocks: u64,
+    ) -> Result<MasterBootRecord, HybridMbrError> {
+        let mut mbr = MasterBootRecord::protective_mbr_with_geometry(
+            num_blocks,
+            self.geometry,
+        );
+        mbr.set_disk_signature(self.disk_signature);
+
+        if self
+            .partitions()
+            .any(|p| p.range.end().to_u64() >= num_blocks)
+        {
+            return Err(HybridMbrError::PartitionOutOfRange);
+        }
+
+        let Some(first_start) =
+            self.partitions().map(|p| p.range.start()).min()
+        else {
+            return Ok(mbr);
+        };
+
+        // OK to unwrap: mirrored partitions start at LBA 2 or later.
+        let protective_range =
+            LbaRangeInclusive::new(Lba(1), Lba(first_start.to_u64() - 1))
+                .unwrap();
+        let protective = HybridMbrPartition {
+            range: protective_range,
+            os_indicator: 0xee,
+            bootable: false,
+        };
+
+        let mut records = [MbrPartitionRecord::default(); 4];
+        let mut slots = records.iter_mut();
+        if self.protective_first {
+            // OK to unwrap: there are four slots.
+            *slots.next().unwrap() = protective.to_record(self.geometry);
+        }
+        for (partition, slot) in self.partitions().zip(&mut slots) {
+            *slot = partition.to_record(self.geometry);
+        }
+        if !self.protective_first {
+            // OK to unwrap: there are at most three mirrored partitions.
+            *slots.next().unwrap() = protective.to_record(self.geometry);
+        }
+
+        mbr.partitions = records;
+        Ok(mbr)
+    }
+}
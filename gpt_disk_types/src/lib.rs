@@ -18,13 +18,13 @@
 //! ```
 //!
 //! 1. The first block of the disk contains a protective MBR. See
-//! [`MasterBootRecord::protective_mbr`].
+//!    [`MasterBootRecord::protective_mbr`].
 //! 2. The second block of the disk contains the primary GPT header. See
-//! [`GptHeader`].
+//!    [`GptHeader`].
 //! 3. Additional blocks after the header contain the partition entry
-//! array. See [`GptPartitionEntry`] and [`GptPartitionEntryArray`].
+//!    array. See [`GptPartitionEntry`] and [`GptPartitionEntryArray`].
 //! 4. At the end of the disk is a secondary GPT header and partition
-//! entry array.
+//!    entry array.
 //!
 //! # Endianness
 //!
@@ -41,11 +41,29 @@
 //!
 //! # Features
 //!
+//! * `alloc`: Adds `GptPartitionEntryArrayBuf`, a partition entry array
+//!   that owns its storage.
+//! * `arbitrary`: Implements arbitrary's `Arbitrary` trait for the same
+//!   types as `zerocopy`, for use in fuzz targets.
 //! * `bytemuck`: Implements bytemuck's `Pod` and `Zeroable` traits for
-//!    many of the types in this crate. Also enables some methods that
-//!    rely on byte access.
-//! * `std`: Provides `std::error::Error` implementations for all of the
-//!   error types. Off by default.
+//!   many of the types in this crate. Also enables some methods that
+//!   rely on byte access.
+//! * `defmt`: Implements defmt's `Format` trait for `Guid`, `GptHeader`,
+//!   `GptPartitionEntry`, `BlockSize`, and the error types, for logging
+//!   from embedded firmware.
+//! * `proptest`: Implements proptest's `Arbitrary` trait for `Guid`,
+//!   `GptHeader`, and `GptPartitionEntry`, and adds the `strategy` module
+//!   with a strategy for valid headers. Also enables `bytemuck`.
+//! * `serde`: Implements serde's `Serialize` and `Deserialize` traits for
+//!   `GptHeader`, `GptPartitionEntry`, `MbrPartitionRecord`, and the types
+//!   of their fields, as well as `Lba`. Integers are encoded as numbers.
+//!   GUIDs and partition names use strings in human-readable formats and
+//!   raw bytes in binary formats.
+//! * `std`: Enables `alloc`. All of the error types implement
+//!   `core::error::Error` regardless of this feature.
+//! * `zerocopy`: Implements zerocopy's `FromBytes`, `IntoBytes`,
+//!   `Immutable`, and `KnownLayout` traits for the same types as `bytemuck`, and
+//!   `Unaligned` for those with an alignment of one.
 //!
 //! # Examples
 //!
@@ -54,8 +72,7 @@
 //! ```
 //! use gpt_disk_types::{guid, Crc32, GptHeader, LbaLe, U32Le};
 //!
-//! let header = GptHeader {
-//!     header_crc32: Crc32(U32Le::from_u32(0xa4877843)),
+//! let mut header = GptHeader {
 //!     my_lba: LbaLe::from_u64(1),
 //!     alternate_lba: LbaLe::from_u64(8191),
 //!     first_usable_lba: LbaLe::from_u64(34),
@@ -66,6 +83,11 @@
 //!     partition_entry_array_crc32: Crc32(U32Le::from_u32(0x9206adff)),
 //!     ..Default::default()
 //! };
+//!
+//! // Set the header's checksum. This must be done after all other
+//! // fields are set.
+//! header.update_header_crc32();
+//! assert!(header.is_header_crc32_valid());
 //! ```
 //!
 //! Construct a GPT partition entry:
@@ -85,7 +107,7 @@
 //! };
 //! ```
 
-#![cfg_attr(not(feature = "std"), no_std)]
+#![no_std]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
@@ -100,26 +122,42 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// The code generated by the `Arbitrary` derive macro refers to `std`.
+#[cfg(feature = "arbitrary")]
+extern crate std;
+
+mod alignment;
 mod block;
 mod crc32;
 mod header;
+mod hybrid_mbr;
 mod mbr;
 mod num;
 mod partition_array;
 mod partition_entry;
-#[cfg(feature = "std")]
-mod std_support;
+mod summary;
+
+#[cfg(feature = "proptest")]
+pub mod strategy;
 
 // Re-export dependencies.
 pub use crc;
 pub use ucs2;
 pub use uguid::{guid, Guid, GuidFromStrError};
 
+pub use alignment::PartitionAlignment;
 pub use block::{BlockSize, Lba, LbaLe, LbaRangeInclusive};
 pub use crc32::Crc32;
-pub use header::{GptHeader, GptHeaderRevision, GptHeaderSignature};
+pub use header::{
+    GptHeader, GptHeaderError, GptHeaderRevision, GptHeaderSignature,
+};
+pub use hybrid_mbr::{HybridMbr, HybridMbrError, HybridMbrPartition};
 pub use mbr::{Chs, DiskGeometry, MasterBootRecord, MbrPartitionRecord};
 pub use num::{U16Le, U32Le, U64Le};
+#[cfg(feature = "alloc")]
+pub use partition_array::GptPartitionEntryArrayBuf;
 pub use partition_array::{
     GptPartitionEntryArray, GptPartitionEntryArrayError,
     GptPartitionEntryArrayLayout,
@@ -129,3 +167,4 @@ pub use partition_entry::{
     GptPartitionEntrySizeError, GptPartitionName, GptPartitionNameFromStrError,
     GptPartitionNameSetCharError, GptPartitionType,
 };
+pub use summary::{GptHeaderSummary, GptPartitionEntrySummary, SizeDisplay};
@@ -7,12 +7,21 @@
 // except according to those terms.
 
 use crate::num::format_u8_slice_lower_hex_le;
-use crate::{Lba, U32Le};
+use crate::{Lba, LbaRangeInclusive, U32Le};
 use core::fmt::{self, Display, Formatter};
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "bytemuck")]
 use bytemuck::{Pod, Zeroable};
 
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
 /// Legacy disk geometry used for converting between [`Lba`] and [`Chs`].
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct DiskGeometry {
@@ -50,11 +59,38 @@ impl Display for DiskGeometry {
 
 /// Legacy MBR cylinder/head/sector.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(C)]
 pub struct Chs(pub [u8; 3]);
 
 impl Chs {
+    /// Maximum CHS value. This is used in place of addresses that are
+    /// too large to represent, such as the end of a partition on a
+    /// large disk.
+    pub const MAX: Self = Self([0xff, 0xff, 0xff]);
+
+    /// Create a `Chs` from the packed three-byte on-disk format.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 3]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the packed three-byte on-disk format.
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; 3] {
+        self.0
+    }
+
     /// Get the 10 cylinder bits as a [`u16`].
     #[must_use]
     pub fn cylinder(self) -> u16 {
@@ -105,9 +141,12 @@ impl Chs {
     #[must_use]
     pub fn from_lba(lba: Lba, geom: DiskGeometry) -> Option<Self> {
         let lba = u32::try_from(lba.0).ok()?;
+        let sectors_per_cylinder = geom
+            .heads_per_cylinder
+            .checked_mul(geom.sectors_per_track)?;
 
         // https://en.wikipedia.org/wiki/Logical_block_addressing
-        let cylinder = lba / (geom.heads_per_cylinder * geom.sectors_per_track);
+        let cylinder = lba.checked_div(sectors_per_cylinder)?;
         let head = (lba / geom.sectors_per_track) % geom.heads_per_cylinder;
         let sector = (lba % geom.sectors_per_track) + 1;
 
@@ -117,6 +156,29 @@ impl Chs {
             sector.try_into().ok()?,
         )
     }
+
+    /// Convert CHS address to LBA. Returns `None` if the head or
+    /// sector is out of range for the geometry. Note that sectors are
+    /// numbered starting at one, so a sector of zero is always invalid.
+    #[must_use]
+    pub fn to_lba(self, geom: DiskGeometry) -> Option<Lba> {
+        let (cylinder, head, sector) = self.as_tuple();
+        let head = u32::from(head);
+        let sector = u32::from(sector);
+        if head >= geom.heads_per_cylinder
+            || sector == 0
+            || sector > geom.sectors_per_track
+        {
+            return None;
+        }
+
+        // https://en.wikipedia.org/wiki/Logical_block_addressing
+        let track = u64::from(cylinder) * u64::from(geom.heads_per_cylinder)
+            + u64::from(head);
+        Some(Lba(
+            track * u64::from(geom.sectors_per_track) + u64::from(sector - 1)
+        ))
+    }
 }
 
 impl Display for Chs {
@@ -135,7 +197,13 @@ impl Display for Chs {
 ///
 /// See Table 5-2 "Legacy MBR Partition Record" in the UEFI Specification.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(C)]
 pub struct MbrPartitionRecord {
     /// A value of `0x80` indicates this is a legacy bootable
@@ -168,6 +236,78 @@ pub struct MbrPartitionRecord {
     pub size_in_lba: U32Le,
 }
 
+impl MbrPartitionRecord {
+    /// Size of the record in bytes.
+    pub const SIZE: usize = 16;
+
+    /// Create a record from its on-disk representation.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        // OK to unwrap since the ranges are in bounds and the lengths
+        // match the target arrays.
+        Self {
+            boot_indicator: bytes[0],
+            start_chs: Chs(bytes[1..4].try_into().unwrap()),
+            os_indicator: bytes[4],
+            end_chs: Chs(bytes[5..8].try_into().unwrap()),
+            starting_lba: U32Le(bytes[8..12].try_into().unwrap()),
+            size_in_lba: U32Le(bytes[12..16].try_into().unwrap()),
+        }
+    }
+
+    /// Get the on-disk representation of the record.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0] = self.boot_indicator;
+        bytes[1..4].copy_from_slice(&self.start_chs.0);
+        bytes[4] = self.os_indicator;
+        bytes[5..8].copy_from_slice(&self.end_chs.0);
+        bytes[8..12].copy_from_slice(&self.starting_lba.0);
+        bytes[12..16].copy_from_slice(&self.size_in_lba.0);
+        bytes
+    }
+
+    /// Check if the [`boot_indicator`] marks this as a legacy bootable
+    /// partition.
+    ///
+    /// [`boot_indicator`]: Self::boot_indicator
+    #[must_use]
+    pub fn is_bootable(&self) -> bool {
+        self.boot_indicator == 0x80
+    }
+
+    /// Check if the record is in use. A record with an
+    /// [`os_indicator`] of zero is unused.
+    ///
+    /// [`os_indicator`]: Self::os_indicator
+    #[must_use]
+    pub fn is_used(&self) -> bool {
+        self.os_indicator != 0
+    }
+
+    /// Check if the record is the fake partition of a protective MBR,
+    /// indicated by an [`os_indicator`] of `0xee`.
+    ///
+    /// [`os_indicator`]: Self::os_indicator
+    #[must_use]
+    pub fn is_protective(&self) -> bool {
+        self.os_indicator == 0xee
+    }
+
+    /// Get the range of blocks covered by this partition. Returns
+    /// `None` if the [`size_in_lba`] is zero.
+    ///
+    /// [`size_in_lba`]: Self::size_in_lba
+    #[must_use]
+    pub fn lba_range(&self) -> Option<LbaRangeInclusive> {
+        let start = u64::from(self.starting_lba.to_u32());
+        let size = u64::from(self.size_in_lba.to_u32());
+        LbaRangeInclusive::new(Lba(start), Lba(start + size.checked_sub(1)?))
+    }
+}
+
 impl Display for MbrPartitionRecord {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str("MbrPartitionRecord { ")?;
@@ -185,6 +325,11 @@ impl Display for MbrPartitionRecord {
 ///
 /// See Table 5-1 "Legacy MBR" in the UEFI Specification.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(C)]
 pub struct MasterBootRecord {
     /// Executable code used on non-UEFI systems select a partition and
@@ -229,6 +374,108 @@ unsafe impl Pod for MasterBootRecord {}
 unsafe impl Zeroable for MasterBootRecord {}
 
 impl MasterBootRecord {
+    /// Size of the MBR in bytes.
+    pub const SIZE: usize = 512;
+
+    /// Value of the [`signature`] field in a valid MBR.
+    ///
+    /// [`signature`]: Self::signature
+    pub const SIGNATURE: [u8; 2] = [0x55, 0xaa];
+
+    /// Create an MBR from its on-disk representation, such as the first
+    /// 512 bytes of a disk.
+    ///
+    /// This does not validate the MBR; use [`is_signature_valid`] to
+    /// check that the data looks like an MBR.
+    ///
+    /// [`is_signature_valid`]: Self::is_signature_valid
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let record = |i: usize| {
+            let start = 446 + i * MbrPartitionRecord::SIZE;
+            let end = start + MbrPartitionRecord::SIZE;
+            // OK to unwrap since the length matches.
+            MbrPartitionRecord::from_bytes(
+                bytes[start..end].try_into().unwrap(),
+            )
+        };
+
+        // OK to unwrap since the ranges are in bounds and the lengths
+        // match the target arrays.
+        Self {
+            boot_strap_code: bytes[0..440].try_into().unwrap(),
+            unique_mbr_disk_signature: bytes[440..444].try_into().unwrap(),
+            unknown: bytes[444..446].try_into().unwrap(),
+            partitions: [record(0), record(1), record(2), record(3)],
+            signature: bytes[510..512].try_into().unwrap(),
+        }
+    }
+
+    /// Get the on-disk representation of the MBR.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..440].copy_from_slice(&self.boot_strap_code);
+        bytes[440..444].copy_from_slice(&self.unique_mbr_disk_signature);
+        bytes[444..446].copy_from_slice(&self.unknown);
+        for (chunk, partition) in bytes[446..510]
+            .chunks_exact_mut(MbrPartitionRecord::SIZE)
+            .zip(&self.partitions)
+        {
+            chunk.copy_from_slice(&partition.to_bytes());
+        }
+        bytes[510..512].copy_from_slice(&self.signature);
+        bytes
+    }
+
+    /// Check if the [`signature`] field matches [`Self::SIGNATURE`].
+    ///
+    /// [`signature`]: Self::signature
+    #[must_use]
+    pub fn is_signature_valid(&self) -> bool {
+        self.signature == Self::SIGNATURE
+    }
+
+    /// Get the [`unique_mbr_disk_signature`] as a [`u32`].
+    ///
+    /// [`unique_mbr_disk_signature`]: Self::unique_mbr_disk_signature
+    #[must_use]
+    pub fn disk_signature(&self) -> u32 {
+        u32::from_le_bytes(self.unique_mbr_disk_signature)
+    }
+
+    /// Set the [`unique_mbr_disk_signature`] from a [`u32`].
+    ///
+    /// [`unique_mbr_disk_signature`]: Self::unique_mbr_disk_signature
+    pub fn set_disk_signature(&mut self, disk_signature: u32) {
+        self.unique_mbr_disk_signature = disk_signature.to_le_bytes();
+    }
+
+    /// Check if this is a protective MBR, meaning that one of the
+    /// partition records has an OS type of `0xee`. See
+    /// [`MbrPartitionRecord::is_protective`].
+    #[must_use]
+    pub fn is_protective(&self) -> bool {
+        self.partitions
+            .iter()
+            .any(MbrPartitionRecord::is_protective)
+    }
+
+    /// Iterate over the partition records that are in use, along with
+    /// their index in [`partitions`]. See
+    /// [`MbrPartitionRecord::is_used`].
+    ///
+    /// [`partitions`]: Self::partitions
+    pub fn used_partitions(
+        &self,
+    ) -> impl Iterator<Item = (usize, &MbrPartitionRecord)> {
+        self.partitions
+            .iter()
+            .enumerate()
+            .filter(|(_, partition)| partition.is_used())
+    }
+
     /// Return whether the [`boot_strap_code`] field is all zeros or not.
     ///
     /// [`boot_strap_code`]: Self::boot_strap_code
@@ -239,10 +486,37 @@ impl MasterBootRecord {
 
     /// Create a protective MBR for the given disk size.
     ///
+    /// The partition covers the whole disk after the first block. If
+    /// the disk is too large for the partition's size to fit in 32
+    /// bits, the size is set to `0xffff_ffff`, and the end CHS is set
+    /// to its maximum value.
+    ///
+    /// The CHS fields are calculated with [`DiskGeometry::UNKNOWN`]; use
+    /// [`protective_mbr_with_geometry`] if the disk's geometry is known.
+    ///
     /// See section 5.2.3 "Protective MBR" of the UEFI Specification.
+    ///
+    /// [`protective_mbr_with_geometry`]: Self::protective_mbr_with_geometry
     #[must_use]
     pub fn protective_mbr(num_blocks: u64) -> Self {
-        let size_in_lba = u32::try_from(num_blocks).unwrap_or(0xffff_ffff);
+        Self::protective_mbr_with_geometry(num_blocks, DiskGeometry::UNKNOWN)
+    }
+
+    /// Create a protective MBR for the given disk size, using `geom`
+    /// to calculate the start and end CHS fields of the partition. If
+    /// an address cannot be represented in CHS, it is set to
+    /// [`Chs::MAX`].
+    ///
+    /// See [`protective_mbr`] for details.
+    ///
+    /// [`protective_mbr`]: Self::protective_mbr
+    #[must_use]
+    pub fn protective_mbr_with_geometry(
+        num_blocks: u64,
+        geom: DiskGeometry,
+    ) -> Self {
+        let last_lba = num_blocks.saturating_sub(1);
+        let size_in_lba = u32::try_from(last_lba).unwrap_or(0xffff_ffff);
 
         Self {
             boot_strap_code: [0; 440],
@@ -251,16 +525,12 @@ impl MasterBootRecord {
             partitions: [
                 MbrPartitionRecord {
                     boot_indicator: 0,
-                    // CHS=0,0,2
-                    start_chs: Chs([0, 2, 0]),
+                    start_chs: Chs::from_lba(Lba(1), geom).unwrap_or(Chs::MAX),
                     os_indicator: 0xee,
-                    end_chs: Chs::from_lba(
-                        Lba(num_blocks - 1),
-                        DiskGeometry::UNKNOWN,
-                    )
-                    .unwrap_or(Chs([0xff, 0xff, 0xff])),
+                    end_chs: Chs::from_lba(Lba(last_lba), geom)
+                        .unwrap_or(Chs::MAX),
                     starting_lba: U32Le::from_u32(1),
-                    size_in_lba: U32Le::from_u32(size_in_lba - 1),
+                    size_in_lba: U32Le::from_u32(size_in_lba),
                 },
                 MbrPartitionRecord::default(),
                 MbrPartitionRecord::default(),
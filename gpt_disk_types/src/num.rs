@@ -6,14 +6,29 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use core::cmp::Ordering;
 use core::fmt::{self, Debug, Display, Formatter, LowerHex};
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
 #[cfg(feature = "bytemuck")]
 use bytemuck::{Pod, Zeroable};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
 /// 16-bit unsigned integer stored as a little-endian.
 #[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct U16Le(pub [u8; 2]);
 
@@ -34,6 +49,24 @@ impl U16Le {
     pub fn set(&mut self, v: u16) {
         *self = Self::from_u16(v);
     }
+
+    /// Add `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_add(self, rhs: u16) -> Option<Self> {
+        match self.to_u16().checked_add(rhs) {
+            Some(v) => Some(Self::from_u16(v)),
+            None => None,
+        }
+    }
+
+    /// Subtract `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_sub(self, rhs: u16) -> Option<Self> {
+        match self.to_u16().checked_sub(rhs) {
+            Some(v) => Some(Self::from_u16(v)),
+            None => None,
+        }
+    }
 }
 
 impl Debug for U16Le {
@@ -56,7 +89,12 @@ impl LowerHex for U16Le {
 
 /// 32-bit unsigned integer stored as a little-endian.
 #[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct U32Le(pub [u8; 4]);
 
@@ -77,6 +115,24 @@ impl U32Le {
     pub fn set(&mut self, v: u32) {
         *self = Self::from_u32(v);
     }
+
+    /// Add `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_add(self, rhs: u32) -> Option<Self> {
+        match self.to_u32().checked_add(rhs) {
+            Some(v) => Some(Self::from_u32(v)),
+            None => None,
+        }
+    }
+
+    /// Subtract `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_sub(self, rhs: u32) -> Option<Self> {
+        match self.to_u32().checked_sub(rhs) {
+            Some(v) => Some(Self::from_u32(v)),
+            None => None,
+        }
+    }
 }
 
 impl Debug for U32Le {
@@ -99,7 +155,12 @@ impl LowerHex for U32Le {
 
 /// 64-bit unsigned integer stored as a little-endian.
 #[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct U64Le(pub [u8; 8]);
 
@@ -120,6 +181,24 @@ impl U64Le {
     pub fn set(&mut self, v: u64) {
         *self = Self::from_u64(v);
     }
+
+    /// Add `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_add(self, rhs: u64) -> Option<Self> {
+        match self.to_u64().checked_add(rhs) {
+            Some(v) => Some(Self::from_u64(v)),
+            None => None,
+        }
+    }
+
+    /// Subtract `rhs`, returning `None` if overflow occurs.
+    #[must_use]
+    pub const fn checked_sub(self, rhs: u64) -> Option<Self> {
+        match self.to_u64().checked_sub(rhs) {
+            Some(v) => Some(Self::from_u64(v)),
+            None => None,
+        }
+    }
 }
 
 impl Debug for U64Le {
@@ -140,6 +219,88 @@ impl LowerHex for U64Le {
     }
 }
 
+/// Implement conversions and comparisons between a little-endian
+/// integer type and the corresponding native integer type.
+///
+/// Note that the derived `Ord` and `PartialOrd` implementations compare
+/// the little-endian bytes, whereas these compare the integer values.
+macro_rules! impl_native_int {
+    ($ty:ident, $int:ident, $to:ident, $from:ident) => {
+        impl From<$int> for $ty {
+            fn from(v: $int) -> Self {
+                Self::$from(v)
+            }
+        }
+
+        impl From<$ty> for $int {
+            fn from(v: $ty) -> Self {
+                v.$to()
+            }
+        }
+
+        impl PartialEq<$int> for $ty {
+            fn eq(&self, other: &$int) -> bool {
+                self.$to() == *other
+            }
+        }
+
+        impl PartialEq<$ty> for $int {
+            fn eq(&self, other: &$ty) -> bool {
+                *self == other.$to()
+            }
+        }
+
+        impl PartialOrd<$int> for $ty {
+            fn partial_cmp(&self, other: &$int) -> Option<Ordering> {
+                self.$to().partial_cmp(other)
+            }
+        }
+
+        impl PartialOrd<$ty> for $int {
+            fn partial_cmp(&self, other: &$ty) -> Option<Ordering> {
+                self.partial_cmp(&other.$to())
+            }
+        }
+    };
+}
+
+impl_native_int!(U16Le, u16, to_u16, from_u16);
+impl_native_int!(U32Le, u32, to_u32, from_u32);
+impl_native_int!(U64Le, u64, to_u64, from_u64);
+
+/// Implement serde's `Serialize` and `Deserialize` traits for a
+/// little-endian integer type. The value is encoded as a native integer
+/// rather than as bytes, so it is readable in formats such as JSON.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde {
+    ($ty:ident, $int:ident, $to:ident, $from:ident) => {
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                self.$to().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                $int::deserialize(deserializer).map(Self::$from)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde!(U16Le, u16, to_u16, from_u16);
+#[cfg(feature = "serde")]
+impl_serde!(U32Le, u32, to_u32, from_u32);
+#[cfg(feature = "serde")]
+impl_serde!(U64Le, u64, to_u64, from_u64);
+
 pub(crate) fn format_u8_slice_lower_hex_le(
     f: &mut Formatter<'_>,
     s: &[u8],
@@ -7,14 +7,18 @@
 // except according to those terms.
 
 use crate::{BlockSize, Crc32, GptPartitionEntrySize, Lba, U32Le};
+use core::error::Error;
 use core::fmt::{self, Display, Formatter};
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 
 #[cfg(feature = "bytemuck")]
 use {
     crate::GptPartitionEntry,
-    bytemuck::{from_bytes, from_bytes_mut},
+    bytemuck::{bytes_of, from_bytes, from_bytes_mut},
     core::mem,
-    core::ops::Range,
 };
 
 /// Disk layout of a GPT partition entry array.
@@ -124,7 +128,10 @@ impl Display for GptPartitionEntryArrayLayout {
 }
 
 /// Errors used by [`GptPartitionEntryArray`].
+///
+/// This type implements the [`Error`] trait.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GptPartitionEntryArrayError {
     /// The storage buffer is not large enough. It must be at least
     /// [`layout.num_bytes_rounded_to_block`] in size.
@@ -134,6 +141,9 @@ pub enum GptPartitionEntryArrayError {
 
     /// Numeric overflow occurred.
     Overflow,
+
+    /// The partition entry index is out of range.
+    InvalidIndex,
 }
 
 impl Display for GptPartitionEntryArrayError {
@@ -141,10 +151,13 @@ impl Display for GptPartitionEntryArrayError {
         match self {
             Self::BufferTooSmall => f.write_str("storage buffer is too small"),
             Self::Overflow => f.write_str("numeric overflow occurred"),
+            Self::InvalidIndex => f.write_str("invalid partition entry index"),
         }
     }
 }
 
+impl Error for GptPartitionEntryArrayError {}
+
 /// Storage for a GPT partition entry array.
 #[allow(missing_debug_implementations)]
 pub struct GptPartitionEntryArray<'a> {
@@ -206,17 +219,31 @@ impl<'a> GptPartitionEntryArray<'a> {
         self.layout.start_lba = start_lba;
     }
 
-    #[cfg(feature = "bytemuck")]
-    fn get_entry_byte_range(&self, index: u32) -> Option<Range<usize>> {
-        if index >= self.layout.num_entries {
-            return None;
-        }
+    /// Get the raw bytes of a partition entry. The `index` is
+    /// zero-based.
+    ///
+    /// The length of the slice is the layout's [`entry_size`]. If that
+    /// is larger than 128 bytes, the slice includes the reserved bytes
+    /// that follow the [`GptPartitionEntry`] fields.
+    ///
+    /// [`GptPartitionEntry`]: crate::GptPartitionEntry
+    /// [`entry_size`]: GptPartitionEntryArrayLayout::entry_size
+    #[must_use]
+    pub fn get_partition_entry_bytes(&self, index: u32) -> Option<&[u8]> {
+        Some(&self.storage[entry_byte_range(&self.layout, index)?])
+    }
 
-        let start = usize::try_from(
-            u64::from(index) * u64::from(self.layout.entry_size.to_u32()),
-        )
-        .ok()?;
-        Some(start..start + mem::size_of::<GptPartitionEntry>())
+    /// Get the mutable raw bytes of a partition entry. The `index` is
+    /// zero-based. See [`get_partition_entry_bytes`].
+    ///
+    /// [`get_partition_entry_bytes`]: Self::get_partition_entry_bytes
+    #[must_use]
+    pub fn get_partition_entry_bytes_mut(
+        &mut self,
+        index: u32,
+    ) -> Option<&mut [u8]> {
+        let range = entry_byte_range(&self.layout, index)?;
+        Some(&mut self.storage[range])
     }
 
     /// Get a partition entry reference. The `index` is zero-based.
@@ -226,7 +253,9 @@ impl<'a> GptPartitionEntryArray<'a> {
         &self,
         index: u32,
     ) -> Option<&GptPartitionEntry> {
-        Some(from_bytes(&self.storage[self.get_entry_byte_range(index)?]))
+        Some(from_bytes(
+            &self.storage[entry_fields_byte_range(&self.layout, index)?],
+        ))
     }
 
     /// Get a mutable partition entry reference. The `index` is zero-based.
@@ -236,10 +265,33 @@ impl<'a> GptPartitionEntryArray<'a> {
         &mut self,
         index: u32,
     ) -> Option<&mut GptPartitionEntry> {
-        let range = self.get_entry_byte_range(index)?;
+        let range = entry_fields_byte_range(&self.layout, index)?;
         Some(from_bytes_mut(&mut self.storage[range]))
     }
 
+    /// Set a partition entry. The `index` is zero-based.
+    ///
+    /// Unlike writing through [`get_partition_entry_mut`], this also
+    /// zeroes the reserved bytes at the end of the entry when the
+    /// layout's entry size is larger than 128 bytes.
+    ///
+    /// [`get_partition_entry_mut`]: Self::get_partition_entry_mut
+    #[cfg(feature = "bytemuck")]
+    pub fn set_partition_entry(
+        &mut self,
+        index: u32,
+        entry: &GptPartitionEntry,
+    ) -> Result<(), GptPartitionEntryArrayError> {
+        let bytes = self
+            .get_partition_entry_bytes_mut(index)
+            .ok_or(GptPartitionEntryArrayError::InvalidIndex)?;
+        let (fields, reserved) =
+            bytes.split_at_mut(mem::size_of::<GptPartitionEntry>());
+        fields.copy_from_slice(bytes_of(entry));
+        reserved.fill(0);
+        Ok(())
+    }
+
     /// Calculate the CRC32 checksum for the partition entry array. The
     /// return value can then be set in the
     /// [`GptHeader::partition_entry_array_crc32`] field.
@@ -247,9 +299,212 @@ impl<'a> GptPartitionEntryArray<'a> {
     /// [`GptHeader::partition_entry_array_crc32`]: crate::GptHeader::partition_entry_array_crc32
     #[must_use]
     pub fn calculate_crc32(&self) -> Crc32 {
-        let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
-        let mut digest = crc.digest();
-        digest.update(&self.storage[..self.num_bytes_exact]);
-        Crc32(U32Le(digest.finalize().to_le_bytes()))
+        calculate_crc32(&self.storage[..self.num_bytes_exact])
+    }
+}
+
+/// Get the byte range of the entry at `index`, including any reserved
+/// bytes at the end of the entry.
+fn entry_byte_range(
+    layout: &GptPartitionEntryArrayLayout,
+    index: u32,
+) -> Option<Range<usize>> {
+    if index >= layout.num_entries {
+        return None;
+    }
+
+    let entry_size = layout.entry_size.to_usize()?;
+    let start = usize::try_from(
+        u64::from(index) * u64::from(layout.entry_size.to_u32()),
+    )
+    .ok()?;
+    Some(start..start + entry_size)
+}
+
+/// Get the byte range of the [`GptPartitionEntry`] fields of the entry
+/// at `index`, excluding any reserved bytes.
+#[cfg(feature = "bytemuck")]
+fn entry_fields_byte_range(
+    layout: &GptPartitionEntryArrayLayout,
+    index: u32,
+) -> Option<Range<usize>> {
+    let range = entry_byte_range(layout, index)?;
+    Some(range.start..range.start + mem::size_of::<GptPartitionEntry>())
+}
+
+fn calculate_crc32(bytes: &[u8]) -> Crc32 {
+    let crc = crc::Crc::<u32>::new(&Crc32::ALGORITHM);
+    let mut digest = crc.digest();
+    digest.update(bytes);
+    Crc32(U32Le(digest.finalize().to_le_bytes()))
+}
+
+/// Owned storage for a GPT partition entry array.
+///
+/// This is the same as [`GptPartitionEntryArray`], except that the
+/// storage is allocated with the correct size rather than borrowed. Use
+/// [`as_array_mut`] to pass it to functions that take a
+/// [`GptPartitionEntryArray`].
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{
+///     BlockSize, GptPartitionEntryArrayBuf, GptPartitionEntryArrayLayout,
+///     GptPartitionEntrySize, Lba,
+/// };
+///
+/// let layout = GptPartitionEntryArrayLayout {
+///     start_lba: Lba(2),
+///     entry_size: GptPartitionEntrySize::default(),
+///     num_entries: 4,
+/// };
+/// let mut array =
+///     GptPartitionEntryArrayBuf::new(layout, BlockSize::BS_512).unwrap();
+///
+/// // The storage is rounded up to a whole block.
+/// assert_eq!(array.storage().len(), 512);
+/// assert_eq!(array.get_partition_entry_bytes(3).unwrap().len(), 128);
+///
+/// // Borrow as a `GptPartitionEntryArray`.
+/// assert_eq!(array.as_array_mut().layout(), &layout);
+/// ```
+///
+/// [`as_array_mut`]: Self::as_array_mut
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct GptPartitionEntryArrayBuf {
+    layout: GptPartitionEntryArrayLayout,
+    block_size: BlockSize,
+    num_bytes_exact: usize,
+    storage: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl GptPartitionEntryArrayBuf {
+    /// Create a new `GptPartitionEntryArrayBuf` with the given
+    /// `layout`. The storage is zero-initialized, and its length is
+    /// [`layout.num_bytes_rounded_to_block`].
+    ///
+    /// [`layout.num_bytes_rounded_to_block`]: GptPartitionEntryArrayLayout::num_bytes_rounded_to_block
+    pub fn new(
+        layout: GptPartitionEntryArrayLayout,
+        block_size: BlockSize,
+    ) -> Result<Self, GptPartitionEntryArrayError> {
+        let num_bytes_required = layout
+            .num_bytes_rounded_to_block_as_usize(block_size)
+            .ok_or(GptPartitionEntryArrayError::Overflow)?;
+
+        let num_bytes_exact = layout
+            .num_bytes_exact_as_usize()
+            .ok_or(GptPartitionEntryArrayError::Overflow)?;
+
+        Ok(Self {
+            layout,
+            block_size,
+            num_bytes_exact,
+            storage: vec![0; num_bytes_required],
+        })
+    }
+
+    /// Get a [`GptPartitionEntryArray`] that borrows this storage.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn as_array_mut(&mut self) -> GptPartitionEntryArray<'_> {
+        // OK to unwrap: the storage was created with the required size.
+        GptPartitionEntryArray::new(
+            self.layout,
+            self.block_size,
+            &mut self.storage,
+        )
+        .unwrap()
+    }
+
+    /// Get a reference to the storage buffer.
+    #[must_use]
+    pub fn storage(&self) -> &[u8] {
+        &self.storage
+    }
+
+    /// Get a mutable reference to the storage buffer.
+    #[must_use]
+    pub fn storage_mut(&mut self) -> &mut [u8] {
+        &mut self.storage
+    }
+
+    /// Convert into the storage buffer.
+    #[must_use]
+    pub fn into_storage(self) -> Vec<u8> {
+        self.storage
+    }
+
+    /// Get the partition entry array layout.
+    #[must_use]
+    pub fn layout(&self) -> &GptPartitionEntryArrayLayout {
+        &self.layout
+    }
+
+    /// Change the partition entry array's start [`Lba`].
+    pub fn set_start_lba(&mut self, start_lba: Lba) {
+        self.layout.start_lba = start_lba;
+    }
+
+    /// Get the raw bytes of a partition entry. See
+    /// [`GptPartitionEntryArray::get_partition_entry_bytes`].
+    #[must_use]
+    pub fn get_partition_entry_bytes(&self, index: u32) -> Option<&[u8]> {
+        Some(&self.storage[entry_byte_range(&self.layout, index)?])
+    }
+
+    /// Get the mutable raw bytes of a partition entry. See
+    /// [`GptPartitionEntryArray::get_partition_entry_bytes`].
+    #[must_use]
+    pub fn get_partition_entry_bytes_mut(
+        &mut self,
+        index: u32,
+    ) -> Option<&mut [u8]> {
+        let range = entry_byte_range(&self.layout, index)?;
+        Some(&mut self.storage[range])
+    }
+
+    /// Get a partition entry reference. The `index` is zero-based.
+    #[cfg(feature = "bytemuck")]
+    #[must_use]
+    pub fn get_partition_entry(
+        &self,
+        index: u32,
+    ) -> Option<&GptPartitionEntry> {
+        Some(from_bytes(
+            &self.storage[entry_fields_byte_range(&self.layout, index)?],
+        ))
+    }
+
+    /// Get a mutable partition entry reference. The `index` is zero-based.
+    #[cfg(feature = "bytemuck")]
+    #[must_use]
+    pub fn get_partition_entry_mut(
+        &mut self,
+        index: u32,
+    ) -> Option<&mut GptPartitionEntry> {
+        let range = entry_fields_byte_range(&self.layout, index)?;
+        Some(from_bytes_mut(&mut self.storage[range]))
+    }
+
+    /// Set a partition entry. See
+    /// [`GptPartitionEntryArray::set_partition_entry`].
+    #[cfg(feature = "bytemuck")]
+    pub fn set_partition_entry(
+        &mut self,
+        index: u32,
+        entry: &GptPartitionEntry,
+    ) -> Result<(), GptPartitionEntryArrayError> {
+        self.as_array_mut().set_partition_entry(index, entry)
+    }
+
+    /// Calculate the CRC32 checksum for the partition entry array. See
+    /// [`GptPartitionEntryArray::calculate_crc32`].
+    #[must_use]
+    pub fn calculate_crc32(&self) -> Crc32 {
+        calculate_crc32(&self.storage[..self.num_bytes_exact])
     }
 }
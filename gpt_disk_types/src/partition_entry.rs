@@ -7,18 +7,41 @@
 // except according to those terms.
 
 use crate::{
-    guid, Guid, GuidFromStrError, LbaLe, LbaRangeInclusive, U16Le, U64Le,
+    guid, BlockSize, GptPartitionEntrySummary, Guid, GuidFromStrError, Lba,
+    LbaLe, LbaRangeInclusive, U16Le, U64Le,
 };
+use core::error::Error;
 use core::fmt::{self, Display, Formatter};
 use core::num::NonZeroU32;
 use core::str::FromStr;
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "serde")]
+use serde::de::{self, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 #[cfg(feature = "bytemuck")]
 use bytemuck::{Pod, Zeroable};
 
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
 /// Unique ID representing the type of a partition.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout)
+)]
 #[repr(transparent)]
 pub struct GptPartitionType(pub Guid);
 
@@ -54,10 +77,353 @@ impl GptPartitionType {
     pub const CHROME_OS_ROOT_FS: Self =
         Self(guid!("3cb8e202-3b7e-47dd-8a3c-7ff2a13cfcec"));
 
-    // TODO: there are many more "known" partition types for which we
-    // could add constants.
+    /// Microsoft reserved partition.
+    pub const MICROSOFT_RESERVED: Self =
+        Self(guid!("e3c9e316-0b5c-4db8-817d-f92df00215ae"));
+
+    /// Windows recovery environment.
+    pub const WINDOWS_RECOVERY: Self =
+        Self(guid!("de94bba4-06d1-4d40-a16a-bfd50179d6ac"));
+
+    /// Windows Logical Disk Manager metadata partition.
+    pub const WINDOWS_LDM_METADATA: Self =
+        Self(guid!("5808c8aa-7e8f-42e0-85d2-e1e90434cfb3"));
+
+    /// Windows Logical Disk Manager data partition.
+    pub const WINDOWS_LDM_DATA: Self =
+        Self(guid!("af9b60a0-1431-4f62-bc68-3311714a69ad"));
+
+    /// ChromeOS reserved partition.
+    pub const CHROME_OS_RESERVED: Self =
+        Self(guid!("2e0a753d-9e48-43b0-8337-b15192cb1b5e"));
+
+    /// BIOS boot partition, used by GRUB on legacy BIOS systems.
+    pub const BIOS_BOOT: Self =
+        Self(guid!("21686148-6449-6e6f-744e-656564454649"));
+
+    /// Extended boot loader partition, as defined by the Boot Loader
+    /// Specification.
+    pub const XBOOTLDR: Self =
+        Self(guid!("bc13c2ff-59e6-4262-a352-b275fd6f7172"));
+
+    /// Linux filesystem data.
+    pub const LINUX_FILESYSTEM: Self =
+        Self(guid!("0fc63daf-8483-4772-8e79-3d69d8477de4"));
+
+    /// Linux swap partition.
+    pub const LINUX_SWAP: Self =
+        Self(guid!("0657fd6d-a4ab-43c4-84e5-0933c84b4f4f"));
+
+    /// Linux root partition for x86-64.
+    pub const LINUX_ROOT_X86_64: Self =
+        Self(guid!("4f68bce3-e8cd-4db1-96e7-fbcaf984b709"));
+
+    /// Linux root partition for 64-bit ARM.
+    pub const LINUX_ROOT_ARM64: Self =
+        Self(guid!("b921b045-1df0-41c3-af44-4c6f280d3fae"));
+
+    /// Linux `/home` partition.
+    pub const LINUX_HOME: Self =
+        Self(guid!("933ac7e1-2eb4-4f13-b844-0e14e2aef915"));
+
+    /// Linux `/srv` partition.
+    pub const LINUX_SRV: Self =
+        Self(guid!("3b8f8425-20e0-4f3b-907f-1a25a76f98e8"));
+
+    /// Linux `/var` partition.
+    pub const LINUX_VAR: Self =
+        Self(guid!("4d21b016-b534-45c2-a9fb-5c16e091fd2d"));
+
+    /// Linux Logical Volume Manager partition.
+    pub const LINUX_LVM: Self =
+        Self(guid!("e6d6d379-f507-44c2-a23c-238f2a3df928"));
+
+    /// Linux RAID partition.
+    pub const LINUX_RAID: Self =
+        Self(guid!("a19d880f-05fc-4d3b-a006-743f0f84911e"));
+
+    /// Linux LUKS encrypted partition.
+    pub const LINUX_LUKS: Self =
+        Self(guid!("ca7d7ccb-63ed-4c53-861c-1742536059cc"));
+
+    /// Linux plain dm-crypt partition.
+    pub const LINUX_DM_CRYPT: Self =
+        Self(guid!("7ffec5c9-2d00-49b7-8941-3ea10a5586b7"));
+
+    /// Linux reserved partition.
+    pub const LINUX_RESERVED: Self =
+        Self(guid!("8da63339-0007-60c0-c436-083ac8230908"));
+
+    /// FreeBSD boot partition.
+    pub const FREEBSD_BOOT: Self =
+        Self(guid!("83bd6b9d-7f41-11dc-be0b-001560b84f0f"));
+
+    /// FreeBSD disklabel partition.
+    pub const FREEBSD_DISKLABEL: Self =
+        Self(guid!("516e7cb4-6ecf-11d6-8ff8-00022d09712b"));
+
+    /// FreeBSD swap partition.
+    pub const FREEBSD_SWAP: Self =
+        Self(guid!("516e7cb5-6ecf-11d6-8ff8-00022d09712b"));
+
+    /// FreeBSD UFS partition.
+    pub const FREEBSD_UFS: Self =
+        Self(guid!("516e7cb6-6ecf-11d6-8ff8-00022d09712b"));
+
+    /// FreeBSD ZFS partition.
+    pub const FREEBSD_ZFS: Self =
+        Self(guid!("516e7cba-6ecf-11d6-8ff8-00022d09712b"));
+
+    /// Apple HFS+ partition.
+    pub const APPLE_HFS_PLUS: Self =
+        Self(guid!("48465300-0000-11aa-aa11-00306543ecac"));
+
+    /// Apple APFS container.
+    pub const APPLE_APFS: Self =
+        Self(guid!("7c3457ef-0000-11aa-aa11-00306543ecac"));
+
+    /// Apple boot partition, also known as Recovery HD.
+    pub const APPLE_BOOT: Self =
+        Self(guid!("426f6f74-0000-11aa-aa11-00306543ecac"));
+
+    /// Get a human-readable name for the partition type, such as
+    /// `"EFI system partition"`. Returns `None` if the type is not one
+    /// of the constants defined on `GptPartitionType`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::GptPartitionType;
+    ///
+    /// assert_eq!(
+    ///     GptPartitionType::LINUX_FILESYSTEM.name(),
+    ///     Some("Linux filesystem")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn name(self) -> Option<&'static str> {
+        KNOWN_PARTITION_TYPES
+            .iter()
+            .find(|known| known.partition_type == self)
+            .map(|known| known.name)
+    }
+
+    /// Get the short code used by `gdisk` and `sgdisk` for the
+    /// partition type, such as `0x8300` for
+    /// [`LINUX_FILESYSTEM`]. Returns `None` if the type is not one of
+    /// the constants defined on `GptPartitionType`.
+    ///
+    /// [`LINUX_FILESYSTEM`]: Self::LINUX_FILESYSTEM
+    #[must_use]
+    pub fn sgdisk_code(self) -> Option<u16> {
+        KNOWN_PARTITION_TYPES
+            .iter()
+            .find(|known| known.partition_type == self)
+            .map(|known| known.sgdisk_code)
+    }
+
+    /// Look up a partition type from the short code used by `gdisk` and
+    /// `sgdisk`. Returns `None` if the code is not known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::GptPartitionType;
+    ///
+    /// assert_eq!(
+    ///     GptPartitionType::from_sgdisk_code(0xef00),
+    ///     Some(GptPartitionType::EFI_SYSTEM)
+    /// );
+    ///
+    /// // Codes are typically written as four hex digits.
+    /// let code = u16::from_str_radix("8300", 16).unwrap();
+    /// assert_eq!(
+    ///     GptPartitionType::from_sgdisk_code(code),
+    ///     Some(GptPartitionType::LINUX_FILESYSTEM)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_sgdisk_code(code: u16) -> Option<Self> {
+        KNOWN_PARTITION_TYPES
+            .iter()
+            .find(|known| known.sgdisk_code == code)
+            .map(|known| known.partition_type)
+    }
 }
 
+/// Entry in [`KNOWN_PARTITION_TYPES`].
+struct KnownPartitionType {
+    partition_type: GptPartitionType,
+    sgdisk_code: u16,
+    name: &'static str,
+}
+
+/// Table of partition types with a constant defined on
+/// [`GptPartitionType`]. The codes and names match those used by
+/// `gdisk`.
+const KNOWN_PARTITION_TYPES: &[KnownPartitionType] = &[
+    KnownPartitionType {
+        partition_type: GptPartitionType::EFI_SYSTEM,
+        sgdisk_code: 0xef00,
+        name: "EFI system partition",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LEGACY_MBR,
+        sgdisk_code: 0xef01,
+        name: "MBR partition scheme",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::BIOS_BOOT,
+        sgdisk_code: 0xef02,
+        name: "BIOS boot partition",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::XBOOTLDR,
+        sgdisk_code: 0xea00,
+        name: "Freedesktop $BOOT",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::BASIC_DATA,
+        sgdisk_code: 0x0700,
+        name: "Microsoft basic data",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::MICROSOFT_RESERVED,
+        sgdisk_code: 0x0c01,
+        name: "Microsoft reserved",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::WINDOWS_RECOVERY,
+        sgdisk_code: 0x2700,
+        name: "Windows RE",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::WINDOWS_LDM_DATA,
+        sgdisk_code: 0x4200,
+        name: "Windows LDM data",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::WINDOWS_LDM_METADATA,
+        sgdisk_code: 0x4201,
+        name: "Windows LDM metadata",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::CHROME_OS_KERNEL,
+        sgdisk_code: 0x7f00,
+        name: "ChromeOS kernel",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::CHROME_OS_ROOT_FS,
+        sgdisk_code: 0x7f01,
+        name: "ChromeOS root",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::CHROME_OS_RESERVED,
+        sgdisk_code: 0x7f02,
+        name: "ChromeOS reserved",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_SWAP,
+        sgdisk_code: 0x8200,
+        name: "Linux swap",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_FILESYSTEM,
+        sgdisk_code: 0x8300,
+        name: "Linux filesystem",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_RESERVED,
+        sgdisk_code: 0x8301,
+        name: "Linux reserved",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_HOME,
+        sgdisk_code: 0x8302,
+        name: "Linux /home",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_ROOT_X86_64,
+        sgdisk_code: 0x8304,
+        name: "Linux x86-64 root (/)",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_ROOT_ARM64,
+        sgdisk_code: 0x8305,
+        name: "Linux ARM64 root (/)",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_SRV,
+        sgdisk_code: 0x8306,
+        name: "Linux /srv",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_DM_CRYPT,
+        sgdisk_code: 0x8308,
+        name: "Linux dm-crypt",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_LUKS,
+        sgdisk_code: 0x8309,
+        name: "Linux LUKS",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_VAR,
+        sgdisk_code: 0x8310,
+        name: "Linux /var",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_LVM,
+        sgdisk_code: 0x8e00,
+        name: "Linux LVM",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::FREEBSD_DISKLABEL,
+        sgdisk_code: 0xa500,
+        name: "FreeBSD disklabel",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::FREEBSD_BOOT,
+        sgdisk_code: 0xa501,
+        name: "FreeBSD boot",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::FREEBSD_SWAP,
+        sgdisk_code: 0xa502,
+        name: "FreeBSD swap",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::FREEBSD_UFS,
+        sgdisk_code: 0xa503,
+        name: "FreeBSD UFS",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::FREEBSD_ZFS,
+        sgdisk_code: 0xa504,
+        name: "FreeBSD ZFS",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::APPLE_BOOT,
+        sgdisk_code: 0xab00,
+        name: "Recovery HD",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::APPLE_HFS_PLUS,
+        sgdisk_code: 0xaf00,
+        name: "Apple HFS/HFS+",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::APPLE_APFS,
+        sgdisk_code: 0xaf0a,
+        name: "Apple APFS",
+    },
+    KnownPartitionType {
+        partition_type: GptPartitionType::LINUX_RAID,
+        sgdisk_code: 0xfd00,
+        name: "Linux RAID",
+    },
+];
+
 impl Display for GptPartitionType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if self == &Self::UNUSED {
@@ -79,7 +445,17 @@ impl FromStr for GptPartitionType {
 
 /// Partition attribute bits.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(transparent)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct GptPartitionAttributes(pub U64Le);
 
@@ -169,6 +545,287 @@ impl GptPartitionAttributes {
         self.0 .0[6] = attrs.0[0];
         self.0 .0[7] = attrs.0[1];
     }
+
+    /// Get one of the type-specific bits. `bit` is relative to the
+    /// start of the type-specific attributes, so bit `0` is attribute
+    /// bit `48`. See [`type_specific_attributes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit` is greater than `15`.
+    ///
+    /// [`type_specific_attributes`]: Self::type_specific_attributes
+    #[must_use]
+    #[track_caller]
+    pub fn type_specific_bit(self, bit: u8) -> bool {
+        assert!(bit < 16, "type-specific bit out of range");
+        self.type_specific_attributes().to_u16() & (1 << bit) != 0
+    }
+
+    /// Set one of the type-specific bits. `bit` is relative to the
+    /// start of the type-specific attributes, so bit `0` is attribute
+    /// bit `48`. See [`update_type_specific_attributes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit` is greater than `15`.
+    ///
+    /// [`update_type_specific_attributes`]: Self::update_type_specific_attributes
+    #[track_caller]
+    pub fn update_type_specific_bit(&mut self, bit: u8, set: bool) {
+        assert!(bit < 16, "type-specific bit out of range");
+        let mut attrs = self.type_specific_attributes().to_u16();
+        if set {
+            attrs |= 1 << bit;
+        } else {
+            attrs &= !(1 << bit);
+        }
+        self.update_type_specific_attributes(U16Le::from_u16(attrs));
+    }
+
+    /// Builder-style version of [`update_required_partition`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::GptPartitionAttributes;
+    ///
+    /// let attrs = GptPartitionAttributes::default()
+    ///     .with_required_partition(true)
+    ///     .with_type_specific_bit(12, true);
+    /// assert_eq!(attrs.0.to_u64(), 0x1000_0000_0000_0001);
+    /// ```
+    ///
+    /// [`update_required_partition`]: Self::update_required_partition
+    #[must_use]
+    pub fn with_required_partition(mut self, required: bool) -> Self {
+        self.update_required_partition(required);
+        self
+    }
+
+    /// Builder-style version of [`update_no_block_io_protocol`].
+    ///
+    /// [`update_no_block_io_protocol`]: Self::update_no_block_io_protocol
+    #[must_use]
+    pub fn with_no_block_io_protocol(
+        mut self,
+        no_block_io_protocol: bool,
+    ) -> Self {
+        self.update_no_block_io_protocol(no_block_io_protocol);
+        self
+    }
+
+    /// Builder-style version of [`update_legacy_bios_bootable`].
+    ///
+    /// [`update_legacy_bios_bootable`]: Self::update_legacy_bios_bootable
+    #[must_use]
+    pub fn with_legacy_bios_bootable(
+        mut self,
+        legacy_bios_bootable: bool,
+    ) -> Self {
+        self.update_legacy_bios_bootable(legacy_bios_bootable);
+        self
+    }
+
+    /// Builder-style version of [`update_type_specific_attributes`].
+    ///
+    /// [`update_type_specific_attributes`]: Self::update_type_specific_attributes
+    #[must_use]
+    pub fn with_type_specific_attributes(mut self, attrs: U16Le) -> Self {
+        self.update_type_specific_attributes(attrs);
+        self
+    }
+
+    /// Builder-style version of [`update_type_specific_bit`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit` is greater than `15`.
+    ///
+    /// [`update_type_specific_bit`]: Self::update_type_specific_bit
+    #[must_use]
+    #[track_caller]
+    pub fn with_type_specific_bit(mut self, bit: u8, set: bool) -> Self {
+        self.update_type_specific_bit(bit, set);
+        self
+    }
+}
+
+/// Accessors for the type-specific bits used by ChromeOS kernel
+/// partitions and Android A/B slots.
+///
+/// These only have meaning for partitions of the corresponding type;
+/// the caller is responsible for checking
+/// [`GptPartitionEntry::partition_type_guid`].
+// This lint incorrectly says that "ChromeOS" should be in backticks.
+#[allow(clippy::doc_markdown)]
+impl GptPartitionAttributes {
+    /// Maximum value of [`cros_priority`] and [`cros_tries`].
+    ///
+    /// [`cros_priority`]: Self::cros_priority
+    /// [`cros_tries`]: Self::cros_tries
+    pub const CROS_MAX: u8 = 15;
+
+    /// Maximum value of [`android_priority`].
+    ///
+    /// [`android_priority`]: Self::android_priority
+    pub const ANDROID_MAX_PRIORITY: u8 = 3;
+
+    /// Maximum value of [`android_retry_count`].
+    ///
+    /// [`android_retry_count`]: Self::android_retry_count
+    pub const ANDROID_MAX_RETRY_COUNT: u8 = 7;
+
+    /// Get a field of `max.count_ones()` bits at `shift` within the
+    /// type-specific attributes.
+    fn type_specific_field(self, shift: u8, max: u8) -> u8 {
+        let bits = self.type_specific_attributes().to_u16() >> shift;
+        // OK to unwrap: the value is masked to fit in a `u8`.
+        u8::try_from(bits & u16::from(max)).unwrap()
+    }
+
+    /// Set a field of `max.count_ones()` bits at `shift` within the
+    /// type-specific attributes. Returns `false` without modifying the
+    /// attributes if `value` is greater than `max`.
+    fn update_type_specific_field(
+        &mut self,
+        shift: u8,
+        max: u8,
+        value: u8,
+    ) -> bool {
+        if value > max {
+            return false;
+        }
+        let mask = u16::from(max) << shift;
+        let old = self.type_specific_attributes().to_u16();
+        let new = (old & !mask) | (u16::from(value) << shift);
+        self.update_type_specific_attributes(U16Le::from_u16(new));
+        true
+    }
+
+    /// Get the ChromeOS kernel boot priority, from `0` (not bootable)
+    /// to [`CROS_MAX`] (highest). Stored in bits `48..=51`.
+    ///
+    /// [`CROS_MAX`]: Self::CROS_MAX
+    #[must_use]
+    pub fn cros_priority(self) -> u8 {
+        self.type_specific_field(0, Self::CROS_MAX)
+    }
+
+    /// Set the ChromeOS kernel boot priority. Returns `false` without
+    /// modifying the attributes if `priority` is greater than
+    /// [`CROS_MAX`].
+    ///
+    /// [`CROS_MAX`]: Self::CROS_MAX
+    pub fn update_cros_priority(&mut self, priority: u8) -> bool {
+        self.update_type_specific_field(0, Self::CROS_MAX, priority)
+    }
+
+    /// Get the number of ChromeOS kernel boot attempts remaining, from
+    /// `0` to [`CROS_MAX`]. Stored in bits `52..=55`.
+    ///
+    /// [`CROS_MAX`]: Self::CROS_MAX
+    #[must_use]
+    pub fn cros_tries(self) -> u8 {
+        self.type_specific_field(4, Self::CROS_MAX)
+    }
+
+    /// Set the number of ChromeOS kernel boot attempts remaining.
+    /// Returns `false` without modifying the attributes if `tries` is
+    /// greater than [`CROS_MAX`].
+    ///
+    /// [`CROS_MAX`]: Self::CROS_MAX
+    pub fn update_cros_tries(&mut self, tries: u8) -> bool {
+        self.update_type_specific_field(4, Self::CROS_MAX, tries)
+    }
+
+    /// Get whether the ChromeOS kernel has booted successfully. Stored
+    /// in bit `56`.
+    #[must_use]
+    pub fn cros_successful(self) -> bool {
+        self.type_specific_bit(8)
+    }
+
+    /// Set whether the ChromeOS kernel has booted successfully.
+    pub fn update_cros_successful(&mut self, successful: bool) {
+        self.update_type_specific_bit(8, successful);
+    }
+
+    /// Get the Android A/B slot priority, from `0` to
+    /// [`ANDROID_MAX_PRIORITY`]. Stored in bits `48..=49`.
+    ///
+    /// [`ANDROID_MAX_PRIORITY`]: Self::ANDROID_MAX_PRIORITY
+    #[must_use]
+    pub fn android_priority(self) -> u8 {
+        self.type_specific_field(0, Self::ANDROID_MAX_PRIORITY)
+    }
+
+    /// Set the Android A/B slot priority. Returns `false` without
+    /// modifying the attributes if `priority` is greater than
+    /// [`ANDROID_MAX_PRIORITY`].
+    ///
+    /// [`ANDROID_MAX_PRIORITY`]: Self::ANDROID_MAX_PRIORITY
+    pub fn update_android_priority(&mut self, priority: u8) -> bool {
+        self.update_type_specific_field(0, Self::ANDROID_MAX_PRIORITY, priority)
+    }
+
+    /// Get whether the Android A/B slot is active. Stored in bit `50`.
+    #[must_use]
+    pub fn android_active(self) -> bool {
+        self.type_specific_bit(2)
+    }
+
+    /// Set whether the Android A/B slot is active.
+    pub fn update_android_active(&mut self, active: bool) {
+        self.update_type_specific_bit(2, active);
+    }
+
+    /// Get the number of boot attempts remaining for the Android A/B
+    /// slot, from `0` to [`ANDROID_MAX_RETRY_COUNT`]. Stored in bits
+    /// `51..=53`.
+    ///
+    /// [`ANDROID_MAX_RETRY_COUNT`]: Self::ANDROID_MAX_RETRY_COUNT
+    #[must_use]
+    pub fn android_retry_count(self) -> u8 {
+        self.type_specific_field(3, Self::ANDROID_MAX_RETRY_COUNT)
+    }
+
+    /// Set the number of boot attempts remaining for the Android A/B
+    /// slot. Returns `false` without modifying the attributes if
+    /// `retry_count` is greater than [`ANDROID_MAX_RETRY_COUNT`].
+    ///
+    /// [`ANDROID_MAX_RETRY_COUNT`]: Self::ANDROID_MAX_RETRY_COUNT
+    pub fn update_android_retry_count(&mut self, retry_count: u8) -> bool {
+        self.update_type_specific_field(
+            3,
+            Self::ANDROID_MAX_RETRY_COUNT,
+            retry_count,
+        )
+    }
+
+    /// Get whether the Android A/B slot has booted successfully. Stored
+    /// in bit `54`.
+    #[must_use]
+    pub fn android_successful(self) -> bool {
+        self.type_specific_bit(6)
+    }
+
+    /// Set whether the Android A/B slot has booted successfully.
+    pub fn update_android_successful(&mut self, successful: bool) {
+        self.update_type_specific_bit(6, successful);
+    }
+
+    /// Get whether the Android A/B slot is marked unbootable. Stored in
+    /// bit `55`.
+    #[must_use]
+    pub fn android_unbootable(self) -> bool {
+        self.type_specific_bit(7)
+    }
+
+    /// Set whether the Android A/B slot is marked unbootable.
+    pub fn update_android_unbootable(&mut self, unbootable: bool) {
+        self.update_type_specific_bit(7, unbootable);
+    }
 }
 
 impl Display for GptPartitionAttributes {
@@ -212,7 +869,7 @@ struct GptPartitionNameCharIter<'a> {
     byte_index: usize,
 }
 
-impl<'a> Iterator for GptPartitionNameCharIter<'a> {
+impl Iterator for GptPartitionNameCharIter<'_> {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -242,11 +899,11 @@ impl<'a> Iterator for GptPartitionNameCharIter<'a> {
 
 /// Error type for [`GptPartitionName::set_char`].
 ///
-/// If the `std` feature is enabled, this type implements the [`Error`]
-/// trait.
+/// This type implements the [`Error`] trait.
 ///
-/// [`Error`]: std::error::Error
+/// [`Error`]: core::error::Error
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GptPartitionNameSetCharError {
     /// Character index is outside the range `0..36`.
     Index,
@@ -266,6 +923,8 @@ impl Display for GptPartitionNameSetCharError {
     }
 }
 
+impl Error for GptPartitionNameSetCharError {}
+
 /// Human readable partition label encoded as a null-terminated UCS-2
 /// string.
 ///
@@ -279,6 +938,11 @@ impl Display for GptPartitionNameSetCharError {
 /// let partition_name: GptPartitionName = "hacktheplanet".parse().unwrap();
 /// ```
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(transparent)]
 pub struct GptPartitionName(pub [u8; 72]);
 
@@ -309,6 +973,87 @@ impl GptPartitionName {
         }
     }
 
+    /// Get an iterator over the characters in the partition name, using
+    /// UTF-16 decoding. This is like [`chars`], except that surrogate
+    /// pairs are decoded as a single character rather than two
+    /// replacement characters. Unpaired surrogates are replaced with
+    /// the Unicode replacement character (`�`).
+    ///
+    /// The UEFI Specification requires UCS-2, but some tools write
+    /// names containing UTF-16 surrogate pairs.
+    ///
+    /// [`chars`]: Self::chars
+    pub fn chars_utf16_lossy(&self) -> impl Iterator<Item = char> + '_ {
+        char::decode_utf16(self.code_units())
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Get the length of the name in UCS-2 characters, not including
+    /// the null terminator. This is at most `36`, in which case the
+    /// name has no null terminator.
+    #[must_use]
+    pub fn len_chars(&self) -> usize {
+        self.code_units().count()
+    }
+
+    /// Iterate over the 16-bit code units up to the first null
+    /// terminator.
+    fn code_units(&self) -> impl Iterator<Item = u16> + '_ {
+        self.0
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|c| *c != 0)
+    }
+
+    /// Set the name from a UTF-8 string. Unlike [`from_str`], a string
+    /// that is too long is truncated rather than rejected. As with
+    /// [`from_str`], at most 35 characters are stored so that there is
+    /// room for a null terminator.
+    ///
+    /// Returns `true` if the string was truncated. If the stored part
+    /// of the string contains a character that cannot be represented
+    /// in UCS-2, [`GptPartitionNameFromStrError::InvalidChar`] is
+    /// returned and the name is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpt_disk_types::GptPartitionName;
+    ///
+    /// let mut name = GptPartitionName::default();
+    /// assert_eq!(name.set_from_str("root"), Ok(false));
+    /// assert_eq!(name.to_string(), "root");
+    ///
+    /// let long = "0123456789".repeat(4);
+    /// assert_eq!(name.set_from_str(&long), Ok(true));
+    /// assert_eq!(name.to_string(), long[..35]);
+    /// ```
+    ///
+    /// [`from_str`]: Self::from_str
+    pub fn set_from_str(
+        &mut self,
+        s: &str,
+    ) -> Result<bool, GptPartitionNameFromStrError> {
+        let mut name = Self::default();
+        let mut truncated = false;
+
+        // Leave room for null terminator.
+        let max_chars = name.0.len() / 2 - 1;
+
+        for (index, c) in s.chars().enumerate() {
+            if index >= max_chars {
+                truncated = true;
+                break;
+            }
+            let c = u16::try_from(u32::from(c))
+                .map_err(|_| GptPartitionNameFromStrError::InvalidChar)?;
+            name.0[index * 2..index * 2 + 2].copy_from_slice(&c.to_le_bytes());
+        }
+
+        *self = name;
+        Ok(truncated)
+    }
+
     /// Set a UCS-2 character. The `index` is by UCS-2 character rather
     /// than byte (e.g. index 3 indicates byte offset 6). This is valid
     /// because UCS-2 is a fixed-width encoding.
@@ -318,7 +1063,7 @@ impl GptPartitionName {
         c: char,
     ) -> Result<(), GptPartitionNameSetCharError> {
         // Ensure the index is valid.
-        if index > self.0.len() / 2 {
+        if index >= self.0.len() / 2 {
             return Err(GptPartitionNameSetCharError::Index);
         }
 
@@ -346,13 +1091,89 @@ impl Default for GptPartitionName {
     }
 }
 
+/// Human-readable formats such as JSON use the string form (see
+/// [`Display`]). Binary formats use the raw 72 bytes.
+#[cfg(feature = "serde")]
+impl Serialize for GptPartitionName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct GptPartitionNameVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for GptPartitionNameVisitor {
+    type Value = GptPartitionName;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a partition name string or 72 bytes")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let bytes = <[u8; 72]>::try_from(value)
+            .map_err(|_| E::invalid_length(value.len(), &self))?;
+        Ok(GptPartitionName(bytes))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut name = GptPartitionName::default();
+        for (i, byte) in name.0.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(73, &self));
+        }
+        Ok(name)
+    }
+}
+
+/// Accepts the forms produced by the [`Serialize`] implementation,
+/// depending on whether the format is human-readable.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for GptPartitionName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(GptPartitionNameVisitor)
+        } else {
+            deserializer.deserialize_bytes(GptPartitionNameVisitor)
+        }
+    }
+}
+
 /// Error type for [`GptPartitionName::from_str`].
 ///
-/// If the `std` feature is enabled, this type implements the [`Error`]
-/// trait.
+/// This type implements the [`Error`] trait.
 ///
-/// [`Error`]: std::error::Error
+/// [`Error`]: core::error::Error
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GptPartitionNameFromStrError {
     /// Input string is too long.
     Length,
@@ -370,6 +1191,8 @@ impl Display for GptPartitionNameFromStrError {
     }
 }
 
+impl Error for GptPartitionNameFromStrError {}
+
 impl From<ucs2::Error> for GptPartitionNameFromStrError {
     fn from(err: ucs2::Error) -> Self {
         match err {
@@ -405,7 +1228,13 @@ impl FromStr for GptPartitionName {
 
 /// An entry within the GPT partition array.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)
+)]
 #[repr(C, packed)]
 pub struct GptPartitionEntry {
     /// Unique ID representing the partition's type. If the type is
@@ -438,6 +1267,42 @@ impl GptPartitionEntry {
         LbaRangeInclusive::new(self.starting_lba.into(), self.ending_lba.into())
     }
 
+    /// Get the number of blocks in the partition. The `ending_lba` is
+    /// inclusive, so a partition whose `starting_lba` and `ending_lba`
+    /// are equal contains one block.
+    ///
+    /// Returns `None` if the `ending_lba` is less than the
+    /// `starting_lba`, or if the number of blocks does not fit in a
+    /// [`u64`].
+    #[must_use]
+    pub fn num_blocks(&self) -> Option<u64> {
+        self.ending_lba
+            .to_u64()
+            .checked_sub(self.starting_lba.to_u64())?
+            .checked_add(1)
+    }
+
+    /// Get the size of the partition in bytes. See [`num_blocks`].
+    ///
+    /// Returns `None` if the `ending_lba` is less than the
+    /// `starting_lba`, or if the size does not fit in a [`u64`].
+    ///
+    /// [`num_blocks`]: Self::num_blocks
+    #[must_use]
+    pub fn num_bytes(&self, block_size: BlockSize) -> Option<u64> {
+        self.num_blocks()?.checked_mul(block_size.to_u64())
+    }
+
+    /// Check if `lba` is within the partition. Both the `starting_lba`
+    /// and the `ending_lba` are considered part of the partition.
+    ///
+    /// Returns `false` if the `ending_lba` is less than the
+    /// `starting_lba`.
+    #[must_use]
+    pub fn contains_lba(&self, lba: Lba) -> bool {
+        self.lba_range().is_some_and(|range| range.contains(lba))
+    }
+
     /// Check if the entry is in use. If the [`partition_type_guid`] is
     /// [`GptPartitionType::UNUSED`], the entry is considered unused,
     /// which means there is no partition data associated with the entry.
@@ -448,6 +1313,27 @@ impl GptPartitionEntry {
         let partition_type_guid = self.partition_type_guid;
         partition_type_guid != GptPartitionType::UNUSED
     }
+
+    /// Get an adapter for displaying a one-line summary of the entry,
+    /// similar to a row in the partition table printed by `gdisk -p`.
+    /// The `number` is shown in the first column; `gdisk` numbers
+    /// partitions starting at one. The `block_size` is used to
+    /// calculate the size of the partition.
+    ///
+    /// The [`Display`] implementation of `GptPartitionEntry` shows the
+    /// raw fields instead.
+    #[must_use]
+    pub fn display_summary(
+        &self,
+        number: u32,
+        block_size: BlockSize,
+    ) -> GptPartitionEntrySummary<'_> {
+        GptPartitionEntrySummary {
+            entry: self,
+            number,
+            block_size,
+        }
+    }
 }
 
 impl Display for GptPartitionEntry {
@@ -465,13 +1351,34 @@ impl Display for GptPartitionEntry {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for GptPartitionEntry {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "GptPartitionEntry {{ partition_type_guid: {}, \
+             unique_partition_guid: {}, starting_lba: {=u64}, \
+             ending_lba: {=u64}, attributes: {=u64:#x}, name: \"",
+            { self.partition_type_guid }.0,
+            { self.unique_partition_guid },
+            self.starting_lba.to_u64(),
+            self.ending_lba.to_u64(),
+            self.attributes.0.to_u64(),
+        );
+        for c in self.name.chars() {
+            defmt::write!(f, "{=char}", c);
+        }
+        defmt::write!(f, "\" }}");
+    }
+}
+
 /// Error returned by [`GptPartitionEntrySize::new`].
 ///
-/// If the `std` feature is enabled, this type implements the [`Error`]
-/// trait.
+/// This type implements the [`Error`] trait.
 ///
-/// [`Error`]: std::error::Error
+/// [`Error`]: core::error::Error
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GptPartitionEntrySizeError;
 
 impl Display for GptPartitionEntrySizeError {
@@ -480,6 +1387,8 @@ impl Display for GptPartitionEntrySizeError {
     }
 }
 
+impl Error for GptPartitionEntrySizeError {}
+
 /// Size in bytes of entries in the partition entry array.
 ///
 /// A valid partition entry size must be a value of 128×2ⁿ, where n is
@@ -517,6 +1426,15 @@ impl GptPartitionEntrySize {
         self.0.get() as u64
     }
 
+    /// Get the number of reserved bytes at the end of each entry, after
+    /// the [`GptPartitionEntry`] fields. The UEFI Specification requires
+    /// these bytes to be zero.
+    #[must_use]
+    pub const fn num_reserved_bytes(self) -> u32 {
+        // No underflow: the size is at least 128.
+        self.to_u32() - 128
+    }
+
     /// Get the entry size in bytes as a [`usize`].
     #[must_use]
     pub fn to_usize(self) -> Option<usize> {
@@ -0,0 +1,117 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`proptest`] strategies for GPT types.
+//!
+//! [`GptHeader`] and [`GptPartitionEntry`] implement proptest's
+//! [`Arbitrary`] trait by filling the whole structure with random
+//! bytes, so most generated headers are not valid. Use
+//! [`valid_gpt_header`] to generate headers that are.
+//!
+//! [`proptest`]: https://docs.rs/proptest
+
+use crate::{
+    BlockSize, Crc32, GptHeader, GptHeaderRevision, GptHeaderSignature,
+    GptPartitionEntry, GptPartitionEntryArrayLayout, GptPartitionEntrySize,
+    Guid, Lba, LbaLe, U32Le,
+};
+use bytemuck::pod_read_unaligned;
+use core::mem;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+/// Generate a `T` from `size_of::<T>()` random bytes.
+fn any_pod<T: bytemuck::Pod + core::fmt::Debug>() -> BoxedStrategy<T> {
+    vec(any::<u8>(), mem::size_of::<T>())
+        .prop_map(|bytes| pod_read_unaligned(&bytes))
+        .boxed()
+}
+
+impl Arbitrary for GptHeader {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        any_pod()
+    }
+}
+
+impl Arbitrary for GptPartitionEntry {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        any_pod()
+    }
+}
+
+/// Generate valid primary headers for disks with 512-byte blocks.
+///
+/// The signature, revision, header size, and header CRC32 are valid,
+/// and the reserved field is zero. The header is at LBA 1 and the
+/// partition entry array starts at LBA 2. The alternate header is in
+/// the last block of the disk, preceded by a copy of the partition
+/// entry array. The usable range is everything in between, and is at
+/// least one block.
+///
+/// The disk GUID, number of partition entries (1 to 128), size of
+/// each partition entry (128, 256, or 512 bytes), number of usable
+/// blocks, and partition entry array CRC32 are random.
+pub fn valid_gpt_header() -> impl Strategy<Value = GptHeader> {
+    (
+        any::<Guid>(),
+        1..=128u32,
+        0..=2u32,
+        1..=1_000_000u64,
+        any::<u32>(),
+    )
+        .prop_map(create_valid_gpt_header)
+}
+
+fn create_valid_gpt_header(
+    (disk_guid, num_entries, entry_size_shift, num_usable, crc): (
+        Guid,
+        u32,
+        u32,
+        u64,
+        u32,
+    ),
+) -> GptHeader {
+    // OK to unwrap: the entry size is a valid power of two, and the
+    // array is small enough that the number of blocks can't overflow.
+    let entry_size =
+        GptPartitionEntrySize::new(128 << entry_size_shift).unwrap();
+    let layout = GptPartitionEntryArrayLayout {
+        start_lba: Lba(2),
+        entry_size,
+        num_entries,
+    };
+    let array_blocks = layout.num_blocks(BlockSize::BS_512).unwrap();
+
+    let first_usable = 2 + array_blocks;
+    let last_usable = first_usable + num_usable - 1;
+    let num_blocks = last_usable + array_blocks + 2;
+
+    let mut header = GptHeader {
+        signature: GptHeaderSignature::EFI_COMPATIBLE_PARTITION_TABLE_HEADER,
+        revision: GptHeaderRevision::VERSION_1_0,
+        my_lba: LbaLe::from_u64(1),
+        alternate_lba: LbaLe::from_u64(num_blocks - 1),
+        first_usable_lba: LbaLe::from_u64(first_usable),
+        last_usable_lba: LbaLe::from_u64(last_usable),
+        disk_guid,
+        partition_entry_lba: LbaLe::from_u64(2),
+        number_of_partition_entries: U32Le::from_u32(num_entries),
+        size_of_partition_entry: U32Le::from_u32(entry_size.to_u32()),
+        partition_entry_array_crc32: Crc32(U32Le::from_u32(crc)),
+        ..Default::default()
+    };
+    header.update_header_crc32();
+    header
+}
@@ -0,0 +1,238 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{BlockSize, GptHeader, GptPartitionEntry};
+use core::fmt::{self, Display, Formatter, Write};
+use core::str;
+
+/// Fixed-size buffer for formatting short strings without allocating.
+struct StrBuf {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl StrBuf {
+    fn as_str(&self) -> &str {
+        // OK to unwrap: only complete `str`s are written to the buffer.
+        str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl fmt::Write for StrBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let dst = self
+            .bytes
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// Size in bytes, displayed with binary units and one decimal place,
+/// such as "512.0 MiB" or "3.6 TiB". Sizes smaller than 1 KiB are
+/// displayed in bytes. Width and alignment flags are applied to the
+/// whole string.
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{BlockSize, SizeDisplay};
+///
+/// assert_eq!(SizeDisplay::from_bytes(100).to_string(), "100 bytes");
+/// assert_eq!(SizeDisplay::from_bytes(1536).to_string(), "1.5 KiB");
+/// assert_eq!(
+///     SizeDisplay::from_blocks(1_048_576, BlockSize::BS_512).to_string(),
+///     "512.0 MiB"
+/// );
+/// assert_eq!(
+///     format!("[{:>10}]", SizeDisplay::from_bytes(1024)),
+///     "[   1.0 KiB]"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct SizeDisplay(u128);
+
+impl SizeDisplay {
+    /// Create a `SizeDisplay` from a size in bytes.
+    #[allow(clippy::as_conversions)]
+    #[must_use]
+    pub const fn from_bytes(num_bytes: u64) -> Self {
+        Self(num_bytes as u128)
+    }
+
+    /// Create a `SizeDisplay` from a number of blocks of `block_size`
+    /// bytes. This cannot overflow, even if the size in bytes does not
+    /// fit in a [`u64`].
+    #[must_use]
+    pub fn from_blocks(num_blocks: u64, block_size: BlockSize) -> Self {
+        Self(u128::from(num_blocks) * u128::from(block_size.to_u64()))
+    }
+
+    /// Get the size in bytes.
+    #[must_use]
+    pub const fn to_bytes(self) -> u128 {
+        self.0
+    }
+}
+
+impl Display for SizeDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        let bytes = self.0;
+        let mut unit_size: u128 = 1;
+        let mut unit_name = None;
+        for name in UNITS {
+            if bytes < unit_size * 1024 {
+                break;
+            }
+            unit_size *= 1024;
+            unit_name = Some(name);
+        }
+
+        // Format into a buffer first so that padding is applied to the
+        // whole string.
+        let mut buf = StrBuf {
+            bytes: [0; 32],
+            len: 0,
+        };
+        if let Some(unit_name) = unit_name {
+            // Round to the nearest tenth of a unit.
+            let tenths = (bytes * 10 + unit_size / 2) / unit_size;
+            write!(buf, "{}.{} {unit_name}", tenths / 10, tenths % 10)?;
+        } else {
+            write!(buf, "{bytes} bytes")?;
+        }
+        f.pad(buf.as_str())
+    }
+}
+
+/// Adapter for displaying a multi-line summary of a [`GptHeader`],
+/// similar to the output of `gdisk -p`.
+///
+/// Created with [`GptHeader::display_summary`].
+#[derive(Clone, Copy, Debug)]
+pub struct GptHeaderSummary<'a> {
+    pub(crate) header: &'a GptHeader,
+    pub(crate) block_size: BlockSize,
+}
+
+impl Display for GptHeaderSummary<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let header = self.header;
+        writeln!(f, "Sector size: {} bytes", self.block_size)?;
+        writeln!(f, "Disk identifier (GUID): {}", &{ header.disk_guid })?;
+        writeln!(
+            f,
+            "Partition table holds up to {} entries",
+            header.number_of_partition_entries
+        )?;
+        write!(
+            f,
+            "Partition table begins at sector {}",
+            header.partition_entry_lba
+        )?;
+        let array_end = header
+            .get_partition_entry_array_layout()
+            .ok()
+            .and_then(|layout| layout.num_blocks(self.block_size))
+            .filter(|num_blocks| *num_blocks > 0)
+            .and_then(|num_blocks| {
+                header
+                    .partition_entry_lba
+                    .to_u64()
+                    .checked_add(num_blocks - 1)
+            });
+        if let Some(array_end) = array_end {
+            write!(f, " and ends at sector {array_end}")?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "First usable sector is {}, last usable sector is {}",
+            header.first_usable_lba, header.last_usable_lba
+        )
+    }
+}
+
+/// Adapter for displaying a one-line summary of a
+/// [`GptPartitionEntry`], similar to a row in the partition table
+/// printed by `gdisk -p`. The columns are the partition number, the
+/// start and end LBA, the size, the partition type, and the name.
+///
+/// Use [`GptPartitionEntrySummary::HEADER`] as the first line of a
+/// table of summaries.
+///
+/// Created with [`GptPartitionEntry::display_summary`].
+///
+/// # Examples
+///
+/// ```
+/// use gpt_disk_types::{
+///     BlockSize, GptPartitionEntry, GptPartitionEntrySummary,
+///     GptPartitionType, LbaLe,
+/// };
+///
+/// let entry = GptPartitionEntry {
+///     partition_type_guid: GptPartitionType::EFI_SYSTEM,
+///     starting_lba: LbaLe::from_u64(2048),
+///     ending_lba: LbaLe::from_u64(206847),
+///     name: "boot".parse().unwrap(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     GptPartitionEntrySummary::HEADER,
+///     "Number  Start (sector)    End (sector)        Size  Type                   Name"
+/// );
+/// assert_eq!(
+///     entry.display_summary(1, BlockSize::BS_512).to_string(),
+///     "     1            2048          206847   100.0 MiB  EFI system partition   boot"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct GptPartitionEntrySummary<'a> {
+    pub(crate) entry: &'a GptPartitionEntry,
+    pub(crate) number: u32,
+    pub(crate) block_size: BlockSize,
+}
+
+impl GptPartitionEntrySummary<'_> {
+    /// Column headings that line up with the summary.
+    pub const HEADER: &'static str =
+        "Number  Start (sector)    End (sector)        Size  Type                   Name";
+}
+
+impl Display for GptPartitionEntrySummary<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let entry = self.entry;
+        write!(
+            f,
+            "{:>6}  {:>14}  {:>14}  ",
+            self.number,
+            entry.starting_lba.to_u64(),
+            entry.ending_lba.to_u64()
+        )?;
+
+        if let Some(num_blocks) = entry.num_blocks() {
+            let size = SizeDisplay::from_blocks(num_blocks, self.block_size);
+            write!(f, "{size:>10}  ")?;
+        } else {
+            write!(f, "{:>10}  ", "invalid")?;
+        }
+
+        let partition_type = entry.partition_type_guid;
+        if let Some(name) = partition_type.name() {
+            write!(f, "{name:<21}")?;
+        } else {
+            write!(f, "{:<21}", partition_type.0)?;
+        }
+        write!(f, "  {}", entry.name)
+    }
+}
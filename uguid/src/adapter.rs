@@ -0,0 +1,112 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::Guid;
+use core::fmt::{self, Display, Formatter};
+use core::str;
+
+/// Adapter for displaying a [`Guid`] in the braced format used by
+/// Microsoft Windows, "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}".
+///
+/// Created with [`Guid::braced`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Braced(pub(crate) Guid);
+
+impl Braced {
+    /// Get the underlying [`Guid`].
+    #[must_use]
+    pub const fn guid(self) -> Guid {
+        self.0
+    }
+}
+
+impl Display for Braced {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut buf = [0; 38];
+        buf[0] = b'{';
+        buf[1..37].copy_from_slice(&self.0.to_ascii_hex_lower());
+        buf[37] = b'}';
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&buf).unwrap();
+        f.pad(s)
+    }
+}
+
+/// Adapter for displaying a [`Guid`] as a URN, in the format
+/// "urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx".
+///
+/// Created with [`Guid::urn`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Urn(pub(crate) Guid);
+
+impl Urn {
+    /// Get the underlying [`Guid`].
+    #[must_use]
+    pub const fn guid(self) -> Guid {
+        self.0
+    }
+}
+
+impl Display for Urn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut buf = [0; 45];
+        buf[..9].copy_from_slice(b"urn:uuid:");
+        buf[9..].copy_from_slice(&self.0.to_ascii_hex_lower());
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&buf).unwrap();
+        f.pad(s)
+    }
+}
+
+/// Adapter for displaying a [`Guid`] with upper-case hex digits, in the
+/// format "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX".
+///
+/// Created with [`Guid::uppercase`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Uppercase(pub(crate) Guid);
+
+impl Uppercase {
+    /// Get the underlying [`Guid`].
+    #[must_use]
+    pub const fn guid(self) -> Guid {
+        self.0
+    }
+}
+
+impl Display for Uppercase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ascii = self.0.to_ascii_hex_upper();
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&ascii).unwrap();
+        f.pad(s)
+    }
+}
+
+/// Adapter for displaying a [`Guid`] as 32 lower-case hex digits with no
+/// hyphens, in the format "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx".
+///
+/// Created with [`Guid::simple`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Simple(pub(crate) Guid);
+
+impl Simple {
+    /// Get the underlying [`Guid`].
+    #[must_use]
+    pub const fn guid(self) -> Guid {
+        self.0
+    }
+}
+
+impl Display for Simple {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ascii = self.0.to_ascii_hex_simple_lower();
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&ascii).unwrap();
+        f.pad(s)
+    }
+}
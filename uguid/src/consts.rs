@@ -0,0 +1,34 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Well-known GUID constants.
+//!
+//! The namespace GUIDs are defined in [RFC 4122 Appendix C][rfc], and
+//! can be passed to [`Guid::new_v3`] to create name-based GUIDs.
+//!
+//! [rfc]: https://datatracker.ietf.org/doc/html/rfc4122#appendix-C
+
+use crate::{guid, Guid};
+
+/// Nil GUID, with all bits set to zero. Same as [`Guid::ZERO`].
+pub const NIL: Guid = Guid::ZERO;
+
+/// Max GUID, with all bits set to one. Same as [`Guid::MAX`].
+pub const MAX: Guid = Guid::MAX;
+
+/// Namespace for fully-qualified domain names.
+pub const NAMESPACE_DNS: Guid = guid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+
+/// Namespace for URLs.
+pub const NAMESPACE_URL: Guid = guid!("6ba7b811-9dad-11d1-80b4-00c04fd430c8");
+
+/// Namespace for ISO OIDs.
+pub const NAMESPACE_OID: Guid = guid!("6ba7b812-9dad-11d1-80b4-00c04fd430c8");
+
+/// Namespace for X.500 DNs, in DER or text format.
+pub const NAMESPACE_X500: Guid = guid!("6ba7b814-9dad-11d1-80b4-00c04fd430c8");
@@ -6,19 +6,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use core::error::Error;
 use core::fmt::{self, Display, Formatter};
 
 /// Error type for [`Guid::try_parse`] and [`Guid::from_str`].
 ///
-/// If the `std` feature is enabled, this type implements the [`Error`]
-/// trait.
+/// This type implements the [`Error`] trait.
 ///
-/// [`Error`]: std::error::Error
+/// [`Error`]: core::error::Error
 /// [`Guid::from_str`]: core::str::FromStr::from_str
 /// [`Guid::try_parse`]: crate::Guid::try_parse
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GuidFromStrError {
     /// Input has the wrong length, expected 36 bytes.
+    #[default]
     Length,
 
     /// Input is missing a separator (`-`) at this byte index.
@@ -26,12 +28,18 @@ pub enum GuidFromStrError {
 
     /// Input contains invalid ASCII hex at this byte index.
     Hex(u8),
-}
 
-impl Default for GuidFromStrError {
-    fn default() -> Self {
-        Self::Length
-    }
+    /// Input is missing a brace (`{` or `}`) at this byte index. Only
+    /// returned by [`Guid::try_parse_braced`].
+    ///
+    /// [`Guid::try_parse_braced`]: crate::Guid::try_parse_braced
+    Brace(u8),
+
+    /// Input is missing the `urn:uuid:` prefix. Only returned by
+    /// [`Guid::try_parse_urn`].
+    ///
+    /// [`Guid::try_parse_urn`]: crate::Guid::try_parse_urn
+    UrnPrefix,
 }
 
 impl Display for GuidFromStrError {
@@ -50,6 +58,14 @@ impl Display for GuidFromStrError {
                     "GUID string contains invalid ASCII hex at index {index}",
                 )
             }
+            Self::Brace(index) => {
+                write!(f, "GUID string is missing a brace at index {index}")
+            }
+            Self::UrnPrefix => {
+                f.write_str("GUID string is missing the `urn:uuid:` prefix")
+            }
         }
     }
 }
+
+impl Error for GuidFromStrError {}
@@ -6,20 +6,51 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::util::{byte_to_ascii_hex_lower, parse_byte_from_ascii_str_at};
-use crate::GuidFromStrError;
-use core::fmt::{self, Display, Formatter};
+use crate::util::{
+    byte_to_ascii_hex_lower, parse_byte_from_ascii_str_at,
+    swap_field_byte_order,
+};
+use crate::{Braced, GuidFromStrError, Simple, Uppercase, Urn};
+use core::array::TryFromSliceError;
+use core::fmt::{self, Display, Formatter, LowerHex, UpperHex};
+use core::ptr;
 use core::str::{self, FromStr};
 
+#[cfg(feature = "borsh")]
+use borsh::{io, BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "proptest")]
+use proptest::{
+    arbitrary::{any, Arbitrary as PropArbitrary},
+    strategy::{Map, Strategy},
+};
+
+#[cfg(feature = "rand")]
+use rand::distributions::{Distribution, Standard};
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+#[cfg(feature = "schemars")]
+use {
+    alloc::borrow::Cow,
+    schemars::{json_schema, JsonSchema, Schema, SchemaGenerator},
+};
+
 #[cfg(feature = "serde")]
 use {
     serde::de::{self, Visitor},
     serde::{Deserialize, Deserializer, Serialize, Serializer},
 };
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
 #[cfg(feature = "bytemuck")]
 use bytemuck::{Pod, Zeroable};
 
+#[cfg(feature = "zerocopy")]
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
 /// Globally-unique identifier.
 ///
 /// The format is defined in [RFC 4122]. However, unlike "normal" UUIDs
@@ -35,7 +66,12 @@ use bytemuck::{Pod, Zeroable};
 /// [RFC 4122]: https://datatracker.ietf.org/doc/html/rfc4122
 /// [`uuid`]: https://docs.rs/uuid/latest/uuid
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(FromBytes, Immutable, IntoBytes, KnownLayout)
+)]
 #[repr(C)]
 pub struct Guid {
     // Use `u32` rather than `[u8; 4]` here so that the natural
@@ -62,6 +98,16 @@ impl Guid {
         node: [0; 6],
     };
 
+    /// GUID with all bits set to one.
+    pub const MAX: Self = Self {
+        time_low: u32::MAX,
+        time_mid: [0xff, 0xff],
+        time_high_and_version: [0xff, 0xff],
+        clock_seq_high_and_reserved: 0xff,
+        clock_seq_low: 0xff,
+        node: [0xff; 6],
+    };
+
     /// Create a new GUID.
     #[must_use]
     pub const fn new(
@@ -90,6 +136,42 @@ impl Guid {
         }
     }
 
+    /// Create a new GUID from native integer fields. This is the same as
+    /// [`new`], except that the first three fields are integers rather
+    /// than little-endian byte arrays.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// let node = [0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    /// assert_eq!(
+    ///     Guid::from_fields(0x01234567, 0x89ab, 0xcdef, 0x01, 0x23, node),
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+    /// );
+    /// ```
+    ///
+    /// [`new`]: Self::new
+    #[must_use]
+    pub const fn from_fields(
+        time_low: u32,
+        time_mid: u16,
+        time_high_and_version: u16,
+        clock_seq_high_and_reserved: u8,
+        clock_seq_low: u8,
+        node: [u8; 6],
+    ) -> Self {
+        Self::new(
+            time_low.to_le_bytes(),
+            time_mid.to_le_bytes(),
+            time_high_and_version.to_le_bytes(),
+            clock_seq_high_and_reserved,
+            clock_seq_low,
+            node,
+        )
+    }
+
     /// Create a version 4 GUID from provided random bytes.
     ///
     /// See [RFC 4122 section 4.4][rfc] for the definition of a version
@@ -123,6 +205,291 @@ impl Guid {
         Self::from_bytes(random_bytes)
     }
 
+    /// Create a random version 4 GUID, using the [`getrandom`] crate
+    /// to get random bytes from the operating system.
+    ///
+    /// This is available with the `getrandom` feature. On targets
+    /// without an operating system, such as UEFI or embedded targets,
+    /// `getrandom` allows registering a custom source of randomness;
+    /// see its documentation for details. Alternatively, use
+    /// [`from_random_bytes`] with bytes from another source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the random number generator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{Guid, Variant};
+    ///
+    /// let guid = Guid::new_v4();
+    /// assert_eq!(guid.variant(), Variant::Rfc4122);
+    /// assert_eq!(guid.version(), 4);
+    /// ```
+    ///
+    /// [`from_random_bytes`]: Self::from_random_bytes
+    /// [`getrandom`]: https://docs.rs/getrandom
+    #[cfg(feature = "getrandom")]
+    #[must_use]
+    pub fn new_v4() -> Self {
+        let mut random_bytes = [0; 16];
+        getrandom::getrandom(&mut random_bytes)
+            .expect("failed to get random bytes");
+        Self::from_random_bytes(random_bytes)
+    }
+
+    /// Create a version 1 GUID from a timestamp, clock sequence, and
+    /// node ID.
+    ///
+    /// See [RFC 9562 section 5.1][rfc] for the definition of a version
+    /// 1 GUID. `timestamp` is the number of 100-nanosecond intervals
+    /// since the start of the Gregorian calendar (1582-10-15 00:00:00
+    /// UTC); only its low 60 bits are used. Only the low 14 bits of
+    /// `clock_seq` are used. `node` is typically a MAC address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// // Example from RFC 9562 Appendix A.1.
+    /// let node = [0x9f, 0x6b, 0xde, 0xce, 0xd8, 0x46];
+    /// let guid = Guid::new_v1(0x1ec_9414_c232_ab00, 0x33c8, node);
+    /// assert_eq!(guid, guid!("c232ab00-9414-11ec-b3c8-9f6bdeced846"));
+    /// assert_eq!(guid.version(), 1);
+    /// ```
+    ///
+    /// [rfc]: https://datatracker.ietf.org/doc/html/rfc9562#section-5.1
+    #[must_use]
+    pub const fn new_v1(timestamp: u64, clock_seq: u16, node: [u8; 6]) -> Self {
+        let t = timestamp.to_be_bytes();
+        let c = clock_seq.to_be_bytes();
+        // Big-endian field order: time_low, time_mid, then
+        // time_high_and_version.
+        let bytes = [
+            t[4],
+            t[5],
+            t[6],
+            t[7],
+            t[2],
+            t[3],
+            0x10 | (t[0] & 0b0000_1111),
+            t[1],
+            0b1000_0000 | (c[0] & 0b0011_1111),
+            c[1],
+            node[0],
+            node[1],
+            node[2],
+            node[3],
+            node[4],
+            node[5],
+        ];
+        Self::from_bytes(swap_field_byte_order(bytes))
+    }
+
+    /// Create a version 6 GUID from a timestamp, clock sequence, and
+    /// node ID.
+    ///
+    /// See [RFC 9562 section 5.6][rfc] for the definition of a version
+    /// 6 GUID. This is the same as [`new_v1`], except that the
+    /// timestamp is stored with its most significant bits first, so
+    /// that GUIDs created later have a greater string form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// // Example from RFC 9562 Appendix A.5.
+    /// let node = [0x9f, 0x6b, 0xde, 0xce, 0xd8, 0x46];
+    /// let guid = Guid::new_v6(0x1ec_9414_c232_ab00, 0x33c8, node);
+    /// assert_eq!(guid, guid!("1ec9414c-232a-6b00-b3c8-9f6bdeced846"));
+    /// assert_eq!(guid.version(), 6);
+    /// ```
+    ///
+    /// [`new_v1`]: Self::new_v1
+    /// [rfc]: https://datatracker.ietf.org/doc/html/rfc9562#section-5.6
+    #[must_use]
+    pub const fn new_v6(timestamp: u64, clock_seq: u16, node: [u8; 6]) -> Self {
+        // The high 48 bits of the 60-bit timestamp.
+        let h = (timestamp << 4).to_be_bytes();
+        let t = timestamp.to_be_bytes();
+        let c = clock_seq.to_be_bytes();
+        let bytes = [
+            h[0],
+            h[1],
+            h[2],
+            h[3],
+            h[4],
+            h[5],
+            0x60 | (t[6] & 0b0000_1111),
+            t[7],
+            0b1000_0000 | (c[0] & 0b0011_1111),
+            c[1],
+            node[0],
+            node[1],
+            node[2],
+            node[3],
+            node[4],
+            node[5],
+        ];
+        Self::from_bytes(swap_field_byte_order(bytes))
+    }
+
+    /// Create a version 7 GUID from a Unix timestamp in milliseconds
+    /// and random bytes.
+    ///
+    /// See [RFC 9562 section 5.7][rfc] for the definition of a version
+    /// 7 GUID. Only the low 48 bits of `timestamp_ms` are used. The
+    /// variant and version bits are set in `random_bytes`, so 74 of its
+    /// 80 bits are used.
+    ///
+    /// Version 7 GUIDs created later have a greater string form. Note
+    /// that the [`Ord`] implementation of `Guid` compares the
+    /// little-endian fields as stored, so it does not match the time
+    /// order; compare the strings instead.
+    ///
+    /// This constructor does not itself get the time or generate random
+    /// bytes, so it can be used in `no_std` environments. See
+    /// [`new_v7`] for a version that does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// // Example from RFC 9562 Appendix A.6.
+    /// let guid = Guid::new_v7_from_parts(
+    ///     0x017f_22e2_79b0,
+    ///     [0x0c, 0xc3, 0x98, 0xc4, 0xdc, 0x0c, 0x0c, 0x07, 0x39, 0x8f],
+    /// );
+    /// assert_eq!(guid, guid!("017f22e2-79b0-7cc3-98c4-dc0c0c07398f"));
+    /// assert_eq!(guid.version(), 7);
+    /// ```
+    ///
+    /// [`new_v7`]: Self::new_v7
+    /// [rfc]: https://datatracker.ietf.org/doc/html/rfc9562#section-5.7
+    #[must_use]
+    pub const fn new_v7_from_parts(
+        timestamp_ms: u64,
+        random_bytes: [u8; 10],
+    ) -> Self {
+        let t = timestamp_ms.to_be_bytes();
+        let r = random_bytes;
+        // Big-endian field order. The version is in the high nibble of
+        // byte 6, and the variant is in the high two bits of byte 8.
+        let bytes = [
+            t[2],
+            t[3],
+            t[4],
+            t[5],
+            t[6],
+            t[7],
+            0x70 | (r[0] & 0b0000_1111),
+            r[1],
+            0b1000_0000 | (r[2] & 0b0011_1111),
+            r[3],
+            r[4],
+            r[5],
+            r[6],
+            r[7],
+            r[8],
+            r[9],
+        ];
+        Self::from_bytes(swap_field_byte_order(bytes))
+    }
+
+    /// Create a version 7 GUID from the current time and random bytes
+    /// from the operating system. See [`new_v7_from_parts`] for details
+    /// of the format.
+    ///
+    /// If the system clock is set before the Unix epoch, a timestamp of
+    /// zero is used. GUIDs created in the same millisecond are not
+    /// ordered relative to each other.
+    ///
+    /// This is available with the `std` and `getrandom` features.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the random number generator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{Guid, Variant};
+    ///
+    /// let guid = Guid::new_v7();
+    /// assert_eq!(guid.variant(), Variant::Rfc4122);
+    /// assert_eq!(guid.version(), 7);
+    /// ```
+    ///
+    /// [`new_v7_from_parts`]: Self::new_v7_from_parts
+    #[cfg(all(feature = "std", feature = "getrandom"))]
+    #[must_use]
+    pub fn new_v7() -> Self {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        let mut random_bytes = [0; 10];
+        getrandom::getrandom(&mut random_bytes)
+            .expect("failed to get random bytes");
+        Self::new_v7_from_parts(timestamp_ms, random_bytes)
+    }
+
+    /// Create a version 3 GUID from the MD5 hash of a namespace GUID and
+    /// a name.
+    ///
+    /// See [RFC 4122 section 4.3][rfc] for the definition of a version
+    /// 3 GUID. As in the RFC, the namespace is hashed in big-endian
+    /// byte order, so the result matches the string form of a version
+    /// 3 UUID created by other libraries from the same inputs.
+    ///
+    /// MD5 is not collision-resistant; this is intended for
+    /// compatibility with existing systems that use version 3 UUIDs.
+    ///
+    /// This is available with the `md5` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    /// use uguid::{Guid, Variant};
+    ///
+    /// // The DNS namespace from RFC 4122 Appendix C.
+    /// let dns = guid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+    /// let guid = Guid::new_v3(dns, b"www.example.com");
+    /// assert_eq!(guid, guid!("5df41881-3aed-3515-88a7-2f4a814cf09e"));
+    /// assert_eq!(guid.variant(), Variant::Rfc4122);
+    /// assert_eq!(guid.version(), 3);
+    /// ```
+    ///
+    /// [rfc]: https://datatracker.ietf.org/doc/html/rfc4122#section-4.3
+    #[cfg(feature = "md5")]
+    #[must_use]
+    pub fn new_v3(namespace: Self, name: &[u8]) -> Self {
+        use md5::{Digest, Md5};
+
+        let mut hasher = Md5::new();
+        hasher.update(swap_field_byte_order(namespace.to_bytes()));
+        hasher.update(name);
+        Self::from_name_hash(hasher.finalize().into(), 3)
+    }
+
+    /// Create a name-based GUID from the first 16 bytes of a hash,
+    /// which are in big-endian field order.
+    #[cfg(feature = "md5")]
+    const fn from_name_hash(mut hash: [u8; 16], version: u8) -> Self {
+        // Set the version in the most-significant nibble of byte 6.
+        hash[6] = (hash[6] & 0b0000_1111) | (version << 4);
+        // Set the variant in byte 8: set bit 7, clear bit 6.
+        hash[8] = (hash[8] & 0b0011_1111) | 0b1000_0000;
+
+        Self::from_bytes(swap_field_byte_order(hash))
+    }
+
     /// True if all bits are zero, false otherwise.
     ///
     /// # Example
@@ -154,6 +521,21 @@ impl Guid {
             && b[15] == 0
     }
 
+    /// True if all bits are one, false otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// assert!(Guid::MAX.is_max());
+    /// assert!(!guid!("308bbc16-a308-47e8-8977-5e5646c5291f").is_max());
+    /// ```
+    #[must_use]
+    pub const fn is_max(self) -> bool {
+        self.to_u128() == u128::MAX
+    }
+
     /// The little-endian low field of the timestamp.
     #[must_use]
     pub const fn time_low(self) -> [u8; 4] {
@@ -192,6 +574,54 @@ impl Guid {
         self.node
     }
 
+    /// The low field of the timestamp as a native integer.
+    #[must_use]
+    pub const fn time_low_u32(self) -> u32 {
+        u32::from_le_bytes(self.time_low())
+    }
+
+    /// The middle field of the timestamp as a native integer.
+    #[must_use]
+    pub const fn time_mid_u16(self) -> u16 {
+        u16::from_le_bytes(self.time_mid)
+    }
+
+    /// The high field of the timestamp multiplexed with the version
+    /// number, as a native integer.
+    #[must_use]
+    pub const fn time_high_and_version_u16(self) -> u16 {
+        u16::from_le_bytes(self.time_high_and_version)
+    }
+
+    /// All fields of the GUID, in the same order and with the same types
+    /// as the arguments to [`from_fields`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// let node = [0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    /// assert_eq!(
+    ///     guid.fields(),
+    ///     (0x01234567, 0x89ab, 0xcdef, 0x01, 0x23, node)
+    /// );
+    /// ```
+    ///
+    /// [`from_fields`]: Self::from_fields
+    #[must_use]
+    pub const fn fields(self) -> (u32, u16, u16, u8, u8, [u8; 6]) {
+        (
+            self.time_low_u32(),
+            self.time_mid_u16(),
+            self.time_high_and_version_u16(),
+            self.clock_seq_high_and_reserved,
+            self.clock_seq_low,
+            self.node,
+        )
+    }
+
     /// Get the GUID variant.
     ///
     /// # Example
@@ -237,10 +667,102 @@ impl Guid {
         (self.time_high_and_version[1] & 0b1111_0000) >> 4
     }
 
+    /// Get the GUID version as a [`Version`]. This is the same as
+    /// [`version`], but easier to match on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Version};
+    ///
+    /// assert_eq!(
+    ///     guid!("308bbc16-a308-47e8-8977-5e5646c5291f").get_version(),
+    ///     Version::Random
+    /// );
+    /// ```
+    ///
+    /// [`version`]: Self::version
+    #[must_use]
+    pub const fn get_version(self) -> Version {
+        Version::from_u8(self.version())
+    }
+
+    /// Get the timestamp of a time-based GUID.
+    ///
+    /// Returns `None` unless the GUID has the [`Variant::Rfc4122`]
+    /// variant and is version 1, 6, or 7. See [`new_v1`], [`new_v6`],
+    /// and [`new_v7_from_parts`] for the formats.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Timestamp};
+    ///
+    /// assert_eq!(
+    ///     guid!("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").timestamp(),
+    ///     Some(Timestamp::UnixMillis(0x017f_22e2_79b0))
+    /// );
+    /// assert_eq!(
+    ///     guid!("308bbc16-a308-47e8-8977-5e5646c5291f").timestamp(),
+    ///     None
+    /// );
+    /// ```
+    ///
+    /// [`new_v1`]: Self::new_v1
+    /// [`new_v6`]: Self::new_v6
+    /// [`new_v7_from_parts`]: Self::new_v7_from_parts
+    #[must_use]
+    pub const fn timestamp(self) -> Option<Timestamp> {
+        if !matches!(self.variant(), Variant::Rfc4122) {
+            return None;
+        }
+
+        // Big-endian field order.
+        let b = swap_field_byte_order(self.to_bytes());
+        let low_nibble = b[6] & 0b0000_1111;
+        match self.version() {
+            1 => Some(Timestamp::Gregorian(u64::from_be_bytes([
+                low_nibble, b[7], b[4], b[5], b[0], b[1], b[2], b[3],
+            ]))),
+            6 => {
+                let high = u64::from_be_bytes([
+                    0, 0, b[0], b[1], b[2], b[3], b[4], b[5],
+                ]);
+                let low =
+                    u64::from_be_bytes([0, 0, 0, 0, 0, 0, low_nibble, b[7]]);
+                Some(Timestamp::Gregorian((high << 12) | low))
+            }
+            7 => Some(Timestamp::UnixMillis(u64::from_be_bytes([
+                0, 0, b[0], b[1], b[2], b[3], b[4], b[5],
+            ]))),
+            _ => None,
+        }
+    }
+
     /// Parse a GUID from a string.
     ///
     /// This is functionally the same as [`Self::from_str`], but is
     /// exposed separately to provide a `const` method for parsing.
+    /// Unlike [`parse_or_panic`], errors can be handled in const code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{Guid, GuidFromStrError};
+    ///
+    /// const PARSED: Result<Guid, GuidFromStrError> =
+    ///     Guid::try_parse("01234567-89ab-cdef-0123-456789abcdef");
+    /// const GUID: Guid = match PARSED {
+    ///     Ok(guid) => guid,
+    ///     Err(_) => Guid::ZERO,
+    /// };
+    /// assert!(!GUID.is_zero());
+    ///
+    /// const INVALID: bool = Guid::try_parse("not a guid").is_err();
+    /// assert!(INVALID);
+    /// ```
+    ///
+    /// [`parse_or_panic`]: Self::parse_or_panic
     pub const fn try_parse(s: &str) -> Result<Self, GuidFromStrError> {
         // Treat input as ASCII.
         let s = s.as_bytes();
@@ -249,69 +771,313 @@ impl Guid {
             return Err(GuidFromStrError::Length);
         }
 
-        let sep = b'-';
-        if s[8] != sep {
-            return Err(GuidFromStrError::Separator(8));
-        }
-        if s[13] != sep {
-            return Err(GuidFromStrError::Separator(13));
+        Self::parse_hyphenated_at(s, 0)
+    }
+
+    /// Parse a GUID from a string in the braced format used by
+    /// Microsoft Windows, "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}".
+    ///
+    /// Returns [`GuidFromStrError::Length`] if the input is not exactly
+    /// 38 bytes long, and [`GuidFromStrError::Brace`] if the first or
+    /// last character is not a brace. Other errors are the same as for
+    /// [`try_parse`], with byte indices relative to the whole input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// assert_eq!(
+    ///     Guid::try_parse_braced("{01234567-89AB-CDEF-0123-456789ABCDEF}"),
+    ///     Ok(guid!("01234567-89ab-cdef-0123-456789abcdef"))
+    /// );
+    /// ```
+    ///
+    /// [`try_parse`]: Self::try_parse
+    pub const fn try_parse_braced(s: &str) -> Result<Self, GuidFromStrError> {
+        // Treat input as ASCII.
+        let s = s.as_bytes();
+
+        if s.len() != 38 {
+            return Err(GuidFromStrError::Length);
         }
-        if s[18] != sep {
-            return Err(GuidFromStrError::Separator(18));
+        if s[0] != b'{' {
+            return Err(GuidFromStrError::Brace(0));
         }
-        if s[23] != sep {
-            return Err(GuidFromStrError::Separator(23));
+        if s[37] != b'}' {
+            return Err(GuidFromStrError::Brace(37));
         }
 
-        Ok(Self::from_bytes([
-            mtry!(parse_byte_from_ascii_str_at(s, 6)),
-            mtry!(parse_byte_from_ascii_str_at(s, 4)),
-            mtry!(parse_byte_from_ascii_str_at(s, 2)),
-            mtry!(parse_byte_from_ascii_str_at(s, 0)),
-            mtry!(parse_byte_from_ascii_str_at(s, 11)),
-            mtry!(parse_byte_from_ascii_str_at(s, 9)),
-            mtry!(parse_byte_from_ascii_str_at(s, 16)),
-            mtry!(parse_byte_from_ascii_str_at(s, 14)),
-            mtry!(parse_byte_from_ascii_str_at(s, 19)),
-            mtry!(parse_byte_from_ascii_str_at(s, 21)),
-            mtry!(parse_byte_from_ascii_str_at(s, 24)),
-            mtry!(parse_byte_from_ascii_str_at(s, 26)),
-            mtry!(parse_byte_from_ascii_str_at(s, 28)),
-            mtry!(parse_byte_from_ascii_str_at(s, 30)),
-            mtry!(parse_byte_from_ascii_str_at(s, 32)),
-            mtry!(parse_byte_from_ascii_str_at(s, 34)),
-        ]))
+        Self::parse_hyphenated_at(s, 1)
     }
 
-    /// Parse a GUID from a string, panicking on failure.
+    /// Parse a GUID from a URN string in the format
+    /// "urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx".
     ///
-    /// The input must be in "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"
-    /// format, where each `x` is a hex digit (any of `0-9`, `a-f`, or
-    /// `A-F`).
+    /// The `urn:uuid:` prefix is matched case-insensitively. Returns
+    /// [`GuidFromStrError::Length`] if the input is not exactly 45 bytes
+    /// long, and [`GuidFromStrError::UrnPrefix`] if the prefix is
+    /// missing. Other errors are the same as for [`try_parse`], with
+    /// byte indices relative to the whole input.
     ///
-    /// This function is marked `track_caller` so that error messages
-    /// point directly to the invalid GUID string.
+    /// # Example
     ///
-    /// # Panics
+    /// ```
+    /// use uguid::{guid, Guid};
     ///
-    /// This function will panic if the input is not in the format shown
-    /// above. In particular, it will panic if the input is not exactly
-    /// 36 bytes long, or if the input does not have separators at the
-    /// expected positions, or if any of the remaining characters are
-    /// not valid hex digits.
-    #[must_use]
-    #[track_caller]
-    pub const fn parse_or_panic(s: &str) -> Self {
-        match Self::try_parse(s) {
-            Ok(g) => g,
-            Err(GuidFromStrError::Length) => {
-                panic!("GUID string has wrong length (expected 36 bytes)");
-            }
-            Err(GuidFromStrError::Separator(_)) => {
-                panic!("GUID string is missing one or more separators (`-`)");
-            }
-            Err(GuidFromStrError::Hex(_)) => {
-                panic!("GUID string contains one or more invalid characters");
+    /// assert_eq!(
+    ///     Guid::try_parse_urn("urn:uuid:01234567-89ab-cdef-0123-456789abcdef"),
+    ///     Ok(guid!("01234567-89ab-cdef-0123-456789abcdef"))
+    /// );
+    /// ```
+    ///
+    /// [`try_parse`]: Self::try_parse
+    pub const fn try_parse_urn(s: &str) -> Result<Self, GuidFromStrError> {
+        // Treat input as ASCII.
+        let s = s.as_bytes();
+
+        if s.len() != 45 {
+            return Err(GuidFromStrError::Length);
+        }
+
+        let prefix = b"urn:uuid:";
+        let mut i = 0;
+        while i < prefix.len() {
+            if !s[i].eq_ignore_ascii_case(&prefix[i]) {
+                return Err(GuidFromStrError::UrnPrefix);
+            }
+            i += 1;
+        }
+
+        Self::parse_hyphenated_at(s, 9)
+    }
+
+    /// Parse a GUID from a UCS-2 string, as used by UEFI.
+    ///
+    /// The input must be in the same "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"
+    /// format as [`try_parse`]. A single trailing null character is
+    /// allowed, so a null-terminated UEFI string can be passed
+    /// directly. Non-ASCII characters are treated as invalid. Errors are
+    /// the same as for [`try_parse`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// let ucs2 = guid.to_ucs2_hex_lower();
+    /// assert_eq!(Guid::try_parse_ucs2(&ucs2), Ok(guid));
+    /// ```
+    ///
+    /// [`try_parse`]: Self::try_parse
+    pub const fn try_parse_ucs2(s: &[u16]) -> Result<Self, GuidFromStrError> {
+        let s = if let [s @ .., 0] = s { s } else { s };
+        if s.len() != 36 {
+            return Err(GuidFromStrError::Length);
+        }
+
+        // Convert to ASCII. Non-ASCII characters are replaced with a
+        // byte that is neither a hex digit nor a separator, so that the
+        // error index is preserved.
+        let mut ascii = [0; 36];
+        let mut i = 0;
+        while i < ascii.len() {
+            ascii[i] = if s[i] < 0x80 {
+                s[i].to_le_bytes()[0]
+            } else {
+                0xff
+            };
+            i += 1;
+        }
+
+        Self::parse_hyphenated_at(&ascii, 0)
+    }
+
+    /// Parse a GUID from a string in any of the formats described by
+    /// [`GuidFormat`], returning the GUID and the detected format.
+    ///
+    /// The format is chosen by the length of the input. Returns
+    /// [`GuidFromStrError::Length`] if the length does not match any
+    /// format. Other errors are the same as for the parser of the
+    /// detected format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid, GuidFormat};
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// assert_eq!(
+    ///     Guid::parse_lenient("0123456789abcdef0123456789abcdef"),
+    ///     Ok((guid, GuidFormat::Simple))
+    /// );
+    /// assert_eq!(
+    ///     Guid::parse_lenient("{01234567-89ab-cdef-0123-456789abcdef}"),
+    ///     Ok((guid, GuidFormat::Braced))
+    /// );
+    /// ```
+    pub const fn parse_lenient(
+        s: &str,
+    ) -> Result<(Self, GuidFormat), GuidFromStrError> {
+        let (result, format) = match s.len() {
+            32 => (Self::parse_simple(s.as_bytes()), GuidFormat::Simple),
+            36 => (Self::try_parse(s), GuidFormat::Hyphenated),
+            38 => (Self::try_parse_braced(s), GuidFormat::Braced),
+            45 => (Self::try_parse_urn(s), GuidFormat::Urn),
+            _ => return Err(GuidFromStrError::Length),
+        };
+        match result {
+            Ok(guid) => Ok((guid, format)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parse the "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx" format. The caller
+    /// must check that `s` is 32 bytes long.
+    const fn parse_simple(s: &[u8]) -> Result<Self, GuidFromStrError> {
+        Ok(Self::from_bytes([
+            mtry!(parse_byte_from_ascii_str_at(s, 6)),
+            mtry!(parse_byte_from_ascii_str_at(s, 4)),
+            mtry!(parse_byte_from_ascii_str_at(s, 2)),
+            mtry!(parse_byte_from_ascii_str_at(s, 0)),
+            mtry!(parse_byte_from_ascii_str_at(s, 10)),
+            mtry!(parse_byte_from_ascii_str_at(s, 8)),
+            mtry!(parse_byte_from_ascii_str_at(s, 14)),
+            mtry!(parse_byte_from_ascii_str_at(s, 12)),
+            mtry!(parse_byte_from_ascii_str_at(s, 16)),
+            mtry!(parse_byte_from_ascii_str_at(s, 18)),
+            mtry!(parse_byte_from_ascii_str_at(s, 20)),
+            mtry!(parse_byte_from_ascii_str_at(s, 22)),
+            mtry!(parse_byte_from_ascii_str_at(s, 24)),
+            mtry!(parse_byte_from_ascii_str_at(s, 26)),
+            mtry!(parse_byte_from_ascii_str_at(s, 28)),
+            mtry!(parse_byte_from_ascii_str_at(s, 30)),
+        ]))
+    }
+
+    /// Parse the "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx" format starting
+    /// at byte index `start` of `s`. The caller must check that `s` is
+    /// long enough. Errors contain byte indices relative to `s`.
+    const fn parse_hyphenated_at(
+        s: &[u8],
+        start: u8,
+    ) -> Result<Self, GuidFromStrError> {
+        // This `as` conversion is needed because this is a const
+        // function. It is always valid since `usize` is always bigger
+        // than a u8.
+        #[allow(clippy::as_conversions)]
+        let start_usize = start as usize;
+
+        let sep = b'-';
+        if s[start_usize + 8] != sep {
+            return Err(GuidFromStrError::Separator(start + 8));
+        }
+        if s[start_usize + 13] != sep {
+            return Err(GuidFromStrError::Separator(start + 13));
+        }
+        if s[start_usize + 18] != sep {
+            return Err(GuidFromStrError::Separator(start + 18));
+        }
+        if s[start_usize + 23] != sep {
+            return Err(GuidFromStrError::Separator(start + 23));
+        }
+
+        Ok(Self::from_bytes([
+            mtry!(parse_byte_from_ascii_str_at(s, start + 6)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 4)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 2)),
+            mtry!(parse_byte_from_ascii_str_at(s, start)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 11)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 9)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 16)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 14)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 19)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 21)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 24)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 26)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 28)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 30)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 32)),
+            mtry!(parse_byte_from_ascii_str_at(s, start + 34)),
+        ]))
+    }
+
+    /// Parse a GUID from a string, panicking on failure.
+    ///
+    /// The input must be in "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"
+    /// format, where each `x` is a hex digit (any of `0-9`, `a-f`, or
+    /// `A-F`).
+    ///
+    /// This function is marked `track_caller` so that error messages
+    /// point directly to the invalid GUID string.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the input is not in the format shown
+    /// above. In particular, it will panic if the input is not exactly
+    /// 36 bytes long, or if the input does not have separators at the
+    /// expected positions, or if any of the remaining characters are
+    /// not valid hex digits.
+    #[must_use]
+    #[track_caller]
+    pub const fn parse_or_panic(s: &str) -> Self {
+        match Self::try_parse(s) {
+            Ok(g) => g,
+            Err(GuidFromStrError::Length) => {
+                panic!("GUID string has wrong length (expected 36 bytes)");
+            }
+            Err(GuidFromStrError::Separator(_)) => {
+                panic!("GUID string is missing one or more separators (`-`)");
+            }
+            Err(GuidFromStrError::Hex(_)) => {
+                panic!("GUID string contains one or more invalid characters");
+            }
+            // Not returned by `try_parse`.
+            Err(GuidFromStrError::Brace(_)) => {
+                panic!("GUID string is missing one or more braces");
+            }
+            Err(GuidFromStrError::UrnPrefix) => {
+                panic!("GUID string is missing the `urn:uuid:` prefix");
+            }
+        }
+    }
+
+    /// Parse a GUID from a string in the braced format used by
+    /// Microsoft Windows, panicking on failure.
+    ///
+    /// The input must be in "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}"
+    /// format. See [`try_parse_braced`] for details.
+    ///
+    /// This function is marked `track_caller` so that error messages
+    /// point directly to the invalid GUID string.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the input is not in the format shown
+    /// above.
+    ///
+    /// [`try_parse_braced`]: Self::try_parse_braced
+    #[must_use]
+    #[track_caller]
+    pub const fn parse_braced_or_panic(s: &str) -> Self {
+        match Self::try_parse_braced(s) {
+            Ok(g) => g,
+            Err(GuidFromStrError::Length) => {
+                panic!("GUID string has wrong length (expected 38 bytes)");
+            }
+            Err(GuidFromStrError::Brace(_)) => {
+                panic!("GUID string is missing one or more braces");
+            }
+            Err(GuidFromStrError::Separator(_)) => {
+                panic!("GUID string is missing one or more separators (`-`)");
+            }
+            Err(GuidFromStrError::Hex(_)) => {
+                panic!("GUID string contains one or more invalid characters");
+            }
+            // Not returned by `try_parse_braced`.
+            Err(GuidFromStrError::UrnPrefix) => {
+                panic!("GUID string is missing the `urn:uuid:` prefix");
             }
         }
     }
@@ -357,6 +1123,213 @@ impl Guid {
         ]
     }
 
+    /// Create a GUID from a 16-byte array in the big-endian byte order
+    /// used by [RFC 4122] and by the [`uuid`] crate's `Uuid::from_bytes`.
+    /// The byte order of the first three fields is reversed to get the
+    /// mixed-endian order used by [`Guid`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// assert_eq!(
+    ///     Guid::from_bytes_be([
+    ///         0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23,
+    ///         0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ///     ]),
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+    /// );
+    /// ```
+    ///
+    /// [RFC 4122]: https://datatracker.ietf.org/doc/html/rfc4122
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn from_bytes_be(bytes: [u8; 16]) -> Self {
+        Self::from_bytes(swap_field_byte_order(bytes))
+    }
+
+    /// Convert to a 16-byte array in the big-endian byte order used by
+    /// [RFC 4122] and by the [`uuid`] crate's `Uuid::as_bytes`. This is
+    /// the inverse of [`from_bytes_be`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// assert_eq!(
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef").to_bytes_be(),
+    ///     [
+    ///         0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23,
+    ///         0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [RFC 4122]: https://datatracker.ietf.org/doc/html/rfc4122
+    /// [`from_bytes_be`]: Self::from_bytes_be
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn to_bytes_be(self) -> [u8; 16] {
+        swap_field_byte_order(self.to_bytes())
+    }
+
+    /// Create a GUID from a 128-bit integer whose hex digits, from most
+    /// to least significant, are the hex digits of the GUID's string
+    /// form. This matches `Uuid::from_u128` in the [`uuid`] crate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// assert_eq!(
+    ///     Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef),
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+    /// );
+    /// ```
+    ///
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn from_u128(v: u128) -> Self {
+        Self::from_bytes_be(v.to_be_bytes())
+    }
+
+    /// Convert to a 128-bit integer whose hex digits, from most to least
+    /// significant, are the hex digits of the GUID's string form. This
+    /// is the inverse of [`from_u128`], and matches `Uuid::as_u128` in
+    /// the [`uuid`] crate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// assert_eq!(
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef").to_u128(),
+    ///     0x01234567_89ab_cdef_0123_456789abcdef
+    /// );
+    /// ```
+    ///
+    /// [`from_u128`]: Self::from_u128
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn to_u128(self) -> u128 {
+        u128::from_be_bytes(self.to_bytes_be())
+    }
+
+    /// Create a GUID from a 128-bit integer with the byte order of
+    /// [`from_u128`] reversed, so the least significant byte of `v` is
+    /// the first byte of the GUID's string form. This matches
+    /// `Uuid::from_u128_le` in the [`uuid`] crate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::{guid, Guid};
+    ///
+    /// assert_eq!(
+    ///     Guid::from_u128_le(0xefcdab89_6745_2301_efcd_ab8967452301),
+    ///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+    /// );
+    /// ```
+    ///
+    /// [`from_u128`]: Self::from_u128
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn from_u128_le(v: u128) -> Self {
+        Self::from_u128(v.swap_bytes())
+    }
+
+    /// Convert to a 128-bit integer with the byte order of [`to_u128`]
+    /// reversed. This is the inverse of [`from_u128_le`], and matches
+    /// `Uuid::to_u128_le` in the [`uuid`] crate.
+    ///
+    /// [`from_u128_le`]: Self::from_u128_le
+    /// [`to_u128`]: Self::to_u128
+    /// [`uuid`]: https://docs.rs/uuid/latest/uuid
+    #[must_use]
+    pub const fn to_u128_le(self) -> u128 {
+        self.to_u128().swap_bytes()
+    }
+
+    /// Get an adapter that displays the GUID in the braced format used
+    /// by Microsoft Windows, "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// assert_eq!(
+    ///     guid.braced().to_string(),
+    ///     "{01234567-89ab-cdef-0123-456789abcdef}"
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn braced(self) -> Braced {
+        Braced(self)
+    }
+
+    /// Get an adapter that displays the GUID as a URN, in the format
+    /// "urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// assert_eq!(
+    ///     guid.urn().to_string(),
+    ///     "urn:uuid:01234567-89ab-cdef-0123-456789abcdef"
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn urn(self) -> Urn {
+        Urn(self)
+    }
+
+    /// Get an adapter that displays the GUID with upper-case hex digits,
+    /// in "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// assert_eq!(
+    ///     guid.uppercase().to_string(),
+    ///     "01234567-89AB-CDEF-0123-456789ABCDEF"
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn uppercase(self) -> Uppercase {
+        Uppercase(self)
+    }
+
+    /// Get an adapter that displays the GUID as 32 lower-case hex digits
+    /// with no hyphens, in "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx" format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uguid::guid;
+    ///
+    /// let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    /// assert_eq!(
+    ///     guid.simple().to_string(),
+    ///     "0123456789abcdef0123456789abcdef"
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn simple(self) -> Simple {
+        Simple(self)
+    }
+
     /// Convert to a lower-case hex ASCII string.
     ///
     /// The output is in "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx" format.
@@ -387,6 +1360,52 @@ impl Guid {
         (buf[34], buf[35]) = byte_to_ascii_hex_lower(bytes[15]);
         buf
     }
+
+    /// Convert to 32 lower-case hex ASCII digits with no hyphens.
+    pub(crate) const fn to_ascii_hex_simple_lower(self) -> [u8; 32] {
+        let ascii = self.to_ascii_hex_lower();
+        let mut buf = [0; 32];
+        let mut i = 0;
+        let mut j = 0;
+        while i < ascii.len() {
+            if ascii[i] != b'-' {
+                buf[j] = ascii[i];
+                j += 1;
+            }
+            i += 1;
+        }
+        buf
+    }
+
+    /// Convert to a lower-case hex UCS-2 string, as used by UEFI.
+    ///
+    /// The output is in "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx" format,
+    /// without a null terminator.
+    #[must_use]
+    pub const fn to_ucs2_hex_lower(self) -> [u16; 36] {
+        let ascii = self.to_ascii_hex_lower();
+        let mut buf = [0; 36];
+        let mut i = 0;
+        while i < buf.len() {
+            buf[i] = u16::from_le_bytes([ascii[i], 0]);
+            i += 1;
+        }
+        buf
+    }
+
+    /// Convert to an upper-case hex ASCII string.
+    ///
+    /// The output is in "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" format.
+    #[must_use]
+    pub const fn to_ascii_hex_upper(self) -> [u8; 36] {
+        let mut buf = self.to_ascii_hex_lower();
+        let mut i = 0;
+        while i < buf.len() {
+            buf[i] = buf[i].to_ascii_uppercase();
+            i += 1;
+        }
+        buf
+    }
 }
 
 impl Default for Guid {
@@ -400,7 +1419,99 @@ impl Display for Guid {
         let ascii = self.to_ascii_hex_lower();
         // OK to unwrap since the ascii output is valid utf-8.
         let s = str::from_utf8(&ascii).unwrap();
-        f.write_str(s)
+        f.pad(s)
+    }
+}
+
+/// Format as 32 lower-case hex digits with no hyphens, in the same
+/// order as the string form. The alternate flag (`{:#x}`) adds a `0x`
+/// prefix.
+impl LowerHex for Guid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ascii = self.to_ascii_hex_simple_lower();
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&ascii).unwrap();
+        f.pad_integral(true, "0x", s)
+    }
+}
+
+/// Format as 32 upper-case hex digits with no hyphens, in the same
+/// order as the string form. The alternate flag (`{:#X}`) adds a `0x`
+/// prefix.
+impl UpperHex for Guid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut ascii = self.to_ascii_hex_simple_lower();
+        ascii.make_ascii_uppercase();
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&ascii).unwrap();
+        f.pad_integral(true, "0x", s)
+    }
+}
+
+/// Generate arbitrary GUIDs from 16 random bytes. The GUIDs are not
+/// required to have a valid variant or version.
+#[cfg(feature = "proptest")]
+impl PropArbitrary for Guid {
+    type Parameters = ();
+    type Strategy =
+        Map<<[u8; 16] as PropArbitrary>::Strategy, fn([u8; 16]) -> Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        any::<[u8; 16]>().prop_map(Self::from_bytes)
+    }
+}
+
+/// Generate a random version 4 GUID. See [`Guid::from_random_bytes`].
+///
+/// # Example
+///
+/// ```
+/// use rand::Rng;
+/// use uguid::{Guid, Variant};
+///
+/// # let mut rng = rand::rngs::mock::StepRng::new(0, 1);
+/// let guid: Guid = rng.gen();
+/// assert_eq!(guid.variant(), Variant::Rfc4122);
+/// assert_eq!(guid.version(), 4);
+/// ```
+#[cfg(feature = "rand")]
+impl Distribution<Guid> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Guid {
+        Guid::from_random_bytes(rng.gen())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Guid {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        let ascii = self.to_ascii_hex_lower();
+        // OK to unwrap since the ascii output is valid utf-8.
+        let s = str::from_utf8(&ascii).unwrap();
+        defmt::write!(f, "{=str}", s);
+    }
+}
+
+/// The schema is a string with the `uuid` format, matching the
+/// human-readable serde representation.
+#[cfg(feature = "schemars")]
+impl JsonSchema for Guid {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Guid".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "uguid::Guid".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "format": "uuid",
+        })
     }
 }
 
@@ -417,16 +1528,89 @@ impl FromStr for Guid {
     }
 }
 
+/// Convert from bytes in the same order as [`Guid::from_bytes`].
+impl From<[u8; 16]> for Guid {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Convert to bytes in the same order as [`Guid::to_bytes`].
+impl From<Guid> for [u8; 16] {
+    fn from(guid: Guid) -> Self {
+        guid.to_bytes()
+    }
+}
+
+/// Convert from a byte slice in the same order as [`Guid::from_bytes`].
+/// Fails if the slice is not exactly 16 bytes long.
+impl TryFrom<&[u8]> for Guid {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from_bytes(bytes.try_into()?))
+    }
+}
+
+/// Get a reference to the bytes of the GUID, in the same order as
+/// [`Guid::to_bytes`].
+impl AsRef<[u8; 16]> for Guid {
+    fn as_ref(&self) -> &[u8; 16] {
+        // SAFETY: `Guid` is `repr(C)`, 16 bytes in size with no padding,
+        // and all of its fields are plain integers. Its alignment is at
+        // least that of `[u8; 16]`. The `time_low` field is stored in
+        // native-endian order from the bytes passed to `new`, so the
+        // in-memory bytes match `to_bytes`.
+        #[allow(unsafe_code)]
+        unsafe {
+            &*ptr::from_ref(self).cast::<[u8; 16]>()
+        }
+    }
+}
+
+/// Get a reference to the bytes of the GUID, in the same order as
+/// [`Guid::to_bytes`].
+impl AsRef<[u8]> for Guid {
+    fn as_ref(&self) -> &[u8] {
+        AsRef::<[u8; 16]>::as_ref(self)
+    }
+}
+
+/// Convert from a [`uuid::Uuid`]. The byte order of the first three
+/// fields is swapped, so the GUID has the same string form as the UUID.
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Guid {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Self::from_bytes_be(uuid.into_bytes())
+    }
+}
+
+/// Convert to a [`uuid::Uuid`]. The byte order of the first three fields
+/// is swapped, so the UUID has the same string form as the GUID.
+#[cfg(feature = "uuid")]
+impl From<Guid> for uuid::Uuid {
+    fn from(guid: Guid) -> Self {
+        Self::from_bytes(guid.to_bytes_be())
+    }
+}
+
+/// Human-readable formats such as JSON use the string form of the GUID
+/// (see [`Display`]). Binary formats such as bincode and postcard use
+/// the 16 bytes from [`Guid::to_bytes`].
 #[cfg(feature = "serde")]
 impl Serialize for Guid {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let ascii = self.to_ascii_hex_lower();
-        // OK to unwrap since the ascii output is valid utf-8.
-        let s = str::from_utf8(&ascii).unwrap();
-        serializer.serialize_str(s)
+        if serializer.is_human_readable() {
+            let ascii = self.to_ascii_hex_lower();
+            // OK to unwrap since the ascii output is valid utf-8.
+            let s = str::from_utf8(&ascii).unwrap();
+            serializer.serialize_str(s)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
     }
 }
 
@@ -434,7 +1618,7 @@ impl Serialize for Guid {
 struct DeserializerVisitor;
 
 #[cfg(feature = "serde")]
-impl<'de> Visitor<'de> for DeserializerVisitor {
+impl Visitor<'_> for DeserializerVisitor {
     type Value = Guid;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -451,13 +1635,75 @@ impl<'de> Visitor<'de> for DeserializerVisitor {
     }
 }
 
+/// Visitor for the binary form of a GUID. Formats that don't have a
+/// native byte array type may pass the bytes as a sequence.
+#[cfg(feature = "serde")]
+struct BytesDeserializerVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for BytesDeserializerVisitor {
+    type Value = Guid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("16 bytes")
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let bytes = <[u8; 16]>::try_from(value)
+            .map_err(|_| E::invalid_length(value.len(), &self))?;
+        Ok(Guid::from_bytes(bytes))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bytes = [0; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(17, &self));
+        }
+        Ok(Guid::from_bytes(bytes))
+    }
+}
+
+/// Accepts the forms produced by the [`Serialize`] implementation,
+/// depending on whether the format is human-readable.
 #[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for Guid {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_str(DeserializerVisitor)
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(DeserializerVisitor)
+        } else {
+            deserializer.deserialize_bytes(BytesDeserializerVisitor)
+        }
+    }
+}
+
+/// Encoded as the 16 bytes from [`Guid::to_bytes`].
+#[cfg(feature = "borsh")]
+impl BorshSerialize for Guid {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+}
+
+/// Decodes the 16 bytes produced by the [`BorshSerialize`]
+/// implementation.
+#[cfg(feature = "borsh")]
+impl BorshDeserialize for Guid {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        <[u8; 16]>::deserialize_reader(reader).map(Self::from_bytes)
     }
 }
 
@@ -478,3 +1724,105 @@ pub enum Variant {
     /// Reserved for future use.
     ReservedFuture,
 }
+
+/// String format of a GUID, as detected by [`Guid::parse_lenient`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum GuidFormat {
+    /// "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx", as parsed by
+    /// [`Guid::try_parse`].
+    Hyphenated,
+
+    /// "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx", as displayed by
+    /// [`Guid::simple`].
+    Simple,
+
+    /// "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}", as parsed by
+    /// [`Guid::try_parse_braced`].
+    Braced,
+
+    /// "urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx", as parsed by
+    /// [`Guid::try_parse_urn`].
+    Urn,
+}
+
+/// Version of a GUID, as defined in [RFC 9562]. See
+/// [`Guid::get_version`].
+///
+/// [RFC 9562]: https://datatracker.ietf.org/doc/html/rfc9562#section-4.2
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Version {
+    /// Version 1, time-based with a node ID.
+    Time,
+
+    /// Version 2, DCE security.
+    Dce,
+
+    /// Version 3, name-based using MD5.
+    Md5,
+
+    /// Version 4, random.
+    Random,
+
+    /// Version 5, name-based using SHA-1.
+    Sha1,
+
+    /// Version 6, time-ordered with a node ID.
+    SortMac,
+
+    /// Version 7, time-ordered with random bits.
+    SortRand,
+
+    /// Version 8, custom format.
+    Custom,
+
+    /// Any other version number, including 0 (used by the nil GUID).
+    Unknown(u8),
+}
+
+impl Version {
+    /// Convert from a version number. Only the low four bits of `v` are
+    /// used.
+    #[must_use]
+    pub const fn from_u8(v: u8) -> Self {
+        match v & 0b1111 {
+            1 => Self::Time,
+            2 => Self::Dce,
+            3 => Self::Md5,
+            4 => Self::Random,
+            5 => Self::Sha1,
+            6 => Self::SortMac,
+            7 => Self::SortRand,
+            8 => Self::Custom,
+            v => Self::Unknown(v),
+        }
+    }
+
+    /// Convert to a version number.
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        match self {
+            Self::Time => 1,
+            Self::Dce => 2,
+            Self::Md5 => 3,
+            Self::Random => 4,
+            Self::Sha1 => 5,
+            Self::SortMac => 6,
+            Self::SortRand => 7,
+            Self::Custom => 8,
+            Self::Unknown(v) => v,
+        }
+    }
+}
+
+/// Timestamp embedded in a time-based GUID. See [`Guid::timestamp`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Timestamp {
+    /// Number of 100-nanosecond intervals since the start of the
+    /// Gregorian calendar (1582-10-15 00:00:00 UTC), from a version 1
+    /// or version 6 GUID. The value fits in 60 bits.
+    Gregorian(u64),
+
+    /// Number of milliseconds since the Unix epoch (1970-01-01 00:00:00
+    /// UTC), from a version 7 GUID. The value fits in 48 bits.
+    UnixMillis(u64),
+}
@@ -21,9 +21,31 @@
 //!
 //! No features are enabled by default.
 //!
+//! * `arbitrary`: Implements arbitrary's `Arbitrary` trait for `Guid`, for
+//!   use in fuzz targets.
+//! * `borsh`: Implements borsh's `BorshSerialize` and `BorshDeserialize`
+//!   traits for `Guid`. The encoding is the 16 bytes from `Guid::to_bytes`.
 //! * `bytemuck`: Implements bytemuck's `Pod` and `Zeroable` traits for `Guid`.
+//! * `defmt`: Implements defmt's `Format` trait for `Guid` and
+//!   `GuidFromStrError`.
+//! * `getrandom`: Adds `Guid::new_v4`, which creates a random GUID
+//!   using the `getrandom` crate.
+//! * `md5`: Adds `Guid::new_v3`, which creates a name-based GUID using
+//!   the MD5 hash.
+//! * `proptest`: Implements proptest's `Arbitrary` trait for `Guid`.
+//! * `rand`: Implements rand's `Distribution<Guid>` for `Standard`, so
+//!   `rng.gen::<Guid>()` creates a random version 4 GUID.
+//! * `schemars`: Implements schemars's `JsonSchema` trait for `Guid`. The
+//!   schema is a string with the `uuid` format.
 //! * `serde`: Implements serde's `Serialize` and `Deserialize` traits for `Guid`.
-//! * `std`: Provides `std::error::Error` implementation for the error type.
+//!   Human-readable formats use the string form, and binary formats use the
+//!   16 bytes from `Guid::to_bytes`.
+//! * `std`: Together with `getrandom`, adds `Guid::new_v7`, which creates
+//!   a time-ordered GUID from the system clock. The error type implements
+//!   `core::error::Error` regardless of this feature.
+//! * `uuid`: Implements conversions between `Guid` and `uuid::Uuid`.
+//! * `zerocopy`: Implements zerocopy's `FromBytes`, `IntoBytes`,
+//!   `Immutable`, and `KnownLayout` traits for `Guid`.
 //!
 //! # Examples
 //!
@@ -82,7 +104,7 @@
 //! );
 //! ```
 
-#![cfg_attr(not(feature = "std"), no_std)]
+#![no_std]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
@@ -96,6 +118,13 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
 
+#[cfg(feature = "schemars")]
+extern crate alloc;
+// `std` is needed for the system clock in `Guid::new_v7`, and by the
+// code generated by the `Arbitrary` derive macro.
+#[cfg(any(feature = "arbitrary", feature = "std"))]
+extern crate std;
+
 /// Macro replacement for the `?` operator, which cannot be used in
 /// const functions.
 macro_rules! mtry {
@@ -109,17 +138,27 @@ macro_rules! mtry {
     };
 }
 
+mod adapter;
+pub mod consts;
 mod error;
 mod guid;
 mod util;
 
+pub use adapter::{Braced, Simple, Uppercase, Urn};
 pub use error::GuidFromStrError;
-pub use guid::{Guid, Variant};
-
-#[cfg(feature = "std")]
-impl std::error::Error for GuidFromStrError {}
+pub use guid::{Guid, GuidFormat, Timestamp, Variant, Version};
 
-/// Create a [`Guid`] from a string at compile time.
+/// Create a [`Guid`] at compile time.
+///
+/// The input can be a string in either the
+/// "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx" format or the braced
+/// "{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}" format. An invalid string
+/// is a compile-time error.
+///
+/// The input can also be a list of fields in the same form as the
+/// `EFI_GUID` definitions in C headers: a `u32`, two `u16`s, and an
+/// array of eight bytes (in either brackets or braces). This allows
+/// GUIDs to be transcribed directly from C code.
 ///
 /// # Examples
 ///
@@ -136,13 +175,70 @@ impl std::error::Error for GuidFromStrError {}
 ///         [0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
 ///     )
 /// );
+/// assert_eq!(
+///     guid!("{01234567-89ab-cdef-0123-456789abcdef}"),
+///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+/// );
+/// assert_eq!(
+///     guid!(
+///         0x01234567,
+///         0x89ab,
+///         0xcdef,
+///         [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]
+///     ),
+///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+/// );
+/// assert_eq!(
+///     guid!(
+///         0x01234567,
+///         0x89ab,
+///         0xcdef,
+///         { 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef }
+///     ),
+///     guid!("01234567-89ab-cdef-0123-456789abcdef")
+/// );
 /// ```
 #[macro_export]
 macro_rules! guid {
     ($s:literal) => {{
         // Create a temporary const value to force an error in the input
         // to fail at compile time.
-        const G: $crate::Guid = $crate::Guid::parse_or_panic($s);
+        const G: $crate::Guid = if ::core::matches!($s.as_bytes().first(), Some(b'{'))
+        {
+            $crate::Guid::parse_braced_or_panic($s)
+        } else {
+            $crate::Guid::parse_or_panic($s)
+        };
+        G
+    }};
+    (
+        $time_low:expr,
+        $time_mid:expr,
+        $time_high_and_version:expr,
+        [$($data4:expr),* $(,)?] $(,)?
+    ) => {
+        $crate::guid!(
+            $time_low,
+            $time_mid,
+            $time_high_and_version,
+            { $($data4),* }
+        )
+    };
+    (
+        $time_low:expr,
+        $time_mid:expr,
+        $time_high_and_version:expr,
+        { $($data4:expr),* $(,)? } $(,)?
+    ) => {{
+        const D: [u8; 8] = [$($data4),*];
+        const G: $crate::Guid = $crate::Guid::from_fields(
+            $time_low,
+            $time_mid,
+            $time_high_and_version,
+            D[0],
+            D[1],
+            [D[2], D[3], D[4], D[5], D[6], D[7]],
+        );
         G
     }};
 }
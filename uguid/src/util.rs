@@ -82,6 +82,18 @@ pub(crate) const fn parse_byte_from_ascii_str_at(
     }
 }
 
+/// Convert GUID bytes between the mixed-endian order used by [`Guid`]
+/// and the big-endian order used by RFC 4122, by reversing the bytes of
+/// the first three fields. The conversion is its own inverse.
+///
+/// [`Guid`]: crate::Guid
+pub(crate) const fn swap_field_byte_order(b: [u8; 16]) -> [u8; 16] {
+    [
+        b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9], b[10],
+        b[11], b[12], b[13], b[14], b[15],
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
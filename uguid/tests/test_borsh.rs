@@ -0,0 +1,28 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "borsh")]
+
+use uguid::{guid, Guid};
+
+#[test]
+fn test_borsh() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+
+    let encoded = borsh::to_vec(&guid).unwrap();
+    assert_eq!(encoded, guid.to_bytes());
+    assert_eq!(borsh::from_slice::<Guid>(&encoded).unwrap(), guid);
+
+    // Too few bytes.
+    assert!(borsh::from_slice::<Guid>(&encoded[..15]).is_err());
+
+    // Too many bytes.
+    let mut too_long = encoded.clone();
+    too_long.push(0);
+    assert!(borsh::from_slice::<Guid>(&too_long).is_err());
+}
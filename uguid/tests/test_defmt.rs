@@ -0,0 +1,22 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "defmt")]
+
+use uguid::{Guid, GuidFromStrError};
+
+fn assert_format<T: defmt::Format>() {}
+
+// Logging with defmt requires a global logger, which isn't available
+// when running tests on the host, so just check that the trait is
+// implemented.
+#[test]
+fn test_defmt() {
+    assert_format::<Guid>();
+    assert_format::<GuidFromStrError>();
+}
@@ -7,7 +7,10 @@
 // except according to those terms.
 
 use core::mem;
-use uguid::{guid, Guid, GuidFromStrError, Variant};
+use uguid::{
+    consts, guid, Guid, GuidFormat, GuidFromStrError, Timestamp, Variant,
+    Version,
+};
 
 #[test]
 fn test_guid() {
@@ -86,6 +89,429 @@ fn test_from_random_bytes() {
     assert_eq!(guid.version(), 4);
 }
 
+#[cfg(feature = "getrandom")]
+#[test]
+fn test_new_v4() {
+    let guid1 = Guid::new_v4();
+    let guid2 = Guid::new_v4();
+    assert_ne!(guid1, guid2);
+    for guid in [guid1, guid2] {
+        assert_eq!(guid.variant(), Variant::Rfc4122);
+        assert_eq!(guid.version(), 4);
+    }
+}
+
+#[cfg(feature = "md5")]
+#[test]
+fn test_new_v3() {
+    // Expected values are from Python's `uuid.uuid3`.
+    let dns = guid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+    let url = guid!("6ba7b811-9dad-11d1-80b4-00c04fd430c8");
+    assert_eq!(
+        Guid::new_v3(dns, b"www.example.com"),
+        guid!("5df41881-3aed-3515-88a7-2f4a814cf09e")
+    );
+    assert_eq!(
+        Guid::new_v3(url, b"https://example.com/"),
+        guid!("b9dcdff8-af4a-365d-8043-0f8361942709")
+    );
+
+    let guid = Guid::new_v3(dns, b"");
+    assert_eq!(guid.variant(), Variant::Rfc4122);
+    assert_eq!(guid.version(), 3);
+}
+
+#[test]
+fn test_new_v1_v6() {
+    let node = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab];
+    let v1 = Guid::new_v1(0x0123_4567_89ab_cdef, 0x1234, node);
+    assert_eq!(v1, guid!("89abcdef-4567-1123-9234-0123456789ab"));
+    assert_eq!(v1.variant(), Variant::Rfc4122);
+    assert_eq!(v1.version(), 1);
+    let v6 = Guid::new_v6(0x0123_4567_89ab_cdef, 0x1234, node);
+    assert_eq!(v6, guid!("12345678-9abc-6def-9234-0123456789ab"));
+    assert_eq!(v6.variant(), Variant::Rfc4122);
+    assert_eq!(v6.version(), 6);
+
+    // Only the low 60 bits of the timestamp and the low 14 bits of the
+    // clock sequence are used.
+    assert_eq!(Guid::new_v1(0xf123_4567_89ab_cdef, 0xd234, node), v1);
+    assert_eq!(Guid::new_v6(0xf123_4567_89ab_cdef, 0xd234, node), v6);
+
+    // Version 6 GUIDs are ordered by time.
+    let later = Guid::new_v6(0x0123_4567_89ab_cdf0, 0, [0; 6]);
+    assert!(later.to_string() > v6.to_string());
+}
+
+#[test]
+fn test_timestamp() {
+    let node = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab];
+    let ts = 0x0123_4567_89ab_cdef;
+    assert_eq!(
+        Guid::new_v1(ts, 0, node).timestamp(),
+        Some(Timestamp::Gregorian(ts))
+    );
+    assert_eq!(
+        Guid::new_v6(ts, 0, node).timestamp(),
+        Some(Timestamp::Gregorian(ts))
+    );
+    assert_eq!(
+        Guid::new_v7_from_parts(0x0123_4567_89ab, [0xff; 10]).timestamp(),
+        Some(Timestamp::UnixMillis(0x0123_4567_89ab))
+    );
+
+    // Not time-based.
+    assert_eq!(Guid::from_random_bytes([0xff; 16]).timestamp(), None);
+    assert_eq!(Guid::ZERO.timestamp(), None);
+    // Version 1, but not the RFC 4122 variant.
+    assert_eq!(
+        guid!("89abcdef-4567-1123-1234-0123456789ab").timestamp(),
+        None
+    );
+}
+
+#[test]
+fn test_new_v7_from_parts() {
+    let random_bytes =
+        [0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    let guid = Guid::new_v7_from_parts(0x0123_4567_89ab, random_bytes);
+    assert_eq!(guid, guid!("01234567-89ab-7def-8123-456789abcdef"));
+    assert_eq!(guid.variant(), Variant::Rfc4122);
+    assert_eq!(guid.version(), 7);
+
+    // Only the low 48 bits of the timestamp are used.
+    assert_eq!(
+        Guid::new_v7_from_parts(0xffff_0123_4567_89ab, random_bytes),
+        guid
+    );
+
+    // Later timestamps have a greater string form.
+    let later = Guid::new_v7_from_parts(0x0123_4567_89ac, [0; 10]);
+    assert!(later.to_string() > guid.to_string());
+}
+
+#[cfg(all(feature = "std", feature = "getrandom"))]
+#[test]
+fn test_new_v7() {
+    let guid1 = Guid::new_v7();
+    let guid2 = Guid::new_v7();
+    assert_ne!(guid1, guid2);
+    for guid in [guid1, guid2] {
+        assert_eq!(guid.variant(), Variant::Rfc4122);
+        assert_eq!(guid.version(), 7);
+    }
+}
+
+#[test]
+fn test_fields() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let node = [0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    assert_eq!(
+        Guid::from_fields(0x01234567, 0x89ab, 0xcdef, 0x01, 0x23, node),
+        guid
+    );
+    assert_eq!(guid.time_low_u32(), 0x01234567);
+    assert_eq!(guid.time_mid_u16(), 0x89ab);
+    assert_eq!(guid.time_high_and_version_u16(), 0xcdef);
+    assert_eq!(
+        guid.fields(),
+        (0x01234567, 0x89ab, 0xcdef, 0x01, 0x23, node)
+    );
+}
+
+#[test]
+fn test_byte_conversions() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let bytes = guid.to_bytes();
+
+    assert_eq!(AsRef::<[u8; 16]>::as_ref(&guid), &bytes);
+    assert_eq!(AsRef::<[u8]>::as_ref(&guid), bytes.as_slice());
+
+    assert_eq!(Guid::from(bytes), guid);
+    assert_eq!(<[u8; 16]>::from(guid), bytes);
+
+    assert_eq!(Guid::try_from(bytes.as_slice()).unwrap(), guid);
+    assert!(Guid::try_from(&bytes[..15]).is_err());
+    assert!(Guid::try_from([0; 17].as_slice()).is_err());
+}
+
+#[test]
+fn test_bytes_be() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let be = [
+        0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67,
+        0x89, 0xab, 0xcd, 0xef,
+    ];
+    assert_eq!(guid.to_bytes_be(), be);
+    assert_eq!(Guid::from_bytes_be(be), guid);
+    assert_eq!(
+        guid.to_bytes(),
+        [
+            0x67, 0x45, 0x23, 0x01, 0xab, 0x89, 0xef, 0xcd, 0x01, 0x23, 0x45,
+            0x67, 0x89, 0xab, 0xcd, 0xef,
+        ]
+    );
+}
+
+#[test]
+fn test_u128() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let v = 0x0123_4567_89ab_cdef_0123_4567_89ab_cdef;
+    let v_le = 0xefcd_ab89_6745_2301_efcd_ab89_6745_2301;
+    assert_eq!(guid.to_u128(), v);
+    assert_eq!(Guid::from_u128(v), guid);
+    assert_eq!(guid.to_u128_le(), v_le);
+    assert_eq!(Guid::from_u128_le(v_le), guid);
+    assert_eq!(format!("{v:032x}"), guid.to_string().replace('-', ""));
+
+    assert_eq!(Guid::ZERO.to_u128(), 0);
+    assert_eq!(Guid::from_u128(u128::MAX).to_bytes(), [0xff; 16]);
+}
+
+#[test]
+fn test_uppercase_and_simple() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(
+        guid.to_ascii_hex_upper(),
+        *b"01234567-89AB-CDEF-0123-456789ABCDEF"
+    );
+    assert_eq!(
+        guid.uppercase().to_string(),
+        "01234567-89AB-CDEF-0123-456789ABCDEF"
+    );
+    assert_eq!(guid.uppercase().guid(), guid);
+    assert_eq!(
+        guid.simple().to_string(),
+        "0123456789abcdef0123456789abcdef"
+    );
+    assert_eq!(guid.simple().guid(), guid);
+}
+
+#[test]
+fn test_format_flags() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let s = "01234567-89ab-cdef-0123-456789abcdef";
+    assert_eq!(format!("{guid:>40}"), format!("    {s}"));
+    assert_eq!(format!("{guid:<40}|"), format!("{s}    |"));
+    assert_eq!(format!("{guid:*^40}"), format!("**{s}**"));
+    assert_eq!(format!("{guid:10}"), s);
+    assert_eq!(format!("{guid:.8}"), "01234567");
+
+    assert_eq!(
+        format!("{:>40}", guid.braced()),
+        "  {01234567-89ab-cdef-0123-456789abcdef}"
+    );
+    assert_eq!(
+        format!("{:<46}|", guid.urn()),
+        "urn:uuid:01234567-89ab-cdef-0123-456789abcdef |"
+    );
+    assert_eq!(format!("{:>34}", guid.simple()).len(), 34);
+    assert_eq!(format!("{:>38}", guid.uppercase()).len(), 38);
+}
+
+#[test]
+fn test_hex() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(format!("{guid:x}"), "0123456789abcdef0123456789abcdef");
+    assert_eq!(format!("{guid:X}"), "0123456789ABCDEF0123456789ABCDEF");
+    assert_eq!(format!("{guid:#x}"), "0x0123456789abcdef0123456789abcdef");
+    assert_eq!(format!("{guid:#X}"), "0x0123456789ABCDEF0123456789ABCDEF");
+    assert_eq!(format!("{guid:>34x}"), "  0123456789abcdef0123456789abcdef");
+    assert_eq!(format!("{:x}", Guid::ZERO), "0".repeat(32));
+}
+
+#[test]
+fn test_braced() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(
+        guid.braced().to_string(),
+        "{01234567-89ab-cdef-0123-456789abcdef}"
+    );
+    assert_eq!(guid.braced().guid(), guid);
+
+    assert_eq!(
+        Guid::try_parse_braced("{01234567-89AB-CDEF-0123-456789ABCDEF}"),
+        Ok(guid)
+    );
+    assert_eq!(
+        Guid::try_parse_braced("01234567-89ab-cdef-0123-456789abcdef"),
+        Err(GuidFromStrError::Length)
+    );
+    assert_eq!(
+        Guid::try_parse_braced("(01234567-89ab-cdef-0123-456789abcdef}"),
+        Err(GuidFromStrError::Brace(0))
+    );
+    assert_eq!(
+        Guid::try_parse_braced("{01234567-89ab-cdef-0123-456789abcdef)"),
+        Err(GuidFromStrError::Brace(37))
+    );
+    assert_eq!(
+        Guid::try_parse_braced("{01234567089ab-cdef-0123-456789abcdef}"),
+        Err(GuidFromStrError::Separator(9))
+    );
+    assert_eq!(
+        Guid::try_parse_braced("{01234567-89ab-cdef-0123-456789abcdeg}"),
+        Err(GuidFromStrError::Hex(35))
+    );
+    assert_eq!(
+        GuidFromStrError::Brace(0).to_string(),
+        "GUID string is missing a brace at index 0"
+    );
+}
+
+#[test]
+fn test_urn() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(
+        guid.urn().to_string(),
+        "urn:uuid:01234567-89ab-cdef-0123-456789abcdef"
+    );
+    assert_eq!(guid.urn().guid(), guid);
+
+    assert_eq!(
+        Guid::try_parse_urn("urn:uuid:01234567-89ab-cdef-0123-456789abcdef"),
+        Ok(guid)
+    );
+    assert_eq!(
+        Guid::try_parse_urn("URN:UUID:01234567-89AB-CDEF-0123-456789ABCDEF"),
+        Ok(guid)
+    );
+    assert_eq!(
+        Guid::try_parse_urn("01234567-89ab-cdef-0123-456789abcdef"),
+        Err(GuidFromStrError::Length)
+    );
+    assert_eq!(
+        Guid::try_parse_urn("urn:guid:01234567-89ab-cdef-0123-456789abcdef"),
+        Err(GuidFromStrError::UrnPrefix)
+    );
+    assert_eq!(
+        Guid::try_parse_urn("urn:uuid:01234567089ab-cdef-0123-456789abcdef"),
+        Err(GuidFromStrError::Separator(17))
+    );
+    assert_eq!(
+        GuidFromStrError::UrnPrefix.to_string(),
+        "GUID string is missing the `urn:uuid:` prefix"
+    );
+}
+
+#[test]
+fn test_ucs2() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let s = "01234567-89ab-cdef-0123-456789abcdef";
+    let ucs2: Vec<u16> = s.encode_utf16().collect();
+    assert_eq!(guid.to_ucs2_hex_lower().as_slice(), ucs2);
+
+    assert_eq!(Guid::try_parse_ucs2(&ucs2), Ok(guid));
+    let upper: Vec<u16> = s.to_uppercase().encode_utf16().collect();
+    assert_eq!(Guid::try_parse_ucs2(&upper), Ok(guid));
+
+    // Null terminator.
+    let mut nul = ucs2.clone();
+    nul.push(0);
+    assert_eq!(Guid::try_parse_ucs2(&nul), Ok(guid));
+    nul.push(0);
+    assert_eq!(Guid::try_parse_ucs2(&nul), Err(GuidFromStrError::Length));
+
+    assert_eq!(Guid::try_parse_ucs2(&[]), Err(GuidFromStrError::Length));
+    let mut bad = ucs2.clone();
+    bad[8] = u16::from(b'_');
+    assert_eq!(
+        Guid::try_parse_ucs2(&bad),
+        Err(GuidFromStrError::Separator(8))
+    );
+    let mut bad = ucs2;
+    // Non-ASCII character whose low byte is a valid hex digit.
+    bad[3] = 0x0130;
+    assert_eq!(Guid::try_parse_ucs2(&bad), Err(GuidFromStrError::Hex(2)));
+}
+
+#[test]
+fn test_parse_lenient() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(
+        Guid::parse_lenient("01234567-89ab-cdef-0123-456789abcdef"),
+        Ok((guid, GuidFormat::Hyphenated))
+    );
+    assert_eq!(
+        Guid::parse_lenient("0123456789ABCDEF0123456789ABCDEF"),
+        Ok((guid, GuidFormat::Simple))
+    );
+    assert_eq!(
+        Guid::parse_lenient("{01234567-89ab-cdef-0123-456789abcdef}"),
+        Ok((guid, GuidFormat::Braced))
+    );
+    assert_eq!(
+        Guid::parse_lenient("urn:uuid:01234567-89ab-cdef-0123-456789abcdef"),
+        Ok((guid, GuidFormat::Urn))
+    );
+
+    assert_eq!(Guid::parse_lenient(""), Err(GuidFromStrError::Length));
+    assert_eq!(
+        Guid::parse_lenient("0123456789abcdef0123456789abcdeg"),
+        Err(GuidFromStrError::Hex(30))
+    );
+    assert_eq!(
+        Guid::parse_lenient("[01234567-89ab-cdef-0123-456789abcdef]"),
+        Err(GuidFromStrError::Brace(0))
+    );
+}
+
+#[test]
+fn test_try_parse_const() {
+    const GOOD: Result<Guid, GuidFromStrError> =
+        Guid::try_parse("01234567-89ab-cdef-0123-456789abcdef");
+    const BAD_LEN: Result<Guid, GuidFromStrError> = Guid::try_parse("");
+    const BAD_HEX: Result<Guid, GuidFromStrError> =
+        Guid::try_parse("g1234567-89ab-cdef-0123-456789abcdef");
+    const LENIENT: Result<(Guid, GuidFormat), GuidFromStrError> =
+        Guid::parse_lenient("{01234567-89ab-cdef-0123-456789abcdef}");
+
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(GOOD, Ok(guid));
+    assert_eq!(BAD_LEN, Err(GuidFromStrError::Length));
+    assert_eq!(BAD_HEX, Err(GuidFromStrError::Hex(0)));
+    assert_eq!(LENIENT, Ok((guid, GuidFormat::Braced)));
+}
+
+#[test]
+fn test_guid_macro_forms() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    assert_eq!(guid!("{01234567-89AB-CDEF-0123-456789ABCDEF}"), guid);
+    assert_eq!(
+        guid!(
+            0x01234567,
+            0x89ab,
+            0xcdef,
+            [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+        ),
+        guid
+    );
+    assert_eq!(
+        guid!(
+            0x01234567,
+            0x89ab,
+            0xcdef,
+            { 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef }
+        ),
+        guid
+    );
+}
+
+#[test]
+fn test_parse_braced_or_panic() {
+    assert_eq!(
+        Guid::parse_braced_or_panic("{01234567-89ab-cdef-0123-456789abcdef}"),
+        guid!("01234567-89ab-cdef-0123-456789abcdef")
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_parse_braced_or_panic_brace() {
+    let _g =
+        Guid::parse_braced_or_panic("{01234567-89ab-cdef-0123-456789abcdef)");
+}
+
 #[test]
 fn test_parse_or_panic_success() {
     let _g = Guid::parse_or_panic("01234567-89ab-cdef-0123-456789abcdef");
@@ -172,12 +598,64 @@ fn test_guid_version() {
     assert_eq!(guid!("00000000-0000-4000-8000-000000000000").version(), 4);
 }
 
+#[test]
+fn test_guid_get_version() {
+    assert_eq!(Guid::ZERO.get_version(), Version::Unknown(0));
+    assert_eq!(
+        guid!("00000000-0000-1000-8000-000000000000").get_version(),
+        Version::Time
+    );
+    assert_eq!(
+        guid!("00000000-0000-7000-8000-000000000000").get_version(),
+        Version::SortRand
+    );
+    assert_eq!(
+        guid!("00000000-0000-f000-8000-000000000000").get_version(),
+        Version::Unknown(15)
+    );
+
+    for v in 0..16 {
+        assert_eq!(Version::from_u8(v).to_u8(), v);
+    }
+    assert_eq!(Version::from_u8(0x14), Version::Random);
+}
+
 #[test]
 fn test_guid_is_zero() {
     assert!(guid!("00000000-0000-0000-0000-000000000000").is_zero());
     assert!(!guid!("308bbc16-a308-47e8-8977-5e5646c5291f").is_zero());
 }
 
+#[test]
+fn test_guid_is_max() {
+    assert!(Guid::MAX.is_max());
+    assert!(!Guid::ZERO.is_max());
+    assert_eq!(
+        Guid::MAX.to_string(),
+        "ffffffff-ffff-ffff-ffff-ffffffffffff"
+    );
+    assert!(!guid!("ffffffff-ffff-ffff-ffff-fffffffffffe").is_max());
+}
+
+#[test]
+fn test_consts() {
+    assert!(consts::NIL.is_zero());
+    assert!(consts::MAX.is_max());
+    assert_eq!(
+        consts::NAMESPACE_DNS.to_string(),
+        "6ba7b810-9dad-11d1-80b4-00c04fd430c8"
+    );
+    for namespace in [
+        consts::NAMESPACE_DNS,
+        consts::NAMESPACE_URL,
+        consts::NAMESPACE_OID,
+        consts::NAMESPACE_X500,
+    ] {
+        assert_eq!(namespace.variant(), Variant::Rfc4122);
+        assert_eq!(namespace.version(), 1);
+    }
+}
+
 /// Inner module that only imports the `guid!` macro.
 mod inner {
     use uguid::guid;
@@ -187,5 +665,7 @@ mod inner {
     #[test]
     fn test_guid_macro_paths() {
         let _g = guid!("01234567-89ab-cdef-0123-456789abcdef");
+        let _g = guid!("{01234567-89ab-cdef-0123-456789abcdef}");
+        let _g = guid!(0, 0, 0, [0, 0, 0, 0, 0, 0, 0, 0]);
     }
 }
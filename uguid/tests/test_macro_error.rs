@@ -7,8 +7,36 @@
 // except according to those terms.
 
 //! Tests errors from the guid macros.
+//!
+//! The expected compiler output in `tests/ui` changes between rustc
+//! releases, so it is written for the MSRV toolchain only. The test is
+//! ignored by default; CI runs it on the MSRV with:
+//!
+//! ```text
+//! cargo test -p uguid --test test_macro_error -- --ignored
+//! ```
+
+use std::env;
+use std::process::Command;
+
+fn rustc_version() -> String {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(rustc).arg("--version").output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
 #[test]
+#[ignore = "expected output is for the MSRV toolchain; run with --ignored"]
 fn test_compilation_errors() {
+    let version = rustc_version();
+    let msrv = env!("CARGO_PKG_RUST_VERSION");
+    assert!(
+        version.starts_with(&format!("rustc {msrv}.")),
+        "the expected output in tests/ui is for Rust {msrv}, but the \
+         active toolchain is {}",
+        version.trim_end()
+    );
+
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/ui/*.rs");
 }
@@ -0,0 +1,20 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use uguid::Guid;
+
+proptest! {
+    #[test]
+    fn test_guid_string_round_trip(guid in any::<Guid>()) {
+        prop_assert_eq!(guid.to_string().parse::<Guid>(), Ok(guid));
+        prop_assert_eq!(Guid::from_bytes(guid.to_bytes()), guid);
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "rand")]
+
+use rand::rngs::mock::StepRng;
+use rand::Rng;
+use uguid::{Guid, Variant};
+
+#[test]
+fn test_rand() {
+    let mut rng = StepRng::new(0x0123_4567_89ab_cdef, 1);
+    let a: Guid = rng.gen();
+    let b: Guid = rng.gen();
+    assert_ne!(a, b);
+    for guid in [a, b] {
+        assert_eq!(guid.variant(), Variant::Rfc4122);
+        assert_eq!(guid.version(), 4);
+    }
+}
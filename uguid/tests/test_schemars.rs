@@ -0,0 +1,31 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "schemars")]
+
+use schemars::{json_schema, schema_for, JsonSchema, SchemaGenerator};
+use uguid::Guid;
+
+#[test]
+fn test_schemars() {
+    let schema = schema_for!(Guid);
+    assert_eq!(schema.get("type").unwrap(), "string");
+    assert_eq!(schema.get("format").unwrap(), "uuid");
+    assert_eq!(Guid::schema_name(), "Guid");
+
+    // The schema is inlined rather than added to the definitions.
+    let mut generator = SchemaGenerator::default();
+    assert_eq!(
+        generator.subschema_for::<Guid>(),
+        json_schema!({
+            "type": "string",
+            "format": "uuid",
+        })
+    );
+    assert!(generator.definitions().is_empty());
+}
@@ -8,7 +8,7 @@
 
 #![cfg(feature = "serde")]
 
-use serde_test::Token;
+use serde_test::{Configure, Token};
 use uguid::{guid, Guid};
 
 #[test]
@@ -16,16 +16,44 @@ fn test_serde() {
     let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
 
     serde_test::assert_tokens(
-        &guid,
+        &guid.readable(),
         &[Token::Str("01234567-89ab-cdef-0123-456789abcdef")],
     );
 
-    serde_test::assert_de_tokens_error::<Guid>(
+    serde_test::assert_de_tokens_error::<serde_test::Readable<Guid>>(
         &[Token::Str("1234")],
         "GUID string has wrong length (expected 36 bytes)",
     );
 
-    serde_test::assert_de_tokens_error::<Guid>(
+    serde_test::assert_de_tokens_error::<serde_test::Readable<Guid>>(
         &[Token::U64(1234)],
         "invalid type: integer `1234`, expected a string in the format \"xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx\"");
 }
+
+#[test]
+fn test_serde_compact() {
+    const GUID: Guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    const BYTES: [u8; 16] = GUID.to_bytes();
+    let guid = GUID;
+
+    serde_test::assert_tokens(&guid.compact(), &[Token::Bytes(&BYTES)]);
+
+    // Bytes as a sequence.
+    let mut tokens = vec![Token::Seq { len: Some(16) }];
+    tokens.extend(BYTES.iter().map(|b| Token::U8(*b)));
+    tokens.push(Token::SeqEnd);
+    serde_test::assert_de_tokens(&guid.compact(), &tokens);
+
+    serde_test::assert_de_tokens_error::<serde_test::Compact<Guid>>(
+        &[Token::Bytes(&[1, 2, 3])],
+        "invalid length 3, expected 16 bytes",
+    );
+    serde_test::assert_de_tokens_error::<serde_test::Compact<Guid>>(
+        &[Token::Seq { len: Some(1) }, Token::U8(1), Token::SeqEnd],
+        "invalid length 1, expected 16 bytes",
+    );
+    serde_test::assert_de_tokens_error::<serde_test::Compact<Guid>>(
+        &[Token::Str("01234567-89ab-cdef-0123-456789abcdef")],
+        "invalid type: string \"01234567-89ab-cdef-0123-456789abcdef\", expected 16 bytes",
+    );
+}
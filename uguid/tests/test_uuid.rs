@@ -0,0 +1,23 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "uuid")]
+
+use uguid::{guid, Guid};
+use uuid::{uuid, Uuid};
+
+#[test]
+fn test_uuid() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let uuid = uuid!("01234567-89ab-cdef-0123-456789abcdef");
+
+    assert_eq!(Guid::from(uuid), guid);
+    assert_eq!(Uuid::from(guid), uuid);
+    assert_eq!(Uuid::from(guid).to_string(), guid.to_string());
+    assert_eq!(uuid.as_u128(), guid.to_u128());
+}
@@ -0,0 +1,23 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "zerocopy")]
+
+use uguid::{guid, Guid};
+use zerocopy::{FromBytes, FromZeros, IntoBytes};
+
+#[test]
+fn test_zerocopy() {
+    let guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+    let bytes = guid.to_bytes();
+
+    assert_eq!(guid.as_bytes(), bytes);
+    assert_eq!(Guid::read_from_bytes(&bytes).unwrap(), guid);
+    assert!(Guid::read_from_bytes(&bytes[..15]).is_err());
+    assert_eq!(Guid::new_zeroed(), Guid::ZERO);
+}
@@ -6,10 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{DiskError, SliceBlockIoError};
-use std::error::Error;
-use std::fmt::{Debug, Display};
+use uguid::guid;
 
-impl<Custom> Error for DiskError<Custom> where Custom: Debug + Display {}
-
-impl Error for SliceBlockIoError {}
+fn main() {
+    let _g = guid!("{01234567-89ab-cdef-0123-456789abcdef)");
+}
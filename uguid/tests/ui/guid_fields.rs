@@ -0,0 +1,13 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use uguid::guid;
+
+fn main() {
+    let _g = guid!(0x01234567, 0x89ab, 0xcdef, [0x01, 0x23, 0x45]);
+}
@@ -15,9 +15,20 @@ use std::process::{exit, Command};
 use util::run_cmd;
 
 const FEAT_OPTIONS: [bool; 2] = [false, true];
+const FEAT_ALLOC: &str = "alloc";
+const FEAT_ARBITRARY: &str = "arbitrary";
+const FEAT_BORSH: &str = "borsh";
 const FEAT_BYTEMUCK: &str = "bytemuck";
+const FEAT_DEFMT: &str = "defmt";
+const FEAT_GETRANDOM: &str = "getrandom";
+const FEAT_MD5: &str = "md5";
+const FEAT_PROPTEST: &str = "proptest";
+const FEAT_RAND: &str = "rand";
+const FEAT_SCHEMARS: &str = "schemars";
 const FEAT_SERDE: &str = "serde";
 const FEAT_STD: &str = "std";
+const FEAT_UUID: &str = "uuid";
+const FEAT_ZEROCOPY: &str = "zerocopy";
 
 #[derive(Clone, Copy)]
 enum CargoAction {
@@ -77,6 +88,24 @@ fn test_uguid() {
             }
         }
     }
+
+    // Features that don't interact with the others are tested on their
+    // own rather than in every combination.
+    for feature in [
+        FEAT_ARBITRARY,
+        FEAT_BORSH,
+        FEAT_DEFMT,
+        FEAT_GETRANDOM,
+        FEAT_MD5,
+        FEAT_PROPTEST,
+        FEAT_RAND,
+        FEAT_SCHEMARS,
+        FEAT_UUID,
+        FEAT_ZEROCOPY,
+    ] {
+        test_package(Package::Uguid, &[feature]);
+    }
+    test_package(Package::Uguid, &[FEAT_GETRANDOM, FEAT_STD]);
 }
 
 fn test_gpt_disk_types() {
@@ -93,6 +122,13 @@ fn test_gpt_disk_types() {
             test_package(Package::GptDiskTypes, &features);
         }
     }
+
+    test_package(Package::GptDiskTypes, &[FEAT_ALLOC]);
+    test_package(Package::GptDiskTypes, &[FEAT_ARBITRARY]);
+    test_package(Package::GptDiskTypes, &[FEAT_DEFMT]);
+    test_package(Package::GptDiskTypes, &[FEAT_PROPTEST]);
+    test_package(Package::GptDiskTypes, &[FEAT_SERDE]);
+    test_package(Package::GptDiskTypes, &[FEAT_ZEROCOPY]);
 }
 
 fn test_gpt_disk_io() {
@@ -101,6 +137,10 @@ fn test_gpt_disk_io() {
         vec!["alloc"],
         // std implicitly enabled alloc, so no need for a separate alloc+std.
         vec!["std"],
+        // test_util implicitly enables alloc.
+        vec!["test_util"],
+        vec!["std", "test_util"],
+        vec!["sha2"],
     ];
 
     for features in feature_lists {